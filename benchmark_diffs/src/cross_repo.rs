@@ -44,7 +44,7 @@ pub fn windowed_commits_compare(
             println!("{}:({},{})", x.configured_repo.spec(), &x.before, &x.after);
             repo_names.push(x.configured_repo.spec().to_string());
             (preprocessed.pre_process_with_limit(
-                &mut x.configured_repo.clone().fetch(),
+                &mut x.configured_repo.clone().fetch().unwrap(),
                 x.before,
                 x.before,
                 // x.dir_path,
@@ -11,9 +11,12 @@ use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
 use crate::{
-    commit, fetch, file,
+    auth, blame, call_graph, commit, diff,
+    encoding::Encoding,
+    fetch, file,
+    jobs::{self, Target},
     scripting::{
-        self, ScriptContent, ScriptContentDepth, ScriptingError, ScriptingParam,
+        self, ScriptContent, ScriptContentDepth, ScriptingError, ScriptingParam, ScriptingQuery,
     },
     track, view, SharedState,
 };
@@ -32,20 +35,45 @@ impl IntoResponse for ScriptingError {
 
 // #[axum_macros::debug_handler]
 async fn scripting(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<ScriptingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
     axum::extract::Json(script): axum::extract::Json<ScriptContent>,
-) -> axum::response::Result<Json<scripting::ComputeResult>> {
+) -> axum::response::Result<(jobs::Resolved, Json<scripting::ComputeResult>)> {
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 2)?;
+    auth::charge(&state, &user, &target.repo(), 0, 1)?;
     let r = scripting::simple(script, state, path)?;
-    Ok(r)
+    Ok((resolved, r))
 }
 async fn scripting_depth(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<ScriptingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<ScriptingQuery>,
     axum::extract::State(state): axum::extract::State<SharedState>,
     axum::extract::Json(script): axum::extract::Json<ScriptContentDepth>,
-) -> axum::response::Result<Json<scripting::ComputeResults>> {
-    let r = scripting::simple_depth(script, state, path)?;
-    Ok(r)
+) -> axum::response::Result<(jobs::Resolved, Json<scripting::ComputeResults>)> {
+    let merges = query.merges;
+    let resolved = jobs::ready_or_enqueue_with(&state, &user, &target, script.commits(), merges)?;
+    auth::charge(&state, &user, &target.repo(), 0, 1)?;
+    let r = scripting::simple_depth(script, state, path, merges)?;
+    Ok((resolved, r))
+}
+async fn scripting_diff(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<ScriptingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<ScriptingQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(script): axum::extract::Json<ScriptContentDepth>,
+) -> axum::response::Result<(jobs::Resolved, Json<scripting::ComputeResults>)> {
+    let merges = query.merges;
+    let limit = script.commits() + 1;
+    let resolved = jobs::ready_or_enqueue_with(&state, &user, &target, limit, merges)?;
+    auth::charge(&state, &user, &target.repo(), 0, 1)?;
+    let r = scripting::simple_diff(script, state, path, merges)?;
+    Ok((resolved, r))
 }
 
 pub fn scripting_app(_st: SharedState) -> Router<SharedState> {
@@ -62,13 +90,21 @@ pub fn scripting_app(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/script/github/:user/:name/:commit",
+            "/script/:forge/:user/:name/:commit",
             post(scripting).layer(scripting_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/script-depth/github/:user/:name/:commit",
+            "/script-depth/:forge/:user/:name/:commit",
             post(scripting_depth).layer(scripting_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
+        .route(
+            "/script-diff/:forge/:user/:name/:commit",
+            post(scripting_diff).layer(scripting_service_config.clone()),
+        )
+        .route(
+            "/script-stream/:forge/:user/:name/:commit",
+            get(crate::ws::connect_script_stream),
+        )
         .route(
             "/shared-scripts-db",
             get(crate::ws::connect_db), // .with_state(Arc::clone(&shared_state)),
@@ -95,19 +131,54 @@ pub fn fetch_git_file(_st: SharedState) -> Router<SharedState> {
         // .request_body_limit(1024 * 5_000 /* ~5mb */)
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
-    Router::new().route(
-        "/file/github/:user/:name/:commit/*file",
-        get(file).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
-    )
+    Router::new()
+        .route(
+            "/file/:forge/:user/:name/:commit/*file",
+            get(file).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
+        )
+        .route(
+            "/source/:forge/:user/:name/:commit/*path",
+            get(source_at_path).layer(service_config.clone()),
+        )
+        .route(
+            "/source-id/:forge/:user/:name/:commit/:id",
+            get(source_with_node_id).layer(service_config.clone()),
+        )
 }
 
 // #[axum_macros::debug_handler]
 async fn file(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<file::FetchFileParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<(jobs::Resolved, String)> {
+    dbg!(&path);
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 2)?;
+    let r = file::from_hyper_ast(state, path)?;
+    Ok((resolved, r))
+}
+async fn source_at_path(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<file::FetchSourceParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<(jobs::Resolved, file::Source)> {
+    dbg!(&path);
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 1)?;
+    let r = file::source_at_path(state, path)?;
+    Ok((resolved, r))
+}
+async fn source_with_node_id(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<file::FetchSourceIdParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
-) -> axum::response::Result<String> {
+) -> axum::response::Result<(jobs::Resolved, file::Source)> {
     dbg!(&path);
-    file::from_hyper_ast(state, path).map_err(|err| err.into())
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 1)?;
+    let r = file::source_with_node_id(state, path)?;
+    Ok((resolved, r))
 }
 
 pub fn track_code_route(_st: SharedState) -> Router<SharedState> {
@@ -124,46 +195,162 @@ pub fn track_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/track/github/:user/:name/:commit/*file",
+            "/track/:forge/:user/:name/:commit/*file",
             get(track_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/track_at_path/github/:user/:name/:commit/*path",
+            "/track_at_path/:forge/:user/:name/:commit/*path",
             get(track_code_at_path).layer(service_config.clone()),
         )
         .route(
-            "/track_at_path_with_changes/github/:user/:name/:commit/*path",
+            "/track_at_path_with_changes/:forge/:user/:name/:commit/*path",
             get(track_code_at_path_with_changes).layer(service_config.clone()),
         )
+        .route(
+            "/track-batch/:forge/:user/:name/:commit",
+            post(track_code_batch).layer(service_config.clone()),
+        )
+        .route(
+            "/blame/:forge/:user/:name/:commit/*file",
+            get(blame_file).layer(service_config.clone()),
+        )
 }
 
 // #[axum_macros::debug_handler]
 async fn track_code(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<track::TrackingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::Query(query): axum::extract::Query<track::TrackingQuery>,
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> impl IntoResponse {
     dbg!(&path);
     dbg!(&query);
-    track::track_code(state, path, query)
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 2) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    // the walked commits are charged once they can be walked
+    if let Err(e) = auth::charge(&state, &user, &target.repo(), query.charged_commits(), 0) {
+        return e.into_response();
+    }
+    (resolved, track::track_code(state, path, query)).into_response()
 }
 async fn track_code_at_path(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<track::TrackingAtPathParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
     axum::extract::Query(query): axum::extract::Query<track::TrackingQuery>,
 ) -> impl IntoResponse {
     dbg!(&path);
     dbg!(&query);
-    track::track_code_at_path(state, path, query)
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 2) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    // the walked commits are charged once they can be walked
+    if let Err(e) = auth::charge(&state, &user, &target.repo(), query.charged_commits(), 0) {
+        return e.into_response();
+    }
+    (resolved, track::track_code_at_path(state, path, query)).into_response()
 }
 async fn track_code_at_path_with_changes(
+    user: auth::User,
+    encoding: Encoding,
     axum::extract::Path(path): axum::extract::Path<track::TrackingAtPathParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
     axum::extract::Query(query): axum::extract::Query<track::TrackingQuery>,
 ) -> impl IntoResponse {
     dbg!(&path);
     dbg!(&query);
-    track::track_code_at_path_with_changes(state, path, query)
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 2) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    // the walked commits are charged once they can be walked
+    if let Err(e) = auth::charge(&state, &user, &target.repo(), query.charged_commits(), 0) {
+        return e.into_response();
+    }
+    (
+        resolved,
+        track::track_code_at_path_with_changes(state, path, query).map(|r| r.encoded(encoding)),
+    )
+        .into_response()
+}
+async fn track_code_batch(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<track::BatchTrackingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<track::BatchTrackingQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(batch): axum::extract::Json<track::BatchTracking>,
+) -> impl IntoResponse {
+    dbg!(&path);
+    dbg!(&query);
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 2) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    // the walked commits are charged once they can be walked, for each target
+    let commits = query.charged_commits(&batch);
+    if let Err(e) = auth::charge(&state, &user, &target.repo(), commits, 0) {
+        return e.into_response();
+    }
+    (resolved, track::track_code_batch(state, path, query, batch)).into_response()
+}
+async fn blame_file(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<blame::BlameParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<blame::BlameQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> impl IntoResponse {
+    dbg!(&path);
+    dbg!(&query);
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 2) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    // the walked commits are charged once they can be walked
+    if let Err(e) = auth::charge(&state, &user, &target.repo(), query.charged_commits(), 0) {
+        return e.into_response();
+    }
+    (resolved, blame::blame(state, path, query)).into_response()
+}
+
+pub fn call_graph_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            dbg!(e);
+        }))
+        .load_shed()
+        .concurrency_limit(4)
+        .buffer(20)
+        .rate_limit(2, Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
+    Router::new().route(
+        "/call-graph/:forge/:user/:name/:commit",
+        get(compute_call_graph).layer(service_config.clone()),
+    )
+}
+
+async fn compute_call_graph(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<call_graph::CallGraphParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<call_graph::CallGraphQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> impl IntoResponse {
+    dbg!(&path);
+    dbg!(&query);
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target, 1) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    (resolved, call_graph::call_graph(state, path, query)).into_response()
 }
 
 pub fn view_code_route(_st: SharedState) -> Router<SharedState> {
@@ -180,29 +367,33 @@ pub fn view_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/view/github/:user/:name/:commit/*path",
+            "/view/:forge/:user/:name/:commit/*path",
             get(view_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/view/github/:user/:name/:commit/",
+            "/view/:forge/:user/:name/:commit/",
             get(view_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/view/:id",
+            "/view/node/:id",
             get(view_code_with_node_id).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
 }
 
 // #[axum_macros::debug_handler]
 async fn view_code(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<view::Parameters>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
-) -> axum::response::Result<Json<view::ViewRes>> {
+) -> axum::response::Result<(jobs::Resolved, Json<view::ViewRes>)> {
     dbg!(&path);
-    view::view(state, path).map_err(|err| err.into())
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 2)?;
+    let r = view::view(state, path)?;
+    Ok((resolved, r))
 }
 async fn view_code_with_node_id(
-    axum::extract::Path(id): axum::extract::Path<u64>,
+    axum::extract::Path(view::NodeParam { id }): axum::extract::Path<view::NodeParam>,
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<Json<view::ViewRes>> {
     view::view_with_node_id(state, id).map_err(|err| err.into())
@@ -222,11 +413,11 @@ pub fn fetch_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/fetch/github/:user/:name/:commit/*path",
+            "/fetch/:forge/:user/:name/:commit/*path",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/fetch/github/:user/:name/:commit/",
+            "/fetch/:forge/:user/:name/:commit/",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -240,11 +431,15 @@ pub fn fetch_code_route(_st: SharedState) -> Router<SharedState> {
 }
 // #[axum_macros::debug_handler]
 async fn fetch_code(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<fetch::Parameters>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
     axum::extract::State(state): axum::extract::State<SharedState>,
-) -> axum::response::Result<fetch::FetchedNodes> {
+) -> axum::response::Result<(jobs::Resolved, fetch::FetchedNodes)> {
     dbg!(&path);
-    fetch::fetch(state, path).map_err(|err| err.into())
+    let resolved = jobs::ready_or_enqueue(&state, &user, &target, 2)?;
+    let r = fetch::fetch(state, path)?;
+    Ok((resolved, r))
 }
 async fn fetch_code_with_node_ids(
     axum::extract::Path(ids): axum::extract::Path<String>,
@@ -294,21 +489,83 @@ pub fn commit_metadata_route(_st: SharedState) -> Router<SharedState> {
         // .request_body_limit(1024 * 5_000 /* ~5mb */)
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
+    Router::new()
+        .route(
+            "/commit/:forge/:user/:name/:version",
+            get(commit_metadata).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
+        )
+        .route(
+            "/commits/:forge/:user/:name/:version",
+            get(commit_history).layer(service_config.clone()),
+        )
+}
+
+pub fn diff_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            dbg!(e);
+        }))
+        .load_shed()
+        .concurrency_limit(8)
+        .buffer(20)
+        .rate_limit(2, Duration::from_secs(2))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
     Router::new().route(
-        "/commit/github/:user/:name/:version",
-        get(commit_metadata).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
+        "/diff/:forge/:user/:name/:src/:dst",
+        get(diff_commits).layer(service_config.clone()),
     )
 }
 
+async fn diff_commits(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<diff::DiffParam>,
+    axum::extract::Query(query): axum::extract::Query<diff::DiffQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> impl IntoResponse {
+    dbg!(&path);
+    dbg!(&query);
+    let target = |commit: &String| {
+        Target::new(
+            path.forge,
+            path.user.clone(),
+            path.name.clone(),
+            commit.clone(),
+        )
+    };
+    if let Err(pending) = jobs::ready_or_enqueue(&state, &user, &target(&path.src), 1) {
+        return pending;
+    }
+    let resolved = match jobs::ready_or_enqueue(&state, &user, &target(&path.dst), 1) {
+        Ok(resolved) => resolved,
+        Err(pending) => return pending,
+    };
+    (resolved, diff::diff(state, path, query)).into_response()
+}
+
 #[axum_macros::debug_handler]
 async fn commit_metadata(
+    user: auth::User,
     axum::extract::Path(path): axum::extract::Path<commit::Param>,
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<Json<commit::Metadata>> {
     dbg!(&path);
+    // nothing is constructed, only checking access
+    auth::charge(&state, &user, &path.repo(), 0, 0)?;
     commit::commit_metadata(state, path).map_err(|err| err.into())
 }
 
+async fn commit_history(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<commit::Param>,
+    axum::extract::Query(query): axum::extract::Query<commit::HistoryQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Json<commit::History>> {
+    dbg!(&path);
+    auth::charge(&state, &user, &path.repo(), query.charged_commits(), 0)?;
+    commit::commit_history(state, path, query).map_err(|err| err.into())
+}
+
 pub struct Timed<T> {
     pub(crate) time: f64,
     pub(crate) content: T,
@@ -0,0 +1,397 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use axum::{
+    async_trait,
+    error_handling::HandleErrorLayer,
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    BoxError, Json, Router,
+};
+use http::{request::Parts, StatusCode};
+use hyper_ast_cvs_git::git::{Forge, Repo};
+use serde::{Deserialize, Serialize};
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+use crate::SharedState;
+
+/// Limits on what a user can ask the server, `None` meaning unlimited.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default)]
+pub struct Quota {
+    pub commits: Option<usize>,
+    pub scripts: Option<usize>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct Usage {
+    pub commits: usize,
+    pub scripts: usize,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UserConfig {
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub admin: bool,
+    #[serde(flatten)]
+    pub quota: Quota,
+}
+
+/// Content of the access control file given with `--access`.
+///
+/// ```json
+/// {
+///   "allowed": ["github.com/INRIA/spoon"],
+///   "users": [{ "name": "admin", "token": "...", "admin": true }],
+///   "anonymous": { "commits": 100, "scripts": 20 }
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct AccessConfig {
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    #[serde(default)]
+    pub users: Vec<UserConfig>,
+    #[serde(default)]
+    pub anonymous: Quota,
+}
+
+impl AccessConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&s).map_err(|e| e.to_string())
+    }
+}
+
+struct UserState {
+    config: UserConfig,
+    usage: Mutex<Usage>,
+}
+
+/// Allow-list of repositories and users with their quotas.
+/// Repositories are denied by default.
+pub(crate) struct AccessControl {
+    allowed: RwLock<HashMap<Forge, HashSet<(String, String)>>>,
+    users: RwLock<HashMap<String, Arc<UserState>>>,
+    anonymous: RwLock<Arc<UserState>>,
+}
+
+impl Default for AccessControl {
+    fn default() -> Self {
+        Self {
+            allowed: Default::default(),
+            users: Default::default(),
+            anonymous: RwLock::new(Arc::new(UserState {
+                config: UserConfig {
+                    name: "anonymous".to_string(),
+                    token: "".to_string(),
+                    admin: false,
+                    quota: Default::default(),
+                },
+                usage: Default::default(),
+            })),
+        }
+    }
+}
+
+impl AccessControl {
+    pub(crate) fn configure(&self, config: AccessConfig) -> Result<(), String> {
+        for repo in &config.allowed {
+            self.allow(repo.parse()?);
+        }
+        for user in config.users {
+            self.add_user(user);
+        }
+        let anonymous = &mut self.anonymous.write().unwrap();
+        *anonymous = Arc::new(UserState {
+            config: UserConfig {
+                quota: config.anonymous,
+                ..anonymous.config.clone()
+            },
+            usage: Default::default(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn allow(&self, repo: Repo) {
+        self.allowed
+            .write()
+            .unwrap()
+            .entry(repo.forge)
+            .or_default()
+            .insert((repo.user, repo.name));
+    }
+
+    pub(crate) fn deny(&self, repo: &Repo) -> bool {
+        self.allowed
+            .write()
+            .unwrap()
+            .get_mut(&repo.forge)
+            .map_or(false, |x| x.remove(&(repo.user.clone(), repo.name.clone())))
+    }
+
+    pub(crate) fn is_allowed(&self, repo: &Repo) -> bool {
+        self.allowed
+            .read()
+            .unwrap()
+            .get(&repo.forge)
+            .map_or(false, |x| x.contains(&(repo.user.clone(), repo.name.clone())))
+    }
+
+    fn allowed(&self) -> Vec<String> {
+        let mut r: Vec<_> = self
+            .allowed
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|(forge, repos)| {
+                repos.iter().map(move |(user, name)| {
+                    format!("{}/{}/{}", forge.host(), user, name)
+                })
+            })
+            .collect();
+        r.sort();
+        r
+    }
+
+    pub(crate) fn add_user(&self, config: UserConfig) {
+        let token = config.token.clone();
+        let user = Arc::new(UserState {
+            config,
+            usage: Default::default(),
+        });
+        self.users.write().unwrap().insert(token, user);
+    }
+}
+
+/// The user making a request, identified by the `Authorization: Bearer <token>` header,
+/// or anonymous without it.
+pub struct User(Arc<UserState>);
+
+impl User {
+    /// Name of the user, `anonymous` without token.
+    pub(crate) fn name(&self) -> &str {
+        &self.0.config.name
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new(name: &str, quota: Quota) -> Self {
+        User(Arc::new(UserState {
+            config: UserConfig {
+                name: name.to_string(),
+                token: name.to_string(),
+                admin: false,
+                quota,
+            },
+            usage: Default::default(),
+        }))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<SharedState> for User {
+    type Rejection = AccessError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &SharedState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(http::header::AUTHORIZATION) else {
+            return Ok(User(state.access.anonymous.read().unwrap().clone()));
+        };
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|x| x.strip_prefix("Bearer "))
+            .ok_or_else(|| AccessError::Forbidden("malformed authorization".to_string()))?;
+        state
+            .access
+            .users
+            .read()
+            .unwrap()
+            .get(token.trim())
+            .cloned()
+            .map(User)
+            .ok_or_else(|| AccessError::Forbidden("unknown token".to_string()))
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub enum AccessError {
+    /// Repository not allowed or user unknown, or not an admin
+    Forbidden(String),
+    /// Quota of the user exceeded
+    QuotaExceeded { quota: Quota, usage: Usage },
+}
+
+impl IntoResponse for AccessError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AccessError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AccessError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+        };
+        let mut resp = Json(self).into_response();
+        *resp.status_mut() = status;
+        resp
+    }
+}
+
+/// Checks that `user` can access `repo`,
+/// then charges the given commits and scripts on the user's quota.
+pub(crate) fn charge(
+    state: &SharedState,
+    user: &User,
+    repo: &Repo,
+    commits: usize,
+    scripts: usize,
+) -> Result<(), AccessError> {
+    if !user.0.config.admin && !state.access.is_allowed(repo) {
+        return Err(AccessError::Forbidden(format!(
+            "{}/{} is not allowed",
+            repo.user, repo.name
+        )));
+    }
+    let quota = user.0.config.quota;
+    let usage = &mut user.0.usage.lock().unwrap();
+    let exceeds = |limit: Option<usize>, used: usize, more: usize| {
+        limit.map_or(false, |limit| used + more > limit)
+    };
+    if exceeds(quota.commits, usage.commits, commits)
+        || exceeds(quota.scripts, usage.scripts, scripts)
+    {
+        return Err(AccessError::QuotaExceeded {
+            quota,
+            usage: **usage,
+        });
+    }
+    usage.commits += commits;
+    usage.scripts += scripts;
+    Ok(())
+}
+
+pub(crate) fn admin(user: &User) -> Result<(), AccessError> {
+    if user.0.config.admin {
+        Ok(())
+    } else {
+        Err(AccessError::Forbidden("reserved to admins".to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RepoParam {
+    forge: String,
+    user: String,
+    name: String,
+}
+
+impl RepoParam {
+    pub(crate) fn repo(self) -> Result<Repo, AccessError> {
+        let forge: Forge = self.forge.parse().map_err(AccessError::Forbidden)?;
+        Ok(forge.repo(self.user, self.name))
+    }
+}
+
+#[derive(Serialize)]
+pub struct UserView {
+    name: String,
+    admin: bool,
+    quota: Quota,
+    usage: Usage,
+}
+
+pub fn admin_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            dbg!(e);
+        }))
+        .load_shed()
+        .concurrency_limit(8)
+        .buffer(20)
+        .rate_limit(10, Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
+    Router::new()
+        .route(
+            "/admin/allowed",
+            get(list_allowed).layer(service_config.clone()),
+        )
+        .route(
+            "/admin/allowed/:forge/:user/:name",
+            post(allow_repo)
+                .delete(deny_repo)
+                .layer(service_config.clone()),
+        )
+        .route(
+            "/admin/users",
+            get(list_users)
+                .post(add_user)
+                .layer(service_config.clone()),
+        )
+}
+
+async fn list_allowed(
+    user: User,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Result<Json<Vec<String>>, AccessError> {
+    admin(&user)?;
+    Ok(Json(state.access.allowed()))
+}
+
+async fn allow_repo(
+    user: User,
+    axum::extract::Path(path): axum::extract::Path<RepoParam>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Result<StatusCode, AccessError> {
+    admin(&user)?;
+    state.access.allow(path.repo()?);
+    Ok(StatusCode::OK)
+}
+
+async fn deny_repo(
+    user: User,
+    axum::extract::Path(path): axum::extract::Path<RepoParam>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Result<StatusCode, AccessError> {
+    admin(&user)?;
+    if state.access.deny(&path.repo()?) {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+async fn list_users(
+    user: User,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Result<Json<Vec<UserView>>, AccessError> {
+    admin(&user)?;
+    let users = state.access.users.read().unwrap();
+    let mut r: Vec<_> = users
+        .values()
+        .map(|x| UserView {
+            name: x.config.name.clone(),
+            admin: x.config.admin,
+            quota: x.config.quota,
+            usage: *x.usage.lock().unwrap(),
+        })
+        .collect();
+    r.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(r))
+}
+
+async fn add_user(
+    user: User,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(config): axum::extract::Json<UserConfig>,
+) -> Result<StatusCode, AccessError> {
+    admin(&user)?;
+    state.access.add_user(config);
+    Ok(StatusCode::CREATED)
+}
@@ -0,0 +1,512 @@
+use axum::{response::IntoResponse, Json};
+use hyper_ast::{
+    position::compute_range,
+    store::defaults::NodeIdentifier,
+    types::{
+        AnyType, HyperType, IterableChildren, NodeStore, Shared, TypeStore, TypeTrait, WithChildren,
+    },
+};
+use hyper_ast_cvs_git::{
+    git::{MergeStrategy, Oid},
+    preprocessed::child_at_path_tracked,
+    processing::{ConfiguredRepo2, ConfiguredRepoTrait},
+};
+use hyper_ast_gen_ts_java as java;
+use hyper_diff::{
+    decompressed_tree_store::{
+        DecompressedWithParent, LazyDecompressedTreeStore, ShallowDecompressedTreeStore,
+    },
+    matchers::{mapping_store::MonoMappingStore, Mapper},
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::{matching, no_space, track::construct_with_parents, utils::get_pair_simp, SharedState};
+
+/// Default number of commits explored to blame the nodes of a file.
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BlameParam {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+    commit: String,
+    file: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BlameQuery {
+    /// maximum number of commits explored, defaults to 100
+    limit: Option<usize>,
+    #[serde(default)]
+    merges: MergeStrategy,
+}
+
+impl BlameQuery {
+    /// Commits explored at most, to be charged on the quota of the user.
+    /// Nothing is charged for invalid limits, as they are rejected.
+    pub(crate) fn charged_commits(&self) -> usize {
+        match self.limit.unwrap_or(DEFAULT_LIMIT) {
+            limit @ 1..=MAX_LIMIT => limit,
+            _ => 0,
+        }
+    }
+}
+
+/// Kind of the change that last affected a node.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Inserted,
+    /// the node itself or its content changed
+    Updated,
+    Moved,
+}
+
+/// A node of the blamed file, with the commit that last changed it.
+#[derive(Serialize, Debug)]
+pub struct BlamedNode {
+    kind: String,
+    /// offsets from the root of the blamed commit, with spaces like in `track_at_path`
+    path: Vec<usize>,
+    start: usize,
+    end: usize,
+    /// the commit that last changed the node,
+    /// or the oldest explored commit if the limit was reached first
+    commit: String,
+    /// None if the limit was reached before finding the change
+    change: Option<Change>,
+}
+
+#[derive(Serialize)]
+pub struct BlameResult {
+    pub compute_time: f64,
+    commits_processed: usize,
+    commit: String,
+    file: String,
+    nodes: Vec<BlamedNode>,
+}
+
+impl IntoResponse for BlameResult {
+    fn into_response(self) -> axum::response::Response {
+        let mut resp = serde_json::to_string(&self).unwrap().into_response();
+        let headers = resp.headers_mut();
+        headers.insert(
+            "Server-Timing",
+            format!("blame;desc=\"Compute Time\";dur={}", self.compute_time)
+                .parse()
+                .unwrap(),
+        );
+        resp
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct BlameError {
+    pub compute_time: f64,
+    commits_processed: usize,
+    #[serde(skip)]
+    kind: BlameErrorKind,
+    message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BlameErrorKind {
+    /// missing repository, commit or file
+    NotFound,
+    /// bad query, or the path is not a file
+    Invalid,
+    Internal,
+}
+
+impl IntoResponse for BlameError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self.kind {
+            BlameErrorKind::NotFound => http::StatusCode::NOT_FOUND,
+            BlameErrorKind::Invalid => http::StatusCode::BAD_REQUEST,
+            BlameErrorKind::Internal => http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let mut resp = Json(self).into_response();
+        *resp.status_mut() = status;
+        resp
+    }
+}
+
+/// A node still waiting for its blame, identified by its path without spaces relative to the file.
+struct Pending {
+    node: usize,
+    path: Vec<u16>,
+    change: Option<Change>,
+}
+
+/// Nodes of the file to blame, passed on to `commit`.
+struct Frontier {
+    commit: Oid,
+    /// offsets to the file from the root of `commit`
+    file: Vec<u16>,
+    nodes: Vec<Pending>,
+}
+
+/// Annotates the declarations and statements of a file with the commit that last changed them,
+/// like `git blame` but following nodes with the mappings of hyper_diff instead of lines.
+///
+/// Nodes are passed on to the first parent where they are unchanged,
+/// so with [`MergeStrategy::AllParents`] a node can be blamed on a commit of a merged branch.
+pub fn blame(
+    state: SharedState,
+    path: BlameParam,
+    query: BlameQuery,
+) -> Result<BlameResult, BlameError> {
+    let now = Instant::now();
+    let BlameParam {
+        forge,
+        user,
+        name,
+        commit,
+        file,
+    } = path;
+    let BlameQuery { limit, merges } = query;
+    let mut commits_processed = 0;
+    let error = |commits_processed, kind, message: String| BlameError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed,
+        kind,
+        message,
+    };
+    use BlameErrorKind::*;
+    let limit = match limit.unwrap_or(DEFAULT_LIMIT) {
+        limit @ 1..=MAX_LIMIT => limit,
+        limit => {
+            let message = format!("limit {} should be between 1 and {}", limit, MAX_LIMIT);
+            return Err(error(0, Invalid, message));
+        }
+    };
+    let repo_specifier = forge.repo(user, name);
+    let repo_handle = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_specifier)
+        .ok_or_else(|| error(0, NotFound, "missing config for repository".to_string()))?;
+    let mut repository = repo_handle
+        .fetch()
+        .map_err(|e| error(0, Internal, e.to_string()))?;
+
+    let (oid, _) = construct_with_parents(&state, &mut repository, &commit, merges)
+        .map_err(|e| error(0, NotFound, e))?;
+    let (mut nodes, frontier) = {
+        let repositories = state.repositories.read().unwrap();
+        let root = repositories
+            .get_commit(repository.config(), &oid)
+            .ok_or_else(|| error(0, Internal, format!("missing commit {}", oid)))?
+            .ast_root;
+        let stores = &repositories.processor.main_stores;
+        let (file_node, offsets_to_file) = child_at_path_tracked(stores, root, file.split("/"))
+            .ok_or_else(|| error(0, NotFound, format!("{} not found", file)))?;
+        let file_type = stores
+            .type_store
+            .resolve_type(&stores.node_store.resolve(file_node));
+        if !file_type.is_file() {
+            return Err(error(0, Invalid, format!("{} is not a file", file)));
+        }
+        let mut nodes = vec![];
+        let mut pending = vec![];
+        for (kind, path, no_spaces_path) in blamable_nodes(stores, file_node) {
+            let (start, end, _) = compute_range(file_node, &mut path.iter().copied(), stores);
+            pending.push(Pending {
+                node: nodes.len(),
+                path: no_spaces_path,
+                change: None,
+            });
+            nodes.push(BlamedNode {
+                kind,
+                path: offsets_to_file.iter().chain(path.iter()).copied().collect(),
+                start,
+                end,
+                commit: oid.to_string(),
+                change: None,
+            });
+        }
+        let frontier = Frontier {
+            commit: oid,
+            file: offsets_to_file.iter().map(|x| *x as u16).collect(),
+            nodes: pending,
+        };
+        (nodes, frontier)
+    };
+
+    let mut annotate = |pending: Vec<Pending>, commit: Oid, change: Option<Change>| {
+        for p in pending {
+            nodes[p.node].commit = commit.to_string();
+            nodes[p.node].change = change.or(p.change);
+        }
+    };
+    let mut frontiers = vec![frontier];
+    while let Some(frontier) = frontiers.pop() {
+        if frontier.nodes.is_empty() {
+            continue;
+        }
+        if commits_processed >= limit {
+            annotate(frontier.nodes, frontier.commit, None);
+            continue;
+        }
+        commits_processed += 1;
+        let (oid, parents) = construct_with_parents(
+            &state,
+            &mut repository,
+            &frontier.commit.to_string(),
+            merges,
+        )
+        .map_err(|e| error(commits_processed, Internal, e))?;
+        if parents.is_empty() {
+            // every remaining node appeared in the initial commit
+            annotate(frontier.nodes, oid, Some(Change::Inserted));
+            continue;
+        }
+        let mut pending = frontier.nodes;
+        for parent in parents {
+            if pending.is_empty() {
+                break;
+            }
+            let (file, carried, changed) =
+                blame_step(&state, &repository, oid, parent, &frontier.file, pending)
+                    .map_err(|e| error(commits_processed, Internal, e))?;
+            pending = changed;
+            if let Some(file) = file {
+                frontiers.push(Frontier {
+                    commit: parent,
+                    file,
+                    nodes: carried,
+                });
+            }
+        }
+        annotate(pending, oid, None);
+    }
+
+    Ok(BlameResult {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed,
+        commit: oid.to_string(),
+        file,
+        nodes,
+    })
+}
+
+/// Nodes annotated by the blame, ie. declarations and statements, in pre-order.
+/// Returns their type, their offsets in the file with spaces, and the same offsets without spaces.
+fn blamable_nodes(
+    stores: &hyper_ast_cvs_git::SimpleStores,
+    file_node: NodeIdentifier,
+) -> Vec<(String, Vec<usize>, Vec<u16>)> {
+    let mut result = vec![];
+    let mut stack = vec![(file_node, vec![], vec![])];
+    while let Some((id, path, no_spaces_path)) = stack.pop() {
+        let n = stores.node_store.resolve(id);
+        let t = stores.type_store.resolve_type(&n);
+        if id != file_node && is_blamable(&t) {
+            result.push((t.to_string(), path.clone(), no_spaces_path.clone()));
+        }
+        let Some(cs) = n.children() else {
+            continue;
+        };
+        let mut children = vec![];
+        let mut no_spaces_offset = 0;
+        for (i, x) in cs.iter_children().enumerate() {
+            let c = stores.node_store.resolve(*x);
+            if stores.type_store.resolve_type(&c).is_spaces() {
+                continue;
+            }
+            let mut path = path.clone();
+            path.push(i);
+            let mut no_spaces_path = no_spaces_path.clone();
+            no_spaces_path.push(no_spaces_offset);
+            no_spaces_offset += 1;
+            children.push((*x, path, no_spaces_path));
+        }
+        stack.extend(children.into_iter().rev());
+    }
+    result
+}
+
+/// Declarations and statements, blocks are left to the statements containing them.
+/// Other languages than Java only blame their type declarations.
+fn is_blamable(t: &AnyType) -> bool {
+    match t.as_any().downcast_ref::<java::types::Type>() {
+        Some(t) => {
+            t.is_type_declaration()
+                || t.is_value_member()
+                || t.is_executable_member()
+                || (t.is_statement() && !t.is_block_related())
+        }
+        None => t.as_shared() == Shared::TypeDeclaration,
+    }
+}
+
+/// Classifies the `pending` nodes of the file at `file` in `commit`, by mapping `commit` to `parent`.
+/// Returns the offsets to the file in `parent` with the nodes unchanged in `parent`,
+/// and the nodes that changed with `commit`.
+fn blame_step(
+    state: &SharedState,
+    repository: &ConfiguredRepo2,
+    commit: Oid,
+    parent: Oid,
+    file: &[u16],
+    pending: Vec<Pending>,
+) -> Result<(Option<Vec<u16>>, Vec<Pending>, Vec<Pending>), String> {
+    let repositories = state.repositories.read().unwrap();
+    let src_tr = repositories
+        .get_commit(repository.config(), &commit)
+        .ok_or_else(|| format!("missing commit {}", commit))?
+        .ast_root;
+    let dst_tr = repositories
+        .get_commit(repository.config(), &parent)
+        .ok_or_else(|| format!("missing commit {}", parent))?
+        .ast_root;
+    let with_spaces_stores = &repositories.processor.main_stores;
+
+    // most commits do not touch the file, nodes can be passed on without computing mappings
+    let file_src = node_at(with_spaces_stores, src_tr, file);
+    if file_src.is_some() && file_src == node_at(with_spaces_stores, dst_tr, file) {
+        let carried = pending
+            .into_iter()
+            .map(|p| Pending { change: None, ..p })
+            .collect();
+        return Ok((Some(file.to_vec()), carried, vec![]));
+    }
+
+    let stores = &no_space::as_nospaces(with_spaces_stores);
+    let node_store = &stores.node_store;
+    let pair = get_pair_simp(&state.partial_decomps, stores, &src_tr, &dst_tr);
+    let mapped = {
+        let mappings_cache = &state.mappings_alone;
+        use hyper_diff::matchers::mapping_store::MappingStore;
+        use hyper_diff::matchers::mapping_store::VecStore;
+        use hyper_diff::matchers::Mapping;
+        let hyperast = stores;
+        match mappings_cache.entry((src_tr, dst_tr)) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => entry.into_ref().downgrade(),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let mappings = VecStore::default();
+                let (src_arena, dst_arena) = (pair.0.get_mut(), pair.1.get_mut());
+                let mut mapper = Mapper {
+                    hyperast,
+                    mapping: Mapping {
+                        src_arena,
+                        dst_arena,
+                        mappings,
+                    },
+                };
+                mapper.mapping.mappings.topit(
+                    mapper.mapping.src_arena.len(),
+                    mapper.mapping.dst_arena.len(),
+                );
+                let vec_store = matching::full2(hyperast, mapper);
+                entry
+                    .insert((crate::MappingStage::Bottomup, vec_store))
+                    .downgrade()
+            }
+        }
+    };
+    let (src_arena, dst_arena) = (pair.0.get_mut(), pair.1.get_mut());
+    let mappings = &mapped.1;
+
+    let root = src_arena.root();
+    let file_idx = src_arena.child_decompressed(node_store, &root, file);
+    let file_dst = mappings.get_dst(&file_idx).map(|x| {
+        let x = dst_arena.decompress_to(node_store, &x);
+        dst_arena.path(&dst_arena.root(), &x)
+    });
+    let mut carried = vec![];
+    let mut changed = vec![];
+    for mut p in pending {
+        let path: Vec<u16> = file.iter().chain(p.path.iter()).copied().collect();
+        let idx = src_arena.child_decompressed(node_store, &root, &path);
+        let change = if let Some(x) = mappings.get_dst(&idx) {
+            let x = dst_arena.decompress_to(node_store, &x);
+            let moved_from = src_arena.parent(&idx).and_then(|p| mappings.get_dst(&p));
+            if src_arena.original(&idx) != dst_arena.original(&x) {
+                Change::Updated
+            } else if moved_from != dst_arena.parent(&x) {
+                Change::Moved
+            } else {
+                let path = dst_arena.path(&dst_arena.root(), &x);
+                match &file_dst {
+                    Some(f) if path.starts_with(f) => {
+                        p.path = path[f.len()..].to_vec();
+                        p.change = None;
+                        carried.push(p);
+                        continue;
+                    }
+                    // now in another file
+                    _ => Change::Moved,
+                }
+            }
+        } else {
+            Change::Inserted
+        };
+        // the first parent decides the kind of change, like for a regular diff
+        p.change.get_or_insert(change);
+        changed.push(p);
+    }
+    Ok((file_dst, carried, changed))
+}
+
+fn node_at(
+    stores: &hyper_ast_cvs_git::SimpleStores,
+    root: NodeIdentifier,
+    offsets: &[u16],
+) -> Option<NodeIdentifier> {
+    let mut x = root;
+    for o in offsets {
+        let n = stores.node_store.resolve(x);
+        x = n.child(o)?;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(kind: BlameErrorKind) -> http::StatusCode {
+        let e = BlameError {
+            compute_time: 0.,
+            commits_processed: 0,
+            kind,
+            message: String::new(),
+        };
+        e.into_response().status()
+    }
+
+    #[test]
+    fn error_status() {
+        assert_eq!(
+            status(BlameErrorKind::NotFound),
+            http::StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(BlameErrorKind::Invalid),
+            http::StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(BlameErrorKind::Internal),
+            http::StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn blamable_java_nodes() {
+        use java::types::Type;
+        let blamable = |t: Type| is_blamable(&java::types::as_any(&t));
+        assert!(blamable(Type::ClassDeclaration));
+        assert!(blamable(Type::MethodDeclaration));
+        assert!(blamable(Type::FieldDeclaration));
+        assert!(blamable(Type::LocalVariableDeclaration));
+        assert!(blamable(Type::IfStatement));
+        assert!(!blamable(Type::Block));
+        assert!(!blamable(Type::Identifier));
+        assert!(!blamable(Type::BinaryExpression));
+    }
+}
@@ -0,0 +1,175 @@
+use axum::response::IntoResponse;
+use http::{header, HeaderValue, StatusCode};
+use hyper_ast_cvs_git::call_graph::CallGraph;
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::SharedState;
+
+/// Default number of calls followed from the selected methods.
+const DEFAULT_DEPTH: usize = 1;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CallGraphParam {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+    commit: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    #[default]
+    Json,
+    Dot,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CallGraphQuery {
+    /// only keep the methods around the ones whose qualified name ends with it, eg. `A.m`
+    method: Option<String>,
+    /// number of calls followed from the selected methods, defaults to 1
+    depth: Option<usize>,
+    #[serde(default)]
+    format: Format,
+}
+
+pub struct CallGraphResult {
+    pub compute_time: f64,
+    format: Format,
+    content: Vec<u8>,
+}
+
+impl IntoResponse for CallGraphResult {
+    fn into_response(self) -> axum::response::Response {
+        let content_type = match self.format {
+            Format::Json => "application/json",
+            Format::Dot => "text/vnd.graphviz",
+        };
+        let mut resp = self.content.into_response();
+        let headers = resp.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        headers.insert(
+            "Server-Timing",
+            format!("calls;desc=\"Compute Time\";dur={}", self.compute_time)
+                .parse()
+                .unwrap(),
+        );
+        resp
+    }
+}
+
+/// Computes the calls between the methods of the java code at `commit`,
+/// see [`CallGraph`] for how invocations are resolved.
+///
+/// Unknown repositories, commits and methods are 404.
+pub fn call_graph(
+    state: SharedState,
+    path: CallGraphParam,
+    query: CallGraphQuery,
+) -> Result<CallGraphResult, (StatusCode, String)> {
+    let now = Instant::now();
+    let not_found = |e: String| (StatusCode::NOT_FOUND, e);
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    let CallGraphParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path;
+    let CallGraphQuery {
+        method,
+        depth,
+        format,
+    } = query;
+    let repo_spec = forge.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| not_found("missing config for repository".to_string()))?;
+    let mut repo = repo.fetch().map_err(|e| internal(e.to_string()))?;
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(&mut repo, "", &commit, 1)
+        .map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => not_found(e.to_string()),
+            _ => internal(e.to_string()),
+        })?;
+    let commit_oid = *commits
+        .first()
+        .ok_or_else(|| not_found(format!("{} not found", commit)))?;
+    let repositories = state.repositories.read().unwrap();
+    let root = repositories
+        .get_commit(&repo.config, &commit_oid)
+        .ok_or_else(|| internal(format!("missing commit {}", commit_oid)))?
+        .ast_root;
+    let stores = &repositories.processor.main_stores;
+    let md_cache = repositories
+        .processor
+        .java_md_cache()
+        .ok_or_else(|| not_found("no java code was processed".to_string()))?;
+    let mut graph = CallGraph::new(stores, md_cache, root);
+    if let Some(method) = &method {
+        let selected: Vec<usize> = graph.find(method).collect();
+        if selected.is_empty() {
+            return Err(not_found(format!("no method matching {}", method)));
+        }
+        graph = graph.around(selected, depth.unwrap_or(DEFAULT_DEPTH));
+    }
+    let mut content = vec![];
+    match format {
+        Format::Json => graph.write_json(stores, &mut content),
+        Format::Dot => graph.write_dot(&mut content),
+    }
+    .map_err(|e| internal(e.to_string()))?;
+    Ok(CallGraphResult {
+        compute_time: now.elapsed().as_secs_f64(),
+        format,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+    use hyper_ast_cvs_git::{git::Forge, processing::RepoConfig};
+
+    #[test]
+    fn unknown_repository_commit_or_method_is_not_found() {
+        let state = crate::SharedState::default();
+        let (dir, git) = repository("call_graph_not_found");
+        let java = "package p;\nclass A { void m() {} }\n";
+        let files = [("pom.xml", POM), ("src/main/java/p/A.java", java)];
+        let first = commit(&git, &files).to_string();
+        register(&state, "call_graph_not_found", &dir, RepoConfig::JavaMaven);
+        let param = |name: &str, commit: &str| CallGraphParam {
+            forge: Forge::Local,
+            user: "test".to_string(),
+            name: name.to_string(),
+            commit: commit.to_string(),
+        };
+        let query = |method: Option<&str>| CallGraphQuery {
+            method: method.map(str::to_string),
+            depth: None,
+            format: Format::Json,
+        };
+        let status = |param, query| {
+            call_graph(state.clone(), param, query)
+                .err()
+                .map(|(status, _)| status)
+        };
+        let not_found = Some(StatusCode::NOT_FOUND);
+        let name = "call_graph_not_found";
+        assert_eq!(status(param("missing", &first), query(None)), not_found);
+        let missing = "0123456789012345678901234567890123456789";
+        assert_eq!(status(param(name, missing), query(None)), not_found);
+        assert_eq!(status(param(name, &first), query(Some("B.m"))), not_found);
+        assert_eq!(status(param(name, &first), query(Some("A.m"))), None);
+    }
+}
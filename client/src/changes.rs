@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 use hyper_ast::{
+    position::CompressedTopoIndexes,
     store::defaults::NodeIdentifier,
     types::{HyperAST, HyperType, IterableChildren, TypeStore, WithChildren, WithStats},
 };
@@ -20,7 +21,7 @@ pub struct SrcChanges {
     name: String,
     commit: String,
     /// Global position of deleted elements
-    deletions: Vec<u32>, // TODO diff encode
+    deletions: CompressedTopoIndexes,
 }
 #[derive(Deserialize, Serialize)]
 pub struct DstChanges {
@@ -28,7 +29,19 @@ pub struct DstChanges {
     name: String,
     commit: String,
     /// Global position of added elements
-    additions: Vec<u32>, // TODO diff encode
+    additions: CompressedTopoIndexes,
+}
+
+impl SrcChanges {
+    pub(crate) fn deletions_count(&self) -> usize {
+        self.deletions.len()
+    }
+}
+
+impl DstChanges {
+    pub(crate) fn additions_count(&self) -> usize {
+        self.additions.len()
+    }
 }
 
 pub(crate) fn added_deleted(
@@ -143,13 +156,13 @@ pub(crate) fn added_deleted(
             user: repo_handle.spec().user.to_string(),
             name: repo_handle.spec().name.to_string(),
             commit: src_oid.to_string(),
-            deletions: unmapped_src,
+            deletions: unmapped_src.into(),
         },
         DstChanges {
             user: repo_handle.spec().user.to_string(),
             name: repo_handle.spec().name.to_string(),
             commit: dst_oid.to_string(),
-            additions: unmapped_dst,
+            additions: unmapped_dst.into(),
         },
     ))
 }
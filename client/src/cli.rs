@@ -17,8 +17,28 @@ pub(super) struct Options {
     /// 
     /// use the following syntax: <forge>/<user>/<name>:<config>
     /// example: github.com/INRIA/spoon:Java
+    ///
+    /// local and remote repositories also need a location: <forge>/<user>/<name>=<location>:<config>
+    /// example: local/me/project=/path/to/project:Java
     #[clap(short, long)]
     pub repository: Vec<RepoConfig>,
+
+    /// json file listing allowed repositories, users and their quotas
+    ///
+    /// repositories given with --repository are also allowed
+    #[clap(long)]
+    pub access: Option<std::path::PathBuf>,
+
+    /// refspec fetched when updating repositories (multiple uses)
+    ///
+    /// by default all branches and tags are fetched
+    /// example: +refs/heads/main:refs/heads/main
+    #[clap(long)]
+    pub refspec: Vec<String>,
+
+    /// json file where repositories added through the api are persisted
+    #[clap(long, default_value = "repositories.json")]
+    pub registry: std::path::PathBuf,
 }
 
 pub(super) struct RepoConfig {
@@ -30,7 +50,8 @@ impl std::str::FromStr for RepoConfig {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (repo, config) = s.split_once(":").ok_or("")?;
+        // the location of the repository can contain ':'
+        let (repo, config) = s.rsplit_once(":").ok_or("")?;
         let repo = repo.parse()?;
         let config = config.parse()?;
 
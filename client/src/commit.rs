@@ -1,26 +1,41 @@
-use std::{fmt::Display, rc::Rc};
+use std::{collections::HashMap, fmt::Display, rc::Rc};
 
 use axum::{body::HttpBody, Json};
 // use hyper_ast::types::LabelStore;
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    types::{HyperType, IterableChildren, Labeled, NodeStore, TypeStore, WithChildren},
+};
 use hyper_ast_cvs_git::{
-    git::{fetch_github_repository, retrieve_commit},
+    git::{commits_between_with, retrieve_commit, Commit, MergeStrategy, Oid, Repo},
     preprocessed::child_at_path,
+    processing::{ConfiguredRepoHandle2, ConfiguredRepoTrait},
+    SimpleStores,
 };
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
-use crate::SharedState;
+use crate::{changes, SharedState};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Param {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
-    /// either a commit id or a tag
+    /// a commit id, a branch, a tag or a revspec like `HEAD~2`
     version: String,
 }
 
+impl Param {
+    pub(crate) fn repo(&self) -> Repo {
+        self.forge.repo(&self.user, &self.name)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Metadata {
+    /// id of the commit that version resolved to
+    id: String,
     /// commit message
     message: Option<String>,
     /// parents commits
@@ -32,34 +47,282 @@ pub struct Metadata {
     timezone: i32,
     /// seconds
     time: i64,
+    author: Author,
+    /// only available if the HyperAST of the commit was already constructed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    processing: Option<Processing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Author {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Processing {
+    /// time spent constructing the HyperAST of the commit, in nanoseconds
+    time: u128,
+    /// memory used while constructing the HyperAST of the commit, in bytes
+    memory_used: isize,
+    /// changes with the first parent, if it was also constructed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<ChangesSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangesSummary {
+    files_changed: usize,
+    /// only computed on demand as it requires matching the whole commits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes_inserted: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes_deleted: Option<usize>,
 }
 
-pub fn commit_metadata(_state: SharedState, path: Param) -> Result<Json<Metadata>, String> {
+pub fn commit_metadata(state: SharedState, path: Param) -> Result<Json<Metadata>, String> {
     let Param {
+        forge,
         user,
         name,
         version,
     } = path.clone();
-    let repo = fetch_github_repository(&format!("{}/{}", user, name));
+    let repo_spec = forge.repo(&user, &name);
+    // configured repositories know where local and remote repositories are
+    let repo_handle = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec.clone());
+    let repo_spec = repo_handle.as_ref().map_or(repo_spec, |x| x.spec.clone());
+    let repo = repo_spec.fetch().map_err(|e| e.to_string())?;
     log::warn!("done cloning {user}/{name}");
     let commit = retrieve_commit(&repo, &version);
     let commit = commit.map_err(|err| err.to_string())?;
     log::warn!("done retrieving version {version}");
 
+    let processing = repo_handle
+        .as_ref()
+        .and_then(|handle| processing(&state, handle, &commit, false));
+    Ok(Json(metadata(&commit, processing)))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryQuery {
+    /// the `next` commit of the previous page, the history starts at `version` if absent
+    cursor: Option<String>,
+    /// defaults to 20, at most 100
+    limit: Option<usize>,
+    #[serde(default)]
+    merges: MergeStrategy,
+    /// also count inserted and deleted nodes, computing the mappings if needed
+    #[serde(default)]
+    changes: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct History {
+    commits: Vec<Metadata>,
+    /// cursor of the next page, absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<String>,
+}
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+/// Commits walked at most for a page, including the ones skipped to reach the cursor.
+const MAX_WALK: usize = 10_000;
+
+impl HistoryQuery {
+    pub(crate) fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Commits whose mappings might be computed, to be charged on the quota of the user.
+    pub(crate) fn charged_commits(&self) -> usize {
+        if self.changes {
+            self.limit()
+        } else {
+            0
+        }
+    }
+}
+
+/// Lists the commits reachable from `version`, most recent first, one page at a time.
+///
+/// The cursor is the last commit of the previous page,
+/// so pages stay consistent when new commits are pushed on a branch given as `version`.
+pub fn commit_history(
+    state: SharedState,
+    path: Param,
+    query: HistoryQuery,
+) -> Result<Json<History>, String> {
+    let Param {
+        forge,
+        user,
+        name,
+        version,
+    } = path;
+    let limit = query.limit();
+    let HistoryQuery {
+        cursor,
+        merges,
+        changes,
+        ..
+    } = query;
+    let repo_spec = forge.repo(&user, &name);
+    let repo_handle = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec.clone());
+    let repo_spec = repo_handle.as_ref().map_or(repo_spec, |x| x.spec.clone());
+    let repo = repo_spec.fetch().map_err(|e| e.to_string())?;
+    log::warn!("done cloning {user}/{name}");
+
+    let mut walk = commits_between_with(&repo, "", &version, merges)
+        .map_err(|e| e.to_string())?
+        .take(MAX_WALK);
+    if let Some(cursor) = &cursor {
+        let cursor = Oid::from_str(cursor).map_err(|e| e.to_string())?;
+        // the whole history is not in topological order with all parents,
+        // so the walk restarts at `version` instead of `cursor`
+        loop {
+            match walk.next() {
+                Some(Ok(oid)) if oid == cursor => break,
+                Some(Ok(_)) => (),
+                Some(Err(e)) => return Err(e.to_string()),
+                None => {
+                    return Err(format!(
+                        "{} is not in the {} last commits of {}",
+                        cursor, MAX_WALK, version
+                    ))
+                }
+            }
+        }
+    }
+    let mut oids = walk
+        .take(limit + 1)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let has_next = oids.len() > limit;
+    oids.truncate(limit);
+    let next = has_next.then(|| oids.last().unwrap().to_string());
+
+    let mut commits = vec![];
+    for oid in oids {
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let processing = repo_handle
+            .as_ref()
+            .and_then(|handle| processing(&state, handle, &commit, changes));
+        commits.push(metadata(&commit, processing));
+    }
+    Ok(Json(History { commits, next }))
+}
+
+fn metadata(commit: &Commit, processing: Option<Processing>) -> Metadata {
     let time = commit.time();
     let timezone = time.offset_minutes();
     let time = time.seconds();
     let tree = commit.tree().ok().map(|x| x.id().to_string());
     let parents = commit.parent_ids().map(|x| x.to_string()).collect();
     let message = commit.message().map(|s| s.to_string());
-
-    Ok(Json(Metadata {
+    let author = commit.author();
+    let author = Author {
+        name: author.name().map(|s| s.to_string()),
+        email: author.email().map(|s| s.to_string()),
+    };
+    Metadata {
+        id: commit.id().to_string(),
         message,
         parents,
         tree,
         timezone,
         time,
-    }))
+        author,
+        processing,
+    }
+}
+
+/// Statistics about the construction of `commit`, and its changes if its first parent was constructed.
+/// Nothing is constructed here, listing commits should stay cheap.
+fn processing(
+    state: &SharedState,
+    handle: &ConfiguredRepoHandle2,
+    commit: &Commit,
+    with_nodes: bool,
+) -> Option<Processing> {
+    let parent = commit.parent_id(0).ok();
+    let (mut processing, parent) = {
+        let repositories = state.repositories.read().unwrap();
+        let c = repositories.get_commit(handle.config(), &commit.id())?;
+        let parent = parent.and_then(|p| Some((p, repositories.get_commit(handle.config(), &p)?)));
+        let changes = parent.map(|(_, p)| ChangesSummary {
+            files_changed: files_changed(
+                &repositories.processor.main_stores,
+                p.ast_root,
+                c.ast_root,
+            ),
+            nodes_inserted: None,
+            nodes_deleted: None,
+        });
+        let processing = Processing {
+            time: c.processing_time(),
+            memory_used: c.memory_used().bytes(),
+            changes,
+        };
+        (processing, parent.map(|(p, _)| p))
+    };
+    if let (true, Some(parent), Some(summary)) = (with_nodes, parent, &mut processing.changes) {
+        // added_deleted locks the repositories by itself
+        if let Ok((src, dst)) = changes::added_deleted(state.clone(), handle, parent, commit.id()) {
+            summary.nodes_deleted = Some(src.deletions_count());
+            summary.nodes_inserted = Some(dst.additions_count());
+        }
+    }
+    Some(processing)
+}
+
+/// Counts the files that differ between two roots,
+/// directories are compared by name and identical ones are skipped thanks to hash-consing.
+fn files_changed(stores: &SimpleStores, src: NodeIdentifier, dst: NodeIdentifier) -> usize {
+    let is_dir = |x: &NodeIdentifier| {
+        let n = stores.node_store.resolve(*x);
+        stores.type_store.resolve_type(&n).is_directory()
+    };
+    let mut count = 0;
+    let mut stack = vec![(Some(src), Some(dst))];
+    while let Some((src, dst)) = stack.pop() {
+        if src == dst {
+            continue;
+        }
+        if src.filter(|x| !is_dir(x)).is_some() || dst.filter(|x| !is_dir(x)).is_some() {
+            count += 1;
+        }
+        let mut by_name: HashMap<LabelIdentifier, (Option<_>, Option<_>)> = HashMap::default();
+        for (x, is_src) in [(src, true), (dst, false)] {
+            let Some(x) = x.filter(is_dir) else {
+                continue;
+            };
+            let n = stores.node_store.resolve(x);
+            let Some(cs) = n.children() else {
+                continue;
+            };
+            for c in cs.iter_children() {
+                let Some(l) = stores.node_store.resolve(*c).try_get_label().copied() else {
+                    continue;
+                };
+                let e = by_name.entry(l).or_default();
+                if is_src {
+                    e.0 = Some(*c);
+                } else {
+                    e.1 = Some(*c);
+                }
+            }
+        }
+        stack.extend(by_name.into_values());
+    }
+    count
 }
 
 #[derive(Default)]
@@ -78,3 +341,57 @@ impl From<BuffOut> for String {
         value.buff
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+    use hyper_ast_cvs_git::{git::Forge, processing::RepoConfig};
+
+    #[test]
+    fn paginated_history() {
+        let (dir, git) = repository("history");
+        let a = "src/main/java/A.java";
+        let c1 = commit(&git, &[("pom.xml", POM), (a, "class A {}")]);
+        let c2 = commit(&git, &[("pom.xml", POM), (a, "class A { int i; }")]);
+        let c3 = commit(&git, &[("pom.xml", POM), (a, "class A { int j; }")]);
+        let state = crate::SharedState::default();
+        let spec = register(&state, "history", &dir, RepoConfig::JavaMaven);
+        {
+            // only the last 2 commits are constructed
+            let mut repositories = state.repositories.write().unwrap();
+            let mut repo = repositories.get_config(spec).unwrap().fetch().unwrap();
+            repositories
+                .pre_process_with_limit(&mut repo, "", &c3.to_string(), 2)
+                .unwrap();
+        }
+        let path = Param {
+            forge: Forge::Local,
+            user: "test".to_string(),
+            name: "history".to_string(),
+            version: "HEAD".to_string(),
+        };
+        let page = |query: &str| {
+            let query = serde_json::from_str(query).unwrap();
+            let Json(history) = commit_history(state.clone(), path.clone(), query).unwrap();
+            history
+        };
+        let first = page(r#"{"limit": 2}"#);
+        let ids: Vec<_> = first.commits.iter().map(|x| x.id.clone()).collect();
+        assert_eq!(ids, [c3.to_string(), c2.to_string()]);
+        assert_eq!(first.next, Some(c2.to_string()));
+        assert_eq!(first.commits[0].parents, [c2.to_string()]);
+        let processing = first.commits[0].processing.as_ref().unwrap();
+        let changes = processing.changes.as_ref().map(|x| x.files_changed);
+        assert_eq!(changes, Some(1));
+        // the parent of c2 was not constructed
+        let processing = first.commits[1].processing.as_ref().unwrap();
+        assert!(processing.changes.is_none());
+
+        let last = page(&format!(r#"{{"limit": 2, "cursor": "{}"}}"#, c2));
+        assert_eq!(last.commits.len(), 1);
+        assert_eq!(last.commits[0].id, c1.to_string());
+        assert!(last.commits[0].processing.is_none());
+        assert_eq!(last.next, None);
+    }
+}
@@ -0,0 +1,428 @@
+use axum::{response::IntoResponse, Json};
+use http::StatusCode;
+use hyper_ast::{
+    position::{compute_position, path_with_spaces},
+    store::defaults::NodeIdentifier,
+    types::{LabelStore, Labeled, NodeStore, TypeStore},
+};
+use hyper_ast_cvs_git::{git::Oid, preprocessed::child_at_path_tracked};
+use hyper_diff::{
+    actions::script_generator2::{Act, SimpleAction},
+    algorithms,
+    decompressed_tree_store::{DecompressedWithParent, ShallowDecompressedTreeStore},
+    matchers::mapping_store::{MappingStore, MonoMappingStore},
+    tree::tree_path::{CompressedTreePath, TreePath},
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::{no_space, SharedState};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiffParam {
+    pub(crate) forge: hyper_ast_cvs_git::git::Forge,
+    pub(crate) user: String,
+    pub(crate) name: String,
+    pub(crate) src: String,
+    pub(crate) dst: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiffQuery {
+    #[serde(default)]
+    matcher: Matcher,
+    /// only diff this subdirectory or file, eg. `src/main/java`
+    path: Option<String>,
+}
+
+/// The variants of the gumtree matcher provided by hyper_diff.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Matcher {
+    /// fully decompresses both trees before matching
+    Greedy,
+    /// decompresses the trees while matching, the default
+    #[default]
+    Lazy,
+    /// lazy top-down phase, then completes the trees for the bottom-up phase
+    #[serde(alias = "partial-lazy")]
+    PartialLazy,
+}
+
+/// Location of a node on one side of the diff.
+#[derive(Serialize, Debug)]
+pub struct Side {
+    file: String,
+    start: usize,
+    end: usize,
+    /// offsets from the root of the commit, with spaces like in `track_at_path`
+    path: Vec<usize>,
+}
+
+/// An action of the edit script, only the roots of inserted and deleted subtrees are listed.
+#[derive(Serialize, Debug)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum EditAction {
+    Insert {
+        dst: Side,
+    },
+    Delete {
+        src: Side,
+    },
+    Update {
+        src: Side,
+        dst: Side,
+        old: String,
+        new: String,
+    },
+    Move {
+        src: Side,
+        dst: Side,
+    },
+}
+
+/// An action of the edit script generated by hyper_diff.
+///
+/// Paths are offsets without spaces from the compared subtree,
+/// `path` is in the source tree and `mid` in the tree where previous actions were applied.
+#[derive(Serialize, Debug)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScriptAction {
+    Delete {
+        path: Vec<u16>,
+        mid: Vec<u16>,
+    },
+    Update {
+        path: Vec<u16>,
+        mid: Vec<u16>,
+        new: String,
+    },
+    Move {
+        path: Vec<u16>,
+        mid: Vec<u16>,
+        from: Vec<u16>,
+    },
+    MovUpd {
+        path: Vec<u16>,
+        mid: Vec<u16>,
+        from: Vec<u16>,
+        new: String,
+    },
+    Insert {
+        path: Vec<u16>,
+        mid: Vec<u16>,
+        #[serde(rename = "type")]
+        kind: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct DiffResult {
+    pub compute_time: f64,
+    src: String,
+    dst: String,
+    matcher: Matcher,
+    mappings: usize,
+    actions: Vec<EditAction>,
+    /// the edit script of hyper_diff, absent if it could not be generated
+    script: Option<Vec<ScriptAction>>,
+}
+
+impl IntoResponse for DiffResult {
+    fn into_response(self) -> axum::response::Response {
+        let mut resp = serde_json::to_string(&self).unwrap().into_response();
+        let headers = resp.headers_mut();
+        headers.insert(
+            "Server-Timing",
+            format!("diff;desc=\"Compute Time\";dur={}", self.compute_time)
+                .parse()
+                .unwrap(),
+        );
+        resp
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DiffError {
+    pub compute_time: f64,
+    message: String,
+    /// 404 for unknown repositories, commits or paths, 500 for failed fetches or constructions
+    #[serde(skip)]
+    status: StatusCode,
+}
+
+impl IntoResponse for DiffError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status;
+        let mut resp = Json(self).into_response();
+        *resp.status_mut() = status;
+        resp
+    }
+}
+
+/// Computes the edit script between the `src` and `dst` commits with the selected [`Matcher`].
+///
+/// The actions are derived from the mappings, like for `script-diff`:
+/// unmapped nodes are inserted or deleted, mapped nodes are updated if their label changed,
+/// and moved if their parent is not mapped to the parent of their counterpart.
+pub fn diff(
+    state: SharedState,
+    path: DiffParam,
+    query: DiffQuery,
+) -> Result<DiffResult, DiffError> {
+    let now = Instant::now();
+    let error = |status: StatusCode, message: String| DiffError {
+        compute_time: now.elapsed().as_secs_f64(),
+        message,
+        status,
+    };
+    let not_found = |message: String| error(StatusCode::NOT_FOUND, message);
+    let git_error = |e: git2::Error| match e.code() {
+        git2::ErrorCode::NotFound => not_found(e.to_string()),
+        _ => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let DiffParam {
+        forge,
+        user,
+        name,
+        src,
+        dst,
+    } = path;
+    let DiffQuery { matcher, path } = query;
+    let repo_spec = forge.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| not_found("missing config for repository".to_string()))?;
+    let mut repo = repo
+        .fetch()
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut construct = |commit: &str| -> Result<Oid, DiffError> {
+        let commits = state
+            .repositories
+            .write()
+            .unwrap()
+            .pre_process_with_limit(&mut repo, "", commit, 1)
+            .map_err(git_error)?;
+        commits
+            .first()
+            .copied()
+            .ok_or_else(|| not_found(format!("{} not found", commit)))
+    };
+    let src_oid = construct(&src)?;
+    let dst_oid = construct(&dst)?;
+    log::warn!(
+        "done construction of {src_oid} and {dst_oid} in {}",
+        repo.spec
+    );
+
+    let config = repo.config;
+
+    let ((src_root, src_tr, src_prefix), (dst_root, dst_tr, dst_prefix)) = {
+        let repositories = state.repositories.read().unwrap();
+        let with_spaces_stores = &repositories.processor.main_stores;
+        let subtree = |oid: &Oid| {
+            let root = repositories
+                .get_commit(&config, oid)
+                .ok_or_else(|| {
+                    let message = format!("missing commit {}", oid);
+                    error(StatusCode::INTERNAL_SERVER_ERROR, message)
+                })?
+                .ast_root;
+            match &path {
+                None => Ok((root, root, vec![])),
+                Some(p) => {
+                    let (node, offsets) =
+                        child_at_path_tracked(with_spaces_stores, root, p.split("/"))
+                            .ok_or_else(|| not_found(format!("{} not found", p)))?;
+                    // in directories offsets with spaces are the same as without
+                    let offsets: Vec<u16> = offsets.into_iter().map(|x| x as u16).collect();
+                    Ok((root, node, offsets))
+                }
+            }
+        };
+        (subtree(&src_oid)?, subtree(&dst_oid)?)
+    };
+
+    let mut result = DiffResult {
+        compute_time: 0.,
+        src: src_oid.to_string(),
+        dst: dst_oid.to_string(),
+        matcher,
+        mappings: 0,
+        actions: vec![],
+        script: None,
+    };
+    if src_tr == dst_tr {
+        // hash-consing, nothing changed in the compared subtrees
+        result.compute_time = now.elapsed().as_secs_f64();
+        return Ok(result);
+    }
+
+    // The matchers read the nodes lazily from the shared stores, which cannot be cloned,
+    // so the read guard is held during the whole matching,
+    // until the actions are converted to owned values.
+    // It is released before serializing the response.
+    let repositories = state.repositories.read().unwrap();
+    let with_spaces_stores = &repositories.processor.main_stores;
+    let stores = &no_space::as_nospaces(with_spaces_stores);
+    let node_store = &stores.node_store;
+    let diff = match matcher {
+        Matcher::Greedy => algorithms::gumtree::diff(stores, &src_tr, &dst_tr),
+        Matcher::Lazy => algorithms::gumtree_lazy::diff(stores, &src_tr, &dst_tr),
+        Matcher::PartialLazy => algorithms::gumtree_partial_lazy::diff(stores, &src_tr, &dst_tr),
+    };
+    let src_arena = &diff.mapper.mapping.src_arena;
+    let dst_arena = &diff.mapper.mapping.dst_arena;
+    let mappings = &diff.mapper.mapping.mappings;
+    result.mappings = mappings.len();
+
+    let side = |root: NodeIdentifier, prefix: &[u16], path: Vec<u16>| {
+        let no_spaces: Vec<u16> = prefix.iter().copied().chain(path).collect();
+        let (path,) = path_with_spaces(root, &mut no_spaces.into_iter(), with_spaces_stores);
+        let (pos, _) = compute_position(root, &mut path.iter().copied(), with_spaces_stores);
+        let range = pos.range();
+        Side {
+            file: pos.file().to_str().unwrap().to_string(),
+            start: range.start,
+            end: range.end,
+            path: path.into_iter().map(|x| x as usize).collect(),
+        }
+    };
+    let src_side = |x| side(src_root, &src_prefix, src_arena.path(&src_arena.root(), &x));
+    let dst_side = |x| side(dst_root, &dst_prefix, dst_arena.path(&dst_arena.root(), &x));
+    let label = |id: NodeIdentifier| {
+        with_spaces_stores
+            .node_store
+            .resolve(id)
+            .try_get_label()
+            .map(|l| with_spaces_stores.label_store.resolve(l).to_string())
+    };
+
+    // identical subtrees that are mapped together are skipped
+    let mut stack = vec![dst_arena.root()];
+    while let Some(x) = stack.pop() {
+        match mappings.get_src(&x) {
+            None => {
+                if dst_arena.parent(&x).map_or(true, |p| mappings.is_dst(&p)) {
+                    result.actions.push(EditAction::Insert { dst: dst_side(x) });
+                }
+            }
+            Some(s) => {
+                let (before, after) = (src_arena.original(&s), dst_arena.original(&x));
+                let moved =
+                    src_arena.parent(&s).and_then(|p| mappings.get_dst(&p)) != dst_arena.parent(&x);
+                if before == after && !moved {
+                    continue;
+                }
+                if moved {
+                    result.actions.push(EditAction::Move {
+                        src: src_side(s),
+                        dst: dst_side(x),
+                    });
+                }
+                let (old, new) = (label(before), label(after));
+                if old != new {
+                    result.actions.push(EditAction::Update {
+                        src: src_side(s),
+                        dst: dst_side(x),
+                        old: old.unwrap_or_default(),
+                        new: new.unwrap_or_default(),
+                    });
+                }
+            }
+        }
+        // inserted subtrees can still contain moved nodes
+        stack.extend(dst_arena.children(node_store, &x).into_iter().rev());
+    }
+    let mut stack = vec![src_arena.root()];
+    while let Some(x) = stack.pop() {
+        match mappings.get_dst(&x) {
+            None => {
+                if src_arena.parent(&x).map_or(true, |p| mappings.is_src(&p)) {
+                    result.actions.push(EditAction::Delete { src: src_side(x) });
+                }
+            }
+            Some(d) if src_arena.original(&x) == dst_arena.original(&d) => continue,
+            Some(_) => (),
+        }
+        stack.extend(src_arena.children(node_store, &x).into_iter().rev());
+    }
+
+    let offsets = |p: &CompressedTreePath<u16>| TreePath::iter(p).collect::<Vec<_>>();
+    let resolve_label = |l| with_spaces_stores.label_store.resolve(l).to_string();
+    let kind = |id: &NodeIdentifier| {
+        let node = with_spaces_stores.node_store.resolve(*id);
+        with_spaces_stores
+            .type_store
+            .resolve_type(&node)
+            .to_string()
+    };
+    result.script = diff.actions.as_ref().map(|actions| {
+        actions
+            .iter()
+            .map(|SimpleAction { path, action }| {
+                let (path, mid) = (offsets(&path.ori), offsets(&path.mid));
+                match action {
+                    Act::Delete {} => ScriptAction::Delete { path, mid },
+                    Act::Update { new } => ScriptAction::Update {
+                        path,
+                        mid,
+                        new: resolve_label(new),
+                    },
+                    Act::Move { from } => ScriptAction::Move {
+                        path,
+                        mid,
+                        from: offsets(&from.ori),
+                    },
+                    Act::MovUpd { from, new } => ScriptAction::MovUpd {
+                        path,
+                        mid,
+                        from: offsets(&from.ori),
+                        new: resolve_label(new),
+                    },
+                    Act::Insert { sub } => ScriptAction::Insert {
+                        path,
+                        mid,
+                        kind: kind(sub),
+                    },
+                }
+            })
+            .collect()
+    });
+    drop(diff);
+    drop(repositories);
+    result.compute_time = now.elapsed().as_secs_f64();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+    use hyper_ast_cvs_git::{git::Forge, processing::RepoConfig};
+
+    #[test]
+    fn unknown_repository_or_commit_is_not_found() {
+        let state = crate::SharedState::default();
+        let (dir, git) = repository("diff_not_found");
+        let first = commit(&git, &[("pom.xml", POM)]).to_string();
+        register(&state, "diff_not_found", &dir, RepoConfig::JavaMaven);
+        let param = |name: &str, dst: &str| DiffParam {
+            forge: Forge::Local,
+            user: "test".to_string(),
+            name: name.to_string(),
+            src: first.clone(),
+            dst: dst.to_string(),
+        };
+        let query = || serde_json::from_str::<DiffQuery>("{}").unwrap();
+        let status = |param| diff(state.clone(), param, query()).err().map(|e| e.status);
+        let not_found = Some(StatusCode::NOT_FOUND);
+        assert_eq!(status(param("missing", &first)), not_found);
+        let missing = "0123456789012345678901234567890123456789";
+        assert_eq!(status(param("diff_not_found", missing)), not_found);
+        assert_eq!(status(param("diff_not_found", &first)), None);
+    }
+}
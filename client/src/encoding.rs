@@ -0,0 +1,81 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::{header, request::Parts, HeaderValue, StatusCode};
+use serde::Serialize;
+
+/// Format of a response body, negotiated with the `Accept` header of the request.
+///
+/// Binary formats are much smaller for large payloads, eg. the changes attached to tracking results.
+/// Falls back to JSON for any other media type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl Encoding {
+    fn from_accept(accept: &str) -> Self {
+        for media in accept.split(',') {
+            match media.split(';').next().unwrap_or_default().trim() {
+                "application/cbor" => return Self::Cbor,
+                "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                    return Self::MessagePack
+                }
+                "application/json" => return Self::Json,
+                _ => (),
+            }
+        }
+        Self::Json
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Json => "application/json",
+            Encoding::Cbor => "application/cbor",
+            Encoding::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Serializes `value` in this format, with the matching `Content-Type`.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Response {
+        let body = match self {
+            Encoding::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Encoding::Cbor => {
+                let mut body = vec![];
+                ciborium::ser::into_writer(value, &mut body)
+                    .map(|_| body)
+                    .map_err(|e| e.to_string())
+            }
+            Encoding::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        };
+        match body {
+            Ok(body) => {
+                let mut resp = body.into_response();
+                resp.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(self.content_type()),
+                );
+                resp
+            }
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err).into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Encoding {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|x| x.to_str().ok())
+            .map_or(Encoding::Json, Encoding::from_accept))
+    }
+}
@@ -1,5 +1,16 @@
-use hyper_ast::types::LabelStore;
-use hyper_ast_cvs_git::{git::fetch_github_repository, preprocessed::child_at_path};
+use std::collections::HashSet;
+
+use http::StatusCode;
+use hyper_ast::{
+    nodes::TextSerializer,
+    position::compute_position,
+    store::defaults::NodeIdentifier,
+    types::{
+        AnyType, HyperType, IterableChildren, LabelStore, NodeStore, TypeStore, WithChildren,
+        WithStats,
+    },
+};
+use hyper_ast_cvs_git::preprocessed::child_at_path;
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
@@ -7,6 +18,7 @@ use crate::SharedState;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct FetchFileParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -16,19 +28,20 @@ pub struct FetchFileParam {
 pub fn from_hyper_ast(state: SharedState, path: FetchFileParam) -> Result<String, String> {
     let now = Instant::now();
     let FetchFileParam {
+        forge,
         user,
         name,
         commit,
         file,
     } = path.clone();
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
     log::warn!("done cloning {}", repo.spec);
     let commits = state
         .repositories
@@ -56,6 +69,243 @@ pub fn from_hyper_ast(state: SharedState, path: FetchFileParam) -> Result<String
     Ok(file.to_string())
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct FetchSourceParam {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+    commit: String,
+    /// offsets from the root of the commit, with spaces like in `track_at_path`
+    path: String,
+}
+
+/// Source text of a subtree.
+#[derive(Serialize, Debug)]
+pub struct Source {
+    kind: String,
+    text: String,
+    /// only known when the node is reached from a commit, as identical subtrees are shared
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+}
+
+/// Where a subtree is in its file,
+/// lines and columns start at 1 and columns are counted in characters like in most editors.
+#[derive(Serialize, Debug)]
+pub struct Location {
+    file: String,
+    start: usize,
+    end: usize,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+pub fn source_at_path(
+    state: SharedState,
+    path: FetchSourceParam,
+) -> Result<Source, (StatusCode, String)> {
+    let FetchSourceParam {
+        forge,
+        user,
+        name,
+        commit,
+        path,
+    } = path;
+    let not_found = |e: String| (StatusCode::NOT_FOUND, e);
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    let repo_spec = forge.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| not_found("missing config for repository".to_string()))?;
+    let mut repo = repo.fetch().map_err(|e| internal(e.to_string()))?;
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(&mut repo, "", &commit, 1)
+        .map_err(|e| not_found(e.to_string()))?;
+    let repositories = state.repositories.read().unwrap();
+    let root = repositories
+        .get_commit(&repo.config, &commits[0])
+        .ok_or_else(|| internal(format!("missing commit {}", commits[0])))?
+        .ast_root;
+    let stores = &repositories.processor.main_stores;
+    let offsets = path
+        .split("/")
+        .filter(|x| !x.is_empty())
+        .map(|x| x.parse())
+        .collect::<Result<Vec<u16>, _>>()
+        .map_err(|_| {
+            let message = format!("{} is not a path of offsets", path);
+            (StatusCode::BAD_REQUEST, message)
+        })?;
+    // compute_position panics on offsets without a child
+    if !leads_to_node(stores, root, &offsets) {
+        return Err(not_found(format!("{} not found", path)));
+    }
+
+    let (pos, node) = compute_position(root, &mut offsets.iter().copied(), stores);
+    let kind = node_kind(stores, node);
+    if kind.is_directory() {
+        let message = "the path leads to a directory, not to a node of a file";
+        return Err((StatusCode::BAD_REQUEST, message.to_string()));
+    }
+    let text = TextSerializer::new(stores, node).to_string();
+
+    let file = pos.file().to_str().unwrap().to_string();
+    let range = pos.range();
+    let file_node = child_at_path(stores, root, file.split("/"))
+        .ok_or_else(|| internal(format!("{} not found", file)))?;
+    let file_text = TextSerializer::new(stores, file_node).to_string();
+    let line_column = |offset: usize| {
+        let before = file_text
+            .get(..offset)
+            .ok_or_else(|| format!("{} is not a valid offset in {}", offset, file))?;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Ok::<_, String>((
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        ))
+    };
+    let (start_line, start_column) = line_column(range.start).map_err(internal)?;
+    let (end_line, end_column) = line_column(range.end).map_err(internal)?;
+    Ok(Source {
+        kind: kind.to_string(),
+        text,
+        location: Some(Location {
+            file,
+            start: range.start,
+            end: range.end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }),
+    })
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FetchSourceIdParam {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+    commit: String,
+    id: u64,
+}
+
+/// Serves the text of a node given its id, eg. as sent by `fetch`, without its location.
+/// As node ids are sequential, the node must be in `commit`, so that only the code of
+/// repositories the user can access is served.
+pub fn source_with_node_id(
+    state: SharedState,
+    path: FetchSourceIdParam,
+) -> Result<Source, (StatusCode, String)> {
+    let FetchSourceIdParam {
+        forge,
+        user,
+        name,
+        commit,
+        id,
+    } = path;
+    let not_found = |e: String| (StatusCode::NOT_FOUND, e);
+    let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    let repo_spec = forge.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| not_found("missing config for repository".to_string()))?;
+    let mut repo = repo.fetch().map_err(|e| internal(e.to_string()))?;
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(&mut repo, "", &commit, 1)
+        .map_err(|e| not_found(e.to_string()))?;
+    let repositories = state.repositories.read().unwrap();
+    let root = repositories
+        .get_commit(&repo.config, &commits[0])
+        .ok_or_else(|| internal(format!("missing commit {}", commits[0])))?
+        .ast_root;
+    let stores = &repositories.processor.main_stores;
+    let unknown = || not_found(format!("unknown node {} in {}", id, commit));
+    let id = stores.node_store.id_from_u64(id).ok_or_else(unknown)?;
+    if !contains(stores, root, id) {
+        return Err(unknown());
+    }
+    let kind = node_kind(stores, id);
+    if kind.is_directory() {
+        let message = "the node is a directory".to_string();
+        return Err((StatusCode::BAD_REQUEST, message));
+    }
+    Ok(Source {
+        kind: kind.to_string(),
+        text: TextSerializer::new(stores, id).to_string(),
+        location: None,
+    })
+}
+
+/// Whether `node` is in the subtree of `root`,
+/// only going down subtrees higher than `node` and through shared subtrees once.
+fn contains(
+    stores: &hyper_ast_cvs_git::SimpleStores,
+    root: NodeIdentifier,
+    node: NodeIdentifier,
+) -> bool {
+    let height = stores.node_store.resolve(node).height();
+    let mut visited = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(x) = stack.pop() {
+        if x == node {
+            return true;
+        }
+        if !visited.insert(x) {
+            continue;
+        }
+        let n = stores.node_store.resolve(x);
+        if n.height() <= height {
+            continue;
+        }
+        if let Some(cs) = n.children() {
+            stack.extend(cs.iter_children().copied());
+        }
+    }
+    false
+}
+
+fn leads_to_node(
+    stores: &hyper_ast_cvs_git::SimpleStores,
+    root: NodeIdentifier,
+    offsets: &[u16],
+) -> bool {
+    let mut node = root;
+    for o in offsets {
+        let n = stores.node_store.resolve(node);
+        if *o >= n.child_count() {
+            return false;
+        }
+        node = n.child(o).unwrap();
+    }
+    true
+}
+
+fn node_kind(stores: &hyper_ast_cvs_git::SimpleStores, id: NodeIdentifier) -> AnyType {
+    let n = stores.node_store.resolve(id);
+    stores.type_store.resolve_type(&n)
+}
+
+impl axum::response::IntoResponse for Source {
+    fn into_response(self) -> axum::response::Response {
+        axum::Json(self).into_response()
+    }
+}
+
 #[derive(Default)]
 struct BuffOut {
     buff: String,
@@ -72,3 +322,95 @@ impl From<BuffOut> for String {
         value.buff
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+    use hyper_ast::types::Labeled;
+    use hyper_ast_cvs_git::{
+        git::{Forge, Oid, Repo},
+        processing::RepoConfig,
+    };
+
+    static A: &str = "class A {\n    int i;\n}\n";
+
+    /// Constructs a repository with `A.java` as its only source file.
+    fn constructed(name: &str) -> (SharedState, Repo, Oid) {
+        let (dir, git) = repository(name);
+        let oid = commit(&git, &[("pom.xml", POM), ("src/main/java/A.java", A)]);
+        let state = SharedState::default();
+        let spec = register(&state, name, &dir, RepoConfig::JavaMaven);
+        let mut repositories = state.repositories.write().unwrap();
+        let handle = repositories.get_config(spec.clone()).unwrap();
+        let mut repo = handle.fetch().unwrap();
+        repositories
+            .pre_process_with_limit(&mut repo, "", &oid.to_string(), 1)
+            .unwrap();
+        drop(repositories);
+        (state, spec, oid)
+    }
+
+    /// Offsets, with spaces, of the file at `path` in `commit`.
+    fn offsets_of(state: &SharedState, spec: Repo, commit: Oid, path: &str) -> Vec<u16> {
+        let config = state.repositories.write().unwrap().get_config(spec);
+        let repositories = state.repositories.read().unwrap();
+        let stores = &repositories.processor.main_stores;
+        let mut node = repositories
+            .get_commit(&config.unwrap().config, &commit)
+            .unwrap()
+            .ast_root;
+        let mut offsets = vec![];
+        for name in path.split("/") {
+            let n = stores.node_store.resolve(node);
+            let (i, x) = n
+                .children()
+                .unwrap()
+                .iter_children()
+                .enumerate()
+                .find(|(_, x)| {
+                    let label = stores.node_store.resolve(**x).try_get_label().copied();
+                    label.map(|l| stores.label_store.resolve(&l)) == Some(name)
+                })
+                .unwrap();
+            offsets.push(i as u16);
+            node = *x;
+        }
+        offsets
+    }
+
+    fn path(offsets: &[u16]) -> String {
+        let offsets: Vec<_> = offsets.iter().map(|x| x.to_string()).collect();
+        offsets.join("/")
+    }
+
+    #[test]
+    fn source_and_location_at_path() {
+        let (state, spec, oid) = constructed("source_at_path");
+        let file = offsets_of(&state, spec.clone(), oid, "src/main/java/A.java");
+        let param = |path: String| FetchSourceParam {
+            forge: Forge::Local,
+            user: spec.user.clone(),
+            name: spec.name.clone(),
+            commit: oid.to_string(),
+            path,
+        };
+
+        let source = source_at_path(state.clone(), param(path(&file))).unwrap();
+        assert_eq!(source.text, A);
+        // the class declaration, without the trailing line break of the file
+        let class: Vec<_> = file.iter().copied().chain([0]).collect();
+        let source = source_at_path(state.clone(), param(path(&class))).unwrap();
+        assert_eq!(source.text, A.trim_end());
+        let location = source.location.unwrap();
+        assert_eq!(location.file, "src/main/java/A.java");
+        assert_eq!((location.start, location.end), (0, A.trim_end().len()));
+        assert_eq!((location.start_line, location.start_column), (1, 1));
+        assert_eq!((location.end_line, location.end_column), (3, 2));
+
+        let status = |path: String| source_at_path(state.clone(), param(path)).unwrap_err().0;
+        assert_eq!(status(path(&file[..1])), StatusCode::BAD_REQUEST);
+        assert_eq!(status("0/a".to_string()), StatusCode::BAD_REQUEST);
+        assert_eq!(status(path(&[u16::MAX])), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,649 @@
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+    routing::{get, post},
+    BoxError, Json, Router,
+};
+use dashmap::DashMap;
+use http::{HeaderValue, StatusCode};
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{HyperType, IterableChildren, TypeStore, WithChildren},
+};
+use hyper_ast_cvs_git::git::{
+    commits_between_with, resolve_commit, Forge, MergeStrategy, Oid, Repo,
+};
+use serde::{Deserialize, Serialize};
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+use crate::{auth, SharedState};
+
+pub(crate) type JobId = usize;
+
+/// Failed jobs are reported for this long, then their construction can be requested again.
+const FAILED_JOB_EXPIRY: Duration = Duration::from_secs(60);
+
+/// Identifies the commits to construct, can be extracted from the path of most routes.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Target {
+    forge: Forge,
+    user: String,
+    name: String,
+    commit: String,
+}
+
+impl Target {
+    pub(crate) fn new(forge: Forge, user: String, name: String, commit: String) -> Self {
+        Self {
+            forge,
+            user,
+            name,
+            commit,
+        }
+    }
+
+    pub(crate) fn repo(&self) -> Repo {
+        self.forge.repo(&self.user, &self.name)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnqueueQuery {
+    limit: Option<usize>,
+    /// which parents of merge commits are constructed
+    #[serde(default)]
+    merges: MergeStrategy,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Cancelled,
+    Failed(String),
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Progress {
+    pub commits_total: usize,
+    pub commits_done: usize,
+    /// Files in the constructed commits
+    pub files_parsed: usize,
+    /// Sum of the memory used by the construction of each commit, in bytes
+    pub memory_used: isize,
+    /// Oids of constructed commits, newest first
+    pub commits: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct JobView {
+    id: JobId,
+    user: String,
+    name: String,
+    /// Resolved commit id
+    commit: String,
+    limit: usize,
+    merges: MergeStrategy,
+    status: JobStatus,
+    progress: Progress,
+}
+
+pub(crate) struct Job {
+    /// Name of the user that requested the job
+    owner: String,
+    repo: Repo,
+    commit: String,
+    limit: usize,
+    merges: MergeStrategy,
+    cancel: AtomicBool,
+    state: Mutex<(JobStatus, Progress)>,
+    /// When the job stopped running
+    ended: Mutex<Option<Instant>>,
+}
+
+impl Job {
+    fn view(&self, id: JobId) -> JobView {
+        let (status, progress) = self.state.lock().unwrap().clone();
+        JobView {
+            id,
+            user: self.repo.user.clone(),
+            name: self.repo.name.clone(),
+            commit: self.commit.clone(),
+            limit: self.limit,
+            merges: self.merges,
+            status,
+            progress,
+        }
+    }
+    fn status(&self) -> JobStatus {
+        self.state.lock().unwrap().0.clone()
+    }
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+    /// Failed jobs expire so that their construction can be retried.
+    fn expired(&self) -> bool {
+        match (self.status(), *self.ended.lock().unwrap()) {
+            (JobStatus::Failed(_), Some(ended)) => ended.elapsed() > FAILED_JOB_EXPIRY,
+            _ => false,
+        }
+    }
+}
+
+/// Background construction of repositories,
+/// jobs are processed one at a time by a single worker.
+#[derive(Default)]
+pub(crate) struct Jobs {
+    jobs: DashMap<JobId, Arc<Job>>,
+    next: std::sync::atomic::AtomicUsize,
+    /// Pending jobs, in the order they were enqueued
+    queue: Mutex<VecDeque<JobId>>,
+    enqueued: Condvar,
+    worker: AtomicBool,
+}
+
+impl Jobs {
+    pub(crate) fn enqueue(
+        &self,
+        owner: &auth::User,
+        repo: Repo,
+        commit: String,
+        limit: usize,
+        merges: MergeStrategy,
+    ) -> JobId {
+        let id = self.insert(owner, repo, commit, limit, merges, JobStatus::Pending);
+        self.queue.lock().unwrap().push_back(id);
+        self.enqueued.notify_one();
+        id
+    }
+
+    /// Records commits that were already constructed, without going through the worker.
+    pub(crate) fn done(
+        &self,
+        owner: &auth::User,
+        repo: Repo,
+        commit: String,
+        limit: usize,
+        merges: MergeStrategy,
+    ) -> JobId {
+        self.insert(owner, repo, commit, limit, merges, JobStatus::Done)
+    }
+
+    fn insert(
+        &self,
+        owner: &auth::User,
+        repo: Repo,
+        commit: String,
+        limit: usize,
+        merges: MergeStrategy,
+        status: JobStatus,
+    ) -> JobId {
+        let id = self.next.fetch_add(1, Ordering::SeqCst);
+        let job = Job {
+            owner: owner.name().to_string(),
+            repo,
+            commit,
+            limit,
+            merges,
+            cancel: AtomicBool::new(false),
+            state: Mutex::new((status, Progress::default())),
+            ended: Mutex::new(None),
+        };
+        self.jobs.insert(id, Arc::new(job));
+        id
+    }
+
+    /// Waits for the next pending job.
+    fn pop(&self) -> JobId {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(id) = queue.pop_front() {
+                return id;
+            }
+            queue = self.enqueued.wait(queue).unwrap();
+        }
+    }
+
+    /// Cancels the job `id` of `owner`.
+    /// A pending job is taken out of the queue,
+    /// a running job stops before constructing its next commit.
+    pub(crate) fn cancel(&self, id: JobId, owner: &auth::User) -> Option<JobView> {
+        let job = self.get(id, owner)?;
+        if !matches!(job.status(), JobStatus::Pending | JobStatus::Running) {
+            return Some(job.view(id));
+        }
+        job.cancel.store(true, Ordering::SeqCst);
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(i) = queue.iter().position(|x| *x == id) {
+            queue.remove(i);
+            job.state.lock().unwrap().0 = JobStatus::Cancelled;
+            *job.ended.lock().unwrap() = Some(Instant::now());
+        }
+        Some(job.view(id))
+    }
+
+    /// The job `id` if it belongs to `owner`.
+    fn get(&self, id: JobId, owner: &auth::User) -> Option<Arc<Job>> {
+        let job = self.jobs.get(&id)?;
+        (job.owner == owner.name()).then(|| job.clone())
+    }
+
+    pub(crate) fn view(&self, id: JobId, owner: &auth::User) -> Option<JobView> {
+        self.get(id, owner).map(|job| job.view(id))
+    }
+
+    /// The jobs of `owner`.
+    pub(crate) fn views(&self, owner: &auth::User) -> Vec<JobView> {
+        let mut r: Vec<_> = (self.jobs.iter())
+            .filter(|x| x.owner == owner.name())
+            .map(|x| x.view(*x.key()))
+            .collect();
+        r.sort_by_key(|x| x.id);
+        r
+    }
+
+    /// Finds the latest job constructing at least `limit` commits from `commit`,
+    /// going through merges like `merges`, that was not cancelled and did not expire.
+    fn find(
+        &self,
+        repo: &Repo,
+        commit: &str,
+        limit: usize,
+        merges: MergeStrategy,
+    ) -> Option<(JobId, JobStatus)> {
+        self.jobs
+            .iter()
+            .filter(|x| &x.repo == repo && x.commit == commit && x.limit >= limit)
+            .filter(|x| x.merges == merges && !x.expired() && !x.cancelled())
+            .map(|x| (*x.key(), x.status()))
+            .filter(|(_, s)| s != &JobStatus::Cancelled)
+            .max_by_key(|(id, _)| *id)
+    }
+}
+
+/// Returned by routes when the requested commits are still under construction,
+/// or when their construction failed.
+/// Also returned by the jobs route when the commits were already constructed.
+#[derive(Serialize)]
+pub struct Pending {
+    job: JobId,
+    status: JobStatus,
+}
+
+impl IntoResponse for Pending {
+    fn into_response(self) -> Response {
+        let status = match self.status {
+            JobStatus::Done => StatusCode::OK,
+            JobStatus::Failed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::ACCEPTED,
+        };
+        let mut resp = Json(self).into_response();
+        *resp.status_mut() = status;
+        resp
+    }
+}
+
+/// The commit id a [`Target`] was resolved to,
+/// added to responses as a `x-commit` header.
+pub struct Resolved(pub Oid);
+
+impl IntoResponseParts for Resolved {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let value = HeaderValue::from_str(&self.0.to_string()).expect("an oid is a valid header");
+        res.headers_mut().insert("x-commit", value);
+        Ok(res)
+    }
+}
+
+/// Resolves the commit of `target`, that can be a branch, a tag or a revspec like `HEAD~2`.
+/// Full commit ids are not looked up, so the repository is only fetched for other revspecs.
+pub(crate) fn resolve(state: &SharedState, target: &Target) -> Result<Oid, Response> {
+    if target.commit.len() == 40 {
+        if let Ok(oid) = Oid::from_str(&target.commit) {
+            return Ok(oid);
+        }
+    }
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(target.repo())
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "missing config for repository".to_string(),
+            )
+                .into_response()
+        })?;
+    let repo = repo
+        .fetch()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+    resolve_commit(&repo.repo, &target.commit)
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()).into_response())
+}
+
+/// Ok if the `limit` commits preceding `target` are constructed,
+/// otherwise enqueue their construction (if not already done) and respond with the pending job.
+/// Failed jobs are retried once they expired.
+/// Enqueued commits are charged to the quota of `user`.
+///
+/// Jobs are identified by resolved commit ids, so that moving branches are constructed again.
+pub(crate) fn ready_or_enqueue(
+    state: &SharedState,
+    user: &auth::User,
+    target: &Target,
+    limit: usize,
+) -> Result<Resolved, Response> {
+    ready_or_enqueue_with(state, user, target, limit, MergeStrategy::default())
+}
+
+/// Like [`ready_or_enqueue`] but going through merge commits according to `merges`.
+pub(crate) fn ready_or_enqueue_with(
+    state: &SharedState,
+    user: &auth::User,
+    target: &Target,
+    limit: usize,
+    merges: MergeStrategy,
+) -> Result<Resolved, Response> {
+    let repo = target.repo();
+    auth::charge(state, user, &repo, 0, 0).map_err(|e| e.into_response())?;
+    let oid = resolve(state, target)?;
+    let commit = oid.to_string();
+    match state.jobs.find(&repo, &commit, limit, merges) {
+        Some((_, JobStatus::Done)) => Ok(Resolved(oid)),
+        Some((job, status)) => Err(Pending { job, status }.into_response()),
+        None if constructed(state, &repo, oid, limit, merges) => {
+            state.jobs.done(user, repo, commit, limit, merges);
+            Ok(Resolved(oid))
+        }
+        None => {
+            auth::charge(state, user, &repo, limit, 0).map_err(|e| e.into_response())?;
+            let job = state.jobs.enqueue(user, repo, commit, limit, merges);
+            Err(Pending {
+                job,
+                status: JobStatus::Pending,
+            }
+            .into_response())
+        }
+    }
+}
+
+/// Checks the processed commits,
+/// as commits can also be constructed without jobs, e.g. by the scripting routes.
+fn constructed(
+    state: &SharedState,
+    repo: &Repo,
+    oid: Oid,
+    limit: usize,
+    merges: MergeStrategy,
+) -> bool {
+    let Some(repo) = state.repositories.write().unwrap().get_config(repo.clone()) else {
+        return false;
+    };
+    let config = repo.config;
+    let is_constructed = |oid: &Oid| {
+        let repositories = state.repositories.read().unwrap();
+        repositories.get_commit(&config, oid).is_some()
+    };
+    if !is_constructed(&oid) {
+        return false;
+    } else if limit <= 1 {
+        return true;
+    }
+    let Ok(repo) = repo.fetch() else {
+        return false;
+    };
+    let Ok(rw) = commits_between_with(&repo.repo, "", &oid.to_string(), merges) else {
+        return false;
+    };
+    rw.take(limit)
+        .all(|oid| oid.map_or(false, |oid| is_constructed(&oid)))
+}
+
+/// Spawns the worker constructing enqueued jobs.
+pub(crate) fn spawn_worker(state: SharedState) {
+    let spawned = state.jobs.worker.swap(true, Ordering::SeqCst);
+    assert!(!spawned, "jobs worker already spawned");
+    tokio::task::spawn_blocking(move || loop {
+        let id = state.jobs.pop();
+        let Some(job) = state.jobs.jobs.get(&id).map(|x| x.clone()) else {
+            continue;
+        };
+        // cancelled after being taken out of the queue
+        let status = if job.cancelled() {
+            JobStatus::Cancelled
+        } else {
+            job.state.lock().unwrap().0 = JobStatus::Running;
+            match run(&state, &job) {
+                Ok(status) => status,
+                Err(e) => {
+                    log::error!("job {} on {} failed: {}", id, job.repo, e);
+                    JobStatus::Failed(e)
+                }
+            }
+        };
+        job.state.lock().unwrap().0 = status;
+        *job.ended.lock().unwrap() = Some(Instant::now());
+    });
+}
+
+/// Constructs commits one by one,
+/// so that the repositories are not locked for the whole job and it can be cancelled in between.
+fn run(state: &SharedState, job: &Job) -> Result<JobStatus, String> {
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(job.repo.clone())
+        .ok_or_else(|| "missing config for repository".to_string())?;
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
+    log::warn!("done cloning {}", &repo.spec);
+    let oids = commits_between_with(&repo.repo, "", &job.commit, job.merges)
+        .map_err(|e| e.to_string())?
+        .take(job.limit)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    job.state.lock().unwrap().1.commits_total = oids.len();
+    for oid in oids {
+        if job.cancelled() {
+            return Ok(JobStatus::Cancelled);
+        }
+        state
+            .repositories
+            .write()
+            .unwrap()
+            .pre_process_with_limit(&mut repo, "", &oid.to_string(), 1)
+            .map_err(|e| e.to_string())?;
+        let repositories = state.repositories.read().unwrap();
+        let commit = repositories
+            .get_commit(&repo.config, &oid)
+            .ok_or_else(|| format!("missing commit {} after construction", oid))?;
+        let files = count_files(&repositories.processor.main_stores, commit.ast_root);
+        let memory_used = commit.memory_used().bytes();
+        drop(repositories);
+        let progress = &mut job.state.lock().unwrap().1;
+        progress.commits_done += 1;
+        progress.files_parsed += files;
+        progress.memory_used += memory_used;
+        progress.commits.push(oid.to_string());
+    }
+    Ok(JobStatus::Done)
+}
+
+fn count_files(stores: &hyper_ast_cvs_git::SimpleStores, root: NodeIdentifier) -> usize {
+    let mut count = 0;
+    let mut stack = vec![root];
+    while let Some(id) = stack.pop() {
+        let n = stores.node_store.resolve(id);
+        let t = stores.type_store.resolve_type(&n);
+        if t.is_file() {
+            count += 1;
+        } else if t.is_directory() {
+            if let Some(cs) = n.children() {
+                stack.extend(cs.iter_children().copied());
+            }
+        }
+    }
+    count
+}
+
+pub fn jobs_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            dbg!(e);
+        }))
+        .load_shed()
+        .concurrency_limit(8)
+        .buffer(20)
+        .rate_limit(10, Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
+    Router::new()
+        .route(
+            "/jobs/:forge/:user/:name/:commit",
+            post(enqueue_job).layer(service_config.clone()),
+        )
+        .route("/jobs", get(list_jobs).layer(service_config.clone()))
+        .route(
+            "/job/:id",
+            get(job_status)
+                .delete(cancel_job)
+                .layer(service_config.clone()),
+        )
+}
+
+async fn enqueue_job(
+    user: auth::User,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<EnqueueQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Pending> {
+    let repo = target.repo();
+    let limit = query.limit.unwrap_or(1);
+    auth::charge(&state, &user, &repo, 0, 0)?;
+    let oid = resolve(&state, &target)?;
+    if constructed(&state, &repo, oid, limit, query.merges) {
+        let job = (state.jobs).done(&user, repo, oid.to_string(), limit, query.merges);
+        return Ok(Pending {
+            job,
+            status: JobStatus::Done,
+        });
+    }
+    auth::charge(&state, &user, &repo, limit, 0)?;
+    let job = (state.jobs).enqueue(&user, repo, oid.to_string(), limit, query.merges);
+    Ok(Pending {
+        job,
+        status: JobStatus::Pending,
+    })
+}
+
+/// Lists the jobs of the user.
+async fn list_jobs(
+    user: auth::User,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Json<Vec<JobView>> {
+    Json(state.jobs.views(&user))
+}
+
+/// Jobs of other users are not found.
+async fn job_status(
+    user: auth::User,
+    axum::extract::Path(id): axum::extract::Path<JobId>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Json<JobView>> {
+    state
+        .jobs
+        .view(id, &user)
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("no job {}", id)).into())
+}
+
+/// Only the user that requested a job can cancel it.
+async fn cancel_job(
+    user: auth::User,
+    axum::extract::Path(id): axum::extract::Path<JobId>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Json<JobView>> {
+    state
+        .jobs
+        .cancel(id, &user)
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("no job {}", id)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(name: &str) -> auth::User {
+        auth::User::new(name, Default::default())
+    }
+
+    #[test]
+    fn enqueue_and_cancel() {
+        let jobs = Jobs::default();
+        let (alice, bob) = (user("alice"), user("bob"));
+        let repo = Forge::Github.repo("INRIA", "spoon");
+        let merges = MergeStrategy::default();
+        let a = jobs.enqueue(&alice, repo.clone(), "c".to_string(), 10, merges);
+        let b = jobs.enqueue(&bob, repo.clone(), "d".to_string(), 10, merges);
+        assert_eq!(jobs.view(a, &alice).unwrap().status, JobStatus::Pending);
+        assert_eq!(
+            jobs.find(&repo, "c", 5, merges),
+            Some((a, JobStatus::Pending))
+        );
+        // jobs of other users are not visible
+        assert!(jobs.view(a, &bob).is_none());
+        let ids = |user| {
+            jobs.views(user)
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&alice), vec![a]);
+        assert_eq!(ids(&bob), vec![b]);
+
+        // only the owner can cancel
+        assert!(jobs.cancel(a, &bob).is_none());
+        assert_eq!(jobs.view(a, &alice).unwrap().status, JobStatus::Pending);
+        let cancelled = jobs.cancel(a, &alice).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert_eq!(jobs.find(&repo, "c", 5, merges), None);
+        // taken out of the queue
+        assert_eq!(jobs.pop(), b);
+        assert!(jobs.queue.lock().unwrap().is_empty());
+
+        // a job cancelled while running is only flagged, the worker stops it
+        jobs.jobs.get(&b).unwrap().state.lock().unwrap().0 = JobStatus::Running;
+        assert_eq!(jobs.cancel(b, &bob).unwrap().status, JobStatus::Running);
+        assert!(jobs.jobs.get(&b).unwrap().cancelled());
+        assert_eq!(jobs.find(&repo, "d", 10, merges), None);
+    }
+
+    #[test]
+    fn done_jobs_are_not_cancelled() {
+        let jobs = Jobs::default();
+        let alice = user("alice");
+        let repo = Forge::Github.repo("INRIA", "spoon");
+        let merges = MergeStrategy::default();
+        let a = jobs.done(&alice, repo.clone(), "c".to_string(), 2, merges);
+        assert_eq!(jobs.cancel(a, &alice).unwrap().status, JobStatus::Done);
+        assert_eq!(jobs.find(&repo, "c", 2, merges), Some((a, JobStatus::Done)));
+        // constructing more commits needs another job
+        assert_eq!(jobs.find(&repo, "c", 3, merges), None);
+    }
+}
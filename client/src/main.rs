@@ -18,8 +18,8 @@ use tower_http::cors::CorsLayer;
 
 use crate::{
     app::{
-        commit_metadata_route, fetch_code_route, fetch_git_file, scripting_app, track_code_route,
-        view_code_route,
+        call_graph_route, commit_metadata_route, diff_route, fetch_code_route, fetch_git_file,
+        scripting_app, track_code_route, view_code_route,
     },
     examples::{example_app, kv_store_app},
 };
@@ -27,13 +27,22 @@ use axum::{body::Bytes, Router};
 use hyper_ast::store::nodes::legion::NodeIdentifier;
 
 mod app;
+mod auth;
+mod blame;
+mod call_graph;
 mod changes;
 mod commit;
+mod diff;
+mod encoding;
 mod examples;
 mod fetch;
 mod file;
+mod jobs;
 mod matching;
+mod registry;
 mod scripting;
+#[cfg(test)]
+mod tests;
 mod track;
 mod utils;
 mod view;
@@ -44,7 +53,7 @@ mod cli;
 pub struct AppState {
     db: DashMap<String, Bytes>,
     repositories: RwLock<PreProcessedRepositories>,
-    // configs: RwLock<RepoConfigs>,
+    registry: registry::Registry,
     mappings: MappingCache,
     mappings_alone: MappingAloneCache,
     partial_decomps: PartialDecompCache,
@@ -59,6 +68,8 @@ pub struct AppState {
     )>,
     // Multiple shared docs
     doc2: ws::SharedDocs,
+    jobs: jobs::Jobs,
+    access: auth::AccessControl,
 }
 
 impl Default for AppState {
@@ -75,23 +86,13 @@ impl Default for AppState {
                 Default::default(),
             )),
             doc2: Default::default(),
+            jobs: Default::default(),
+            access: Default::default(),
+            registry: Default::default(),
         }
     }
 }
 
-// #[derive(Default)]
-// struct RepoConfigs(HashMap<hyper_ast_cvs_git::git::Repo, hyper_ast_cvs_git::processing::RepoConfig2>);
-// impl RepoConfigs {
-//     pub(crate) fn resolve(&self, specifier: hyper_ast_cvs_git::git::Repo) -> Option<ConfiguredRepoHandle> {
-//         let config = self.0
-//             .get(&specifier)?;
-//         Some(ConfiguredRepoHandle {
-//             spec: specifier,
-//             config: *config,
-//         })
-//     }
-// }
-
 pub(crate) type PartialDecompCache = DashMap<NodeIdentifier, DS<PersistedNode<NodeIdentifier>>>;
 pub(crate) type MappingAloneCache =
     DashMap<(NodeIdentifier, NodeIdentifier), (MappingStage, VecStore<u32>)>;
@@ -112,19 +113,11 @@ async fn main() {
     let opts = crate::cli::parse();
 
     let shared_state = SharedState::default();
-    {
-        use hyper_ast_cvs_git::processing::RepoConfig;
-        let mut repos = shared_state.repositories.write().unwrap();
-        repos.register_config(Forge::Github.repo("INRIA", "spoon"), RepoConfig::JavaMaven);
-        repos.register_config(
-            Forge::Github.repo("official-stockfish", "Stockfish"),
-            RepoConfig::CppMake,
-        );
-        repos.register_config(Forge::Github.repo("torvalds", "linux"), RepoConfig::CppMake);
-        opts.repository.iter().for_each(|x| {
-            repos.register_config(x.repo.clone(), x.config);
-        })
+    if let Err(e) = configure(&shared_state, &opts) {
+        eprintln!("invalid configuration: {}", e);
+        std::process::exit(1);
     }
+    jobs::spawn_worker(Arc::clone(&shared_state));
     let app = Router::new()
         .fallback(fallback)
         .route("/ws", axum::routing::get(ws::ws_handler))
@@ -135,14 +128,16 @@ async fn main() {
         .merge(view_code_route(Arc::clone(&shared_state)))
         .merge(fetch_code_route(Arc::clone(&shared_state)))
         .merge(commit_metadata_route(Arc::clone(&shared_state)))
+        .merge(diff_route(Arc::clone(&shared_state)))
+        .merge(call_graph_route(Arc::clone(&shared_state)))
+        .merge(jobs::jobs_route(Arc::clone(&shared_state)))
+        .merge(auth::admin_route(Arc::clone(&shared_state)))
+        .merge(registry::registry_route(Arc::clone(&shared_state)))
         .merge(example_app())
         .layer(CorsLayer::permissive()) // WARN unwanted for deployment
         .with_state(Arc::clone(&shared_state));
-    // TODOs auth admin to list pending constructions,
-    // all repositories are blacklised by default
-    // give provider per forge
+    // TODOs give provider per forge
     // to whitelist repositories either for all past commits or also all future commits
-    // manage users and quota
     tracing::debug!("listening on {}", opts.address);
     axum::Server::bind(&opts.address)
         .serve(app.into_make_service_with_connect_info::<SocketAddr>())
@@ -150,6 +145,35 @@ async fn main() {
         .await
         .unwrap();
 }
+/// Registers the hard-coded repositories, the ones given in cli and the persisted ones,
+/// then configures the access control.
+fn configure(st: &SharedState, opts: &cli::Options) -> Result<(), String> {
+    use hyper_ast_cvs_git::processing::RepoConfig;
+    use registry::Registry;
+    st.registry.set_refspecs(opts.refspec.clone());
+    let hard_coded = [
+        (Forge::Github.repo("INRIA", "spoon"), RepoConfig::JavaMaven),
+        (
+            Forge::Github.repo("official-stockfish", "Stockfish"),
+            RepoConfig::CppMake,
+        ),
+        (Forge::Github.repo("torvalds", "linux"), RepoConfig::CppMake),
+    ];
+    let cli = opts.repository.iter().map(|x| (x.repo.clone(), x.config));
+    for (repo, config) in hard_coded.into_iter().chain(cli) {
+        let context = format!("registering {}", repo.to_spec());
+        Registry::register(st, repo, config, false).map_err(|e| format!("{}: {:?}", context, e))?;
+    }
+    Registry::load(st, opts.registry.clone())
+        .map_err(|e| format!("loading registry {}: {:?}", opts.registry.display(), e))?;
+    if let Some(path) = &opts.access {
+        let context = |e| format!("loading access file {}: {}", path.display(), e);
+        let config = auth::AccessConfig::load(path).map_err(context)?;
+        st.access.configure(config).map_err(context)?;
+    }
+    Ok(())
+}
+
 pub(crate) use hyper_ast_cvs_git::no_space;
 /// axum handler for any request that fails to match the router routes.
 /// This implementation returns HTTP status code Not Found (404).
@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::Duration,
+};
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    BoxError, Json, Router,
+};
+use http::StatusCode;
+use hyper_ast_cvs_git::{
+    git::{Forge, Repo},
+    processing::RepoConfig,
+};
+use serde::{Deserialize, Serialize};
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+use crate::{auth, SharedState};
+
+/// A configured repository, as listed by the API and persisted on disk.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Entry {
+    /// eg. github.com/INRIA/spoon or local/me/project=/path/to/project
+    pub repo: String,
+    pub config: RepoConfig,
+    /// Only persisted entries are written to disk,
+    /// others come from the cli or are hard-coded.
+    #[serde(default, skip_deserializing)]
+    pub persisted: bool,
+}
+
+/// Registry of repository configurations,
+/// persisted entries are stored in a json file and reloaded at startup.
+#[derive(Default)]
+pub(crate) struct Registry {
+    path: RwLock<Option<PathBuf>>,
+    entries: RwLock<HashMap<Repo, (RepoConfig, bool)>>,
+    /// Refspecs given to the registered repositories that do not have their own
+    refspecs: RwLock<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub enum RegistryError {
+    Invalid(String),
+    Io(String),
+}
+
+impl IntoResponse for RegistryError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            RegistryError::Invalid(_) => StatusCode::BAD_REQUEST,
+            RegistryError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let mut resp = Json(self).into_response();
+        *resp.status_mut() = status;
+        resp
+    }
+}
+
+impl Registry {
+    /// Registers the repositories persisted at `path`, then persists future changes there.
+    pub(crate) fn load(state: &SharedState, path: PathBuf) -> Result<(), RegistryError> {
+        if path.exists() {
+            let s = std::fs::read_to_string(&path).map_err(|e| RegistryError::Io(e.to_string()))?;
+            let entries: Vec<Entry> =
+                serde_json::from_str(&s).map_err(|e| RegistryError::Invalid(e.to_string()))?;
+            for entry in entries {
+                let repo = entry.repo.parse().map_err(RegistryError::Invalid)?;
+                Self::register(state, repo, entry.config, true)?;
+            }
+        }
+        *state.registry.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /// Configures `repo` in the preprocessed repositories and allows it,
+    /// `persist` tells if it should be written to disk.
+    pub(crate) fn register(
+        state: &SharedState,
+        repo: Repo,
+        config: RepoConfig,
+        persist: bool,
+    ) -> Result<(), RegistryError> {
+        let repo = if repo.refspecs.is_empty() {
+            let refspecs = state.registry.refspecs.read().unwrap().clone();
+            repo.with_refspecs(refspecs)
+        } else {
+            repo
+        };
+        state
+            .repositories
+            .write()
+            .unwrap()
+            .register_config(repo.clone(), config);
+        state.access.allow(repo.clone());
+        let mut entries = state.registry.entries.write().unwrap();
+        // the key holds the location, but is equal to the previous one whatever its location
+        entries.remove(&repo);
+        entries.insert(repo, (config, persist));
+        drop(entries);
+        if persist {
+            state.registry.save()?;
+        }
+        Ok(())
+    }
+
+    /// Sets the refspecs fetched for the repositories registered from now on,
+    /// an empty list falls back to the [`hyper_ast_cvs_git::git::DEFAULT_REFSPECS`].
+    pub(crate) fn set_refspecs(&self, refspecs: Vec<String>) {
+        *self.refspecs.write().unwrap() = refspecs;
+    }
+
+    pub(crate) fn remove(state: &SharedState, repo: &Repo) -> Result<bool, RegistryError> {
+        let removed = state.registry.entries.write().unwrap().remove(repo);
+        state.repositories.write().unwrap().remove_config(repo);
+        state.access.deny(repo);
+        if let Some((_, true)) = removed {
+            state.registry.save()?;
+        }
+        Ok(removed.is_some())
+    }
+
+    pub(crate) fn entries(&self) -> Vec<Entry> {
+        self.entries_with(|_| true, true)
+    }
+
+    /// The entries of the repositories matching `keep`, with their location if `locations`.
+    fn entries_with(&self, keep: impl Fn(&Repo) -> bool, locations: bool) -> Vec<Entry> {
+        let mut r: Vec<_> = self
+            .entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(repo, _)| keep(repo))
+            .map(|(repo, (config, persisted))| Entry {
+                repo: if locations {
+                    repo.to_spec()
+                } else {
+                    repo.forge.repo(&repo.user, &repo.name).to_spec()
+                },
+                config: *config,
+                persisted: *persisted,
+            })
+            .collect();
+        r.sort_by(|a, b| a.repo.cmp(&b.repo));
+        r
+    }
+
+    fn save(&self) -> Result<(), RegistryError> {
+        let path = self.path.read().unwrap();
+        let Some(path) = path.as_deref() else {
+            return Ok(());
+        };
+        let entries: Vec<_> = self.entries().into_iter().filter(|x| x.persisted).collect();
+        write_entries(path, &entries)
+    }
+}
+
+fn write_entries(path: &Path, entries: &[Entry]) -> Result<(), RegistryError> {
+    let s = serde_json::to_string_pretty(entries).map_err(|e| RegistryError::Io(e.to_string()))?;
+    // write then rename to avoid corrupting the registry if interrupted
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, s).map_err(|e| RegistryError::Io(e.to_string()))?;
+    std::fs::rename(&tmp, path).map_err(|e| RegistryError::Io(e.to_string()))
+}
+
+#[derive(Deserialize)]
+pub struct AddRepo {
+    config: RepoConfig,
+    /// Path of local repositories or url of remote ones
+    location: Option<String>,
+}
+
+pub fn registry_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            dbg!(e);
+        }))
+        .load_shed()
+        .concurrency_limit(8)
+        .buffer(20)
+        .rate_limit(10, Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
+    Router::new()
+        .route(
+            "/repositories",
+            get(list_repositories).layer(service_config.clone()),
+        )
+        .route(
+            "/repositories/:forge/:user/:name",
+            post(add_repository)
+                .delete(remove_repository)
+                .layer(service_config.clone()),
+        )
+}
+
+/// Lists every repository to admins,
+/// other users only get the repositories they can access, without their location.
+async fn list_repositories(
+    user: auth::User,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> Json<Vec<Entry>> {
+    if auth::admin(&user).is_ok() {
+        return Json(state.registry.entries());
+    }
+    let allowed = |repo: &Repo| state.access.is_allowed(repo);
+    Json(state.registry.entries_with(allowed, false))
+}
+
+async fn add_repository(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<auth::RepoParam>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+    axum::extract::Json(body): axum::extract::Json<AddRepo>,
+) -> axum::response::Result<StatusCode> {
+    auth::admin(&user)?;
+    let repo = path.repo()?;
+    let repo = match body.location {
+        Some(location) => repo.with_location(location),
+        None if matches!(repo.forge, Forge::Local | Forge::Remote) => {
+            return Err(RegistryError::Invalid("missing location".to_string()).into())
+        }
+        None => repo,
+    };
+    Registry::register(&state, repo, body.config, true)?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn remove_repository(
+    user: auth::User,
+    axum::extract::Path(path): axum::extract::Path<auth::RepoParam>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<StatusCode> {
+    auth::admin(&user)?;
+    let repo = path.repo()?;
+    if Registry::remove(&state, &repo)? {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Registering a repository again replaces its location, also in the persisted registry.
+    #[test]
+    fn persist_registry() {
+        let dir = std::env::temp_dir().join(format!("hyperast_registry_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("registry.json");
+        let repo = Forge::Local.repo("me", "project");
+
+        let state = SharedState::default();
+        Registry::load(&state, path.clone()).unwrap();
+        let at = |location: &str| repo.clone().with_location(location);
+        Registry::register(&state, at("/old"), RepoConfig::JavaMaven, true).unwrap();
+        Registry::register(&state, at("/new"), RepoConfig::JavaMaven, true).unwrap();
+        let other = Forge::Github.repo("INRIA", "spoon");
+        Registry::register(&state, other.clone(), RepoConfig::JavaMaven, false).unwrap();
+
+        let reloaded = SharedState::default();
+        Registry::load(&reloaded, path.clone()).unwrap();
+        let entries = reloaded.registry.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].repo, "local/me/project=/new");
+        assert!(entries[0].persisted);
+        let spec = reloaded
+            .repositories
+            .write()
+            .unwrap()
+            .get_config(repo.clone());
+        assert_eq!(spec.unwrap().spec.location.as_deref(), Some("/new"));
+        assert!(reloaded.access.is_allowed(&repo));
+
+        Registry::remove(&reloaded, &repo).unwrap();
+        let reloaded = SharedState::default();
+        Registry::load(&reloaded, path).unwrap();
+        assert!(reloaded.registry.entries().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,3 +1,4 @@
+mod diff;
 mod estimate;
 mod finalize;
 mod fs_container;
@@ -16,6 +17,7 @@ use hyper_ast::{
     store::defaults::NodeIdentifier,
     types::{HyperType, LabelStore, Labeled, TypeStore, WithChildren, WithStats},
 };
+use hyper_ast_cvs_git::git::{commits_between_with, MergeStrategy, Oid};
 use num::ToPrimitive;
 use rhai::{
     packages::{BasicArrayPackage, CorePackage, Package},
@@ -26,6 +28,7 @@ use std::fmt::Display;
 
 #[derive(Deserialize, Clone)]
 pub struct ScriptingParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -38,6 +41,20 @@ pub struct ScriptContentDepth {
     commits: usize,
 }
 
+impl ScriptContentDepth {
+    pub fn commits(&self) -> usize {
+        self.commits
+    }
+}
+
+/// Query of the scripting routes going through multiple commits
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct ScriptingQuery {
+    /// which parents of merge commits are visited
+    #[serde(default)]
+    pub merges: MergeStrategy,
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ScriptContent {
     pub init: String,
@@ -117,13 +134,52 @@ pub fn simple_depth(
     script: ScriptContentDepth,
     state: SharedState,
     path: ScriptingParam,
+    merges: MergeStrategy,
 ) -> Result<Json<ComputeResults>, ScriptingError> {
+    let mut prepare_time = 0.;
+    let mut results = vec![];
+    simple_depth_streamed(
+        script,
+        state,
+        path,
+        merges,
+        |_, t| {
+            prepare_time += t;
+            true
+        },
+        |r| {
+            results.push(r);
+            true
+        },
+    )?;
+    let r = ComputeResults {
+        prepare_time,
+        results,
+    };
+    Ok(Json(r))
+}
+
+/// Evaluates scripts on each commit, emitting each result as soon as its commit is done.
+/// Commits are preprocessed one at a time, `prepared` being notified after each of them.
+/// Stops early if `prepared` or `emit` returns false, e.g. when the client disconnected.
+pub fn simple_depth_streamed(
+    script: ScriptContentDepth,
+    state: SharedState,
+    path: ScriptingParam,
+    merges: MergeStrategy,
+    mut prepared: impl FnMut(&Oid, f64) -> bool,
+    mut emit: impl FnMut(Result<ComputeResultIdentified, String>) -> bool,
+) -> Result<(), ScriptingError> {
     let ScriptContentDepth {
         inner: script,
         commits,
     } = script;
-    let now = Instant::now();
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -136,7 +192,7 @@ pub fn simple_depth(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
@@ -155,22 +211,84 @@ pub fn simple_depth(
         }
     };
     // .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let mut repo = repo.fetch();
+    let mut repo = repo
+        .fetch()
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
     log::warn!("done cloning {}", &repo.spec);
+    let commits: Vec<Oid> = commits_between_with(&repo.repo, "", &commit, merges)
+        .and_then(|rw| rw.take(commits).collect())
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
+    for commit_oid in &commits {
+        let now = Instant::now();
+        // only locking the repositories for one commit at a time
+        state
+            .repositories
+            .write()
+            .unwrap()
+            .pre_process_with_limit(&mut repo, "", &commit_oid.to_string(), 1)
+            .map_err(|e| ScriptingError::Other(e.to_string()))?;
+        if !prepared(commit_oid, now.elapsed().as_secs_f64()) {
+            log::info!("stopped preprocessing of {} at {}", repo.spec, commit_oid);
+            break;
+        }
+        let now = Instant::now();
+        let r = simple_aux(
+            state.clone(),
+            &mut repo,
+            commit_oid,
+            &engine,
+            &init_script,
+            &filter_script,
+            &accumulate_script,
+            now,
+        );
+        let r = match r {
+            Ok(r) => Ok(ComputeResultIdentified {
+                commit: commit_oid.to_string(),
+                inner: r,
+            }),
+            Err(ScriptingError::AtEvaluation(e)) => Err(e),
+            Err(e) => return Err(e),
+        };
+        if !emit(r) {
+            log::info!("stopped scripting on {} before {}", repo.spec, commit_oid);
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Like [`simple_depth`] but evaluates scripts on the changes between consecutive commits,
+/// only visiting inserted, deleted, updated or moved nodes and their ancestors.
+pub fn simple_diff(
+    script: ScriptContentDepth,
+    state: SharedState,
+    path: ScriptingParam,
+    merges: MergeStrategy,
+) -> Result<Json<ComputeResults>, ScriptingError> {
+    let ScriptContentDepth {
+        inner: script,
+        commits,
+    } = script;
+    let now = Instant::now();
+    let (commit, engine, init_script, accumulate_script, filter_script, mut repo) =
+        simple_prepare(path, script, &state)?;
     let commits = state
         .repositories
         .write()
         .unwrap()
-        .pre_process_with_limit(&mut repo, "", &commit, commits)
-        .unwrap();
+        .pre_process_with_strategy(&mut repo, "", &commit, commits + 1, merges)
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
     let prepare_time = now.elapsed().as_secs_f64();
     let mut results = vec![];
-    for commit_oid in &commits {
+    for w in commits.windows(2) {
+        let [dst_oid, src_oid] = w else { unreachable!() };
         let now = Instant::now();
-        let r = simple_aux(
+        let r = diff::simple_diff_aux(
             state.clone(),
-            &mut repo,
-            commit_oid,
+            &repo,
+            src_oid,
+            dst_oid,
             &engine,
             &init_script,
             &filter_script,
@@ -179,7 +297,7 @@ pub fn simple_depth(
         );
         match r {
             Ok(r) => results.push(Ok(ComputeResultIdentified {
-                commit: commit_oid.to_string(),
+                commit: dst_oid.to_string(),
                 inner: r,
             })),
             Err(ScriptingError::AtEvaluation(e)) => results.push(Err(e)),
@@ -208,7 +326,12 @@ fn simple_prepare(
     ),
     ScriptingError,
 > {
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -221,14 +344,16 @@ fn simple_prepare(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let repo = repo.fetch();
+    let repo = repo
+        .fetch()
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
     log::warn!("done cloning {}", &repo.spec);
     Ok((
         commit,
@@ -715,3 +840,62 @@ fn add_utils(engine: &mut Engine) {
             },
         );
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast_cvs_git::{git::Forge, processing::RepoConfig};
+
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+
+    /// Stopping the emission of results, e.g. when the client disconnected,
+    /// stops the construction of the remaining commits.
+    #[test]
+    fn stop_streaming_scripts() {
+        let (dir, git) = repository("script_stream");
+        let a = "src/main/java/A.java";
+        let first = commit(&git, &[("pom.xml", POM), (a, "class A {}\n")]);
+        commit(&git, &[("pom.xml", POM), (a, "class A { int i; }\n")]);
+        let last = commit(&git, &[("pom.xml", POM), (a, "class A { int j; }\n")]);
+        let state = crate::SharedState::default();
+        let spec = register(&state, "script_stream", &dir, RepoConfig::JavaMaven);
+        let script = ScriptContentDepth {
+            inner: ScriptContent {
+                init: "#{files: 0}".to_string(),
+                filter: "[]".to_string(),
+                accumulate: "".to_string(),
+            },
+            commits: 3,
+        };
+        let path = ScriptingParam {
+            forge: Forge::Local,
+            user: "test".to_string(),
+            name: "script_stream".to_string(),
+            commit: last.to_string(),
+        };
+        let mut prepared = vec![];
+        let mut results = vec![];
+        simple_depth_streamed(
+            script,
+            state.clone(),
+            path,
+            MergeStrategy::default(),
+            |commit, _| {
+                prepared.push(*commit);
+                true
+            },
+            |r| {
+                results.push(r);
+                false
+            },
+        )
+        .unwrap();
+        assert_eq!(prepared, vec![last]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_ref().unwrap().commit, last.to_string());
+        let mut repositories = state.repositories.write().unwrap();
+        let config = repositories.get_config(spec).unwrap().config;
+        assert!(repositories.get_commit(&config, &first).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
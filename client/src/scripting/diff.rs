@@ -0,0 +1,467 @@
+use enumset::{EnumSet, EnumSetType};
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{AnyType, HyperType, LabelStore, Labeled, TypeStore, WithStats},
+};
+use hyper_ast_cvs_git::{git::Oid, maven::SemFlags};
+use hyper_diff::{
+    decompressed_tree_store::{
+        DecompressedWithParent, LazyDecompressedTreeStore, ShallowDecompressedTreeStore,
+    },
+    matchers::{mapping_store::MonoMappingStore, Mapper},
+};
+use rhai::{
+    packages::{BasicArrayPackage, CorePackage, Package},
+    Array, Dynamic, Engine, Instant, Scope,
+};
+
+use super::{add_utils, finalize::Finalize, ComputeResult, ScriptingError};
+use crate::{matching, no_space, utils::get_pair_simp, SharedState};
+
+/// Kind of change affecting a node between two commits.
+#[derive(EnumSetType, Debug)]
+pub(super) enum Change {
+    Inserted,
+    Deleted,
+    Updated,
+    Moved,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Src,
+    Dst,
+}
+
+/// A node visited while scripting over the changes between two commits.
+/// Nodes of the dst tree are visited if they changed or contain changes,
+/// nodes of the src tree are only visited if they were deleted.
+#[derive(Clone, Copy, Debug)]
+pub struct DiffNode {
+    side: Side,
+    idx: u32,
+}
+
+/// Properties of the visited node, computed once while holding the stores,
+/// so that builtins do not have to lock the repositories again.
+#[derive(Clone)]
+struct NodeProps {
+    t: AnyType,
+    label: Option<String>,
+    size: usize,
+    size_before: usize,
+    sem: EnumSet<SemFlags>,
+    changes: EnumSet<Change>,
+}
+
+/// Evaluates the scripts on the subtrees that changed between `src_oid` and `dst_oid`,
+/// using the mappings computed by hyper_diff (the same ones used to compute changes).
+pub(super) fn simple_diff_aux(
+    state: SharedState,
+    repo: &hyper_ast_cvs_git::processing::ConfiguredRepo2,
+    src_oid: &Oid,
+    dst_oid: &Oid,
+    engine: &Engine,
+    init_script: &rhai::AST,
+    filter_script: &rhai::AST,
+    accumulate_script: &rhai::AST,
+    now: Instant,
+) -> Result<ComputeResult, ScriptingError> {
+    let repositories = state.repositories.read().unwrap();
+    let src_tr = repositories
+        .get_commit(&repo.config, src_oid)
+        .ok_or_else(|| ScriptingError::Other(format!("missing commit {}", src_oid)))?
+        .ast_root;
+    let dst_tr = repositories
+        .get_commit(&repo.config, dst_oid)
+        .ok_or_else(|| ScriptingError::Other(format!("missing commit {}", dst_oid)))?
+        .ast_root;
+    let with_spaces_stores = &repositories.processor.main_stores;
+    let stores = &no_space::as_nospaces(with_spaces_stores);
+    let node_store = &stores.node_store;
+
+    let pair = get_pair_simp(&state.partial_decomps, stores, &src_tr, &dst_tr);
+
+    let mapped = {
+        let mappings_cache = &state.mappings_alone;
+        use hyper_diff::matchers::mapping_store::MappingStore;
+        use hyper_diff::matchers::mapping_store::VecStore;
+        use hyper_diff::matchers::Mapping;
+        let hyperast = stores;
+        match mappings_cache.entry((src_tr, dst_tr)) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => entry.into_ref().downgrade(),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let mappings = VecStore::default();
+                let (src_arena, dst_arena) = (pair.0.get_mut(), pair.1.get_mut());
+                let mut mapper = Mapper {
+                    hyperast,
+                    mapping: Mapping {
+                        src_arena,
+                        dst_arena,
+                        mappings,
+                    },
+                };
+                mapper.mapping.mappings.topit(
+                    mapper.mapping.src_arena.len(),
+                    mapper.mapping.dst_arena.len(),
+                );
+                let vec_store = matching::full2(hyperast, mapper);
+                entry
+                    .insert((crate::MappingStage::Bottomup, vec_store))
+                    .downgrade()
+            }
+        }
+    };
+    let (src_arena, dst_arena) = (pair.0.get_mut(), pair.1.get_mut());
+    let mappings = &mapped.1;
+
+    let props = |id: NodeIdentifier, size_before: usize, changes: EnumSet<Change>| {
+        let n = with_spaces_stores.node_store.resolve(id);
+        let t = with_spaces_stores.type_store.resolve_type(&n);
+        let label = if t.is_file() || t.is_directory() {
+            n.try_get_label()
+                .map(|l| with_spaces_stores.label_store.resolve(l).to_string())
+        } else {
+            None
+        };
+        NodeProps {
+            t,
+            label,
+            size: n.size(),
+            size_before,
+            sem: n
+                .get_component::<EnumSet<SemFlags>>()
+                .map_or(EnumSet::new(), |x| *x),
+            changes,
+        }
+    };
+    let label_of = |id: NodeIdentifier| {
+        with_spaces_stores
+            .node_store
+            .resolve(id)
+            .try_get_label()
+            .copied()
+    };
+    let init: Dynamic = engine
+        .eval_ast(&init_script)
+        .map_err(|x| ScriptingError::AtEvaluation(x.to_string()))?;
+    let mut stack: Vec<Acc> = vec![];
+    stack.push(Acc {
+        node: DiffNode {
+            side: Side::Dst,
+            idx: dst_arena.root(),
+        },
+        value: Some(init),
+        parent: 0,
+        pending_cs: -1,
+    });
+    let mut acc_engine = Engine::new_raw();
+    acc_engine.on_print(|text| println!("{text}"));
+    let package = CorePackage::new();
+    package.register_into_engine(&mut acc_engine);
+    let package = BasicArrayPackage::new();
+    package.register_into_engine(&mut acc_engine);
+    let mut filter_engine = Engine::new_raw();
+    filter_engine.on_print(|text| println!("{text}"));
+    let package = CorePackage::new();
+    package.register_into_engine(&mut filter_engine);
+    let package = BasicArrayPackage::new();
+    package.register_into_engine(&mut filter_engine);
+    let result: Dynamic = loop {
+        let Some(mut acc) = stack.pop() else {
+            return Err(ScriptingError::Other(
+                "nothing left to accumulate".to_string(),
+            ));
+        };
+
+        let stack_len = stack.len();
+
+        let node = acc.node;
+        let (current, size_before, changes) = match node.side {
+            Side::Dst => {
+                let current = dst_arena.original(&node.idx);
+                let mut changes = EnumSet::new();
+                let size_before = match mappings.get_src(&node.idx) {
+                    None => {
+                        changes |= Change::Inserted;
+                        0
+                    }
+                    Some(s) => {
+                        let before = src_arena.original(&s);
+                        if label_of(before) != label_of(current) {
+                            changes |= Change::Updated;
+                        }
+                        let moved_from = src_arena.parent(&s).and_then(|p| mappings.get_dst(&p));
+                        if moved_from != dst_arena.parent(&node.idx) {
+                            changes |= Change::Moved;
+                        }
+                        with_spaces_stores.node_store.resolve(before).size()
+                    }
+                };
+                (current, size_before, changes)
+            }
+            Side::Src => {
+                let current = src_arena.original(&node.idx);
+                let size_before = with_spaces_stores.node_store.resolve(current).size();
+                (current, size_before, Change::Deleted.into())
+            }
+        };
+        let p = props(current, size_before, changes);
+
+        if acc.pending_cs < 0 {
+            let mut scope = Scope::new();
+            scope.push("s", acc.value.clone().unwrap());
+            filter_engine.disable_symbol("/");
+            let children: Vec<DiffNode> = match node.side {
+                Side::Dst => {
+                    let mut children = vec![];
+                    for c in dst_arena.decompress_children(node_store, &node.idx) {
+                        let changed = match mappings.get_src(&c) {
+                            None => true,
+                            Some(s) => {
+                                src_arena.original(&s) != dst_arena.original(&c)
+                                    || src_arena.parent(&s).and_then(|p| mappings.get_dst(&p))
+                                        != Some(node.idx)
+                            }
+                        };
+                        if changed {
+                            children.push(DiffNode {
+                                side: Side::Dst,
+                                idx: c,
+                            });
+                        }
+                    }
+                    if let Some(s) = mappings.get_src(&node.idx) {
+                        for c in src_arena.decompress_children(node_store, &s) {
+                            if !mappings.is_src(&c) {
+                                children.push(DiffNode {
+                                    side: Side::Src,
+                                    idx: c,
+                                });
+                            }
+                        }
+                    }
+                    children
+                }
+                Side::Src => src_arena
+                    .decompress_children(node_store, &node.idx)
+                    .into_iter()
+                    .filter(|c| !mappings.is_src(c))
+                    .map(|idx| DiffNode {
+                        side: Side::Src,
+                        idx,
+                    })
+                    .collect(),
+            };
+            register_node_fns(&mut filter_engine, &p);
+            filter_engine.register_fn("children", move || {
+                children
+                    .iter()
+                    .map(|x| Dynamic::from(*x))
+                    .collect::<Array>()
+            });
+            add_utils(&mut filter_engine);
+            let prepared: Dynamic = filter_engine
+                .eval_ast_with_scope(&mut scope, &filter_script)
+                .map_err(|x| ScriptingError::AtEvaluation(x.to_string()))?;
+            acc.value = Some(scope.get_value("s").unwrap());
+            let prepared = prepare_children(prepared, stack_len)?;
+            stack.push(Acc {
+                pending_cs: prepared.len() as isize,
+                ..acc
+            });
+            stack.extend(prepared);
+            continue;
+        }
+        if stack.is_empty() {
+            assert_eq!(acc.parent, 0);
+            break acc.value.unwrap();
+        }
+
+        let mut scope = Scope::new();
+        scope.push("s", acc.value.take().unwrap());
+        scope.push("p", stack[acc.parent].value.take().unwrap());
+        acc_engine.disable_symbol("/");
+        register_node_fns(&mut acc_engine, &p);
+        let size = p.size as i64;
+        acc_engine.register_fn("size", move || size);
+        let size_before = p.size_before as i64;
+        acc_engine.register_fn("size_before", move || size_before);
+        add_utils(&mut acc_engine);
+        acc_engine
+            .eval_ast_with_scope(&mut scope, &accumulate_script)
+            .map_err(|x| ScriptingError::AtEvaluation(x.to_string()))?;
+        stack[acc.parent].value = Some(scope.get_value("p").unwrap());
+    };
+    let compute_time = now.elapsed().as_secs_f64();
+    let result = result.finalize();
+    let r = ComputeResult {
+        compute_time,
+        result,
+    };
+    Ok(r)
+}
+
+#[derive(Debug)]
+struct Acc {
+    node: DiffNode,
+    value: Option<Dynamic>,
+    parent: usize,
+    pending_cs: isize,
+}
+
+/// Checks that the filter script returned an array of `[node, value]` pairs,
+/// and makes the accumulators of the children of the node at `parent` in the stack.
+fn prepare_children(prepared: Dynamic, parent: usize) -> Result<Vec<Acc>, ScriptingError> {
+    let err = |t: &str| {
+        ScriptingError::AtEvaluation(format!(
+            "the filter script should return an array of [node, value] pairs, not {}",
+            t
+        ))
+    };
+    let type_name = prepared.type_name().to_string();
+    let prepared = prepared
+        .try_cast::<Array>()
+        .ok_or_else(|| err(&type_name))?;
+    prepared
+        .into_iter()
+        .map(|x| {
+            let type_name = x.type_name().to_string();
+            let pair = x.try_cast::<Array>().ok_or_else(|| err(&type_name))?;
+            let [node, value]: [Dynamic; 2] = pair
+                .try_into()
+                .map_err(|x: Array| err(&format!("an array of length {}", x.len())))?;
+            let type_name = node.type_name().to_string();
+            let node = node.try_cast::<DiffNode>().ok_or_else(|| err(&type_name))?;
+            Ok(Acc {
+                node,
+                value: Some(value),
+                parent,
+                pending_cs: -1,
+            })
+        })
+        .collect()
+}
+
+/// Registers the builtins shared by the filter and the accumulate scripts.
+fn register_node_fns(engine: &mut Engine, p: &NodeProps) {
+    let t = p.t;
+    engine.register_fn("type", move || t.to_string());
+    engine.register_fn("is_directory", move || t.is_directory());
+    engine.register_fn("is_file", move || t.is_file());
+    engine.register_fn("is_type_decl", move || {
+        t.as_shared() == hyper_ast::types::Shared::TypeDeclaration
+    });
+    let label = p.label.clone();
+    engine.register_fn("is_java_file", move || {
+        t.is_file() && label.as_ref().map_or(false, |l| l.ends_with(".java"))
+    });
+    let label = p.label.clone();
+    engine.register_fn("file_name", move || {
+        label.clone().ok_or_else(|| {
+            Box::<rhai::EvalAltResult>::from(
+                "file_name() should be called on a file or a directory",
+            )
+        })
+    });
+    let sem = p.sem;
+    engine.register_fn("is_maven_module", move || {
+        sem.contains(SemFlags::IsMavenModule)
+    });
+    engine.register_fn("hold_maven_submodule", move || {
+        sem.contains(SemFlags::HoldMavenSubModule)
+    });
+    engine.register_fn("hold_java_folder", move || {
+        sem.contains(SemFlags::HoldMainFolder) || sem.contains(SemFlags::HoldTestFolder)
+    });
+    let changes = p.changes;
+    engine.register_fn("is_inserted", move || changes.contains(Change::Inserted));
+    engine.register_fn("is_deleted", move || changes.contains(Change::Deleted));
+    engine.register_fn("is_updated", move || changes.contains(Change::Updated));
+    engine.register_fn("is_moved", move || changes.contains(Change::Moved));
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast_cvs_git::{
+        git::{Forge, MergeStrategy},
+        processing::RepoConfig,
+    };
+
+    use axum::Json;
+
+    use super::*;
+    use crate::{
+        scripting::{ScriptContent, ScriptContentDepth, ScriptingParam},
+        tests::{commit, register, repository, POM},
+    };
+
+    #[test]
+    fn filter_returning_nodes() {
+        let not_pairs = Dynamic::from_array(vec![Dynamic::from_int(1)]);
+        let r = prepare_children(not_pairs, 0);
+        assert!(matches!(r, Err(ScriptingError::AtEvaluation(_))));
+        assert!(prepare_children(Dynamic::UNIT, 0).is_err());
+        let no_children = prepare_children(Dynamic::from_array(vec![]), 0);
+        assert!(no_children.unwrap().is_empty());
+    }
+
+    /// A is modified, B is deleted and C is untouched,
+    /// so only A and B are visited in the changes of the second commit.
+    #[test]
+    fn script_on_changed_files() {
+        let (dir, git) = repository("script_diff");
+        let java = |x| format!("src/main/java/{}.java", x);
+        let (a, b, c) = (java("A"), java("B"), java("C"));
+        commit(
+            &git,
+            &[
+                ("pom.xml", POM),
+                (&a, "class A {}\n"),
+                (&b, "class B {}\n"),
+                (&c, "class C {}\n"),
+            ],
+        );
+        let child = commit(
+            &git,
+            &[
+                ("pom.xml", POM),
+                (&a, "class A { void m() {} }\n"),
+                (&c, "class C {}\n"),
+            ],
+        );
+        let state = crate::SharedState::default();
+        register(&state, "script_diff", &dir, RepoConfig::JavaMaven);
+        let script = ScriptContentDepth {
+            inner: ScriptContent {
+                init: "#{files: 0, deleted: 0}".to_string(),
+                filter: "children().map(|x| [x, #{files: 0, deleted: 0}])".to_string(),
+                accumulate: r#"
+if is_file() {
+    p.files += 1;
+    if is_deleted() { p.deleted += 1; }
+} else {
+    p.files += s.files;
+    p.deleted += s.deleted;
+}"#
+                .to_string(),
+            },
+            commits: 1,
+        };
+        let path = ScriptingParam {
+            forge: Forge::Local,
+            user: "test".to_string(),
+            name: "script_diff".to_string(),
+            commit: child.to_string(),
+        };
+        let Json(r) =
+            crate::scripting::simple_diff(script, state, path, MergeStrategy::default()).unwrap();
+        assert_eq!(r.results.len(), 1);
+        let r = r.results[0].as_ref().unwrap();
+        assert_eq!(r.commit, child.to_string());
+        let result = r.inner.result.clone_cast::<rhai::Map>();
+        assert_eq!(result["files"].as_int(), Ok(2));
+        assert_eq!(result["deleted"].as_int(), Ok(1));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,56 @@
+//! Helpers shared by the tests of the routes, working on local git repositories.
+use std::path::{Path, PathBuf};
+
+use hyper_ast_cvs_git::{
+    git::{Forge, Oid, Repo},
+    processing::RepoConfig,
+};
+
+use crate::SharedState;
+
+/// Initializes an empty git repository in a temporary directory named after `name`.
+pub(crate) fn repository(name: &str) -> (PathBuf, git2::Repository) {
+    let dir = std::env::temp_dir().join(format!("hyperast_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let git = git2::Repository::init(&dir).unwrap();
+    (dir, git)
+}
+
+/// Commits exactly `files`, replacing the content of the previous commit.
+pub(crate) fn commit(repo: &git2::Repository, files: &[(&str, &str)]) -> Oid {
+    let workdir = repo.workdir().unwrap().to_owned();
+    let mut index = repo.index().unwrap();
+    index.clear().unwrap();
+    for (path, text) in files {
+        let file = workdir.join(path);
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, text).unwrap();
+        index.add_path(Path::new(path)).unwrap();
+    }
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("t", "t@t").unwrap();
+    let parent = repo.head().ok().map(|x| x.peel_to_commit().unwrap());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "c", &tree, &parents)
+        .unwrap()
+}
+
+/// Registers the repository at `dir` as the local repository `test/<name>`.
+pub(crate) fn register(state: &SharedState, name: &str, dir: &Path, config: RepoConfig) -> Repo {
+    let spec = Forge::Local
+        .repo("test", name)
+        .with_location(dir.to_string_lossy());
+    state
+        .repositories
+        .write()
+        .unwrap()
+        .register_config(spec.clone(), config);
+    spec
+}
+
+pub(crate) static POM: &str = r#"<project>
+    <modelVersion>4.0.0</modelVersion>
+    <groupId>t</groupId>
+    <artifactId>t</artifactId>
+    <version>1</version>
+</project>"#;
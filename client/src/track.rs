@@ -13,8 +13,11 @@ use hyper_ast::{
     },
 };
 use hyper_ast_cvs_git::{
-    git::Repo, multi_preprocessed, preprocessed::child_at_path_tracked,
-    processing::ConfiguredRepoTrait, TStore,
+    git::{parents_with, renamed_from, MergeStrategy, Oid, Repo},
+    multi_preprocessed,
+    preprocessed::child_at_path_tracked,
+    processing::{ConfiguredRepo2, ConfiguredRepoTrait},
+    TStore,
 };
 use hyper_diff::{
     decompressed_tree_store::{
@@ -32,6 +35,7 @@ use tokio::time::Instant;
 
 use crate::{
     changes::{self, DstChanges, SrcChanges},
+    encoding::Encoding,
     matching, no_space,
     utils::get_pair_simp,
     ConfiguredRepoHandle, MappingAloneCache, PartialDecompCache, SharedState,
@@ -39,6 +43,7 @@ use crate::{
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -47,6 +52,7 @@ pub struct TrackingParam {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingAtPathParam {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -58,10 +64,75 @@ pub struct TrackingQuery {
     start: Option<usize>,
     end: Option<usize>,
     before: Option<String>,
+    /// maximum number of commits walked, defaults to 100, at most 1000
+    limit: Option<usize>,
+    /// which parents of merge commits are tracked through
+    #[serde(default)]
+    merges: MergeStrategy,
     #[serde(flatten)]
     flags: Flags,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct BatchTrackingParam {
+    forge: hyper_ast_cvs_git::git::Forge,
+    user: String,
+    name: String,
+    commit: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BatchTrackingQuery {
+    before: Option<String>,
+    /// maximum number of commits walked for each target, defaults to 100, at most 1000
+    limit: Option<usize>,
+    /// which parents of merge commits are tracked through
+    #[serde(default)]
+    merges: MergeStrategy,
+    #[serde(flatten)]
+    flags: Flags,
+}
+
+/// A piece of code to track, either a range in a file or a structural path.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum TrackingTarget {
+    Range {
+        file: String,
+        start: Option<usize>,
+        end: Option<usize>,
+    },
+    /// offsets from the root of the commit
+    Path { path: Vec<usize> },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BatchTracking {
+    targets: Vec<TrackingTarget>,
+}
+
+impl TrackingQuery {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Commits walked at most, to be charged on the quota of the user.
+    pub(crate) fn charged_commits(&self) -> usize {
+        self.limit()
+    }
+}
+
+impl BatchTrackingQuery {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Commits walked at most for all the targets of `batch`, to be charged on the quota of the user.
+    pub(crate) fn charged_commits(&self, batch: &BatchTracking) -> usize {
+        self.limit() * batch.targets.len().min(MAX_BATCH)
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug)]
 #[serde(default)]
 pub(crate) struct Flags {
@@ -241,7 +312,14 @@ pub struct TrackingResultWithChanges {
 
 impl IntoResponse for TrackingResultWithChanges {
     fn into_response(self) -> axum::response::Response {
-        let mut resp = serde_json::to_string(&self).unwrap().into_response();
+        self.encoded(Encoding::Json)
+    }
+}
+
+impl TrackingResultWithChanges {
+    /// The changes can be large, binary encodings are negotiated by the handler.
+    pub(crate) fn encoded(self, encoding: Encoding) -> axum::response::Response {
+        let mut resp = encoding.encode(&self);
         let headers = resp.headers_mut();
         headers.insert(
             "Server-Timing",
@@ -256,6 +334,27 @@ impl IntoResponse for TrackingResultWithChanges {
     }
 }
 
+/// Results of a [`BatchTracking`], in the same order as its targets.
+#[derive(Serialize)]
+pub struct BatchTrackingResult {
+    pub compute_time: f64,
+    results: Vec<Result<TrackingResult, TrackingError>>,
+}
+
+impl IntoResponse for BatchTrackingResult {
+    fn into_response(self) -> axum::response::Response {
+        let mut resp = serde_json::to_string(&self).unwrap().into_response();
+        let headers = resp.headers_mut();
+        headers.insert(
+            "Server-Timing",
+            format!("track;desc=\"Compute Time\";dur={}", self.compute_time)
+                .parse()
+                .unwrap(),
+        );
+        resp
+    }
+}
+
 // impl Display for TrackingResult {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 //         writeln!()
@@ -297,6 +396,17 @@ where
 
 const MAX_NODES: usize = 200 * 4_000_000;
 
+/// Default number of commits walked to track a piece of code.
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+/// Max number of targets tracked by a single batch
+const MAX_BATCH: usize = 50;
+
+/// Time after which the remaining targets of a batch are not tracked,
+/// below the timeout of the routes so that the results of the tracked ones are still sent.
+const BATCH_BUDGET: Duration = Duration::from_secs(8);
+
 #[derive(Deserialize, Serialize)]
 pub struct TrackingError {
     pub compute_time: f64,
@@ -313,6 +423,15 @@ impl IntoResponse for TrackingError {
     }
 }
 
+/// Why the tracking stopped before finding the code.
+fn exhausted(node_processed: usize, limit: usize) -> String {
+    if node_processed < MAX_NODES {
+        format!("reached the limit of walked commits: (ie. {})", limit)
+    } else {
+        format!("reached max number of diffed nodes: (ie. {})", MAX_NODES)
+    }
+}
+
 pub fn track_code(
     state: SharedState,
     path: TrackingParam,
@@ -320,18 +439,22 @@ pub fn track_code(
 ) -> Result<TrackingResult, TrackingError> {
     let now = Instant::now();
     let TrackingParam {
+        forge,
         user,
         name,
         commit,
         file,
     } = path;
+    let limit = query.limit();
     let TrackingQuery {
         start,
         end,
         before,
+        merges,
         flags,
+        ..
     } = query;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_specifier = forge.repo(user, name);
     let repo_handle = state
         .repositories
         .write()
@@ -343,10 +466,42 @@ pub fn track_code(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|e| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message: e.to_string(),
+    })?;
     log::warn!("done cloning {}", repository.spec);
-    // let mut get_mut = state.write().unwrap();
-    // let state = get_mut.deref_mut();
+    track_code_in(
+        &state,
+        &mut repository,
+        commit,
+        file,
+        start,
+        end,
+        before,
+        merges,
+        limit,
+        &flags,
+        now,
+    )
+}
+
+/// Tracks the range `start..end` of `file` at `commit` through history.
+fn track_code_in(
+    state: &SharedState,
+    repository: &mut ConfiguredRepo2,
+    commit: String,
+    file: String,
+    start: Option<usize>,
+    end: Option<usize>,
+    before: Option<String>,
+    merges: MergeStrategy,
+    limit: usize,
+    flags: &Flags,
+    now: Instant,
+) -> Result<TrackingResult, TrackingError> {
     let mut commit = commit.clone();
     let mut node_processed = 0;
     let mut commits_processed = 1;
@@ -354,34 +509,42 @@ pub fn track_code(
     let mut start = start;
     let mut end = end;
     let mut source = None;
-    while node_processed < MAX_NODES {
+    while node_processed < MAX_NODES && commits_processed <= limit {
         commits_processed += 1;
-        let commits = state
-            .repositories
-            .write()
-            .unwrap()
-            .pre_process_with_limit(&mut repository, "", &commit, 2)
-            .map_err(|e| TrackingError {
+        let (src_oid, parents) = construct_with_parents(state, repository, &commit, merges)
+            .map_err(|message| TrackingError {
                 compute_time: now.elapsed().as_secs_f64(),
                 commits_processed: 0,
                 node_processed: 0,
-                message: e.to_string(),
+                message,
             })?;
-        log::warn!("done construction of {commits:?} in {}", repository.spec);
-        let src_oid = commits[0];
-        let dst_oid = commits[1];
-        match aux(
-            state.clone(),
-            &repository,
-            src_oid,
-            dst_oid,
-            &file,
-            start,
-            end,
-            &flags,
-        ) {
+        log::warn!(
+            "done construction of {src_oid} and {parents:?} in {}",
+            repository.spec
+        );
+        let parents = parents_or_before(state, repository, parents, &before, now)?;
+        let Some((dst_oid, result)) = through_parents(&parents, |dst_oid| {
+            aux(
+                state.clone(),
+                repository,
+                src_oid,
+                dst_oid,
+                &file,
+                start,
+                end,
+                flags,
+            )
+        }) else {
+            return Err(TrackingError {
+                compute_time: now.elapsed().as_secs_f64(),
+                commits_processed,
+                node_processed,
+                message: "this commit has no parent".into(),
+            });
+        };
+        match result {
             MappingResult::Direct { src: aaa, matches } => {
-                let aaa = aaa.globalize(repository.spec, commit);
+                let aaa = aaa.globalize(repository.spec.clone(), commit);
                 let (src, intermediary) = if let Some(src) = source {
                     (src, Some(aaa))
                 } else {
@@ -398,7 +561,7 @@ pub fn track_code(
                 .into());
             }
             MappingResult::Missing { src: aaa, fallback } => {
-                let aaa = aaa.globalize(repository.spec, commit);
+                let aaa = aaa.globalize(repository.spec.clone(), commit);
                 let (src, intermediary) = if let Some(src) = source {
                     (src, Some(aaa))
                 } else {
@@ -421,6 +584,22 @@ pub fn track_code(
                 message: err,
             })?,
             MappingResult::Skipped { nodes, src, next } => {
+                if before.is_some() {
+                    let aaa = src.globalize(repository.spec.clone(), commit);
+                    let (src, intermediary) = if let Some(src) = source {
+                        (src, Some(aaa))
+                    } else {
+                        (aaa, None)
+                    };
+                    return Ok(TrackingResult {
+                        compute_time: now.elapsed().as_secs_f64(),
+                        commits_processed,
+                        src,
+                        intermediary,
+                        fallback: None,
+                        matched: next,
+                    });
+                }
                 node_processed += nodes;
                 dbg!(src_oid, dst_oid);
                 if source.is_none() {
@@ -446,7 +625,7 @@ pub fn track_code(
         compute_time: now.elapsed().as_secs_f64(),
         commits_processed,
         node_processed,
-        message: format!("reached max number of diffed nodes: (ie. {})", MAX_NODES),
+        message: exhausted(node_processed, limit),
     })
 }
 
@@ -456,19 +635,23 @@ pub(crate) fn track_code_at_path(
     query: TrackingQuery,
 ) -> Result<TrackingResult, TrackingError> {
     let now = Instant::now();
+    let limit = query.limit();
     let TrackingQuery {
         start,
         end,
         before,
+        merges,
         flags,
+        ..
     } = query;
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_specifier = forge.repo(user, name);
     let repository = state
         .repositories
         .write()
@@ -480,49 +663,95 @@ pub(crate) fn track_code_at_path(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repository.fetch();
+    let mut repository = repository.fetch().map_err(|e| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message: e.to_string(),
+    })?;
     log::warn!("done cloning {}", repository.spec);
-    // let mut get_mut = state.write().unwrap();
-    // let state = get_mut.deref_mut();
+    let path = path.split("/").filter_map(|x| x.parse().ok()).collect();
+    track_code_at_path_in(
+        &state,
+        &mut repository,
+        commit,
+        path,
+        before,
+        merges,
+        limit,
+        &flags,
+        now,
+    )
+}
+
+/// When tracking directly to the `before` commit, it replaces the parents to go through.
+fn parents_or_before(
+    state: &SharedState,
+    repository: &mut ConfiguredRepo2,
+    parents: Vec<Oid>,
+    before: &Option<String>,
+    now: Instant,
+) -> Result<Vec<Oid>, TrackingError> {
+    let Some(before) = before else {
+        return Ok(parents);
+    };
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(repository, "", before, 2)
+        .map_err(|e| TrackingError {
+            compute_time: now.elapsed().as_secs_f64(),
+            commits_processed: 0,
+            node_processed: 0,
+            message: e.to_string(),
+        })?;
+    Ok(vec![commits[0]])
+}
+
+/// Tracks the node at the structural `path` (offsets from the root) at `commit` through history.
+fn track_code_at_path_in(
+    state: &SharedState,
+    repository: &mut ConfiguredRepo2,
+    commit: String,
+    mut path: Vec<usize>,
+    before: Option<String>,
+    merges: MergeStrategy,
+    limit: usize,
+    flags: &Flags,
+    now: Instant,
+) -> Result<TrackingResult, TrackingError> {
     let mut commit = commit.clone();
     let mut node_processed = 0;
     let mut commits_processed = 1;
-    let mut path: Vec<_> = path.split("/").filter_map(|x| x.parse().ok()).collect();
     let mut source = None;
-    while node_processed < MAX_NODES {
+    while node_processed < MAX_NODES && commits_processed <= limit {
         commits_processed += 1;
-        let commits = state
-            .repositories
-            .write()
-            .unwrap()
-            .pre_process_with_limit(&mut repository, "", &commit, 2)
-            .map_err(|e| TrackingError {
+        let (src_oid, parents) = construct_with_parents(state, repository, &commit, merges)
+            .map_err(|message| TrackingError {
                 compute_time: now.elapsed().as_secs_f64(),
                 commits_processed: 0,
                 node_processed: 0,
-                message: e.to_string(),
+                message,
             })?;
-        log::warn!("done construction of {commits:?} in {}", repository.spec);
-        let src_oid = commits[0];
-        let dst_oid = if let Some(before) = &before {
-            let commits = state
-                .repositories
-                .write()
-                .unwrap()
-                .pre_process_with_limit(&mut repository, "", before, 2)
-                .map_err(|e| TrackingError {
-                    compute_time: now.elapsed().as_secs_f64(),
-                    commits_processed: 0,
-                    node_processed: 0,
-                    message: e.to_string(),
-                })?;
-            commits[0]
-        } else {
-            commits[1]
+        log::warn!(
+            "done construction of {src_oid} and {parents:?} in {}",
+            repository.spec
+        );
+        let parents = parents_or_before(state, repository, parents, &before, now)?;
+        let Some((dst_oid, result)) = through_parents(&parents, |dst_oid| {
+            aux2(state.clone(), repository, src_oid, dst_oid, &path, flags)
+        }) else {
+            return Err(TrackingError {
+                compute_time: now.elapsed().as_secs_f64(),
+                commits_processed,
+                node_processed,
+                message: "this commit has no parent".into(),
+            });
         };
-        match aux2(state.clone(), &repository, src_oid, dst_oid, &path, &flags) {
+        match result {
             MappingResult::Direct { src: aaa, matches } => {
-                let aaa = aaa.globalize(repository.spec, commit);
+                let aaa = aaa.globalize(repository.spec.clone(), commit);
                 let (src, intermediary) = if let Some(src) = source {
                     (src, Some(aaa))
                 } else {
@@ -538,7 +767,7 @@ pub(crate) fn track_code_at_path(
                 });
             }
             MappingResult::Missing { src: aaa, fallback } => {
-                let aaa = aaa.globalize(repository.spec, commit);
+                let aaa = aaa.globalize(repository.spec.clone(), commit);
                 let (src, intermediary) = if let Some(src) = source {
                     (src, Some(aaa))
                 } else {
@@ -562,7 +791,7 @@ pub(crate) fn track_code_at_path(
             MappingResult::Skipped { nodes, src, next } => {
                 // TODO handle cases where there is no more commits
                 if before.is_some() {
-                    let aaa = src.globalize(repository.spec, commit);
+                    let aaa = src.globalize(repository.spec.clone(), commit);
                     let (src, intermediary) = if let Some(src) = source {
                         (src, Some(aaa))
                     } else {
@@ -602,7 +831,103 @@ pub(crate) fn track_code_at_path(
         compute_time: now.elapsed().as_secs_f64(),
         commits_processed,
         node_processed,
-        message: format!("reached max number of diffed nodes: (ie. {})", MAX_NODES),
+        message: exhausted(node_processed, limit),
+    })
+}
+
+/// Tracks many targets from the same commit.
+/// The repository is only fetched once, and as mappings between commits are cached
+/// they are only computed once per pair of commits.
+pub(crate) fn track_code_batch(
+    state: SharedState,
+    path: BatchTrackingParam,
+    query: BatchTrackingQuery,
+    batch: BatchTracking,
+) -> Result<BatchTrackingResult, TrackingError> {
+    let now = Instant::now();
+    let limit = query.limit();
+    let BatchTrackingQuery {
+        before,
+        merges,
+        flags,
+        ..
+    } = query;
+    let BatchTrackingParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path;
+    if batch.targets.len() > MAX_BATCH {
+        return Err(TrackingError {
+            compute_time: now.elapsed().as_secs_f64(),
+            commits_processed: 0,
+            node_processed: 0,
+            message: format!("too many targets (max {})", MAX_BATCH),
+        });
+    }
+    let repo_specifier = forge.repo(user, name);
+    let repository = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_specifier)
+        .ok_or_else(|| TrackingError {
+            compute_time: now.elapsed().as_secs_f64(),
+            commits_processed: 0,
+            node_processed: 0,
+            message: "missing config for repository".to_string(),
+        })?;
+    let mut repository = repository.fetch().map_err(|e| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message: e.to_string(),
+    })?;
+    log::warn!("done cloning {}", repository.spec);
+    let results = batch
+        .targets
+        .into_iter()
+        .map(|target| {
+            if now.elapsed() > BATCH_BUDGET {
+                return Err(TrackingError {
+                    compute_time: 0.0,
+                    commits_processed: 0,
+                    node_processed: 0,
+                    message: "not tracked, the batch ran out of time".to_string(),
+                });
+            }
+            match target {
+                TrackingTarget::Range { file, start, end } => track_code_in(
+                    &state,
+                    &mut repository,
+                    commit.clone(),
+                    file,
+                    start,
+                    end,
+                    before.clone(),
+                    merges,
+                    limit,
+                    &flags,
+                    Instant::now(),
+                ),
+                TrackingTarget::Path { path } => track_code_at_path_in(
+                    &state,
+                    &mut repository,
+                    commit.clone(),
+                    path,
+                    before.clone(),
+                    merges,
+                    limit,
+                    &flags,
+                    Instant::now(),
+                ),
+            }
+        })
+        .collect();
+    Ok(BatchTrackingResult {
+        compute_time: now.elapsed().as_secs_f64(),
+        results,
     })
 }
 
@@ -613,19 +938,21 @@ pub(crate) fn track_code_at_path_with_changes(
     query: TrackingQuery,
 ) -> Result<TrackingResultWithChanges, TrackingError> {
     let now = Instant::now();
+    let limit = query.limit();
     let TrackingQuery {
-        start: _,
-        end: _,
         before,
+        merges,
         flags,
+        ..
     } = query;
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let configs = state.clone();
     let repo_handle = state
         .repositories
@@ -638,7 +965,12 @@ pub(crate) fn track_code_at_path_with_changes(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|e| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message: e.to_string(),
+    })?;
     log::warn!("done cloning {}", repository.spec);
     let mut ori_oid = None;
     let mut commit = commit.clone();
@@ -646,28 +978,25 @@ pub(crate) fn track_code_at_path_with_changes(
     let mut commits_processed = 1;
     let mut path: Vec<_> = path.split("/").filter_map(|x| x.parse().ok()).collect();
     let mut source = None;
-    while node_processed < MAX_NODES {
+    while node_processed < MAX_NODES && commits_processed <= limit {
         commits_processed += 1;
-        let commits = state
-            .repositories
-            .write()
-            .unwrap()
-            .pre_process_with_limit(&mut repository, "", &commit, 4)
-            .map_err(|e| TrackingError {
-                compute_time: now.elapsed().as_secs_f64(),
-                commits_processed: 0,
-                node_processed: 0,
-                message: e.to_string(),
-            })?;
+        let (src_oid, parents) = construct_with_parents(&state, &mut repository, &commit, merges)
+            .map_err(|message| TrackingError {
+            compute_time: now.elapsed().as_secs_f64(),
+            commits_processed: 0,
+            node_processed: 0,
+            message,
+        })?;
         log::warn!(
-            "done construction of {commits:?} in {}",
+            "done construction of {src_oid} and {parents:?} in {}",
             repository.spec.user
         );
-        let src_oid = commits[0];
         if ori_oid.is_none() {
             ori_oid = Some(src_oid);
         }
-        let Some(&dst_oid) = commits.get(1) else {
+        let Some((dst_oid, result)) = through_parents(&parents, |dst_oid| {
+            aux2(state.clone(), &repository, src_oid, dst_oid, &path, &flags)
+        }) else {
             return Err(TrackingError {
                 compute_time: now.elapsed().as_secs_f64(),
                 commits_processed,
@@ -675,7 +1004,7 @@ pub(crate) fn track_code_at_path_with_changes(
                 message: "this commit has no parent".into(),
             });
         };
-        match aux2(state.clone(), &repository, src_oid, dst_oid, &path, &flags) {
+        match result {
             MappingResult::Direct { src: aaa, matches } => {
                 let changes = changes::added_deleted(state, &repository, dst_oid, ori_oid.unwrap())
                     .map_err(|err| TrackingError {
@@ -736,9 +1065,13 @@ pub(crate) fn track_code_at_path_with_changes(
                 dbg!(src_oid, dst_oid);
                 // TODO fix issue of not stoping when failling to match accurately,
                 // most likely related to miss use of fallback value ?
-                if commits.len() < 3 || !(node_processed < MAX_NODES) {
-                    // no commit remaining (first + second < 3)
+                let dst_is_root = repository
+                    .repo
+                    .find_commit(dst_oid)
+                    .map_or(true, |x| x.parent_count() == 0);
+                if dst_is_root || !(node_processed < MAX_NODES) || commits_processed >= limit {
                     // NOTE there is no parent commit to dst_commit, thus we should stop now
+                    // (or the limits were reached)
                     let changes =
                         changes::added_deleted(state, &repository, dst_oid, ori_oid.unwrap())
                             .map_err(|err| TrackingError {
@@ -787,10 +1120,55 @@ pub(crate) fn track_code_at_path_with_changes(
         compute_time: now.elapsed().as_secs_f64(),
         commits_processed,
         node_processed,
-        message: format!("reached max number of diffed nodes: (ie. {})", MAX_NODES),
+        message: exhausted(node_processed, limit),
     })
 }
 
+/// Constructs `commit` and the parents to track through, chosen according to `merges`.
+pub(crate) fn construct_with_parents(
+    state: &SharedState,
+    repository: &mut ConfiguredRepo2,
+    commit: &str,
+    merges: MergeStrategy,
+) -> Result<(Oid, Vec<Oid>), String> {
+    let mut repositories = state.repositories.write().unwrap();
+    let oid = repositories
+        .pre_process_with_limit(repository, "", commit, 1)
+        .map_err(|e| e.to_string())?[0];
+    let parents = {
+        let c = repository
+            .repo
+            .find_commit(oid)
+            .map_err(|e| e.to_string())?;
+        parents_with(&repository.repo, &c, merges).map_err(|e| e.to_string())?
+    };
+    for p in &parents {
+        repositories
+            .pre_process_with_limit(repository, "", &p.to_string(), 1)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok((oid, parents))
+}
+
+/// Tracks through `parents` in order, like blame, following the first parent where the code is unchanged,
+/// otherwise the result of the first parent is returned.
+fn through_parents(
+    parents: &[Oid],
+    mut f: impl FnMut(Oid) -> MappingResult,
+) -> Option<(Oid, MappingResult)> {
+    let mut first = None;
+    for &p in parents {
+        let r = f(p);
+        if let MappingResult::Skipped { .. } = r {
+            return Some((p, r));
+        }
+        if first.is_none() {
+            first = Some((p, r));
+        }
+    }
+    first
+}
+
 enum MappingResult {
     Direct {
         src: LocalPieceOfCode,
@@ -841,9 +1219,7 @@ impl LocalPieceOfCode {
 
 fn aux(
     state: std::sync::Arc<crate::AppState>,
-    repo_handle: &impl ConfiguredRepoTrait<
-        Config = hyper_ast_cvs_git::processing::ParametrizedCommitProcessorHandle,
-    >,
+    repo_handle: &ConfiguredRepo2,
     src_oid: hyper_ast_cvs_git::git::Oid,
     dst_oid: hyper_ast_cvs_git::git::Oid,
     file: &String,
@@ -904,6 +1280,7 @@ fn aux(
     let dst_oid = dst_oid; // WARN not sure what I was doing there commit_dst.clone();
     aux_aux(
         repo_handle,
+        src_oid,
         src_tr,
         dst_tr,
         path_to_target,
@@ -921,9 +1298,7 @@ fn aux(
 
 fn aux2(
     state: std::sync::Arc<crate::AppState>,
-    repo_handle: &impl ConfiguredRepoTrait<
-        Config = hyper_ast_cvs_git::processing::ParametrizedCommitProcessorHandle,
-    >,
+    repo_handle: &ConfiguredRepo2,
     src_oid: hyper_ast_cvs_git::git::Oid,
     dst_oid: hyper_ast_cvs_git::git::Oid,
     path: &[usize],
@@ -973,6 +1348,7 @@ fn aux2(
     let dst_oid = dst_oid; // WARN not sure what I was doing there commit_dst.clone();
     aux_aux(
         repo_handle,
+        src_oid,
         src_tr,
         dst_tr,
         path_to_target,
@@ -989,7 +1365,8 @@ fn aux2(
 }
 
 fn aux_aux(
-    repo_handle: &impl ConfiguredRepoTrait,
+    repo_handle: &ConfiguredRepo2,
+    src_oid: hyper_ast_cvs_git::git::Oid,
     src_tr: NodeIdentifier,
     dst_tr: NodeIdentifier,
     path_to_target: Vec<u16>,
@@ -1459,6 +1836,116 @@ fn aux_aux(
         };
     }
 
+    // not mapped, it might have been moved to another file, or its file renamed
+    let (target_pos, target_path_ids) = compute_position_and_nodes(
+        src_tr,
+        &mut path_to_target.iter().copied(),
+        with_spaces_stores,
+    );
+    let src_file = target_pos.file().to_string_lossy().to_string();
+    let moved = {
+        use hyper_diff::matchers::mapping_store::MappingStore;
+        let renamed = renamed_file(
+            repo_handle,
+            src_oid,
+            dst_oid,
+            &src_file,
+            dst_tr,
+            with_spaces_stores,
+        );
+        let target = stores.node_store.resolve(target_node);
+        let size = target.size();
+        let structural = target.hash(&types::HashKind::structural());
+        // only searching in what changed,
+        // subtrees mapped to identical ones cannot contain code moved there
+        let mut candidates = vec![];
+        let mut stack = vec![mapper_dst_arena.root()];
+        while let Some(d) = stack.pop() {
+            let original = mapper_dst_arena.original(&d);
+            let mapped = mapper_mappings.get_src(&d);
+            if mapped.map_or(false, |s| mapper_src_arena.original(&s) == original) {
+                continue;
+            }
+            let n = stores.node_store.resolve(original);
+            let n_size = n.size();
+            if n_size == size && mapped.is_none() {
+                let identical = original == target_node;
+                if identical || n.hash(&types::HashKind::structural()) == structural {
+                    candidates.push((identical, d));
+                }
+            }
+            if n_size <= size {
+                continue;
+            }
+            let cs = mapper_dst_arena.decompress_children(node_store, &d);
+            stack.extend(cs.into_iter().rev());
+        }
+        // disambiguates identical candidates with the closest mapped ancestors,
+        // then the file git detected as renamed, then the position in the parent
+        let target_parents: Vec<_> = mapper_src_arena.parents(mapping_target).collect();
+        let target_offset = mapper_src_arena.position_in_parent(&mapping_target);
+        let mapper_dst_arena = &*mapper_dst_arena;
+        let key = |(identical, d): &(bool, _)| {
+            let parents: Vec<_> = mapper_dst_arena.parents(*d).collect();
+            let common = parents
+                .iter()
+                .filter_map(|p| mapper_mappings.get_src(p))
+                .find_map(|s| target_parents.iter().position(|x| *x == s));
+            let in_renamed = renamed.map_or(false, |f| {
+                parents.iter().any(|p| mapper_dst_arena.original(p) == f)
+            });
+            let same_offset = mapper_dst_arena.position_in_parent(d) == target_offset;
+            (
+                *identical,
+                std::cmp::Reverse(common.unwrap_or(usize::MAX)),
+                in_renamed,
+                same_offset,
+            )
+        };
+        // max_by_key keeps the last maximum, reversing keeps the first in depth first order
+        let found = candidates.iter().rev().max_by_key(|x| key(*x));
+        found.map(|(identical, d)| (*d, *identical))
+    };
+    if let Some((moved, identical)) = moved {
+        let path = mapper_dst_arena.path(&mapper_dst_arena.root(), &moved);
+        let (path,) = path_with_spaces(
+            dst_tr,
+            &mut path.iter().copied(),
+            &repositories.processor.main_stores,
+        );
+        let (pos, path_ids) =
+            compute_position_and_nodes(dst_tr, &mut path.iter().copied(), with_spaces_stores);
+        let range = pos.range();
+        let matches = vec![PieceOfCode {
+            user: repo_handle.spec().user.clone(),
+            name: repo_handle.spec().name.clone(),
+            commit: dst_oid.to_string(),
+            file: pos.file().to_str().unwrap().to_string(),
+            start: range.start,
+            end: range.end,
+            path: path.iter().map(|x| *x as usize).collect(),
+            path_ids,
+        }];
+        let src = LocalPieceOfCode {
+            file: src_file,
+            start,
+            end,
+            path: path_to_target.iter().map(|x| *x as usize).collect(),
+            path_ids: target_path_ids,
+        };
+        if identical && flags.some() {
+            let src_size = stores.node_store.resolve(src_tr).size();
+            let dst_size = stores.node_store.resolve(dst_tr).size();
+            let nodes = src_size + dst_size;
+            return MappingResult::Skipped {
+                nodes,
+                src,
+                next: matches,
+            };
+        }
+        return MappingResult::Direct { src, matches };
+    }
+
     for parent_target in mapper_src_arena.parents(mapping_target) {
         if let Some(mapped_parent) = mapper_mappings.get_dst(&parent_target) {
             let mapped_parent = mapper_dst_arena.decompress_to(node_store, &mapped_parent);
@@ -1528,6 +2015,28 @@ fn aux_aux(
     }
 }
 
+/// The file of `dst_tr` that git detects as the origin of `src_file`, if it was renamed or copied.
+fn renamed_file(
+    repository: &ConfiguredRepo2,
+    src_oid: hyper_ast_cvs_git::git::Oid,
+    dst_oid: hyper_ast_cvs_git::git::Oid,
+    src_file: &str,
+    dst_tr: NodeIdentifier,
+    stores: &hyper_ast_cvs_git::SimpleStores,
+) -> Option<NodeIdentifier> {
+    match renamed_from(&repository.repo, src_oid, dst_oid, src_file) {
+        Ok(Some(dst_file)) => {
+            log::debug!("{src_file} was renamed from {dst_file}");
+            child_at_path_tracked(stores, dst_tr, dst_file.split("/")).map(|(file, _)| file)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            log::warn!("failed to detect renames: {err}");
+            None
+        }
+    }
+}
+
 // fn diff<'a>(
 //     repositories: &'a multi_preprocessed::PreProcessedRepositories,
 //     mappings: &'a mut crate::MappingCache,
@@ -2257,3 +2766,80 @@ pub fn child_by_type<'store, HAST: HyperAST<'store, IdN = NodeIdentifier>>(
         .map(|(i, x)| (*x, i));
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast_cvs_git::processing::RepoConfig;
+
+    use super::*;
+    use crate::tests::{commit, register, repository, POM};
+
+    static M: &str = "void m() {\n        int i = 0;\n    }";
+
+    fn with_m(class: &str) -> String {
+        format!("class {} {{\n    {}\n}}\n", class, M)
+    }
+
+    #[test]
+    fn charged_commits() {
+        let query: TrackingQuery = serde_json::from_str("{}").unwrap();
+        assert_eq!(query.charged_commits(), DEFAULT_LIMIT);
+        let query: TrackingQuery = serde_json::from_str(r#"{"limit": 100000}"#).unwrap();
+        assert_eq!(query.charged_commits(), MAX_LIMIT);
+        let query: BatchTrackingQuery = serde_json::from_str(r#"{"limit": 10}"#).unwrap();
+        let batch: BatchTracking =
+            serde_json::from_str(r#"{"targets": [{"path": [0]}, {"file": "A.java"}]}"#).unwrap();
+        assert_eq!(query.charged_commits(&batch), 20);
+    }
+
+    /// `m` is moved from B to C, A keeps an identical `m`,
+    /// tracking the `m` of C in the parent commit should lead to B.
+    #[test]
+    fn track_moved_method_next_to_identical_one() {
+        let (dir, git) = repository("track_moved");
+        let java = |x| format!("src/main/java/{}.java", x);
+        let (a, b, c) = (java("A"), java("B"), java("C"));
+        let parent = commit(
+            &git,
+            &[("pom.xml", POM), (&a, &with_m("A")), (&b, &with_m("B"))],
+        );
+        let child = commit(
+            &git,
+            &[
+                ("pom.xml", POM),
+                (&a, &with_m("A")),
+                (&b, "class B {\n}\n"),
+                (&c, &with_m("C")),
+            ],
+        );
+
+        let state = crate::SharedState::default();
+        let spec = register(&state, "moved", &dir, RepoConfig::JavaMaven);
+        let repo = {
+            let mut repositories = state.repositories.write().unwrap();
+            let mut repo = repositories.get_config(spec).unwrap().fetch().unwrap();
+            repositories
+                .pre_process_with_limit(&mut repo, "", &child.to_string(), 2)
+                .unwrap();
+            repo
+        };
+        let start = with_m("C").find(M).unwrap();
+        let end = start + M.len();
+        let r = aux(
+            state.clone(),
+            &repo,
+            child,
+            parent,
+            &c,
+            Some(start),
+            Some(end),
+            &Flags::default(),
+        );
+        let MappingResult::Direct { matches, .. } = r else {
+            panic!("the method should be found in the parent commit");
+        };
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].file, b);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -9,7 +9,7 @@ use hyper_ast::{
         TypeStore, WithChildren,
     },
 };
-use hyper_ast_cvs_git::{git::fetch_github_repository, processing::ConfiguredRepoTrait};
+use hyper_ast_cvs_git::processing::ConfiguredRepoTrait;
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
@@ -17,6 +17,7 @@ use crate::SharedState;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Parameters {
+    forge: hyper_ast_cvs_git::git::Forge,
     user: String,
     name: String,
     commit: String,
@@ -25,6 +26,12 @@ pub struct Parameters {
 
 type NodeId = u64;
 
+/// Param of `/view/node/:id`
+#[derive(Deserialize, Clone, Debug)]
+pub struct NodeParam {
+    pub id: NodeId,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct TypeSys(Vec<String>);
 
@@ -80,20 +87,21 @@ pub struct ViewBoth {
 pub fn view(state: SharedState, path: Parameters) -> Result<Json<ViewRes>, String> {
     let now = Instant::now();
     let Parameters {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
     dbg!(&path);
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
     log::warn!("done cloning {}", repo.spec);
     let commits = state
         .repositories
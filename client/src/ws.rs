@@ -1,7 +1,7 @@
 use automerge::sync::SyncDoc;
 use axum::TypedHeader;
 use axum::{
-    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
     response::IntoResponse,
 };
 use axum_macros::debug_handler;
@@ -16,7 +16,12 @@ use axum::extract::connect_info::ConnectInfo;
 // allows to split the websocket stream into separate TX and RX branches
 use futures::{sink::SinkExt, stream::StreamExt};
 
-use crate::SharedState;
+use crate::scripting::{
+    self, ComputeResultIdentified, ScriptContentDepth, ScriptingError, ScriptingParam,
+    ScriptingQuery,
+};
+use crate::{auth, jobs::Target, SharedState};
+use hyper_ast_cvs_git::git::Repo;
 
 #[debug_handler]
 pub(crate) async fn connect_db(
@@ -676,3 +681,190 @@ async fn handle_socket_automerge_sync(socket: WebSocket, who: SocketAddr, state:
     // returning from the handler closes the websocket connection
     println!("Websocket context {} destroyed", who);
 }
+
+#[derive(Serialize)]
+enum ScriptStreamMsg {
+    Prepared { commit: String, prepare_time: f64 },
+    Result(ComputeResultIdentified),
+    Error(String),
+    Failed(ScriptingError),
+    Denied(auth::AccessError),
+    Done,
+}
+
+/// Streaming variant of `/script-depth`,
+/// the client sends the scripts as its first message
+/// then receives each result as soon as its commit is processed.
+#[debug_handler]
+pub(crate) async fn connect_script_stream(
+    ws: WebSocketUpgrade,
+    user: auth::User,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    axum::extract::Path(path): axum::extract::Path<ScriptingParam>,
+    axum::extract::Path(target): axum::extract::Path<Target>,
+    axum::extract::Query(query): axum::extract::Query<ScriptingQuery>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> impl IntoResponse {
+    println!("{addr} connected to script stream.");
+    ws.on_upgrade(move |socket| {
+        let repo = target.repo();
+        handle_socket_script(socket, addr, state.clone(), path, query, user, repo)
+    })
+}
+
+/// Actual websocket statemachine (one will be spawned per connection)
+async fn handle_socket_script(
+    socket: WebSocket,
+    who: SocketAddr,
+    state: SharedState,
+    path: ScriptingParam,
+    query: ScriptingQuery,
+    user: auth::User,
+    repo: Repo,
+) {
+    let (mut sender, mut receiver) = socket.split();
+    let script: ScriptContentDepth = match receiver.next().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+            Ok(x) => x,
+            Err(e) => {
+                let msg = ScriptStreamMsg::Failed(ScriptingError::Other(e.to_string()));
+                close_script_stream(&mut sender, msg).await;
+                return;
+            }
+        },
+        _ => {
+            println!(">>> {} closed before sending a script", who);
+            return;
+        }
+    };
+
+    // not processed by the jobs queue, so commits are charged here
+    if let Err(e) = auth::charge(&state, &user, &repo, script.commits(), 1) {
+        close_script_stream(&mut sender, ScriptStreamMsg::Denied(e)).await;
+        return;
+    }
+
+    // Dropping the receiving end makes the computation stop at the next commit.
+    let (s, mut r) = tokio::sync::mpsc::channel(50);
+    tokio::task::spawn_blocking(move || {
+        let r = scripting::simple_depth_streamed(
+            script,
+            state,
+            path,
+            query.merges,
+            |commit, prepare_time| {
+                let commit = commit.to_string();
+                let msg = ScriptStreamMsg::Prepared {
+                    commit,
+                    prepare_time,
+                };
+                s.blocking_send(msg).is_ok()
+            },
+            |r| {
+                let msg = match r {
+                    Ok(r) => ScriptStreamMsg::Result(r),
+                    Err(e) => ScriptStreamMsg::Error(e),
+                };
+                s.blocking_send(msg).is_ok()
+            },
+        );
+        let _ = s.blocking_send(match r {
+            Ok(()) => ScriptStreamMsg::Done,
+            Err(e) => ScriptStreamMsg::Failed(e),
+        });
+    });
+
+    let mut send_task = tokio::spawn(async move {
+        let mut cnt = 0;
+        while let Some(msg) = r.recv().await {
+            cnt += 1;
+            if let ScriptStreamMsg::Done | ScriptStreamMsg::Failed(_) = msg {
+                close_script_stream(&mut sender, msg).await;
+                break;
+            }
+            let msg = match serde_json::to_string(&msg) {
+                Ok(msg) => Message::Text(msg),
+                Err(e) => {
+                    let e = ScriptingError::Other(e.to_string());
+                    close_script_stream(&mut sender, ScriptStreamMsg::Failed(e)).await;
+                    break;
+                }
+            };
+            if let Err(e) = sender.send(msg).await {
+                dbg!(e);
+                break;
+            }
+        }
+        cnt
+    });
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            if let Message::Close(_) = msg {
+                break;
+            }
+        }
+    });
+
+    // If any one of the tasks exit, abort the other.
+    // Aborting the send task drops the channel, which stops the computation.
+    tokio::select! {
+        rv_a = (&mut send_task) => {
+            match rv_a {
+                Ok(a) => println!("{} messages sent to {}", a, who),
+                Err(a) => println!("Error sending messages {:?}", a)
+            }
+            recv_task.abort();
+        },
+        _ = (&mut recv_task) => {
+            send_task.abort();
+        }
+    }
+
+    println!("Websocket context {} destroyed", who);
+}
+
+/// Sends the last message of a script stream,
+/// then closes the socket with a code matching the outcome.
+async fn close_script_stream(
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    msg: ScriptStreamMsg,
+) {
+    let (code, reason) = close_reason(&msg);
+    if let Ok(msg) = serde_json::to_string(&msg) {
+        let _ = sender.send(Message::Text(msg)).await;
+    }
+    let frame = CloseFrame {
+        code,
+        reason: reason.into(),
+    };
+    let _ = sender.send(Message::Close(Some(frame))).await;
+}
+
+/// The close code and reason of a script stream ending with `msg`.
+fn close_reason(msg: &ScriptStreamMsg) -> (u16, &'static str) {
+    match msg {
+        ScriptStreamMsg::Done => (close_code::NORMAL, "done"),
+        ScriptStreamMsg::Denied(_) => (close_code::POLICY, "access denied"),
+        ScriptStreamMsg::Failed(ScriptingError::AtCompilation(_)) => {
+            (close_code::INVALID, "script does not compile")
+        }
+        _ => (close_code::ERROR, "failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_stream_close_codes() {
+        let code = |msg| close_reason(&msg).0;
+        assert_eq!(code(ScriptStreamMsg::Done), close_code::NORMAL);
+        let denied = auth::AccessError::Forbidden(String::new());
+        assert_eq!(code(ScriptStreamMsg::Denied(denied)), close_code::POLICY);
+        let e = ScriptingError::AtCompilation(String::new());
+        assert_eq!(code(ScriptStreamMsg::Failed(e)), close_code::INVALID);
+        let e = ScriptingError::Other(String::new());
+        assert_eq!(code(ScriptStreamMsg::Failed(e)), close_code::ERROR);
+    }
+}
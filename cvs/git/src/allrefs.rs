@@ -1,6 +1,11 @@
-use std::{borrow::Borrow, fmt::Display, io::Write, path::Path, time::Instant};
+use std::{
+    borrow::Borrow, cell::Cell, collections::HashMap, fmt::Display, io::Write, path::Path,
+    time::Instant,
+};
 
 use hyper_ast::{
+    filter::BloomResult,
+    nodes::RefContainer,
     position::{
         Position, SpHandle, StructuralPosition, StructuralPositionStore, TreePath, TreePathMut,
         TypedTreePath,
@@ -26,7 +31,11 @@ use hyper_ast_gen_ts_java::{
 };
 use num::ToPrimitive;
 
-use crate::{maven::IterMavenModules, preprocessed::child_by_name_with_idx, SimpleStores};
+use crate::{
+    maven::{IterMavenModules, MavenCoordinates, MavenModuleInfo},
+    preprocessed::child_by_name_with_idx,
+    SimpleStores,
+};
 
 const REFERENCES_SERIALIZATION_SUMMARY: bool = false;
 
@@ -40,17 +49,34 @@ type Scout = hyper_ast::position::Scout<NodeIdentifier, u16>;
 type TypedScout = hyper_ast::position::TypedScout<JavaIdN, u16>;
 
 /// Write in [`out`], the JSON formated reprentation of the reference relations at [`root`] in [`prepro`].
+///
+/// Type declarations of the main source folder of a maven module are also searched in the modules depending on it,
+/// following the dependencies declared in the pom.xml files (see [`MavenModuleGraph`]).
+/// Imports that cannot be resolved to a declaration of the module or of its dependencies are categorized with [`UnresolvedKind`].
 pub fn write_referencial_relations<W: Write>(
     stores: &SimpleStores,
     root: NodeIdentifier,
     out: &mut W,
 ) {
-    let modules = IterMavenModules::new(stores, StructuralPosition::new(root), root);
-    // let declarations = iter_declarations(stores, modules);
+    write_referencial_relations_with_cache(stores, root, &mut ReferencesCache::default(), out)
+}
+
+/// Same as [`write_referencial_relations`], but reuses the references found in the subtrees
+/// already searched on previous commits, see [`ReferencesCache`].
+///
+/// Each call is a commit of the cache, evicting what was not used in its window.
+pub fn write_referencial_relations_with_cache<W: Write>(
+    stores: &SimpleStores,
+    root: NodeIdentifier,
+    cache: &mut ReferencesCache,
+    out: &mut W,
+) {
+    cache.next_commit();
+    let graph = MavenModuleGraph::new(stores, root);
 
     let mut first = true;
 
-    for module in modules {
+    for (m, module) in graph.modules().iter().enumerate() {
         if first {
             first = false;
         } else {
@@ -65,24 +91,113 @@ pub fn write_referencial_relations<W: Write>(
         .unwrap();
         writeln!(out, r#","content": ["#).unwrap();
         let mut writer = Writer::new(out);
-        let declarations = iter_declarations(stores, module);
-        for ExpandedDeclaration(decl, root_folder, of) in declarations {
-            let now = Instant::now();
-            let references =
-                find_declaration_references_position(root, stores, &decl, root_folder, of);
-            if let Some((sk, references)) = references {
-                let decl = decl.make_position(stores);
-                let time = now.elapsed().as_nanos();
-                log::info!("time taken for refs search of {} :\t{}", decl, time);
-                if REFERENCES_SERIALIZATION_SUMMARY {
-                    writer.summary_of_referencial_relations(decl, sk, time, references);
-                } else {
-                    writer.positions_of_referencial_relations(decl, sk, time, references);
+        let (source, source_tests) = graph.folders(m);
+        let dependents = graph.dependent_folders(m);
+        // declarations in test folders are not visible from other modules
+        let folders = [
+            (source_tests.clone(), vec![], vec![]),
+            (
+                source.clone(),
+                source_tests.iter().cloned().collect(),
+                dependents,
+            ),
+        ];
+        for (folder, of, dependents) in folders {
+            let Some(folder) = folder else {
+                continue;
+            };
+            for ExpandedDeclaration(decl, root_folder, of) in make_decl_iter(stores, folder, of) {
+                let now = Instant::now();
+                let references = find_declaration_references_position(
+                    root,
+                    stores,
+                    &decl,
+                    root_folder,
+                    of,
+                    &dependents,
+                    cache,
+                );
+                if let Some((sk, references, module_references)) = references {
+                    let decl = decl.make_position(stores);
+                    let time = now.elapsed().as_nanos();
+                    log::info!("time taken for refs search of {} :\t{}", decl, time);
+                    if REFERENCES_SERIALIZATION_SUMMARY {
+                        writer.summary_of_referencial_relations(
+                            decl,
+                            sk,
+                            time,
+                            references,
+                            module_references,
+                        );
+                    } else {
+                        writer.positions_of_referencial_relations(
+                            decl,
+                            sk,
+                            time,
+                            references,
+                            module_references,
+                        );
+                    }
                 }
             }
         }
-        write!(out, "]}}").unwrap();
+        write!(out, "]").unwrap();
+        write_module_dependencies(stores, &graph, m, out);
+        write!(out, "}}").unwrap();
+    }
+}
+
+/// Write the dependencies of the [`m`]th module of [`graph`],
+/// and the imports of the module that are not resolved in the repository.
+fn write_module_dependencies<W: Write>(
+    stores: &SimpleStores,
+    graph: &MavenModuleGraph,
+    m: usize,
+    out: &mut W,
+) {
+    write!(out, r#","dependencies":["#).unwrap();
+    for (i, d) in graph.dependencies(m).enumerate() {
+        if i > 0 {
+            write!(out, ",").unwrap();
+        }
+        let d = &graph.modules()[d];
+        write!(
+            out,
+            "\"{}\"",
+            d.make_position(stores).file().to_str().unwrap()
+        )
+        .unwrap();
+    }
+    write!(out, r#"],"external_dependencies":["#).unwrap();
+    for (i, d) in graph.external_dependencies(m).iter().enumerate() {
+        if i > 0 {
+            write!(out, ",").unwrap();
+        }
+        let f = |x: &Option<String>| x.clone().unwrap_or_default();
+        write!(
+            out,
+            "\"{}:{}:{}\"",
+            f(&d.group_id),
+            f(&d.artifact_id),
+            f(&d.version)
+        )
+        .unwrap();
+    }
+    write!(out, r#"],"unresolved":["#).unwrap();
+    for (i, (import, kind, p)) in graph.unresolved_imports(stores, m).into_iter().enumerate() {
+        if i > 0 {
+            write!(out, ",").unwrap();
+        }
+        write!(
+            out,
+            r#"{{"import":"{}","kind":"{}","position":{}}}"#,
+            import,
+            kind,
+            p.make_position(stores)
+        )
+        .unwrap();
     }
+    write!(out, "]").unwrap();
 }
 
 pub enum SearchKinds {
@@ -99,23 +214,30 @@ impl Display for SearchKinds {
     }
 }
 
+/// Returns the references to [`declaration`] in its module,
+/// then the ones in the [`dependent_folders`] ie. the source folders of the modules depending on it.
 pub fn find_declaration_references_position(
     root: NodeIdentifier,
     stores: &SimpleStores,
     declaration: &DeclSp,
     root_folder: StructuralPosition,
     other_folders: Vec<StructuralPosition>,
-) -> Option<(SearchKinds, Vec<Position>)> {
+    dependent_folders: &[StructuralPosition],
+    cache: &mut ReferencesCache,
+) -> Option<(SearchKinds, Vec<Position>, Vec<Position>)> {
     let mut structural_positions = StructuralPositionStore::new(root);
-    let (rk, references) = find_declaration_references(
+    let (rk, references, module_references) = find_declaration_references(
         stores,
         &mut structural_positions,
         declaration,
         root_folder,
         other_folders,
+        dependent_folders,
+        cache,
     )?;
     let references = structural_positions.ends_positions(stores, &references);
-    Some((rk, references))
+    let module_references = structural_positions.ends_positions(stores, &module_references);
+    Some((rk, references, module_references))
 }
 
 fn find_declaration_references(
@@ -124,7 +246,9 @@ fn find_declaration_references(
     declaration: &DeclSp,
     root_folder: StructuralPosition,
     other_folders: Vec<StructuralPosition>,
-) -> Option<(SearchKinds, Vec<SpHandle>)> {
+    dependent_folders: &[StructuralPosition],
+    cache: &mut ReferencesCache,
+) -> Option<(SearchKinds, Vec<SpHandle>, Vec<SpHandle>)> {
     let b = stores
         .node_store
         .try_resolve_typed::<JavaIdN>(declaration.node().unwrap())?
@@ -156,6 +280,14 @@ fn find_declaration_references(
             })
         })
         .collect();
+    let dependent_folders: Vec<TypedScout> = dependent_folders
+        .iter()
+        .map(|x| {
+            structural_positions.type_scout(&mut Into::<Scout>::into((x.clone(), 0)), unsafe {
+                JavaIdN::from_ref_id(x.node().unwrap())
+            })
+        })
+        .collect();
 
     let decl = structural_positions
         .type_scout(&mut Into::<Scout>::into((declaration.clone(), 0)), unsafe {
@@ -165,27 +297,28 @@ fn find_declaration_references(
         || t == Type::InterfaceDeclaration
         || t == Type::AnnotationTypeDeclaration
     {
-        let rs = RefsFinder::new(stores, structural_positions)
+        let (rs, module_rs) = RefsFinder::new(stores, structural_positions, cache)
             .find_type_declaration_references_unchecked(
                 decl,
                 root_folder.node().unwrap(),
                 &other_folders,
                 &p_in_of,
+                &dependent_folders,
             );
-        Some((SearchKinds::TypeDecl, rs))
+        Some((SearchKinds::TypeDecl, rs, module_rs))
     } else if SEARCH_MEMBERS && t == Type::FieldDeclaration
     // || t == Type::ConstantDeclaration
     {
-        let rs = RefsFinder::new(stores, structural_positions)
+        let rs = RefsFinder::new(stores, structural_positions, cache)
             .find_field_declaration_references_unchecked(
                 decl,
                 root_folder.node().unwrap(),
                 &p_in_of,
             );
-        Some((SearchKinds::TypeDecl, rs))
+        Some((SearchKinds::TypeDecl, rs, vec![]))
     } else if t == Type::ClassBody {
-        let rs = RefsFinder::new(stores, structural_positions).find_this_unchecked(decl);
-        Some((SearchKinds::LocalDecl, rs))
+        let rs = RefsFinder::new(stores, structural_positions, cache).find_this_unchecked(decl);
+        Some((SearchKinds::LocalDecl, rs, vec![]))
     } else if t == Type::LocalVariableDeclaration
         || t == Type::Resource
         || t == Type::EnhancedForVariable
@@ -195,9 +328,9 @@ fn find_declaration_references(
         || t == Type::Identifier
         || t == Type::TypeParameter
     {
-        let rs = RefsFinder::new(stores, structural_positions)
+        let rs = RefsFinder::new(stores, structural_positions, cache)
             .find_localvar_declaration_references_unchecked(decl);
-        Some((SearchKinds::LocalDecl, rs))
+        Some((SearchKinds::LocalDecl, rs, vec![]))
     } else {
         None
     }
@@ -218,6 +351,7 @@ impl<'a, W: Write> Writer<'a, W> {
         search_kind: SearchKinds,
         time: u128,
         references: Vec<Position>,
+        module_references: Vec<Position>,
     ) {
         let out = &mut self.out;
         if self.start {
@@ -241,6 +375,16 @@ impl<'a, W: Write> Writer<'a, W> {
             }
             write!(out, "{}", x).unwrap();
         }
+        write!(out, r#"],"module_refs":["#).unwrap();
+        let mut first = true;
+        for x in module_references {
+            if first {
+                first = false;
+            } else {
+                write!(out, ",").unwrap();
+            }
+            write!(out, "{}", x).unwrap();
+        }
         write!(out, "]}}").unwrap();
     }
 
@@ -250,6 +394,7 @@ impl<'a, W: Write> Writer<'a, W> {
         search_kind: SearchKinds,
         time: u128,
         references: Vec<Position>,
+        module_references: Vec<Position>,
     ) {
         let out = &mut self.out;
         write!(out, r#"{{"search":"#).unwrap();
@@ -258,6 +403,8 @@ impl<'a, W: Write> Writer<'a, W> {
         write!(out, "{}", time).unwrap();
         write!(out, r#","refs":"#).unwrap();
         write!(out, "{}", references.len()).unwrap();
+        write!(out, r#","module_refs":"#).unwrap();
+        write!(out, "{}", module_references.len()).unwrap();
         write!(out, "}}").unwrap();
         writeln!(out, ",").unwrap();
     }
@@ -306,14 +453,7 @@ fn maven_module_folders(
     stores: &SimpleStores,
     maven_module: MavenModuleSp,
 ) -> Vec<ExpandedMavenModule> {
-    let src = goto_by_name(stores, maven_module.clone(), "src");
-    let source_tests = src
-        .clone()
-        .and_then(|x| goto_by_name(stores, x, "test"))
-        .and_then(|x| goto_by_name(stores, x, "java"));
-    let source = src
-        .and_then(|x| goto_by_name(stores, x, "main"))
-        .and_then(|x| goto_by_name(stores, x, "java"));
+    let (source, source_tests) = maven_module_source_folders(stores, &maven_module);
     let mut r = vec![];
     let mut test_folders = vec![];
     if let Some(source_tests) = source_tests {
@@ -330,10 +470,507 @@ fn maven_module_folders(
     r
 }
 
+/// Returns the source folder and the test source folder of [`maven_module`] if they exist.
+fn maven_module_source_folders(
+    stores: &SimpleStores,
+    maven_module: &MavenModuleSp,
+) -> (Option<FolderSp>, Option<FolderSp>) {
+    // use the directories declared in the pom.xml if available
+    let (source, source_tests) = maven_module
+        .node()
+        .and_then(|x| {
+            let b = stores.node_store.resolve(*x);
+            let info = b.get_component::<MavenModuleInfo>().ok()?;
+            Some((
+                info.source_directory.clone(),
+                info.test_source_directory.clone(),
+            ))
+        })
+        .unwrap_or_else(|| {
+            (
+                MavenModuleInfo::DEFAULT_SOURCE_DIRECTORY.to_string(),
+                MavenModuleInfo::DEFAULT_TEST_SOURCE_DIRECTORY.to_string(),
+            )
+        });
+    let goto_path = |path: &str| {
+        path.split('/')
+            .filter(|x| !x.is_empty() && *x != ".")
+            .try_fold(maven_module.clone(), |p, name| {
+                goto_by_name(stores, p, name)
+            })
+    };
+    (goto_path(&source), goto_path(&source_tests))
+}
+
+/// The maven modules of a repository, linked by the dependencies declared in their pom.xml.
+///
+/// Dependencies are matched with modules using their `groupId` and `artifactId`,
+/// the ones that do not match any module are external, eg. third party jars.
+pub struct MavenModuleGraph {
+    modules: Vec<MavenModuleSp>,
+    /// source folder and test source folder of each module
+    folders: Vec<(Option<FolderSp>, Option<FolderSp>)>,
+    /// for each module, the modules it directly depends on, with the scope of the dependency
+    dependencies: Vec<Vec<(usize, Option<String>)>>,
+    /// for each module, the dependencies that are not modules of the repository
+    external: Vec<Vec<MavenCoordinates>>,
+    /// for each module, the modules its main classes are visible from, ie. reversed [`Self::visible_from`]
+    dependents: Vec<Vec<(usize, bool)>>,
+}
+
+impl MavenModuleGraph {
+    pub fn new(stores: &SimpleStores, root: NodeIdentifier) -> Self {
+        let modules: Vec<MavenModuleSp> =
+            IterMavenModules::new(stores, StructuralPosition::new(root), root).collect();
+        let infos: Vec<Option<MavenModuleInfo>> = modules
+            .iter()
+            .map(|m| {
+                let b = stores.node_store.resolve(*m.node()?);
+                b.get_component::<MavenModuleInfo>().ok().cloned()
+            })
+            .collect();
+        let folders = modules
+            .iter()
+            .map(|m| maven_module_source_folders(stores, m))
+            .collect();
+        let find = |d: &MavenCoordinates| {
+            infos.iter().position(|x| {
+                let Some(x) = x else {
+                    return false;
+                };
+                x.coordinates.artifact_id.is_some()
+                    && x.coordinates.artifact_id == d.artifact_id
+                    && (d.group_id.is_none() || x.coordinates.group_id == d.group_id)
+            })
+        };
+        let mut dependencies = vec![];
+        let mut external = vec![];
+        for (i, info) in infos.iter().enumerate() {
+            let mut deps = vec![];
+            let mut ext = vec![];
+            for d in info.iter().flat_map(|x| &x.dependencies) {
+                match find(&d.coordinates) {
+                    Some(j) if j != i => deps.push((j, d.scope.clone())),
+                    Some(_) => (),
+                    None => ext.push(d.coordinates.clone()),
+                }
+            }
+            dependencies.push(deps);
+            external.push(ext);
+        }
+        let mut graph = Self {
+            modules,
+            folders,
+            dependencies,
+            external,
+            dependents: vec![],
+        };
+        // reversed once, instead of searching the modules that depend on each module
+        let mut dependents = vec![vec![]; graph.modules.len()];
+        for i in 0..graph.modules.len() {
+            for (d, test_only) in graph.visible_from(i) {
+                dependents[d].push((i, test_only));
+            }
+        }
+        graph.dependents = dependents;
+        graph
+    }
+
+    pub fn modules(&self) -> &[MavenModuleSp] {
+        &self.modules
+    }
+
+    pub fn folders(&self, m: usize) -> &(Option<FolderSp>, Option<FolderSp>) {
+        &self.folders[m]
+    }
+
+    /// The modules of the repository that [`m`] directly depends on.
+    pub fn dependencies(&self, m: usize) -> impl Iterator<Item = usize> + '_ {
+        self.dependencies[m].iter().map(|(x, _)| *x)
+    }
+
+    pub fn external_dependencies(&self, m: usize) -> &[MavenCoordinates] {
+        &self.external[m]
+    }
+
+    /// The modules whose main classes can be referenced from [`m`],
+    /// with `true` if they can only be referenced from its tests.
+    ///
+    /// Follows the maven rules for transitive dependencies,
+    /// ie. only `compile` dependencies of dependencies are visible and `runtime` dependencies are never visible.
+    pub fn visible_from(&self, m: usize) -> Vec<(usize, bool)> {
+        // by module, if it is visible and if it is only visible from the tests
+        let mut visible: Vec<Option<bool>> = vec![None; self.modules.len()];
+        let mut r: Vec<usize> = vec![];
+        let mut stack: Vec<(usize, bool)> = vec![];
+        for (d, scope) in &self.dependencies[m] {
+            match scope.as_deref() {
+                Some("runtime") | Some("import") => (),
+                Some("test") => stack.push((*d, true)),
+                _ => stack.push((*d, false)),
+            }
+        }
+        while let Some((d, test_only)) = stack.pop() {
+            match &mut visible[d] {
+                Some(t) if *t && !test_only => *t = false,
+                Some(_) => continue,
+                x => {
+                    *x = Some(test_only);
+                    r.push(d);
+                }
+            }
+            for (dd, scope) in &self.dependencies[d] {
+                if let None | Some("compile") = scope.as_deref() {
+                    if dd != &m {
+                        stack.push((*dd, test_only));
+                    }
+                }
+            }
+        }
+        r.into_iter().map(|d| (d, visible[d].unwrap())).collect()
+    }
+
+    /// The source folders where the main classes of [`m`] can be referenced,
+    /// ie. the ones of the modules that depend on [`m`].
+    pub fn dependent_folders(&self, m: usize) -> Vec<FolderSp> {
+        let mut r = vec![];
+        for (i, test_only) in &self.dependents[m] {
+            let (source, source_tests) = &self.folders[*i];
+            if !test_only {
+                r.extend(source.clone());
+            }
+            r.extend(source_tests.clone());
+        }
+        r
+    }
+
+    /// The imports of [`m`] that are not declared in [`m`] nor in the modules visible from [`m`].
+    pub fn unresolved_imports(
+        &self,
+        stores: &SimpleStores,
+        m: usize,
+    ) -> Vec<(String, UnresolvedKind, StructuralPosition)> {
+        let visible = self.visible_from(m);
+        let mut r = vec![];
+        let (source, source_tests) = &self.folders[m];
+        for (folder, is_test) in [(source, false), (source_tests, true)] {
+            let Some(folder) = folder else {
+                continue;
+            };
+            let mut searched: Vec<&FolderSp> = vec![];
+            searched.extend(source);
+            if is_test {
+                searched.push(folder);
+            }
+            for (d, test_only) in &visible {
+                if is_test || !test_only {
+                    searched.extend(&self.folders[*d].0);
+                }
+            }
+            for (path, asterisk, p) in iter_imports(stores, folder.clone()) {
+                if searched
+                    .iter()
+                    .any(|f| is_declared_in(stores, f, &path, asterisk))
+                {
+                    continue;
+                }
+                let import = path.join(".");
+                let kind = if is_jdk_package(&import) {
+                    UnresolvedKind::Jdk
+                } else if self.folders.iter().any(|(s, t)| {
+                    s.iter()
+                        .chain(t)
+                        .any(|f| is_declared_in(stores, f, &path, asterisk))
+                }) {
+                    UnresolvedKind::OtherModule
+                } else {
+                    UnresolvedKind::ThirdParty
+                };
+                r.push((import, kind, p));
+            }
+        }
+        r
+    }
+}
+
+/// Where an unresolved import is expected to be declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnresolvedKind {
+    /// in the java platform, eg. `java.util.List`
+    Jdk,
+    /// in a module of the repository that is not a dependency
+    OtherModule,
+    /// probably in a third party jar
+    ThirdParty,
+}
+
+impl Display for UnresolvedKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            UnresolvedKind::Jdk => write!(f, "jdk"),
+            UnresolvedKind::OtherModule => write!(f, "module"),
+            UnresolvedKind::ThirdParty => write!(f, "third_party"),
+        }
+    }
+}
+
+const JDK_PACKAGES: &[&str] = &[
+    "java",
+    "javax",
+    "jdk",
+    "sun",
+    "com.sun",
+    "org.w3c.dom",
+    "org.xml.sax",
+    "org.ietf.jgss",
+    "org.omg",
+];
+
+/// Tells if the fully qualified name [`name`] is in a package of the java platform.
+pub fn is_jdk_package(name: &str) -> bool {
+    JDK_PACKAGES.iter().any(|p| {
+        name.strip_prefix(p)
+            .map_or(false, |x| x.is_empty() || x.starts_with('.'))
+    })
+}
+
+/// Tells if [`path`], an imported name, is declared in the source [`folder`],
+/// ie. if a prefix of it designates a java file, or for on demand imports, if it designates a package.
+fn is_declared_in(
+    stores: &SimpleStores,
+    folder: &FolderSp,
+    path: &[String],
+    asterisk: bool,
+) -> bool {
+    let mut p = folder.clone();
+    for (i, name) in path.iter().enumerate() {
+        if goto_by_name(stores, p.clone(), &format!("{}.java", name)).is_some() {
+            return true;
+        }
+        p = match goto_by_name(stores, p, name) {
+            Some(p) => p,
+            None => return false,
+        };
+        if asterisk && i == path.len() - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Iterates over the imports of the java files in [`folder`],
+/// giving the imported name, if it is an on demand import, and the position of the import declaration.
+fn iter_imports(
+    stores: &SimpleStores,
+    folder: FolderSp,
+) -> Vec<(Vec<String>, bool, StructuralPosition)> {
+    let mut r = vec![];
+    let mut stack = vec![folder];
+    while let Some(p) = stack.pop() {
+        let Some((b, _)) = stores
+            .node_store
+            .try_resolve_typed::<JavaIdN>(p.node().unwrap())
+        else {
+            continue;
+        };
+        let t = b.get_type();
+        if t != Type::Directory && t != Type::Program {
+            continue;
+        }
+        let Some(cs) = b.children() else {
+            continue;
+        };
+        for (i, x) in cs.iter_children().enumerate() {
+            let mut p = p.clone();
+            p.goto(*x, num::cast(i).unwrap());
+            if t == Type::Directory {
+                stack.push(p);
+                continue;
+            }
+            if let Some((path, asterisk)) = import_declaration(stores, *x) {
+                r.push((path, asterisk, p));
+            }
+        }
+    }
+    r
+}
+
+/// Returns the imported name and if it is an on demand import, when [`x`] is an import declaration.
+pub(crate) fn import_declaration(
+    stores: &SimpleStores,
+    x: NodeIdentifier,
+) -> Option<(Vec<String>, bool)> {
+    let (b, _) = stores.node_store.try_resolve_typed::<JavaIdN>(&x)?;
+    if b.get_type() != Type::ImportDeclaration {
+        return None;
+    }
+    let mut path = vec![];
+    let mut asterisk = false;
+    for xx in b.children()?.iter_children() {
+        let Some((bb, _)) = stores.node_store.try_resolve_typed::<JavaIdN>(xx) else {
+            continue;
+        };
+        match bb.get_type() {
+            Type::Asterisk => asterisk = true,
+            Type::Identifier | Type::ScopedIdentifier => {
+                extract_qualified_name(stores, *xx, &mut path)
+            }
+            _ => (),
+        }
+    }
+    Some((path, asterisk))
+}
+
+pub(crate) fn extract_qualified_name(
+    stores: &SimpleStores,
+    x: NodeIdentifier,
+    out: &mut Vec<String>,
+) {
+    let Some((b, _)) = stores.node_store.try_resolve_typed::<JavaIdN>(&x) else {
+        return;
+    };
+    if b.get_type() == Type::Identifier {
+        out.push(
+            stores
+                .label_store
+                .resolve(b.get_label_unchecked())
+                .to_string(),
+        );
+    } else if b.get_type() == Type::ScopedIdentifier {
+        for xx in b.children().unwrap().iter_children() {
+            extract_qualified_name(stores, *xx, out);
+        }
+    }
+}
+
+/// References found by the top down searches of [`RefsFinder`], stored per subtree.
+///
+/// Subtrees are deduplicated in the HyperAST, so a subtree untouched by a commit keeps its identifier
+/// and the references found in it can be reused when searching the following commits.
+/// Thus searches are only redone along the spines leading to changed files.
+///
+/// The references found in a subtree do not depend on where it is searched from:
+/// - as [`RefPtr`]s are local to a [`PartialAnalysis`], searches are identified by their serialized package and reference,
+///   which are the only inputs of the search besides the subtree,
+/// - a file is resolved against its own package declaration and imports, which are part of its subtree,
+/// - the references are stored as paths relative to the searched subtree, not as positions from the root.
+///
+/// Entries are kept for a window of commits (see [`ReferencesCache::with_window`]) after their last use,
+/// so memory stays proportional to the trees of the last commits instead of the whole history.
+pub struct ReferencesCache {
+    /// serialized package and reference of searches, with their id and the last commit using them
+    searches: HashMap<(Box<[u8]>, Box<[u8]>), (usize, usize)>,
+    next_search: usize,
+    /// found references, with the last commit using them
+    found: HashMap<(NodeIdentifier, usize), (Found, Cell<usize>)>,
+    commit: usize,
+    window: usize,
+    hits: usize,
+    misses: usize,
+}
+
+impl Default for ReferencesCache {
+    fn default() -> Self {
+        Self::with_window(1)
+    }
+}
+
+impl Found {
+    fn is_empty(&self) -> bool {
+        match self {
+            Found::Children(x) => x.is_empty(),
+            Found::Paths(x) => x.is_empty(),
+        }
+    }
+}
+
+impl ReferencesCache {
+    /// A cache keeping the references that were used in the last [`commits`].
+    pub fn with_window(commits: usize) -> Self {
+        Self {
+            searches: Default::default(),
+            next_search: 0,
+            found: Default::default(),
+            commit: 0,
+            window: commits,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Number of searched subtrees whose references were reused.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of searched subtrees whose references were not in the cache.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Number of subtrees whose references are currently cached.
+    pub fn entries(&self) -> usize {
+        self.found.len()
+    }
+
+    /// Starts a new commit, evicting the searches and references not used in the window.
+    ///
+    /// The subtrees containing references are touched after the subtree containing them
+    /// (see [`Self::materialize`]), so an entry is never kept without the entries it points to.
+    fn next_commit(&mut self) {
+        self.commit += 1;
+        let (commit, window) = (self.commit, self.window);
+        self.searches
+            .retain(|_, (_, used)| *used + window >= commit);
+        self.found
+            .retain(|_, (_, used)| used.get() + window >= commit);
+    }
+
+    fn search_id(&mut self, package: Box<[u8]>, target: Box<[u8]>) -> usize {
+        let (id, used) = self.searches.entry((package, target)).or_insert_with(|| {
+            self.next_search += 1;
+            (self.next_search, 0)
+        });
+        *used = self.commit;
+        *id
+    }
+
+    fn get(&self, node: NodeIdentifier, search: usize) -> Option<&Found> {
+        let (found, used) = self.found.get(&(node, search))?;
+        used.set(self.commit);
+        Some(found)
+    }
+
+    /// Pushes the cached references of [`search`] in the subtree [`node`] at [`from`].
+    fn materialize(
+        &self,
+        search: usize,
+        node: NodeIdentifier,
+        from: SpHandle,
+        structural_positions: &mut StructuralPositionStore,
+        r: &mut Vec<SpHandle>,
+    ) {
+        match self.get(node, search).unwrap() {
+            Found::Children(children) => {
+                for (o, x) in children.iter() {
+                    let from = structural_positions.push_below(from, [(*x, *o)]);
+                    self.materialize(search, *x, from, structural_positions, r);
+                }
+            }
+            Found::Paths(paths) => {
+                for path in paths.iter() {
+                    r.push(structural_positions.push_below(from, path.iter().copied()));
+                }
+            }
+        }
+    }
+}
+
 pub struct RefsFinder<'a> {
     stores: &'a SimpleStores,
     ana: PartialAnalysis,
     structural_positions: &'a mut StructuralPositionStore,
+    cache: &'a mut ReferencesCache,
 }
 
 struct Cursor {
@@ -358,11 +995,13 @@ impl<'a> RefsFinder<'a> {
     pub fn new(
         stores: &'a SimpleStores,
         structural_positions: &'a mut StructuralPositionStore,
+        cache: &'a mut ReferencesCache,
     ) -> Self {
         Self {
             stores: stores,
             ana: PartialAnalysis::default(),
             structural_positions,
+            cache,
         }
     }
 
@@ -372,30 +1011,36 @@ impl<'a> RefsFinder<'a> {
         limit: &NodeIdentifier,
         other_folders: &[TypedScout],
         mirror_packages: &[TypedScout],
-    ) -> Vec<SpHandle> {
+        dependent_folders: &[TypedScout],
+    ) -> (Vec<SpHandle>, Vec<SpHandle>) {
         let mut r = vec![];
+        let mut module_r = vec![];
         // let p = decl.make_position(&self.structural_positions, self.stores);
         let res = self.find_type_declaration_references(
             &mut r,
+            &mut module_r,
             &decl,
             limit,
             other_folders,
             mirror_packages,
+            dependent_folders,
         );
         if let Err(SearchStopEvent::NoMore) = res {
             log::error!("search stoped early");
             // log::error!("search of {} ended with {:?}", p, err);
         }
-        r
+        (r, module_r)
     }
 
     fn find_type_declaration_references(
         mut self,
         r: &mut Vec<SpHandle>,
+        module_r: &mut Vec<SpHandle>,
         decl: &TypedScout,
         limit: &NodeIdentifier,
         other_folders: &[TypedScout],
         mirror_packages: &[TypedScout],
+        dependent_folders: &[TypedScout],
     ) -> Result<(), SearchStopEvent> {
         let mut scout = decl.clone();
         self.structural_positions.push_typed(&mut scout); // memory footprint ?
@@ -421,14 +1066,39 @@ impl<'a> RefsFinder<'a> {
             self.go_through_type_declarations_with_fully_qual_ref(r, &mut cursor, fq_decl_ref)
                 .unwrap_or(());
         }
+        let res = self.go_through_module(
+            r,
+            &mut cursor,
+            &package_ref,
+            &fq_decl_ref,
+            limit,
+            other_folders,
+            mirror_packages,
+        );
+        // being stopped in the module of the declaration says nothing about the dependent modules
+        log::trace!("go_through_dependent_modules");
+        self.go_through_dependent_modules(module_r, &package_ref, &fq_decl_ref, dependent_folders);
+
+        res
+    }
+
+    /// Searches the rest of the module of the declaration, from its package up to its source folders.
+    fn go_through_module(
+        &mut self,
+        r: &mut Vec<SpHandle>,
+        cursor: &mut TypedCursor,
+        package_ref: &RefPtr,
+        fq_decl_ref: &RefPtr,
+        limit: &NodeIdentifier,
+        other_folders: &[TypedScout],
+        mirror_packages: &[TypedScout],
+    ) -> Result<(), SearchStopEvent> {
         log::trace!("go_through_package");
-        self.go_through_package(r, &mut cursor, mirror_packages, &package_ref, &fq_decl_ref)?;
+        self.go_through_package(r, cursor, mirror_packages, package_ref, fq_decl_ref)?;
         log::trace!("go_through_directories");
-        self.go_through_directories(r, &mut cursor, &package_ref, &fq_decl_ref, limit)?;
+        self.go_through_directories(r, cursor, package_ref, fq_decl_ref, limit)?;
         log::trace!("go_through_folders");
-        self.go_through_folders(r, &mut cursor, &package_ref, &fq_decl_ref, other_folders)?;
-
-        Ok(())
+        self.go_through_folders(r, cursor, package_ref, fq_decl_ref, other_folders)
     }
 
     fn find_this_unchecked(mut self, decl: TypedScout) -> Vec<SpHandle> {
@@ -1237,6 +1907,27 @@ impl<'a> RefsFinder<'a> {
         Ok(())
     }
 
+    /// From other modules, the declaration can only be referenced with a fully qualified name or through an import.
+    fn go_through_dependent_modules(
+        &mut self,
+        r: &mut Vec<SpHandle>,
+        package_ref: &RefPtr,
+        fq_decl_ref: &RefPtr,
+        dependent_folders: &[TypedScout],
+    ) {
+        for folder in dependent_folders {
+            log::debug!(
+                "search {} in dependent module folder {:?}",
+                DisplayRef::from((
+                    self.ana.solver.nodes.with(*fq_decl_ref),
+                    &self.stores.label_store
+                )),
+                folder.make_position(&self.structural_positions, self.stores),
+            );
+            r.extend(self.search(package_ref, fq_decl_ref, folder));
+        }
+    }
+
     fn extract_identifier(&mut self, b: &HashedNodeRef<'a, JavaIdN>) -> Option<LabelIdentifier> {
         for xx in b.children().unwrap().iter_children() {
             let bb = self
@@ -1253,16 +1944,87 @@ impl<'a> RefsFinder<'a> {
         None
     }
 
-    /// top down search of references matching [`p`][`i`]
+    /// top down search of references matching [`p`][`i`],
+    /// reusing the references already found in the same subtrees (see [`ReferencesCache`])
     fn search(&mut self, p: &RefPtr, i: &RefPtr, s: &TypedScout) -> Vec<SpHandle> {
         // self.structural_positions
         //     .check_with(&self.stores, s)
         //     .expect("search");
-        usage::RefsFinder::new(self.stores, &mut self.ana, &mut self.structural_positions).find_all(
-            *p,
-            *i,
-            s.clone(),
-        )
+        let search = self.cache.search_id(
+            self.ana.solver.nodes.with(*p).bytes(),
+            self.ana.solver.nodes.with(*i).bytes(),
+        );
+        let x = *s.node_always(&self.structural_positions).unwrap().as_id();
+        let mut r = vec![];
+        if self.search_subtree(p, i, search, s, x) {
+            let from = self.structural_positions.push_typed(&mut s.clone());
+            self.cache
+                .materialize(search, x, from, self.structural_positions, &mut r);
+        }
+        r
+    }
+
+    /// Puts in the cache the references matching [`p`][`i`] in the subtree [`x`] at [`s`],
+    /// going down directories to only search the files that were not searched yet.
+    /// Returns true if the subtree contains references.
+    fn search_subtree(
+        &mut self,
+        p: &RefPtr,
+        i: &RefPtr,
+        search: usize,
+        s: &TypedScout,
+        x: NodeIdentifier,
+    ) -> bool {
+        if let Some(found) = self.cache.get(x, search) {
+            let contains = !found.is_empty();
+            self.cache.hits += 1;
+            return contains;
+        }
+        self.cache.misses += 1;
+        let stores = self.stores;
+        let node_store = &stores.node_store;
+        let found = match node_store.try_resolve_typed::<JavaIdN>(&x) {
+            Some((b, _)) if b.get_type() == Type::Directory => {
+                let mut children = vec![];
+                if b.check(self.ana.solver.nodes.with(*i)) != BloomResult::DoNotContain {
+                    for (o, xx) in b.children().unwrap().iter_children().enumerate() {
+                        let Some((_, tx)) = node_store.try_resolve_typed::<JavaIdN>(xx) else {
+                            continue;
+                        };
+                        let o = num::cast(o).unwrap();
+                        let mut s = s.clone();
+                        s.goto_typed(tx, o);
+                        if self.search_subtree(p, i, search, &s, *xx) {
+                            children.push((o, *xx));
+                        }
+                    }
+                }
+                Found::Children(children.into())
+            }
+            _ => {
+                let refs = usage::RefsFinder::new(
+                    self.stores,
+                    &mut self.ana,
+                    &mut self.structural_positions,
+                )
+                .find_all(*p, *i, s.clone());
+                let paths = refs
+                    .into_iter()
+                    .filter_map(|r| {
+                        let path = self.structural_positions.path_from(&x, r);
+                        if path.is_none() {
+                            log::error!("reference found outside of the searched subtree");
+                        }
+                        path.map(Vec::into_boxed_slice)
+                    })
+                    .collect();
+                Found::Paths(paths)
+            }
+        };
+        let contains = !found.is_empty();
+        let used = Cell::new(self.cache.commit);
+        self.cache.found.insert((x, search), (found, used));
+        contains
     }
 }
 
@@ -1312,3 +2074,206 @@ pub fn find_package_in_other_folders<
         })
         .collect()
 }
+
+#[test]
+fn jdk_packages() {
+    assert!(is_jdk_package("java.util.List"));
+    assert!(is_jdk_package("javax.annotation.Nullable"));
+    assert!(is_jdk_package("com.sun.net.httpserver"));
+    assert!(is_jdk_package("org.w3c.dom.Node"));
+    assert!(!is_jdk_package("javaslang.Tuple"));
+    assert!(!is_jdk_package("com.google.common.collect.Lists"));
+    assert!(!is_jdk_package("org.apache.commons.lang3"));
+}
+
+#[cfg(feature = "maven")]
+#[test]
+fn references_in_dependent_modules() {
+    use crate::{maven::MavenModuleAcc, maven_processor::MavenProcessor, Processor};
+    let pom = |artifact_id: &str, dependency: Option<&str>| {
+        let dependency = dependency.map_or(String::new(), |x| {
+            format!(
+                "<dependencies><dependency><groupId>org.example</groupId><artifactId>{}</artifactId></dependency></dependencies>",
+                x
+            )
+        });
+        format!(
+            "<project><groupId>org.example</groupId><artifactId>{}</artifactId>{}</project>\n",
+            artifact_id, dependency
+        )
+    };
+    let root_pom = "<project><groupId>org.example</groupId><artifactId>root</artifactId>\
+        <modules><module>a</module><module>b</module><module>c</module></modules></project>\n";
+    let (pom_a, pom_b, pom_c) = (pom("a", None), pom("b", Some("a")), pom("c", None));
+    let (repository, tree) = crate::tests::repository(
+        "allrefs-modules",
+        &[
+            ("pom.xml", root_pom),
+            ("a/pom.xml", &pom_a),
+            (
+                "a/src/main/java/org/a/A.java",
+                "package org.a;\npublic class A {}\n",
+            ),
+            ("b/pom.xml", &pom_b),
+            (
+                "b/src/main/java/org/b/B.java",
+                "package org.b;\nimport org.a.A;\nclass B { A a; }\n",
+            ),
+            // references A without depending on a
+            ("c/pom.xml", &pom_c),
+            (
+                "c/src/main/java/org/c/C.java",
+                "package org.c;\nimport org.a.A;\nclass C { A a; }\n",
+            ),
+        ],
+    );
+    let mut prepro = crate::preprocessed::RepositoryProcessor::default();
+    let dir_path = std::path::PathBuf::from("");
+    let mut dir_path = dir_path.components().peekable();
+    let root = MavenProcessor::<true, false, MavenModuleAcc>::new(
+        &repository,
+        &mut prepro,
+        &mut dir_path,
+        b"",
+        tree,
+    )
+    .process()
+    .0;
+    let stores = prepro.main_stores();
+    let graph = MavenModuleGraph::new(stores, root);
+    let module = |name: &str| {
+        (graph.modules().iter())
+            .position(|x| x.make_position(stores).file().ends_with(name))
+            .unwrap()
+    };
+    let (a, b, c) = (module("a"), module("b"), module("c"));
+    assert_eq!(graph.dependencies(b).collect::<Vec<_>>(), vec![a]);
+    assert_eq!(graph.visible_from(b), vec![(a, false)]);
+    assert!(graph.visible_from(c).is_empty());
+
+    let dependents = graph.dependent_folders(a);
+    assert_eq!(dependents.len(), 1);
+    let source = graph.folders(a).0.clone().unwrap();
+    let decl = make_decl_iter(stores, source, vec![])
+        .find(|ExpandedDeclaration(x, _, _)| {
+            x.make_position(stores).file().ends_with("org/a/A.java")
+        })
+        .unwrap();
+    let ExpandedDeclaration(decl, root_folder, of) = decl;
+    let (_, _, module_references) = find_declaration_references_position(
+        root,
+        stores,
+        &decl,
+        root_folder,
+        of,
+        &dependents,
+        &mut ReferencesCache::default(),
+    )
+    .unwrap();
+    let files: Vec<_> = module_references.iter().map(|x| x.file()).collect();
+    assert!(!files.is_empty());
+    assert!(files
+        .iter()
+        .all(|x| x.ends_with("b/src/main/java/org/b/B.java")));
+}
+
+/// a relation written by [`Writer::positions_of_referencial_relations`], without its duration
+#[cfg(feature = "maven")]
+#[cfg(test)]
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+struct Relation {
+    decl: serde_json::Value,
+    search: String,
+    refs: Vec<serde_json::Value>,
+    module_refs: Vec<serde_json::Value>,
+}
+
+#[cfg(feature = "maven")]
+#[cfg(test)]
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+struct PerModule {
+    module: String,
+    content: Vec<Relation>,
+}
+
+#[cfg(feature = "maven")]
+#[cfg(test)]
+fn parse_relations(out: &[u8]) -> Vec<PerModule> {
+    let out = [b"[".as_slice(), out, b"]"].concat();
+    serde_json::from_slice(&out).unwrap()
+}
+
+#[cfg(feature = "maven")]
+#[test]
+fn cached_references_across_commits() {
+    use crate::{maven::MavenModuleAcc, maven_processor::MavenProcessor, Processor};
+    let pom = "<project><groupId>org.example</groupId><artifactId>a</artifactId></project>\n";
+    let a = "package org.a;\npublic class A { public static void f() {} }\n";
+    let b = "package org.a.b;\nimport org.a.A;\nclass B { A a; }\n";
+    let c = "package org.a.c;\nclass C { void g() { org.a.A.f(); } }\n";
+    let b2 = "package org.a.b;\nclass B { org.a.A a; void g() { org.a.A.f(); } }\n";
+    let c2 = "package org.a.c;\nclass C {}\n";
+    // only c changes, then b changes, then back to the first commit
+    let commits: [[&str; 3]; 4] = [[a, b, c], [a, b, c2], [a, b2, c2], [a, b, c]];
+    let mut prepro = crate::preprocessed::RepositoryProcessor::default();
+    let mut cache = ReferencesCache::default();
+    let mut entries = vec![];
+    let mut roots = vec![];
+    for (n, [a, b, c]) in commits.into_iter().enumerate() {
+        let (repository, tree) = crate::tests::repository(
+            &format!("allrefs-cache-{}", n),
+            &[
+                ("pom.xml", pom),
+                ("src/main/java/org/a/A.java", a),
+                ("src/main/java/org/a/b/B.java", b),
+                ("src/main/java/org/a/c/C.java", c),
+            ],
+        );
+        let dir_path = std::path::PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let root = MavenProcessor::<true, false, MavenModuleAcc>::new(
+            &repository,
+            &mut prepro,
+            &mut dir_path,
+            b"",
+            tree,
+        )
+        .process()
+        .0;
+        let stores = prepro.main_stores();
+        let mut uncached = vec![];
+        write_referencial_relations(stores, root, &mut uncached);
+        let mut cached = vec![];
+        write_referencial_relations_with_cache(stores, root, &mut cache, &mut cached);
+        assert_eq!(
+            parse_relations(&cached),
+            parse_relations(&uncached),
+            "commit {}",
+            n
+        );
+        entries.push(cache.entries());
+        roots.push(root);
+    }
+    assert!(cache.hits() > 0);
+    assert!(entries.iter().all(|x| *x > 0));
+
+    // the references of the last commit, searched twice
+    let root = *roots.last().unwrap();
+    let misses = |window| {
+        let mut cache = ReferencesCache::with_window(window);
+        let stores = prepro.main_stores();
+        let mut misses = vec![];
+        for _ in 0..2 {
+            let before = cache.misses();
+            write_referencial_relations_with_cache(stores, root, &mut cache, &mut std::io::sink());
+            misses.push(cache.misses() - before);
+        }
+        misses
+    };
+    let kept = misses(1);
+    assert!(kept[1] < kept[0]);
+    // nothing is kept from one commit to the next
+    let evicted = misses(0);
+    assert_eq!(evicted[0], evicted[1]);
+    assert_eq!(evicted[0], kept[0]);
+}
@@ -0,0 +1,528 @@
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::SubTreeMetrics,
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_xml::{legion::XmlTreeGen, types::Type};
+
+use crate::{
+    cpp_processor::{prepare_dir_exploration, CppProc},
+    git::BasicGitObject,
+    java_processor::JavaProc,
+    preprocessed::RepositoryProcessor,
+    processing::{
+        erased::{CommitProcExt, ParametrizedCommitProc2, ParametrizedCommitProcessor2Handle},
+        CacheHolding, InFiles, ObjectName,
+    },
+    python_processor::PythonProc,
+    rust_processor::RustProc,
+    ts_processor::TsProc,
+    utils::make_text,
+    Accumulator, DefaultMetrics, ParseErr, Processor, SimpleStores, TStore,
+    PROPAGATE_ERROR_ON_BAD_CST_NODE,
+};
+
+/// Text files larger than this are skipped, they are most likely generated or data.
+const MAX_TEXT_SIZE: usize = 1 << 20;
+
+/// Builds a plain hierarchy of directories, without looking for any build system.
+///
+/// Files are dispatched on their extension to the java, cpp, ts, python, rust and xml generators,
+/// other utf8 files are kept as opaque text leaves and binary files are skipped.
+pub struct AnyProcessor<'a, 'b, 'c> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, AnyAcc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: ParametrizedCommitProcessor2Handle<AnyProc>,
+    java_handle: ParametrizedCommitProcessor2Handle<JavaProc>,
+    cpp_handle: ParametrizedCommitProcessor2Handle<CppProc>,
+    ts_handle: ParametrizedCommitProcessor2Handle<TsProc>,
+    python_handle: ParametrizedCommitProcessor2Handle<PythonProc>,
+    rust_handle: ParametrizedCommitProcessor2Handle<RustProc>,
+}
+
+impl<'a, 'b, 'c> AnyProcessor<'a, 'b, 'c> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let handle = AnyProc::register_param(h, Parameter);
+        // the file processors are shared with the other configs,
+        // they must be registered before their caches are used
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<crate::java_processor::JavaProcessorHolder>();
+        let java_handle = JavaProc::register_param(h, crate::java_processor::Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<crate::cpp_processor::CppProcessorHolder>();
+        let cpp_handle = CppProc::register_param(h, crate::cpp_processor::Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<crate::ts_processor::TsProcessorHolder>();
+        let ts_handle = TsProc::register_param(h, crate::ts_processor::Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<crate::python_processor::PythonProcessorHolder>();
+        let python_handle = PythonProc::register_param(h, crate::python_processor::Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<crate::rust_processor::RustProcessorHolder>();
+        let rust_handle = RustProc::register_param(h, crate::rust_processor::Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, AnyAcc::new(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+            java_handle,
+            cpp_handle,
+            ts_handle,
+            python_handle,
+            rust_handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c> Processor<AnyAcc> for AnyProcessor<'a, 'b, 'c> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                match self.handle_blob(oid, &name) {
+                    Ok(full_node) => {
+                        let name = self.prepro.intern_object_name(&name);
+                        let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                        assert!(!parent_acc.children_names.contains(&name));
+                        parent_acc.push((name, full_node));
+                    }
+                    Err(err) => {
+                        log::debug!("skipped {:?}: {:?}", name.try_str(), err);
+                    }
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: AnyAcc) -> Option<(NodeIdentifier, DefaultMetrics)> {
+        let name = acc.name.clone();
+        let full_node = make(acc, self.prepro.main_stores_mut());
+        self.prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .insert(oid, full_node.clone());
+        let name = self.prepro.intern_label(&name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.children_names.contains(&name),
+                "{:?} {:?}",
+                w.children_names,
+                name
+            );
+            w.push((name, full_node));
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, AnyAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c> AnyProcessor<'a, 'b, 'c> {
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        if let Some(s) = self.dir_path.peek() {
+            if name
+                .as_bytes()
+                .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+            {
+                self.dir_path.next();
+                self.stack.last_mut().expect("never empty").1.clear();
+                let tree = self.repository.find_tree(oid).unwrap();
+                let prepared = prepare_dir_exploration(tree);
+                let acc = AnyAcc::new(name.try_into().unwrap());
+                self.stack.push((oid, prepared, acc));
+            }
+            return;
+        }
+        if let Some(already) = self
+            .prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .get(&oid)
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            assert!(!w.children_names.contains(&name));
+            w.push((name, full_node));
+            return;
+        }
+        log::debug!("tree {:?}", name.try_str());
+        let tree = self.repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree);
+        let acc = AnyAcc::new(name.try_into().unwrap());
+        self.stack.push((oid, prepared, acc));
+    }
+
+    /// Dispatches the file on its extension, falling back to a text leaf.
+    fn handle_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+    ) -> Result<(NodeIdentifier, DefaultMetrics), ParseErr> {
+        use crate::processing::file_sys;
+        let repository = self.repository;
+        if file_sys::Java::matches(name) {
+            let (x, _) = self
+                .prepro
+                .handle_java_blob(oid, name, repository, self.java_handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else if file_sys::Cpp::matches(name) {
+            let (x, _) = self
+                .prepro
+                .handle_cpp_blob(oid, name, repository, self.cpp_handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else if file_sys::Ts::matches(name) {
+            let (x, _) = self
+                .prepro
+                .handle_ts_blob(oid, name, repository, self.ts_handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else if file_sys::Python::matches(name) {
+            let (x, _) =
+                self.prepro
+                    .handle_python_blob(oid, name, repository, self.python_handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else if file_sys::Rust::matches(name) {
+            let (x, _) = self
+                .prepro
+                .handle_rust_blob(oid, name, repository, self.rust_handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else if file_sys::Xml::matches(name) {
+            let x = self
+                .prepro
+                .handle_xml_blob(oid, name, repository, self.handle)?;
+            Ok((x.compressed_node, x.metrics))
+        } else {
+            self.prepro
+                .handle_text_blob(oid, name, repository, self.handle)
+        }
+    }
+}
+
+pub struct AnyAcc {
+    pub(crate) name: String,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) metrics: DefaultMetrics,
+}
+
+impl AnyAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for AnyAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, DefaultMetrics));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1);
+    }
+}
+
+impl Accumulator for AnyAcc {
+    type Unlabeled = (NodeIdentifier, DefaultMetrics);
+}
+
+pub(crate) fn make(acc: AnyAcc, stores: &mut SimpleStores) -> (NodeIdentifier, DefaultMetrics) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::legion::{compo, compo::CS, NodeStore},
+    };
+    use hyper_ast_gen_ts_xml::legion::eq_node;
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node(&Type::Directory, Some(&label), &acc.children);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make dir {} {}", &acc.name, acc.children.len());
+        let vacant = insertion.vacant();
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        NodeStore::insert_after_prepare(
+            vacant,
+            (
+                Type::Directory,
+                label,
+                hashs,
+                compo::Size(size),
+                compo::Height(height),
+                compo::SizeNoSpaces(size_no_spaces),
+                CS(acc.children_names.into_boxed_slice()),
+                CS(acc.children.into_boxed_slice()),
+                BloomSize::Much,
+            ),
+        )
+    };
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (node_id, metrics)
+}
+
+impl RepositoryProcessor {
+    fn handle_xml_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: ParametrizedCommitProcessor2Handle<AnyProc>,
+    ) -> Result<hyper_ast_gen_ts_xml::legion::Local, ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::Xml>()
+            .handle2(oid, repository, name, parameters, |_, n, t| {
+                let line_break = if t.contains(&b'\r') {
+                    "\r\n".as_bytes().to_vec()
+                } else {
+                    "\n".as_bytes().to_vec()
+                };
+                let tree = match XmlTreeGen::<TStore>::tree_sitter_parse(t) {
+                    Ok(tree) => tree,
+                    Err(tree) => {
+                        log::warn!("bad CST");
+                        log::debug!("{:?}", n.try_str());
+                        log::debug!("{}", tree.root_node().to_sexp());
+                        if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                            return Err(ParseErr::IllFormed);
+                        } else {
+                            tree
+                        }
+                    }
+                };
+                let mut tree_gen = XmlTreeGen {
+                    line_break,
+                    stores: &mut self.main_stores,
+                };
+                Ok(tree_gen.generate_file(n.as_bytes(), t, tree.walk()).local)
+            })
+    }
+
+    /// The content of the file becomes the label of a single leaf.
+    fn handle_text_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: ParametrizedCommitProcessor2Handle<AnyProc>,
+    ) -> Result<(NodeIdentifier, DefaultMetrics), ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::Text>()
+            .handle(oid, repository, name, parameters, |_, _, t| {
+                if t.len() > MAX_TEXT_SIZE {
+                    return Err(ParseErr::IllFormed);
+                }
+                let text = std::str::from_utf8(t)?;
+                Ok(make_text(text, &mut self.main_stores))
+            })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+
+#[derive(Default)]
+pub struct AnyProcessorHolder(Option<AnyProc>);
+pub struct AnyProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Any,
+    xml_cache: crate::processing::caches::Xml,
+    text_cache: crate::processing::caches::Text,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for AnyProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(AnyProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    xml_cache: Default::default(),
+                    text_cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedAnyCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedAnyCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedAnyCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = AnyProcessor::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let handle = AnyProc::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for AnyProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedAnyCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for AnyProc {
+    type Holder = AnyProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for AnyProcessorHolder {
+    type Proc = AnyProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Any> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Any {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Any {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Any> for AnyProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Any {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Any {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Xml> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Xml {
+        &mut self.xml_cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Xml {
+        &self.xml_cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Text> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Text {
+        &mut self.text_cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Text {
+        &self.text_cache
+    }
+}
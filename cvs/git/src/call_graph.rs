@@ -0,0 +1,1340 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::Write,
+};
+
+use hyper_ast::{
+    position::{StructuralPosition, TreePath, TreePathMut},
+    store::defaults::NodeIdentifier,
+    types::{
+        HyperType, IterableChildren, LabelStore, Labeled, Tree, TypeStore, TypeTrait, Typed,
+        WithChildren,
+    },
+};
+use hyper_ast_gen_ts_java::{
+    impact::{
+        element::{Arguments, LabelPtr, RefPtr, RefsEnum},
+        java_element::Primitive,
+        solver::Solver,
+    },
+    legion_with_refs::MDCache,
+    types::Type,
+};
+
+use crate::{
+    allrefs::{extract_qualified_name, import_declaration},
+    SimpleStores,
+};
+
+type JavaIdN = hyper_ast_gen_ts_java::types::TIdN<NodeIdentifier>;
+
+const PRIMITIVES: [&str; 9] = [
+    "boolean", "void", "float", "double", "byte", "char", "short", "int", "long",
+];
+
+const BOXES: [&str; 8] = [
+    "Boolean",
+    "Float",
+    "Double",
+    "Byte",
+    "Character",
+    "Short",
+    "Integer",
+    "Long",
+];
+
+/// A method or a constructor declared in the repository.
+#[derive(Debug, Clone)]
+pub struct Method {
+    /// identical declarations in different types are distinct methods
+    pub position: StructuralPosition,
+    /// the declaring type, an index in [`CallGraph::types`]
+    pub class: usize,
+    /// the name of the declaring type for constructors
+    pub name: String,
+    pub constructor: bool,
+    pub is_static: bool,
+    /// number of parameters
+    pub arity: usize,
+    pub varargs: bool,
+    /// types of the parameters as written, None for arrays
+    pub params: Vec<Option<String>>,
+    /// as written in the declaration, None for arrays
+    return_type: Option<String>,
+}
+
+impl Method {
+    fn accepts(&self, arity: usize) -> bool {
+        self.arity == arity || (self.varargs && arity + 1 >= self.arity)
+    }
+}
+
+/// A class, an interface, an enum or a record declared in the repository.
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+    /// the declaration, or the instance creation of an anonymous class
+    pub position: StructuralPosition,
+    /// eg. `p.A.Inner`, or `p.A$1` for the first anonymous class of `p.A`
+    pub qualified_name: String,
+    name: String,
+    file: usize,
+    outer: Option<usize>,
+    /// as written in the `extends` clause of a class
+    superclass: Option<String>,
+    /// as written in the `extends` and `implements` clauses
+    supertypes: Vec<String>,
+    /// names and types of the fields, as written
+    fields: Vec<(String, String)>,
+    pub methods: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FileInfo {
+    package: String,
+    /// imported names, and if they are on demand imports
+    imports: Vec<(Vec<String>, bool)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// the callee is the declaration designated by the invocation
+    Direct,
+    /// instance creations and `this(...)` or `super(...)` invocations
+    Constructor,
+    /// the callee overrides the declaration designated by the invocation, so it might be the one executed
+    Override,
+}
+
+impl std::fmt::Display for CallKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallKind::Direct => write!(f, "direct"),
+            CallKind::Constructor => write!(f, "constructor"),
+            CallKind::Override => write!(f, "override"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    /// indexes in [`CallGraph::methods`]
+    pub caller: usize,
+    pub callee: usize,
+    /// the invocation
+    pub site: StructuralPosition,
+    pub kind: CallKind,
+}
+
+/// An invocation without declaration in the repository, eg. a call to the JDK or to a library.
+#[derive(Debug, Clone)]
+pub struct UnresolvedCall {
+    pub caller: usize,
+    pub site: StructuralPosition,
+    /// as written, `new T` for instance creations
+    pub name: String,
+    pub arity: usize,
+}
+
+/// The method to method calls of the java code of a commit.
+///
+/// Invocations are read from the partial analysis of their subtree, kept in the [`MDCache`] of the java trees.
+/// Their receivers and arguments are typed with the declarations of the local variables of the enclosing blocks,
+/// of the parameters and of the fields, or with the declared return type of a method for chained calls.
+/// Overloads are chosen with the types of the arguments when they are known,
+/// so an invocation can still have multiple callees.
+///
+/// Methods and invocations are identified by their structural position,
+/// as identical subtrees are shared by the HyperAST.
+/// Anonymous and local classes are types of their own, their methods are not part of the enclosing one.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    pub methods: Vec<Method>,
+    pub types: Vec<TypeDecl>,
+    pub calls: Vec<Call>,
+    pub unresolved: Vec<UnresolvedCall>,
+    files: Vec<FileInfo>,
+    by_name: HashMap<String, usize>,
+}
+
+impl CallGraph {
+    pub fn new(stores: &SimpleStores, md_cache: &MDCache, root: NodeIdentifier) -> Self {
+        let mut builder = Builder {
+            stores,
+            md_cache,
+            graph: Default::default(),
+            bodies: vec![],
+            supers: vec![],
+            subtypes: vec![],
+            anonymous: Default::default(),
+        };
+        builder.walk(StructuralPosition::new(root));
+        builder.resolve();
+        builder.graph
+    }
+
+    /// eg. `p.A.m`, or `p.A.A` for a constructor
+    pub fn qualified_name(&self, m: usize) -> String {
+        let m = &self.methods[m];
+        format!("{}.{}", self.types[m.class].qualified_name, m.name)
+    }
+
+    /// The methods whose qualified name ends with [`name`], eg. `A.m`.
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = usize> + 'a {
+        (0..self.methods.len()).filter(move |m| {
+            let q = self.qualified_name(*m);
+            q == name || q.ends_with(&format!(".{}", name))
+        })
+    }
+
+    pub fn callees(&self, m: usize) -> impl Iterator<Item = &Call> {
+        self.calls.iter().filter(move |x| x.caller == m)
+    }
+
+    pub fn callers(&self, m: usize) -> impl Iterator<Item = &Call> {
+        self.calls.iter().filter(move |x| x.callee == m)
+    }
+
+    /// Only keeps the methods reachable from [`selected`] with at most [`depth`] calls, in both directions.
+    pub fn around(&self, selected: impl IntoIterator<Item = usize>, depth: usize) -> CallGraph {
+        let mut kept: HashSet<usize> = selected.into_iter().collect();
+        let mut frontier: Vec<usize> = kept.iter().copied().collect();
+        for _ in 0..depth {
+            let mut next = vec![];
+            for m in frontier {
+                for c in self.callees(m).chain(self.callers(m)) {
+                    for x in [c.caller, c.callee] {
+                        if kept.insert(x) {
+                            next.push(x);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+        let mut indexes = vec![None; self.methods.len()];
+        let mut r = CallGraph {
+            types: self.types.clone(),
+            files: self.files.clone(),
+            by_name: self.by_name.clone(),
+            ..Default::default()
+        };
+        for (i, m) in self.methods.iter().enumerate() {
+            if kept.contains(&i) {
+                indexes[i] = Some(r.methods.len());
+                r.methods.push(m.clone());
+            }
+        }
+        for t in &mut r.types {
+            t.methods = t.methods.iter().filter_map(|x| indexes[*x]).collect();
+        }
+        for c in &self.calls {
+            if let (Some(caller), Some(callee)) = (indexes[c.caller], indexes[c.callee]) {
+                r.calls.push(Call {
+                    caller,
+                    callee,
+                    ..c.clone()
+                });
+            }
+        }
+        for c in &self.unresolved {
+            if let Some(caller) = indexes[c.caller] {
+                r.unresolved.push(UnresolvedCall {
+                    caller,
+                    ..c.clone()
+                });
+            }
+        }
+        r
+    }
+
+    /// Write in [`out`], the JSON formated reprentation of the call graph.
+    /// Methods are referred to by their index in `methods` and by the HyperAST node of their declaration,
+    /// invocations by their node and by their position.
+    /// Nodes are shared by identical subtrees, so distinct methods can have the same node.
+    pub fn write_json<W: Write>(&self, stores: &SimpleStores, out: &mut W) -> std::io::Result<()> {
+        write!(out, r#"{{"methods":["#)?;
+        for (i, m) in self.methods.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                r#"{{"name":"{}","arity":{},"constructor":{},"id":{},"position":{}}}"#,
+                self.qualified_name(i),
+                m.arity,
+                m.constructor,
+                node_id(&m.position),
+                m.position.make_position(stores)
+            )?;
+        }
+        write!(out, r#"],"calls":["#)?;
+        for (i, c) in self.calls.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                r#"{{"caller":{},"callee":{},"caller_id":{},"callee_id":{},"site_id":{},"site":{},"kind":"{}"}}"#,
+                c.caller,
+                c.callee,
+                node_id(&self.methods[c.caller].position),
+                node_id(&self.methods[c.callee].position),
+                node_id(&c.site),
+                c.site.make_position(stores),
+                c.kind
+            )?;
+        }
+        write!(out, r#"],"unresolved":["#)?;
+        for (i, c) in self.unresolved.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(
+                out,
+                r#"{{"caller":{},"caller_id":{},"site_id":{},"site":{},"name":"{}","arity":{}}}"#,
+                c.caller,
+                node_id(&self.methods[c.caller].position),
+                node_id(&c.site),
+                c.site.make_position(stores),
+                c.name,
+                c.arity
+            )?;
+        }
+        write!(out, "]}}")
+    }
+
+    /// Write in [`out`], the call graph in the DOT language of graphviz.
+    /// Calls to overriding methods are dashed.
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "digraph calls {{")?;
+        for (i, m) in self.methods.iter().enumerate() {
+            writeln!(
+                out,
+                "  m{} [label=\"{}/{}\"];",
+                i,
+                self.qualified_name(i),
+                m.arity
+            )?;
+        }
+        let mut edges = HashSet::new();
+        for c in &self.calls {
+            if !edges.insert((c.caller, c.callee, c.kind)) {
+                continue;
+            }
+            match c.kind {
+                CallKind::Override => {
+                    writeln!(out, "  m{} -> m{} [style=dashed];", c.caller, c.callee)?
+                }
+                _ => writeln!(out, "  m{} -> m{};", c.caller, c.callee)?,
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// The HyperAST node at [`p`], as the integer used by the client to refer to nodes.
+fn node_id(p: &StructuralPosition) -> u64 {
+    let id: NodeIdentifier = *p.node().unwrap();
+    unsafe { std::mem::transmute(id) }
+}
+
+/// The type of an expression, as far as it is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExprType {
+    Primitive(Primitive),
+    /// declared in the repository
+    Declared(usize),
+    /// declared outside of the repository, by its simple name
+    External(String),
+}
+
+struct Builder<'a> {
+    stores: &'a SimpleStores,
+    md_cache: &'a MDCache,
+    graph: CallGraph,
+    /// body and parameters of each method
+    bodies: Vec<(Option<StructuralPosition>, Vec<(String, Option<String>)>)>,
+    /// resolved supertypes of each type
+    supers: Vec<Vec<usize>>,
+    subtypes: Vec<Vec<usize>>,
+    /// number of anonymous classes declared in each type
+    anonymous: HashMap<usize, usize>,
+}
+
+/// Where an invocation is resolved.
+struct Scope {
+    class: usize,
+    /// types of the parameters and of the local variables of the enclosing blocks, innermost last.
+    /// None when it is not written, eg. with `var` or for the parameters of a lambda
+    locals: Vec<HashMap<String, Option<String>>>,
+}
+
+impl Scope {
+    fn local(&self, name: &str) -> Option<&Option<String>> {
+        self.locals.iter().rev().find_map(|x| x.get(name))
+    }
+
+    fn declare(&mut self, name: String, ty: Option<String>) {
+        let ty = ty.filter(|x| x != "var");
+        self.locals.last_mut().unwrap().insert(name, ty);
+    }
+}
+
+enum Visit {
+    Enter(StructuralPosition),
+    /// leaving a block
+    Exit,
+}
+
+impl<'a> Builder<'a> {
+    fn walk(&mut self, root: StructuralPosition) {
+        let mut stack = vec![root];
+        while let Some(p) = stack.pop() {
+            let x = *p.node().unwrap();
+            match self.kind(&x) {
+                Some(Type::Program) => {
+                    self.collect_file(p);
+                    continue;
+                }
+                Some(Type::Directory) => (),
+                Some(_) => continue,
+                None => {
+                    // eg. directories of maven modules
+                    let n = self.stores.node_store.resolve(x);
+                    if !self.stores.type_store.resolve_type(&n).is_directory() {
+                        continue;
+                    }
+                }
+            }
+            let n = self.stores.node_store.resolve(x);
+            let Some(cs) = n.children() else {
+                continue;
+            };
+            let cs: Vec<NodeIdentifier> = cs.iter_children().copied().collect();
+            for (i, xx) in cs.into_iter().enumerate().rev() {
+                let mut p = p.clone();
+                p.goto(xx, num::cast(i).unwrap());
+                stack.push(p);
+            }
+        }
+    }
+
+    fn collect_file(&mut self, p: StructuralPosition) {
+        let file = self.graph.files.len();
+        self.graph.files.push(Default::default());
+        for (i, t, x) in self.children(p.node().unwrap()) {
+            if t == Type::PackageDeclaration {
+                let mut path = vec![];
+                for (_, _, xx) in self.children(&x) {
+                    extract_qualified_name(self.stores, xx, &mut path);
+                }
+                self.graph.files[file].package = path.join(".");
+            } else if t == Type::ImportDeclaration {
+                if let Some(import) = import_declaration(self.stores, x) {
+                    self.graph.files[file].imports.push(import);
+                }
+            } else if is_type_declaration(t) {
+                let mut p = p.clone();
+                p.goto(x, i);
+                self.collect_type(p, file, None);
+            }
+        }
+    }
+
+    fn collect_type(&mut self, p: StructuralPosition, file: usize, outer: Option<usize>) {
+        let id = *p.node().unwrap();
+        let Some(name) = self.declared_name(&id) else {
+            return;
+        };
+        let qualified_name = match outer {
+            Some(o) => format!("{}.{}", self.graph.types[o].qualified_name, name),
+            None if self.graph.files[file].package.is_empty() => name.clone(),
+            None => format!("{}.{}", self.graph.files[file].package, name),
+        };
+        let t = self.add_type(p.clone(), file, outer, name, qualified_name);
+        for (i, k, x) in self.children(&id) {
+            match k {
+                Type::Superclass | Type::SuperInterfaces | Type::ExtendsInterfaces => {
+                    let names: Vec<String> = self
+                        .children(&x)
+                        .into_iter()
+                        .filter_map(|(_, _, xx)| self.type_name(&xx))
+                        .collect();
+                    if k == Type::Superclass {
+                        self.graph.types[t].superclass = names.first().cloned();
+                    }
+                    self.graph.types[t].supertypes.extend(names);
+                }
+                k if k.is_type_body() => {
+                    let mut p = p.clone();
+                    p.goto(x, i);
+                    self.collect_members(p, file, t);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn add_type(
+        &mut self,
+        position: StructuralPosition,
+        file: usize,
+        outer: Option<usize>,
+        name: String,
+        qualified_name: String,
+    ) -> usize {
+        let t = self.graph.types.len();
+        self.graph.by_name.insert(qualified_name.clone(), t);
+        self.graph.types.push(TypeDecl {
+            position,
+            qualified_name,
+            name,
+            file,
+            outer,
+            superclass: None,
+            supertypes: vec![],
+            fields: vec![],
+            methods: vec![],
+        });
+        t
+    }
+
+    fn collect_members(&mut self, p: StructuralPosition, file: usize, t: usize) {
+        for (i, k, x) in self.children(p.node().unwrap()) {
+            let mut p = p.clone();
+            p.goto(x, i);
+            match k {
+                Type::EnumBodyDeclarations => self.collect_members(p, file, t),
+                Type::MethodDeclaration | Type::ConstructorDeclaration => {
+                    self.collect_method(p, file, t, k == Type::ConstructorDeclaration)
+                }
+                Type::FieldDeclaration | Type::ConstantDeclaration => {
+                    let cs = self.children(&x);
+                    // in initializers
+                    self.collect_local_types(p, file, t);
+                    let Some(ty) = cs.iter().find_map(|(_, _, xx)| self.type_name(xx)) else {
+                        continue;
+                    };
+                    for (_, kk, xx) in cs {
+                        if kk == Type::VariableDeclarator {
+                            if let Some(name) = self.declared_name(&xx) {
+                                self.graph.types[t].fields.push((name, ty.clone()));
+                            }
+                        }
+                    }
+                }
+                k if is_type_declaration(k) => self.collect_type(p, file, Some(t)),
+                _ => (),
+            }
+        }
+    }
+
+    fn collect_method(
+        &mut self,
+        p: StructuralPosition,
+        file: usize,
+        class: usize,
+        constructor: bool,
+    ) {
+        let id = *p.node().unwrap();
+        let mut name = None;
+        let mut is_static = false;
+        let mut return_type = None;
+        let mut params = vec![];
+        let mut varargs = false;
+        let mut body = None;
+        for (i, k, x) in self.children(&id) {
+            match k {
+                Type::Modifiers => {
+                    is_static = self
+                        .children(&x)
+                        .iter()
+                        .any(|(_, kk, _)| *kk == Type::Static)
+                }
+                Type::Identifier => name = self.label(&x),
+                Type::FormalParameters => {
+                    varargs = self
+                        .children(&x)
+                        .iter()
+                        .any(|(_, kk, _)| *kk == Type::SpreadParameter);
+                    params = self.parameters(&x);
+                }
+                Type::Block | Type::ConstructorBody => {
+                    let mut p = p.clone();
+                    p.goto(x, i);
+                    body = Some(p);
+                }
+                _ if name.is_none() && return_type.is_none() => return_type = self.written_type(&x),
+                _ => (),
+            }
+        }
+        let Some(name) = name else {
+            return;
+        };
+        let m = self.graph.methods.len();
+        self.graph.types[class].methods.push(m);
+        self.graph.methods.push(Method {
+            position: p,
+            class,
+            name,
+            constructor,
+            is_static,
+            arity: params.len(),
+            varargs,
+            params: params.iter().map(|(_, ty)| ty.clone()).collect(),
+            return_type,
+        });
+        self.bodies.push((body.clone(), params));
+        if let Some(body) = body {
+            self.collect_local_types(body, file, class);
+        }
+    }
+
+    /// Collects the anonymous and local classes declared in [`p`], eg. in a method body.
+    fn collect_local_types(&mut self, p: StructuralPosition, file: usize, class: usize) {
+        let mut stack = vec![p];
+        while let Some(p) = stack.pop() {
+            let x = *p.node().unwrap();
+            let cs = self.children(&x);
+            match self.kind(&x) {
+                Some(k) if is_type_declaration(k) => {
+                    self.collect_type(p, file, Some(class));
+                    continue;
+                }
+                Some(Type::ObjectCreationExpression) => {
+                    if let Some((i, _, body)) = cs.iter().find(|(_, k, _)| *k == Type::ClassBody) {
+                        let n = self.anonymous.entry(class).or_default();
+                        *n += 1;
+                        let name = n.to_string();
+                        let qualified_name =
+                            format!("{}${}", self.graph.types[class].qualified_name, name);
+                        let t = self.add_type(p.clone(), file, Some(class), name, qualified_name);
+                        let superclass = cs.iter().find_map(|(_, _, x)| self.type_name(x));
+                        self.graph.types[t].superclass = superclass.clone();
+                        self.graph.types[t].supertypes.extend(superclass);
+                        let mut p = p.clone();
+                        p.goto(*body, *i);
+                        self.collect_members(p, file, t);
+                    }
+                }
+                _ => (),
+            }
+            for (i, k, xx) in cs.into_iter().rev() {
+                // the body of an anonymous class, collected above
+                if k == Type::ClassBody {
+                    continue;
+                }
+                let mut p = p.clone();
+                p.goto(xx, i);
+                stack.push(p);
+            }
+        }
+    }
+
+    fn resolve(&mut self) {
+        let n = self.graph.types.len();
+        self.supers = vec![vec![]; n];
+        self.subtypes = vec![vec![]; n];
+        for t in 0..n {
+            for s in self.graph.types[t].supertypes.clone() {
+                if let Some(s) = self.resolve_type_name(&s, t) {
+                    self.supers[t].push(s);
+                    self.subtypes[s].push(t);
+                }
+            }
+        }
+        for m in 0..self.graph.methods.len() {
+            let (Some(body), params) = self.bodies[m].clone() else {
+                continue;
+            };
+            let mut scope = Scope {
+                class: self.graph.methods[m].class,
+                locals: vec![params.into_iter().collect()],
+            };
+            self.resolve_body(m, body, &mut scope);
+        }
+    }
+
+    /// Resolves the invocations in the body of [`caller`], declaring the local variables in their block.
+    /// The bodies of anonymous and local classes are skipped, their methods are resolved on their own.
+    fn resolve_body(&mut self, caller: usize, body: StructuralPosition, scope: &mut Scope) {
+        let mut stack = vec![Visit::Enter(body)];
+        while let Some(v) = stack.pop() {
+            let p = match v {
+                Visit::Enter(p) => p,
+                Visit::Exit => {
+                    scope.locals.pop();
+                    continue;
+                }
+            };
+            let x = *p.node().unwrap();
+            let Some(k) = self.kind(&x) else {
+                continue;
+            };
+            if is_type_declaration(k) {
+                continue;
+            }
+            let cs = self.children(&x);
+            if opens_scope(k) {
+                scope.locals.push(Default::default());
+                stack.push(Visit::Exit);
+            }
+            match k {
+                Type::LocalVariableDeclaration => {
+                    let ty = cs.iter().find_map(|(_, _, xx)| self.written_type(xx));
+                    for (_, kk, xx) in &cs {
+                        if *kk == Type::VariableDeclarator {
+                            if let Some(name) = self.declared_name(xx) {
+                                scope.declare(name, ty.clone());
+                            }
+                        }
+                    }
+                }
+                Type::EnhancedForStatement | Type::Resource | Type::CatchFormalParameter => {
+                    let ty = cs.iter().find_map(|(_, kk, xx)| match kk {
+                        Type::CatchType => self
+                            .children(xx)
+                            .iter()
+                            .find_map(|(_, _, xxx)| self.written_type(xxx)),
+                        _ => self.written_type(xx),
+                    });
+                    // a resource can also be an existing variable
+                    if k != Type::Resource || ty.is_some() {
+                        if let Some(name) = self.declared_name(&x) {
+                            scope.declare(name, ty);
+                        }
+                    }
+                }
+                Type::LambdaExpression => match cs.first() {
+                    Some((_, Type::Identifier, xx)) => {
+                        if let Some(name) = self.label(xx) {
+                            scope.declare(name, None);
+                        }
+                    }
+                    Some((_, Type::InferredParameters, xx)) => {
+                        for (_, kk, xxx) in self.children(xx) {
+                            if let (Type::Identifier, Some(name)) = (kk, self.label(&xxx)) {
+                                scope.declare(name, None);
+                            }
+                        }
+                    }
+                    Some((_, Type::FormalParameters, xx)) => {
+                        for (name, ty) in self.parameters(xx) {
+                            scope.declare(name, ty);
+                        }
+                    }
+                    _ => (),
+                },
+                Type::MethodInvocation
+                | Type::ObjectCreationExpression
+                | Type::ExplicitConstructorInvocation => self.resolve_site(caller, &p, scope),
+                _ => (),
+            }
+            for (i, kk, xx) in cs.into_iter().rev() {
+                // the body of an anonymous class
+                if kk == Type::ClassBody {
+                    continue;
+                }
+                let mut p = p.clone();
+                p.goto(xx, i);
+                stack.push(Visit::Enter(p));
+            }
+        }
+    }
+
+    fn resolve_site(&mut self, caller: usize, p: &StructuralPosition, scope: &Scope) {
+        let site = *p.node().unwrap();
+        let cs = self.children(&site);
+        let arity = self.arity(&cs);
+        let k = self.kind(&site).unwrap();
+        let name = self.written_name(k, &cs);
+        let Some((solver, r)) = self.site_ref(&site) else {
+            log::debug!("no reference for the invocation {}", name);
+            self.unresolved(caller, p, name, arity);
+            return;
+        };
+        let (callees, kind) = match (&solver[r], k) {
+            (RefsEnum::Invocation(o, i, args), Type::MethodInvocation) => {
+                let args = self.arg_types(solver, args, arity, scope);
+                let (callees, is_super) =
+                    self.invocation(solver, *o, &self.symbol(i), &args, scope);
+                let mut r: Vec<(usize, CallKind)> =
+                    callees.iter().map(|x| (*x, CallKind::Direct)).collect();
+                // with super the exact declaration is called
+                if !is_super {
+                    for c in callees {
+                        if self.graph.methods[c].is_static {
+                            continue;
+                        }
+                        r.extend(
+                            self.overriding(c)
+                                .into_iter()
+                                .map(|x| (x, CallKind::Override)),
+                        );
+                    }
+                }
+                (r, CallKind::Direct)
+            }
+            (RefsEnum::ConstructorInvocation(t, args), Type::ObjectCreationExpression) => {
+                let args = self.arg_types(solver, args, arity, scope);
+                let Some(ExprType::Declared(t)) = self.type_ref(solver, *t, scope.class) else {
+                    self.unresolved(caller, p, name, arity);
+                    return;
+                };
+                // without declared constructor there is nothing to call
+                (self.constructors(t, &args), CallKind::Constructor)
+            }
+            (RefsEnum::ConstructorInvocation(t, args), Type::ExplicitConstructorInvocation) => {
+                let args = self.arg_types(solver, args, arity, scope);
+                let class = scope.class;
+                let t = match &solver[*t] {
+                    RefsEnum::Super(_) => {
+                        let s = self.graph.types[class].superclass.clone();
+                        s.as_ref().and_then(|s| self.resolve_type_name(s, class))
+                    }
+                    _ => Some(class),
+                };
+                let Some(t) = t else {
+                    self.unresolved(caller, p, name, arity);
+                    return;
+                };
+                (self.constructors(t, &args), CallKind::Constructor)
+            }
+            _ => (vec![], CallKind::Direct),
+        };
+        if callees.is_empty() && kind == CallKind::Direct {
+            self.unresolved(caller, p, name, arity);
+            return;
+        }
+        for (callee, kind) in callees {
+            self.graph.calls.push(Call {
+                caller,
+                callee,
+                site: p.clone(),
+                kind,
+            });
+        }
+    }
+
+    fn unresolved(&mut self, caller: usize, site: &StructuralPosition, name: String, arity: usize) {
+        self.graph.unresolved.push(UnresolvedCall {
+            caller,
+            site: site.clone(),
+            name,
+            arity,
+        });
+    }
+
+    /// The reference designated by [`x`] in its partial analysis, eg. an invocation.
+    fn site_ref(&self, x: &NodeIdentifier) -> Option<(&'a Solver, RefPtr)> {
+        let md_cache: &'a MDCache = self.md_cache;
+        let ana = md_cache.get(x)?.ana()?;
+        Some((&ana.solver, ana.current_ref()?))
+    }
+
+    /// The types of the arguments, unknown when the partial analysis did not keep all of them.
+    fn arg_types(
+        &self,
+        solver: &Solver,
+        args: &Arguments<RefPtr>,
+        arity: usize,
+        scope: &Scope,
+    ) -> Vec<Option<ExprType>> {
+        match args {
+            Arguments::Given(args) if args.len() == arity => args
+                .iter()
+                .map(|x| self.expr_type(solver, *x, scope))
+                .collect(),
+            _ => vec![None; arity],
+        }
+    }
+
+    /// Returns the declarations designated by the invocation of [`name`] on [`o`],
+    /// with `true` if it is an invocation on `super`.
+    fn invocation(
+        &self,
+        solver: &Solver,
+        o: RefPtr,
+        name: &str,
+        args: &[Option<ExprType>],
+        scope: &Scope,
+    ) -> (Vec<usize>, bool) {
+        match &solver[o] {
+            RefsEnum::MaybeMissing => {
+                // in the enclosing types
+                let mut o = Some(scope.class);
+                let mut r = vec![];
+                while let (Some(t), true) = (o, r.is_empty()) {
+                    r = self.lookup(t, name, args);
+                    o = self.graph.types[t].outer;
+                }
+                (r, false)
+            }
+            RefsEnum::Super(s) if matches!(solver[*s], RefsEnum::MaybeMissing) => {
+                let r = self.supers[scope.class]
+                    .iter()
+                    .flat_map(|s| self.lookup(*s, name, args))
+                    .collect();
+                (r, true)
+            }
+            _ => match self.expr_type(solver, o, scope) {
+                Some(ExprType::Declared(t)) => (self.lookup(t, name, args), false),
+                _ => (vec![], false),
+            },
+        }
+    }
+
+    /// The type of the expression [`r`] of the partial analysis.
+    fn expr_type(&self, solver: &Solver, r: RefPtr, scope: &Scope) -> Option<ExprType> {
+        match &solver[r] {
+            RefsEnum::Primitive(p) => Some(ExprType::Primitive(*p)),
+            RefsEnum::Mask(o, _) => self.expr_type(solver, *o, scope),
+            RefsEnum::This(o) => match &solver[*o] {
+                RefsEnum::MaybeMissing => Some(ExprType::Declared(scope.class)),
+                // eg. `A.this`
+                _ => self.type_ref(solver, *o, scope.class),
+            },
+            RefsEnum::ScopedIdentifier(o, i) if matches!(solver[*o], RefsEnum::MaybeMissing) => {
+                let name = self.symbol(i);
+                if let Some(ty) = scope.local(&name) {
+                    return ty.as_ref().map(|ty| self.written(ty, scope.class));
+                }
+                let mut o = Some(scope.class);
+                while let Some(t) = o {
+                    if let Some(ty) = self.field_type(t, &name) {
+                        return Some(ty);
+                    }
+                    o = self.graph.types[t].outer;
+                }
+                // a static access
+                self.type_ref(solver, r, scope.class)
+            }
+            RefsEnum::ScopedIdentifier(o, i) => match self.expr_type(solver, *o, scope) {
+                Some(ExprType::Declared(t)) => self
+                    .field_type(t, &self.symbol(i))
+                    .or_else(|| self.type_ref(solver, r, scope.class)),
+                // eg. a qualified name
+                _ => self.type_ref(solver, r, scope.class),
+            },
+            RefsEnum::TypeIdentifier(_, _) => self.type_ref(solver, r, scope.class),
+            RefsEnum::ConstructorInvocation(t, _) => self.type_ref(solver, *t, scope.class),
+            RefsEnum::Invocation(o, i, args) => {
+                let args: Vec<_> = match args {
+                    Arguments::Given(args) => args
+                        .iter()
+                        .map(|x| self.expr_type(solver, *x, scope))
+                        .collect(),
+                    Arguments::Unknown => vec![],
+                };
+                let (callees, _) = self.invocation(solver, *o, &self.symbol(i), &args, scope);
+                let m = &self.graph.methods[*callees.first()?];
+                Some(self.written(m.return_type.as_ref()?, m.class))
+            }
+            _ => None,
+        }
+    }
+
+    /// The type designated by the name [`r`] of the partial analysis, as written in the type [`from`].
+    fn type_ref(&self, solver: &Solver, r: RefPtr, from: usize) -> Option<ExprType> {
+        if let RefsEnum::Primitive(p) = &solver[r] {
+            return Some(ExprType::Primitive(*p));
+        }
+        let path = self.path(solver, r)?;
+        let (first, rest) = path.split_first()?;
+        if rest.is_empty() {
+            return Some(self.written(first, from));
+        }
+        let by_name = &self.graph.by_name;
+        let member = self
+            .resolve_type_name(first, from)
+            .map(|t| format!("{}.{}", self.graph.types[t].qualified_name, rest.join(".")));
+        let t = member
+            .and_then(|x| by_name.get(&x))
+            .or_else(|| by_name.get(&path.join(".")));
+        Some(match t {
+            Some(t) => ExprType::Declared(*t),
+            None => ExprType::External(path.last()?.clone()),
+        })
+    }
+
+    /// The identifiers of the name [`r`] of the partial analysis, eg. `["p", "B"]` for `p.B`.
+    fn path(&self, solver: &Solver, r: RefPtr) -> Option<Vec<String>> {
+        match &solver[r] {
+            RefsEnum::Root | RefsEnum::MaybeMissing => Some(vec![]),
+            RefsEnum::ScopedIdentifier(o, i) | RefsEnum::TypeIdentifier(o, i) => {
+                let mut r = self.path(solver, *o)?;
+                r.push(self.symbol(i));
+                Some(r)
+            }
+            _ => None,
+        }
+    }
+
+    /// The type [`ty`] as written in the type [`from`].
+    fn written(&self, ty: &str, from: usize) -> ExprType {
+        if PRIMITIVES.contains(&ty) {
+            ExprType::Primitive(Primitive::from(ty))
+        } else if let Some(t) = self.resolve_type_name(ty, from) {
+            ExprType::Declared(t)
+        } else {
+            ExprType::External(ty.to_string())
+        }
+    }
+
+    /// The type of the field [`name`] of [`t`] or of its supertypes.
+    fn field_type(&self, t: usize, name: &str) -> Option<ExprType> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([t]);
+        while let Some(t) = queue.pop_front() {
+            if !visited.insert(t) {
+                continue;
+            }
+            if let Some((_, ty)) = self.graph.types[t].fields.iter().find(|(n, _)| n == name) {
+                return Some(self.written(ty, t));
+            }
+            queue.extend(&self.supers[t]);
+        }
+        None
+    }
+
+    /// The methods [`name`] applicable to [`args`], declared in [`t`] or else in its closest supertypes.
+    fn lookup(&self, t: usize, name: &str, args: &[Option<ExprType>]) -> Vec<usize> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([t]);
+        while let Some(t) = queue.pop_front() {
+            if !visited.insert(t) {
+                continue;
+            }
+            let candidates = self.graph.types[t].methods.iter().copied().filter(|m| {
+                let m = &self.graph.methods[*m];
+                !m.constructor && m.name == name
+            });
+            let r = self.select(candidates, args);
+            if !r.is_empty() {
+                return r;
+            }
+            queue.extend(&self.supers[t]);
+        }
+        vec![]
+    }
+
+    fn constructors(&self, t: usize, args: &[Option<ExprType>]) -> Vec<(usize, CallKind)> {
+        let candidates = self.graph.types[t]
+            .methods
+            .iter()
+            .copied()
+            .filter(|m| self.graph.methods[*m].constructor);
+        self.select(candidates, args)
+            .into_iter()
+            .map(|m| (m, CallKind::Constructor))
+            .collect()
+    }
+
+    /// The [`candidates`] applicable to [`args`] with the most parameters of the exact type of their argument.
+    fn select(
+        &self,
+        candidates: impl IntoIterator<Item = usize>,
+        args: &[Option<ExprType>],
+    ) -> Vec<usize> {
+        let mut best = 0;
+        let mut r = vec![];
+        for m in candidates {
+            let Some(exact) = self.applicable(m, args) else {
+                continue;
+            };
+            if exact > best {
+                best = exact;
+                r.clear();
+            }
+            if exact == best {
+                r.push(m);
+            }
+        }
+        r
+    }
+
+    /// The number of parameters of [`m`] of the exact type of their argument,
+    /// None if [`m`] does not accept [`args`].
+    fn applicable(&self, m: usize, args: &[Option<ExprType>]) -> Option<usize> {
+        let method = &self.graph.methods[m];
+        if !method.accepts(args.len()) {
+            return None;
+        }
+        let fixed = method.params.len() - method.varargs as usize;
+        let mut exact = 0;
+        for (arg, param) in args.iter().zip(&method.params[..fixed]) {
+            let (Some(arg), Some(param)) = (arg, param) else {
+                continue;
+            };
+            let param = self.written(param, method.class);
+            if *arg == param {
+                exact += 1;
+            } else if !self.converts(arg, &param) {
+                return None;
+            }
+        }
+        Some(exact)
+    }
+
+    /// If a value of type [`from`] might be passed as a [`to`].
+    /// The hierarchy of the types declared outside of the repository is unknown.
+    fn converts(&self, from: &ExprType, to: &ExprType) -> bool {
+        match (from, to) {
+            (ExprType::Primitive(Primitive::Null), to) => !matches!(to, ExprType::Primitive(_)),
+            (ExprType::Primitive(a), ExprType::Primitive(b)) => widens(*a, *b),
+            // boxing
+            (ExprType::Primitive(_), ExprType::External(_)) => true,
+            (ExprType::Declared(a), ExprType::Declared(b)) => self.is_subtype(*a, *b),
+            // eg. Object or a type variable
+            (ExprType::Declared(_), ExprType::External(_)) => true,
+            (ExprType::External(a), ExprType::Primitive(_)) => BOXES.contains(&a.as_str()),
+            (ExprType::External(_), ExprType::External(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn is_subtype(&self, t: usize, s: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![t];
+        while let Some(t) = stack.pop() {
+            if t == s {
+                return true;
+            }
+            if visited.insert(t) {
+                stack.extend(&self.supers[t]);
+            }
+        }
+        false
+    }
+
+    /// The methods of the subtypes of the class of [`m`] with the same name and parameters.
+    fn overriding(&self, m: usize) -> Vec<usize> {
+        let method = &self.graph.methods[m];
+        let mut r = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = self.subtypes[method.class].clone();
+        while let Some(t) = stack.pop() {
+            if !visited.insert(t) {
+                continue;
+            }
+            r.extend(self.graph.types[t].methods.iter().copied().filter(|x| {
+                let x = &self.graph.methods[*x];
+                !x.constructor
+                    && x.name == method.name
+                    && x.arity == method.arity
+                    && x.params.iter().zip(&method.params).all(|(a, b)| {
+                        self.same_parameter((a.as_deref(), x.class), (b.as_deref(), method.class))
+                    })
+            }));
+            stack.extend(&self.subtypes[t]);
+        }
+        r
+    }
+
+    /// If the parameter types [`a`] and [`b`], written in their declaring type, might be the same,
+    /// eg. a type variable and the class given to it.
+    fn same_parameter(&self, a: (Option<&str>, usize), b: (Option<&str>, usize)) -> bool {
+        let ((Some(a), a_class), (Some(b), b_class)) = (a, b) else {
+            return true;
+        };
+        match (self.written(a, a_class), self.written(b, b_class)) {
+            (a @ ExprType::Primitive(_), b) | (a, b @ ExprType::Primitive(_)) => a == b,
+            (a @ ExprType::Declared(_), b @ ExprType::Declared(_)) => a == b,
+            _ => true,
+        }
+    }
+
+    /// Resolves the type [`name`] as written in the type [`from`],
+    /// looking at member types of the enclosing types, imports and then the package.
+    fn resolve_type_name(&self, name: &str, from: usize) -> Option<usize> {
+        let by_name = &self.graph.by_name;
+        let mut o = Some(from);
+        while let Some(t) = o {
+            let t = &self.graph.types[t];
+            if let Some(r) = by_name.get(&format!("{}.{}", t.qualified_name, name)) {
+                return Some(*r);
+            }
+            if t.name == name {
+                return by_name.get(&t.qualified_name).copied();
+            }
+            o = t.outer;
+        }
+        let file = &self.graph.files[self.graph.types[from].file];
+        for (path, asterisk) in &file.imports {
+            if !asterisk && path.last().map_or(false, |x| x == name) {
+                // also shadows the types of the package when declared outside of the repository
+                return by_name.get(&path.join(".")).copied();
+            }
+        }
+        let in_package = if file.package.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", file.package, name)
+        };
+        if let Some(r) = by_name.get(&in_package) {
+            return Some(*r);
+        }
+        file.imports
+            .iter()
+            .filter(|(_, asterisk)| *asterisk)
+            .find_map(|(path, _)| by_name.get(&format!("{}.{}", path.join("."), name)))
+            .copied()
+    }
+
+    fn arity(&self, cs: &[(u16, Type, NodeIdentifier)]) -> usize {
+        cs.iter()
+            .find(|(_, k, _)| *k == Type::ArgumentList)
+            .map_or(0, |(_, _, x)| {
+                self.children(x)
+                    .iter()
+                    .filter(|(_, k, _)| !k.is_syntax() && *k != Type::Comma)
+                    .count()
+            })
+    }
+
+    /// The name of an invocation as written, `new T` for instance creations.
+    fn written_name(&self, k: Type, cs: &[(u16, Type, NodeIdentifier)]) -> String {
+        match k {
+            Type::ObjectCreationExpression => {
+                let ty = cs.iter().find_map(|(_, _, x)| self.type_name(x));
+                format!("new {}", ty.unwrap_or_default())
+            }
+            Type::ExplicitConstructorInvocation if cs.iter().any(|(_, k, _)| *k == Type::Super) => {
+                "super".to_string()
+            }
+            Type::ExplicitConstructorInvocation => "this".to_string(),
+            _ => cs
+                .iter()
+                .position(|(_, k, _)| *k == Type::ArgumentList)
+                .and_then(|i| cs.get(i.checked_sub(1)?))
+                .and_then(|(_, _, x)| self.label(x))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The names and types of the formal parameters [`x`].
+    fn parameters(&self, x: &NodeIdentifier) -> Vec<(String, Option<String>)> {
+        self.children(x)
+            .into_iter()
+            .filter(|(_, k, _)| *k == Type::FormalParameter || *k == Type::SpreadParameter)
+            .map(|(_, _, x)| {
+                let ty = self
+                    .children(&x)
+                    .iter()
+                    .find_map(|(_, _, xx)| self.written_type(xx));
+                (self.declared_name(&x).unwrap_or_default(), ty)
+            })
+            .collect()
+    }
+
+    fn kind(&self, x: &NodeIdentifier) -> Option<Type> {
+        let (b, _) = self.stores.node_store.try_resolve_typed::<JavaIdN>(x)?;
+        Some(b.get_type())
+    }
+
+    /// The java children of [`x`] with their offsets, without spaces and comments.
+    fn children(&self, x: &NodeIdentifier) -> Vec<(u16, Type, NodeIdentifier)> {
+        let Some((b, _)) = self.stores.node_store.try_resolve_typed::<JavaIdN>(x) else {
+            return vec![];
+        };
+        let Some(cs) = b.children() else {
+            return vec![];
+        };
+        cs.iter_children()
+            .enumerate()
+            .filter_map(|(i, xx)| {
+                let k = self.kind(xx)?;
+                if k.is_spaces() || k.is_comment() {
+                    return None;
+                }
+                Some((num::cast(i).unwrap(), k, *xx))
+            })
+            .collect()
+    }
+
+    fn label(&self, x: &NodeIdentifier) -> Option<String> {
+        let (b, _) = self.stores.node_store.try_resolve_typed::<JavaIdN>(x)?;
+        if !b.has_label() {
+            return None;
+        }
+        let l = self.stores.label_store.resolve(b.get_label_unchecked());
+        Some(l.to_string())
+    }
+
+    /// The identifier [`l`] of the partial analysis.
+    fn symbol(&self, l: &LabelPtr) -> String {
+        self.stores.label_store.resolve(l.as_ref()).to_string()
+    }
+
+    /// The name of a declaration, possibly in its variable declarator.
+    fn declared_name(&self, x: &NodeIdentifier) -> Option<String> {
+        let cs = self.children(x);
+        if let Some((_, _, x)) = cs.iter().find(|(_, k, _)| *k == Type::Identifier) {
+            return self.label(x);
+        }
+        let (_, _, x) = cs.iter().find(|(_, k, _)| *k == Type::VariableDeclarator)?;
+        self.declared_name(x)
+    }
+
+    /// The simple name of a type as written, None if it is not a class or an interface.
+    fn type_name(&self, x: &NodeIdentifier) -> Option<String> {
+        match self.kind(x)? {
+            Type::TypeIdentifier => self.label(x),
+            Type::GenericType => self.type_name(&self.children(x).first()?.2),
+            Type::ScopedTypeIdentifier => {
+                let cs = self.children(x);
+                let (_, _, x) = cs.iter().rfind(|(_, k, _)| *k == Type::TypeIdentifier)?;
+                self.label(x)
+            }
+            _ => None,
+        }
+    }
+
+    /// The simple name of a class, an interface or a primitive type as written.
+    fn written_type(&self, x: &NodeIdentifier) -> Option<String> {
+        let k = self.kind(x)?;
+        if !k.is_primitive() {
+            return self.type_name(x);
+        }
+        match self.children(x).first() {
+            Some((_, k, _)) => Some(k.to_str().to_string()),
+            None if k == Type::BooleanType => Some("boolean".to_string()),
+            None if k == Type::VoidType => Some("void".to_string()),
+            None => None,
+        }
+    }
+}
+
+fn is_type_declaration(t: Type) -> bool {
+    t == Type::ClassDeclaration
+        || t == Type::InterfaceDeclaration
+        || t == Type::EnumDeclaration
+        || t == Type::RecordDeclaration
+        || t == Type::AnnotationTypeDeclaration
+}
+
+/// The statements declaring local variables visible in their own subtree only.
+fn opens_scope(t: Type) -> bool {
+    t == Type::Block
+        || t == Type::ConstructorBody
+        || t == Type::SwitchBlock
+        || t == Type::ForStatement
+        || t == Type::EnhancedForStatement
+        || t == Type::CatchClause
+        || t == Type::TryWithResourcesStatement
+        || t == Type::LambdaExpression
+}
+
+/// The primitive widening conversions, eg. from `int` to `long`.
+fn widens(from: Primitive, to: Primitive) -> bool {
+    let rank = |x| match x {
+        Primitive::Byte => Some(0),
+        Primitive::Short | Primitive::Char => Some(1),
+        Primitive::Int => Some(2),
+        Primitive::Long => Some(3),
+        Primitive::Float => Some(4),
+        Primitive::Double => Some(5),
+        _ => None,
+    };
+    from == to || matches!((rank(from), rank(to)), (Some(a), Some(b)) if a <= b)
+}
@@ -0,0 +1,308 @@
+use enumset::EnumSet;
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::SubTreeMetrics,
+};
+use hyper_ast_gen_ts_rust::legion as rust_tree_gen;
+
+use crate::{package_processor::ConfigFile, Accumulator, DefaultMetrics, ParseErr};
+
+/// What is read from the Cargo.toml of a crate or of a workspace.
+///
+/// The Cargo.toml is kept in the tree as a text leaf,
+/// this component summarizes it on the directory containing it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CargoManifest {
+    /// from the `[package]` table, absent for virtual workspaces
+    pub package: Option<CargoPackage>,
+    /// patterns designating the crates of the workspace, relative to it, eg. `crates/*`
+    pub members: Vec<String>,
+    /// paths of the directories excluded from the workspace members
+    pub exclude: Vec<String>,
+    pub dependencies: Vec<CargoDependency>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CargoPackage {
+    pub name: String,
+    /// can be inherited from the workspace, ie. `version.workspace = true`
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CargoDependency {
+    /// the name used in the code, ie. the key of the dependency
+    pub name: String,
+    pub version: Option<String>,
+    /// relative to the crate, for dependencies in the same repository
+    pub path: Option<String>,
+    /// from `dev-dependencies`
+    pub dev: bool,
+}
+
+impl CargoDependency {
+    /// Tells if the dependency must be found in the same repository.
+    pub fn is_local(&self) -> bool {
+        self.path.is_some()
+    }
+}
+
+pub(crate) fn handle_cargo_toml(text: &[u8]) -> Result<CargoManifest, ParseErr> {
+    let text = std::str::from_utf8(text)?;
+    let value: toml::Value = toml::from_str(text).map_err(|e| {
+        log::warn!("bad Cargo.toml: {}", e);
+        ParseErr::IllFormed
+    })?;
+    let package = value.get("package").and_then(|package| {
+        let name = package.get("name")?.as_str()?.to_string();
+        let version = package
+            .get("version")
+            .and_then(|x| x.as_str())
+            .map(str::to_string);
+        Some(CargoPackage { name, version })
+    });
+    let workspace = value.get("workspace");
+    let paths = |k: &str| -> Vec<String> {
+        workspace
+            .and_then(|x| x.get(k))
+            .and_then(|x| x.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|x| x.as_str())
+            .filter_map(normalize_member)
+            .collect()
+    };
+    let members = paths("members");
+    let exclude = paths("exclude");
+    let mut dependencies = vec![];
+    // target specific dependencies are declared in `[target.'cfg(unix)'.dependencies]`
+    let targets = value
+        .get("target")
+        .and_then(|x| x.as_table())
+        .into_iter()
+        .flat_map(|x| x.values());
+    for table in std::iter::once(&value).chain(targets) {
+        for (k, dev) in [
+            ("dependencies", false),
+            ("build-dependencies", false),
+            ("dev-dependencies", true),
+        ] {
+            let Some(deps) = table.get(k).and_then(|x| x.as_table()) else {
+                continue;
+            };
+            dependencies.extend(deps.iter().map(|(name, spec)| {
+                CargoDependency {
+                    name: name.to_string(),
+                    version: match spec {
+                        toml::Value::String(x) => Some(x.to_string()),
+                        spec => spec
+                            .get("version")
+                            .and_then(|x| x.as_str())
+                            .map(str::to_string),
+                    },
+                    path: spec
+                        .get("path")
+                        .and_then(|x| x.as_str())
+                        .map(str::to_string),
+                    dev,
+                }
+            }));
+        }
+    }
+    Ok(CargoManifest {
+        package,
+        members,
+        exclude,
+        dependencies,
+    })
+}
+
+/// Patterns going out of the workspace are ignored.
+fn normalize_member(pattern: &str) -> Option<String> {
+    let pattern = pattern.trim_start_matches("./").trim_matches('/');
+    if pattern.is_empty() || pattern.split('/').any(|x| x == "..") {
+        log::debug!("ignored workspace member {}", pattern);
+        return None;
+    }
+    Some(pattern.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+#[derive(enumset::EnumSetType, Debug)]
+pub enum SemFlags {
+    /// the directory contains a Cargo.toml with a `[package]`
+    IsCargoCrate,
+    /// the directory contains a Cargo.toml declaring workspace members
+    IsCargoWorkspace,
+    HoldCargoCrate,
+}
+
+pub struct CargoCrateAcc {
+    pub(crate) name: String,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+    /// what was read from the Cargo.toml of this directory
+    pub(crate) info: Option<CargoManifest>,
+    /// the directory is designated as a crate by a parent workspace (or is the root)
+    pub(crate) is_member: bool,
+    /// member patterns of the parent workspaces, relative to the directory,
+    /// the ones of its own Cargo.toml are in [`CargoCrateAcc::info`]
+    pub(crate) members: Vec<String>,
+    /// excluded paths of the parent workspaces, relative to the directory
+    pub(crate) exclude: Vec<String>,
+}
+
+impl From<String> for CargoCrateAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl CargoCrateAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+            status: Default::default(),
+            info: None,
+            is_member: false,
+            members: vec![],
+            exclude: vec![],
+        }
+    }
+    pub(crate) fn with_members(
+        name: String,
+        is_member: bool,
+        members: Vec<String>,
+        exclude: Vec<String>,
+    ) -> Self {
+        Self {
+            is_member,
+            members,
+            exclude,
+            ..Self::new(name)
+        }
+    }
+}
+
+impl CargoCrateAcc {
+    pub(crate) fn push_cargo_toml(&mut self, info: CargoManifest) {
+        if info.package.is_some() {
+            self.status |= SemFlags::IsCargoCrate;
+        }
+        if !info.members.is_empty() {
+            self.status |= SemFlags::IsCargoWorkspace;
+        }
+        self.info = Some(info);
+    }
+    /// Keeps the Cargo.toml in the tree,
+    /// it is only summarized if the directory is considered as a crate.
+    pub(crate) fn push_config_file(
+        &mut self,
+        name: LabelIdentifier,
+        file: ConfigFile<CargoManifest>,
+        summarized: bool,
+    ) {
+        if summarized {
+            self.push_cargo_toml(file.info);
+        }
+        self.children.push(file.text.0);
+        self.children_names.push(name);
+        self.metrics.acc(file.text.1);
+    }
+    pub fn push_subcrate(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        if full_node.1.status.contains(SemFlags::HoldCargoCrate)
+            || full_node.1.status.contains(SemFlags::IsCargoCrate)
+        {
+            self.status |= SemFlags::HoldCargoCrate;
+        }
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: rust_tree_gen::Local,
+    ) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(SubTreeMetrics {
+            hashs: full_node.metrics.hashs,
+            size: full_node.metrics.size,
+            height: full_node.metrics.height,
+            size_no_spaces: full_node.metrics.size_no_spaces,
+        });
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for CargoCrateAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+}
+
+impl Accumulator for CargoCrateAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
+
+#[test]
+fn cargo_toml() {
+    let text = br#"
+[package]
+name = "hyper_ast_cvs_git"
+version = "0.1.0"
+
+[dependencies]
+git2 = { version = "0.16", features = ["vendored-libgit2"] }
+hyper_ast = { path = "../../hyper_ast" }
+log = "0.4.6"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[dev-dependencies]
+env_logger = "0.9.0"
+"#;
+    let info = handle_cargo_toml(text).unwrap();
+    let package = info.package.as_ref().unwrap();
+    assert_eq!(package.name, "hyper_ast_cvs_git");
+    assert_eq!(package.version.as_deref(), Some("0.1.0"));
+    assert!(info.members.is_empty());
+    assert_eq!(info.dependencies.len(), 5);
+    assert!(info
+        .dependencies
+        .iter()
+        .any(|x| x.name == "hyper_ast" && x.is_local()));
+    assert!(info.dependencies.iter().any(|x| x.name == "libc"));
+    assert!(info
+        .dependencies
+        .iter()
+        .any(|x| x.name == "env_logger" && x.dev));
+
+    let workspace = br#"
+[workspace]
+members = ["hyper_ast", "./gen/tree-sitter/*", "cvs/git/", "../outside"]
+exclude = ["gen/tree-sitter/java/tree-sitter-java"]
+"#;
+    let info = handle_cargo_toml(workspace).unwrap();
+    assert!(info.package.is_none());
+    assert_eq!(
+        info.members,
+        vec!["hyper_ast", "gen/tree-sitter/*", "cvs/git"]
+    );
+    assert_eq!(info.exclude, vec!["gen/tree-sitter/java/tree-sitter-java"]);
+    assert!(handle_cargo_toml(b"[package").is_err());
+}
@@ -0,0 +1,594 @@
+use std::{
+    iter::Peekable,
+    marker::PhantomData,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::SubTreeMetrics,
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_rust::types::Type;
+
+use crate::{
+    cargo::{CargoCrateAcc, CargoManifest, MD},
+    git::BasicGitObject,
+    package_processor::{ConfigFile, PackageAcc, PackageWalk},
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    utils::{make_text, step_workspaces},
+    Processor, SimpleStores,
+};
+
+/// Builds a hierarchy of cargo crates,
+/// where the .rs files are parsed wherever they are.
+///
+/// Similarly to maven modules, the crates are the directories designated by the `members` of a workspace,
+/// they are flagged with [`crate::cargo::SemFlags`] and their Cargo.toml is summarized as a component.
+/// The Cargo.toml files are kept in the tree, including the ones that are not summarized.
+///
+/// RMS: Resursive Module Search, ie. also consider Cargo.toml files that are not designated by a workspace,
+/// otherwise they are only considered at the root and in the members of a parent workspace.
+pub struct CargoProcessor<'a, 'b, 'c, const RMS: bool> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, CargoCrateAcc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoProc>,
+}
+
+impl<'a, 'b, 'c, const RMS: bool> CargoProcessor<'a, 'b, 'c, RMS> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        mut dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<CargoProcessorHolder>();
+        let handle =
+            <CargoProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, &mut dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, CargoCrateAcc::root(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> Processor<CargoCrateAcc> for CargoProcessor<'a, 'b, 'c, RMS> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                if crate::processing::file_sys::CargoToml::matches(&name) {
+                    let summarized = RMS || parent_acc.is_member;
+                    if !summarized {
+                        log::debug!("Cargo.toml not in a workspace {:?}", parent_acc.name);
+                    }
+                    if let Err(err) = self.prepro.help_handle_cargo_toml(
+                        oid,
+                        parent_acc,
+                        name,
+                        &self.repository,
+                        self.handle.into(),
+                        summarized,
+                    ) {
+                        log::warn!("skipped Cargo.toml in {:?}: {:?}", parent_acc.name, err);
+                    }
+                } else if crate::processing::file_sys::Rust::matches(&name) {
+                    if let Err(err) = self.prepro.help_handle_rust_file(
+                        oid,
+                        parent_acc,
+                        &name,
+                        self.repository,
+                        self.handle.into(),
+                    ) {
+                        log::warn!("skipped {:?}: {:?}", name.try_str(), err);
+                    }
+                } else {
+                    log::debug!("not rust source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: CargoCrateAcc) -> Option<(NodeIdentifier, MD)> {
+        Self::WALK.post(self.prepro, &mut self.stack, oid, acc)
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, CargoCrateAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> CargoProcessor<'a, 'b, 'c, RMS> {
+    const WALK: PackageWalk<CargoCrateAcc> = PackageWalk {
+        prepare: prepare_dir_exploration,
+        object_map: |prepro| {
+            &mut prepro
+                .processing_systems
+                .mut_or_default::<CargoProcessorHolder>()
+                .get_caches_mut()
+                .object_map
+        },
+        make,
+        // build artifacts, sometimes commited by mistake
+        skipped: |name| name.as_bytes() == b"target",
+    };
+
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        Self::WALK.handle_tree(
+            self.prepro,
+            self.repository,
+            self.dir_path,
+            &mut self.stack,
+            name,
+            oid,
+        )
+    }
+}
+
+impl PackageAcc for CargoCrateAcc {
+    type MD = MD;
+    type Ctx = (bool, Vec<String>, Vec<String>);
+    fn ctx(&self) -> Self::Ctx {
+        (self.is_member, self.members.clone(), self.exclude.clone())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn children_names(&self) -> &[LabelIdentifier] {
+        &self.children_names
+    }
+    fn root(name: String) -> Self {
+        CargoCrateAcc::with_members(name, true, vec![], vec![])
+    }
+    fn child(&self, name: String) -> Self {
+        let own = self.info.as_ref();
+        let members: Vec<_> = (self.members.iter())
+            .chain(own.into_iter().flat_map(|x| &x.members))
+            .cloned()
+            .collect();
+        let exclude: Vec<_> = (self.exclude.iter())
+            .chain(own.into_iter().flat_map(|x| &x.exclude))
+            .cloned()
+            .collect();
+        let (is_member, members) = step_workspaces(&members, &name);
+        let (is_excluded, exclude) = step_workspaces(&exclude, &name);
+        CargoCrateAcc::with_members(name, is_member && !is_excluded, members, exclude)
+    }
+    fn push_dir(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        self.push_subcrate(name, full_node)
+    }
+}
+
+pub(crate) fn make(mut acc: CargoCrateAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::legion::{compo, compo::CS, EntryRef, NodeStore},
+    };
+    use hyper_ast_gen_ts_rust::legion::eq_node;
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    // the same Cargo.toml is only summarized in the crates of a workspace,
+    // so what is extracted from it is part of the identity of the directory
+    let info = acc.info.take();
+    let hashable = (hbuilder.most_discriminating(), &info);
+    let eq = {
+        let eq = eq_node(&Type::Directory, Some(&label), &acc.children);
+        let info = info.as_ref();
+        move |x: EntryRef| eq(x) && x.get_component::<CargoManifest>().ok() == info
+    };
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make cargo {} {}", &acc.name, acc.children.len());
+        let vacant = insertion.vacant();
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+        dyn_builder.add(Type::Directory);
+        dyn_builder.add(hashs.clone());
+        dyn_builder.add(label);
+        dyn_builder.add(BloomSize::Much);
+        dyn_builder.add(compo::Size(size));
+        dyn_builder.add(compo::SizeNoSpaces(size_no_spaces));
+        dyn_builder.add(compo::Height(height));
+        dyn_builder.add(CS(acc.children_names.into_boxed_slice()));
+        dyn_builder.add(CS(acc.children.into_boxed_slice()));
+        if !acc.status.is_empty() {
+            dyn_builder.add(acc.status);
+        }
+        if let Some(info) = info.clone() {
+            dyn_builder.add(info);
+        }
+        NodeStore::insert_built_after_prepare(vacant, dyn_builder.build())
+    };
+    let status = acc.status;
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (node_id, MD { metrics, status })
+}
+
+impl RepositoryProcessor {
+    fn help_handle_cargo_toml(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut CargoCrateAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoTomlProc>,
+        summarized: bool,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::CargoToml>()
+            .handle(oid, repository, &name, parameters, |_, _, t| {
+                // still kept in the tree
+                let info = crate::cargo::handle_cargo_toml(t).unwrap_or_else(|err| {
+                    log::warn!("bad Cargo.toml in {:?}: {:?}", parent_acc.name, err);
+                    Default::default()
+                });
+                // there is no toml grammar, the file is kept as text
+                let text = make_text(std::str::from_utf8(t)?, &mut self.main_stores);
+                Ok::<_, crate::ParseErr>(ConfigFile { text, info })
+            })?;
+        let name = self.intern_object_name(name);
+        assert!(!parent_acc.children_names.contains(&name));
+        parent_acc.push_config_file(name, x, summarized);
+        Ok(())
+    }
+}
+
+/// The Cargo.toml is handled first,
+/// as it tells which directories are members of the workspace.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        children_objects.reverse(); // we use it like a stack
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) => crate::processing::file_sys::CargoToml::matches(n),
+            _ => false,
+        });
+    }
+    children_objects
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoTomlProc>
+{
+    fn from(
+        value: crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoProc>,
+    ) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<
+        crate::rust_processor::RustProc,
+    >
+{
+    fn from(
+        value: crate::processing::erased::ParametrizedCommitProcessor2Handle<CargoProc>,
+    ) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+
+// # Cargo.toml
+
+struct CargoTomlProcessorHolder(Option<CargoTomlProc>);
+impl Default for CargoTomlProcessorHolder {
+    fn default() -> Self {
+        Self(Some(CargoTomlProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+struct CargoTomlProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::CargoToml,
+}
+impl crate::processing::erased::Parametrized for CargoTomlProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(CargoTomlProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+// TODO should not have to impl this trait
+impl crate::processing::erased::CommitProc for CargoTomlProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for CargoTomlProc {
+    type Holder = CargoTomlProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for CargoTomlProcessorHolder {
+    type Proc = CargoTomlProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::CargoToml> for CargoTomlProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::CargoToml {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::CargoToml {
+        &self.cache
+    }
+}
+
+// # Cargo
+
+#[derive(Default)]
+pub struct CargoProcessorHolder(Option<CargoProc>);
+pub struct CargoProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Cargo,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for CargoProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(CargoProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedCargoCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedCargoCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedCargoCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = CargoProcessor::<false>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<CargoProcessorHolder>();
+        let handle =
+            <CargoProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for CargoProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedCargoCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for CargoProc {
+    type Holder = CargoProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for CargoProcessorHolder {
+    type Proc = CargoProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Cargo> for CargoProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Cargo {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Cargo {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Cargo> for CargoProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Cargo {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Cargo {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cargo::SemFlags;
+    use crate::tests::repository;
+    use enumset::EnumSet;
+
+    const LIB: &str = "pub fn f() {}\n";
+
+    #[test]
+    fn cargo_members_as_crates() {
+        let mut prepro = RepositoryProcessor::default();
+        let (repository, tree) = repository(
+            "cargo-members",
+            &[
+                ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+                ("crates/a/Cargo.toml", "[package]\nname = \"a\"\n"),
+                ("crates/a/src/lib.rs", LIB),
+                // the same tree, outside of the members
+                ("vendor/a/Cargo.toml", "[package]\nname = \"a\"\n"),
+                ("vendor/a/src/lib.rs", LIB),
+            ],
+        );
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let root = CargoProcessor::<false>::new(&repository, &mut prepro, &mut dir_path, b"", tree)
+            .process()
+            .0;
+        let info = |id| {
+            let stores = prepro.main_stores();
+            let b = stores.node_store.resolve(id);
+            let status = b.get_component::<EnumSet<SemFlags>>().ok().copied();
+            (status, b.get_component::<CargoManifest>().ok().cloned())
+        };
+        let (status, root_info) = info(root);
+        assert!(status.unwrap().contains(SemFlags::IsCargoWorkspace));
+        assert_eq!(root_info.unwrap().members, vec!["crates/*"]);
+        // summarized, but still in the tree
+        assert!(prepro.child_by_name(root, "Cargo.toml").is_some());
+
+        let crates = prepro.child_by_name(root, "crates").unwrap();
+        let a = prepro.child_by_name(crates, "a").unwrap();
+        let (status, a_info) = info(a);
+        assert!(status.unwrap().contains(SemFlags::IsCargoCrate));
+        assert_eq!(a_info.unwrap().package.unwrap().name, "a");
+        assert!(prepro.child_by_name(a, "Cargo.toml").is_some());
+
+        let vendor = prepro.child_by_name(root, "vendor").unwrap();
+        let vendored = prepro.child_by_name(vendor, "a").unwrap();
+        // neither reused from the cache nor deduplicated with the member
+        assert_ne!(a, vendored);
+        assert_eq!(info(vendored), (None, None));
+        assert!(prepro.child_by_name(vendored, "Cargo.toml").is_some());
+    }
+}
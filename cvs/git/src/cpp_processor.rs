@@ -250,7 +250,7 @@ impl CacheHolding<crate::processing::caches::Cpp> for CppProcessorHolder {
 
 #[cfg(feature = "cpp")]
 impl RepositoryProcessor {
-    fn handle_cpp_blob(
+    pub(crate) fn handle_cpp_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-pub use git2::Oid;
+pub use git2::{Commit, Oid};
 use git2::{RemoteCallbacks, Repository, Revwalk, TreeEntry};
 use hyper_ast::position::Position;
 
@@ -35,23 +35,176 @@ pub fn all_commits_between<'a>(
     Ok(rw)
 }
 
+/// How merge commits are traversed, ie. which parents are followed.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Only follow the first parent, ie. the branch the others were merged into
+    FirstParent,
+    /// Follow all parents, commits are visited in topological order
+    #[default]
+    AllParents,
+    /// Follow the parent with the smallest diff (in lines) with the merge commit
+    SmallestDiff,
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "first_parent" => Self::FirstParent,
+            "all_parents" => Self::AllParents,
+            "smallest_diff" => Self::SmallestDiff,
+            x => return Err(format!("'{}' is not a merge strategy", x)),
+        })
+    }
+}
+
+/// Commits from `after` to `before` (included), going through merges according to `strategy`.
+///
+/// With [`MergeStrategy::AllParents`] it is the same as [`all_commits_between`].
+pub fn commits_between_with<'a>(
+    repository: &'a Repository,
+    before: &str,
+    after: &str,
+    strategy: MergeStrategy,
+) -> Result<Box<dyn Iterator<Item = Result<Oid, git2::Error>> + 'a>, git2::Error> {
+    match strategy {
+        MergeStrategy::AllParents => Ok(Box::new(all_commits_between(repository, before, after)?)),
+        MergeStrategy::FirstParent => {
+            let mut rw = all_commits_between(repository, before, after)?;
+            rw.simplify_first_parent()?;
+            Ok(Box::new(rw))
+        }
+        MergeStrategy::SmallestDiff => {
+            let next = if after.is_empty() {
+                repository.head()?.peel_to_commit()?.id()
+            } else {
+                retrieve_commit(repository, after)?.id()
+            };
+            let stop = if before.is_empty() {
+                None
+            } else {
+                Some(retrieve_commit(repository, before)?.id())
+            };
+            Ok(Box::new(SmallestDiffWalk {
+                repository,
+                next: Some(next),
+                stop,
+            }))
+        }
+    }
+}
+
+/// Parents of `commit` that are followed according to `strategy`.
+pub fn parents_with(
+    repository: &Repository,
+    commit: &git2::Commit,
+    strategy: MergeStrategy,
+) -> Result<Vec<Oid>, git2::Error> {
+    Ok(match strategy {
+        MergeStrategy::FirstParent => commit.parent_ids().take(1).collect(),
+        MergeStrategy::AllParents => commit.parent_ids().collect(),
+        MergeStrategy::SmallestDiff => smallest_diff_parent(repository, commit)?
+            .into_iter()
+            .collect(),
+    })
+}
+
+fn smallest_diff_parent(
+    repository: &Repository,
+    commit: &git2::Commit,
+) -> Result<Option<Oid>, git2::Error> {
+    if commit.parent_count() < 2 {
+        return Ok(commit.parent_ids().next());
+    }
+    let tree = commit.tree()?;
+    let mut smallest = None;
+    for parent in commit.parents() {
+        let diff = repository.diff_tree_to_tree(Some(&parent.tree()?), Some(&tree), None)?;
+        let stats = diff.stats()?;
+        let size = stats.insertions() + stats.deletions();
+        if smallest.map_or(true, |(s, _)| size < s) {
+            smallest = Some((size, parent.id()));
+        }
+    }
+    Ok(smallest.map(|x| x.1))
+}
+
+/// Goes through the parents with the smallest diffs, see [`MergeStrategy::SmallestDiff`].
+struct SmallestDiffWalk<'a> {
+    repository: &'a Repository,
+    next: Option<Oid>,
+    stop: Option<Oid>,
+}
+
+impl<'a> Iterator for SmallestDiffWalk<'a> {
+    type Item = Result<Oid, git2::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let oid = self.next.take()?;
+        if Some(oid) != self.stop {
+            let parent = self
+                .repository
+                .find_commit(oid)
+                .and_then(|c| smallest_diff_parent(self.repository, &c));
+            match parent {
+                Ok(parent) => self.next = parent,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        Some(Ok(oid))
+    }
+}
+
+/// Path that `path` had in `parent`, if git detects that it was renamed or copied in `commit`.
+pub fn renamed_from(
+    repository: &Repository,
+    commit: Oid,
+    parent: Oid,
+    path: &str,
+) -> Result<Option<String>, git2::Error> {
+    let new = repository.find_commit(commit)?.tree()?;
+    let old = repository.find_commit(parent)?.tree()?;
+    let mut diff = repository.diff_tree_to_tree(Some(&old), Some(&new), None)?;
+    diff.find_similar(Some(
+        git2::DiffFindOptions::new().renames(true).copies(true),
+    ))?;
+    let path = Path::new(path);
+    let r = diff
+        .deltas()
+        .filter(|d| matches!(d.status(), git2::Delta::Renamed | git2::Delta::Copied))
+        .find(|d| d.new_file().path() == Some(path))
+        .and_then(|d| d.old_file().path().map(|p| p.to_string_lossy().to_string()));
+    Ok(r)
+}
+
+/// Resolves `s` to a commit, `s` can be any revspec understood by git,
+/// eg. a commit id (possibly abbreviated), a branch, a tag or `HEAD~2`.
 pub fn retrieve_commit<'a>(
     repository: &'a Repository,
     s: &str,
 ) -> Result<git2::Commit<'a>, git2::Error> {
-    // TODO make a more advanced search with helpful error msgs
-    match repository.find_reference(&format!("refs/tags/{}", s)) {
-        Ok(c) => match c.peel_to_commit() {
-            Ok(c) => Ok(c),
-            Err(err) => repository.find_commit(Oid::from_str(s)?),
-        },
+    match repository.revparse_single(s) {
+        Ok(x) => x.peel_to_commit(),
         Err(err) => {
-            let oid = Oid::from_str(s).map_err(|_| err)?;
-            repository.find_commit(oid)
+            // repositories fetched with the default git refspec only have remote-tracking branches
+            match repository.revparse_single(&format!("origin/{}", s)) {
+                Ok(x) => x.peel_to_commit(),
+                Err(_) => Err(err),
+            }
         }
     }
 }
 
+/// Resolves `s` to a commit id, see [`retrieve_commit`].
+pub fn resolve_commit(repository: &Repository, s: &str) -> Result<Oid, git2::Error> {
+    retrieve_commit(repository, s).map(|x| x.id())
+}
+
 pub fn all_commits_from_head(repository: &Repository) -> Revwalk {
     use git2::*;
     // let REMOTE_REFS_PREFIX = "refs/remotes/origin/";
@@ -111,15 +264,43 @@ impl Display for Url {
     }
 }
 
-pub fn fetch_repository<'a, T: TryInto<Url>, U: Into<PathBuf>>(url: T, path: U) -> Repository
+pub fn fetch_repository<'a, T: TryInto<Url>, U: Into<PathBuf>>(
+    url: T,
+    path: U,
+) -> Result<Repository, git2::Error>
 where
     <T as TryInto<Url>>::Error: std::fmt::Debug,
 {
-    let url: Url = url.try_into().unwrap();
+    fetch_repository_with(url, path, &default_refspecs())
+}
+
+/// Same as [`fetch_repository`] but fetching the given `refspecs`.
+pub fn fetch_repository_with<'a, T: TryInto<Url>, U: Into<PathBuf>>(
+    url: T,
+    path: U,
+    refspecs: &[String],
+) -> Result<Repository, git2::Error>
+where
+    <T as TryInto<Url>>::Error: std::fmt::Debug,
+{
+    let url: Url = url
+        .try_into()
+        .map_err(|e| git2::Error::from_str(&format!("invalid url: {:?}", e)))?;
     let mut path: PathBuf = path.into();
     path.push(url.path.clone());
     // let url = &format!("{}{}", "https://github.com/", repo_name);
     // let path = &format!("{}{}", "/tmp/hyperastgitresources/repo/", repo_name);
+    up_to_date_repo(&path, fetch_options(), url, refspecs)
+}
+
+/// Refspecs fetched by default, mirroring all the branches and tags.
+pub const DEFAULT_REFSPECS: [&str; 2] = ["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+pub fn default_refspecs() -> Vec<String> {
+    DEFAULT_REFSPECS.iter().map(|x| x.to_string()).collect()
+}
+
+fn fetch_options<'a>() -> git2::FetchOptions<'a> {
     let mut callbacks = RemoteCallbacks::new();
 
     callbacks.transfer_progress(|x| {
@@ -130,15 +311,19 @@ where
     let mut fo = git2::FetchOptions::new();
 
     fo.remote_callbacks(callbacks);
-
-    let repository = up_to_date_repo(&path, fo, url);
-    repository
+    fo.download_tags(git2::AutotagOption::All);
+    fo
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone, serde::Deserialize)]
+#[serde(try_from = "String")]
 pub enum Forge {
     Github,
     Gitlab,
+    /// Repository on the local file system, its path is the [`Repo::location`]
+    Local,
+    /// Repository at an arbitrary url (file://, ssh, self-hosted), given by the [`Repo::location`]
+    Remote,
 }
 
 impl std::str::FromStr for Forge {
@@ -146,18 +331,45 @@ impl std::str::FromStr for Forge {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "github.com" => Self::Github,
-            "gitlab.com" => Self::Gitlab,
+            "github.com" | "github" => Self::Github,
+            "gitlab.com" | "gitlab" => Self::Gitlab,
+            "local" => Self::Local,
+            "remote" => Self::Remote,
             x => return Err(format!("'{}' is not an authorize forge", x))
         })
     }
 }
 
+impl serde::Serialize for Forge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.host())
+    }
+}
+
+impl TryFrom<String> for Forge {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 impl Forge {
     fn url(&self) -> &str {
         match self {
             Forge::Github => "https://github.com/",
             Forge::Gitlab => "https://gitlab.com/",
+            Forge::Local => "",
+            Forge::Remote => "",
+        }
+    }
+    /// Inverse of [`Forge::from_str`]
+    pub fn host(&self) -> &'static str {
+        match self {
+            Forge::Github => "github.com",
+            Forge::Gitlab => "gitlab.com",
+            Forge::Local => "local",
+            Forge::Remote => "remote",
         }
     }
     pub fn repo(self, user: impl Into<String>, name: impl Into<String>) -> Repo {
@@ -167,74 +379,167 @@ impl Forge {
             forge: self,
             user,
             name,
+            location: None,
+            refspecs: vec![],
         }
     }
 }
 
+/// Identifies a repository by its forge, user and name.
+///
+/// [`Forge::Local`] and [`Forge::Remote`] repositories also need a location,
+/// that is not part of their identity, so that they can be referred to by user and name only.
 // TODO use `&'static str`s to derive with Copy
-#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Repo {
     pub forge: Forge,
     pub user: String,
     pub name: String,
+    /// Path of local repositories or url of remote ones
+    pub location: Option<String>,
+    /// Refspecs fetched when updating the repository, the [`DEFAULT_REFSPECS`] if empty
+    pub refspecs: Vec<String>,
+}
+
+impl PartialEq for Repo {
+    fn eq(&self, other: &Self) -> bool {
+        self.forge == other.forge && self.user == other.user && self.name == other.name
+    }
+}
+
+impl Eq for Repo {}
+
+impl std::hash::Hash for Repo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.forge.hash(state);
+        self.user.hash(state);
+        self.name.hash(state);
+    }
 }
 
 impl Repo {
+    pub fn with_location(self, location: impl Into<String>) -> Self {
+        Self {
+            location: Some(location.into()),
+            ..self
+        }
+    }
+    pub fn with_refspecs(self, refspecs: Vec<String>) -> Self {
+        Self { refspecs, ..self }
+    }
+    pub fn refspecs(&self) -> Vec<String> {
+        if self.refspecs.is_empty() {
+            default_refspecs()
+        } else {
+            self.refspecs.clone()
+        }
+    }
     pub fn url(&self) -> String {
-        format!("{}{}/{}", self.forge.url(), self.user, self.name)
+        match self.forge {
+            Forge::Local | Forge::Remote => self.location.clone().unwrap_or_default(),
+            _ => format!("{}{}/{}", self.forge.url(), self.user, self.name),
+        }
+    }
+    pub fn fetch(&self) -> Result<Repository, git2::Error> {
+        let missing_location = || {
+            git2::Error::from_str(&format!(
+                "missing location of {}/{}/{}",
+                self.forge.host(),
+                self.user,
+                self.name
+            ))
+        };
+        match self.forge {
+            Forge::Local => {
+                let path = self.location.as_ref().ok_or_else(missing_location)?;
+                Repository::open(path)
+            }
+            Forge::Remote => {
+                let url = self.location.as_ref().ok_or_else(missing_location)?;
+                let path = PathBuf::from(format!(
+                    "/tmp/hyperastgitresources/remote/{}/{}",
+                    self.user, self.name
+                ));
+                up_to_date_repo(&path, fetch_options(), url, &self.refspecs())
+            }
+            Forge::Github | Forge::Gitlab => {
+                let url = self.url();
+                let path = format!("{}", "/tmp/hyperastgitresources/repo/");
+                fetch_repository_with(url, path, &self.refspecs())
+            }
+        }
     }
-    pub fn fetch(&self) -> Repository {
-        let url = self.url();
-        let path = format!("{}", "/tmp/hyperastgitresources/repo/");
-        fetch_repository(url, path)
+    /// Inverse of [`Repo::from_str`], eg. github.com/INRIA/spoon or local/me/project=/path/to/project
+    pub fn to_spec(&self) -> String {
+        match &self.location {
+            Some(location) => format!(
+                "{}/{}/{}={}",
+                self.forge.host(),
+                self.user,
+                self.name,
+                location
+            ),
+            None => format!("{}/{}/{}", self.forge.host(), self.user, self.name),
+        }
     }
 }
 
 impl Display for Repo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}{}/{}", self.forge.url(), self.user, self.name)
+        writeln!(f, "{}", self.url())
     }
 }
 
 impl std::str::FromStr for Repo {
     type Err = String;
 
+    /// `<forge>/<user>/<name>` optionally followed by `=<location>` for local and remote forges
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, location) = match s.split_once("=") {
+            Some((s, location)) => (s, Some(location.to_string())),
+            None => (s, None),
+        };
         let (forge, repo) = s.split_once("/").ok_or("give a valid repository address without 'https://' and '.git'")?;
         let (user, name) = repo.split_once("/").ok_or("give a valid repository address without 'https://' and '.git'")?;
-        let forge = forge.parse()?;
+        let forge: Forge = forge.parse()?;
         if name.contains("/") {
             return Err(format!("{} should not contain anymore '/' give a valid repository address", name))
         }
+        if location.is_none() && matches!(forge, Forge::Local | Forge::Remote) {
+            return Err(format!("{} repositories need a location, eg. {}=<path or url>", forge.host(), s))
+        }
         let user = user.into();
         let name = name.into();
-        Ok(Self { forge, user, name })
+        let refspecs = vec![];
+        Ok(Self { forge, user, name, location, refspecs })
     }
 }
 
 pub fn fetch_github_repository(repo_name: &str) -> Repository {
     let url = format!("{}{}", "https://github.com/", repo_name);
     let path = format!("{}", "/tmp/hyperastgitresources/repo/");
-    fetch_repository(url, path)
+    fetch_repository(url, path).unwrap_or_else(|e| panic!("failed to fetch {}: {}", repo_name, e))
 }
 
 /// avoid mixing providers
-pub fn up_to_date_repo(path: &Path, mut fo: git2::FetchOptions, url: Url) -> Repository {
-    if path.join(".git").exists() {
-        let repository = match Repository::open(path) {
-            Ok(repo) => repo,
-            Err(e) => panic!("failed to open: {}", e),
-        };
+///
+/// Fetches the given `refspecs`, in a bare clone if `path` does not exist yet.
+pub fn up_to_date_repo(
+    path: &Path,
+    mut fo: git2::FetchOptions,
+    url: impl Display,
+    refspecs: &[String],
+) -> Result<Repository, git2::Error> {
+    if path.exists() {
+        // either cloned by us in `path/.git`, or a repository we did not clone
+        let repository = Repository::open(path)?;
         log::info!("fetch: {:?}", path);
         repository
-            .find_remote("origin")
-            .unwrap()
-            .fetch(&["main"], Some(&mut fo), None)
+            .find_remote("origin")?
+            .fetch(refspecs, Some(&mut fo), None)
             .unwrap_or_else(|e| log::error!("{}", e));
 
-        repository
-    } else if path.exists() {
-        todo!()
+        Ok(repository)
     } else {
         let mut builder = git2::build::RepoBuilder::new();
 
@@ -243,11 +548,13 @@ pub fn up_to_date_repo(path: &Path, mut fo: git2::FetchOptions, url: Url) -> Rep
         builder.fetch_options(fo);
 
         log::info!("clone {} in {:?}", url, path);
-        let repository = match builder.clone(&url.to_string(), path.join(".git").as_path()) {
-            Ok(repo) => repo,
-            Err(e) => panic!("failed to clone: {}", e),
-        };
+        let repository = builder.clone(&url.to_string(), path.join(".git").as_path())?;
+        // the clone only fetched the default refspec
         repository
+            .find_remote("origin")?
+            .fetch(refspecs, Some(&mut fetch_options()), None)
+            .unwrap_or_else(|e| log::error!("{}", e));
+        Ok(repository)
     }
 }
 
@@ -495,3 +802,129 @@ fn own(r: &[u8]) -> Result<String, git2::Error> {
     let r = r.map_err(|x| git2::Error::from_str(&x.to_string()));
     r.map(|s| s.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3 commits on HEAD, `dev` on the first one and `v1` on the second one.
+    fn history(name: &str) -> (Repository, [Oid; 3]) {
+        let (repository, tree) = crate::tests::repository(name, &[("A.java", "class A {}")]);
+        // the trees and commits borrow the repository
+        let commits = {
+            let tree = repository.find_tree(tree).unwrap();
+            let sig = git2::Signature::now("t", "t@t").unwrap();
+            let mut commits: Vec<Oid> = vec![];
+            for _ in 0..3 {
+                let parent = commits.last().map(|x| repository.find_commit(*x).unwrap());
+                let parents: Vec<_> = parent.iter().collect();
+                let id = repository
+                    .commit(Some("HEAD"), &sig, &sig, "c", &tree, &parents)
+                    .unwrap();
+                commits.push(id);
+            }
+            let first = repository.find_commit(commits[0]).unwrap();
+            repository.branch("dev", &first, false).unwrap();
+            let second = repository.find_object(commits[1], None).unwrap();
+            repository.tag("v1", &second, &sig, "v1", false).unwrap();
+            [commits[0], commits[1], commits[2]]
+        };
+        (repository, commits)
+    }
+
+    #[test]
+    fn resolve_revspecs() {
+        let (repository, [c1, c2, c3]) = history("revspecs");
+        let resolve = |s: &str| resolve_commit(&repository, s);
+        assert_eq!(resolve("dev").unwrap(), c1);
+        assert_eq!(resolve("v1").unwrap(), c2);
+        assert_eq!(resolve("HEAD").unwrap(), c3);
+        assert_eq!(resolve("HEAD~2").unwrap(), c1);
+        assert_eq!(resolve("v1~1").unwrap(), c1);
+        assert_eq!(resolve(&c3.to_string()[..10]).unwrap(), c3);
+        assert!(resolve("missing").is_err());
+    }
+
+    #[test]
+    fn fetch_refspecs_of_each_repository() {
+        let (repository, [c1, ..]) = history("fetched");
+        let location = repository.workdir().unwrap().to_string_lossy().to_string();
+        let name = |x: &str| format!("{}-{}", x, std::process::id());
+        let mirrored = Forge::Remote
+            .repo("test", name("mirrored"))
+            .with_location(&location)
+            .with_refspecs(vec!["+refs/heads/*:refs/remotes/mirror/*".to_string()]);
+        let default = Forge::Remote
+            .repo("test", name("default"))
+            .with_location(&location);
+        for repo in [&mirrored, &default] {
+            let path = format!("/tmp/hyperastgitresources/remote/test/{}", repo.name);
+            let _ = std::fs::remove_dir_all(path);
+        }
+        let fetched = mirrored.fetch().unwrap();
+        assert_eq!(resolve_commit(&fetched, "mirror/dev").unwrap(), c1);
+        // the refspecs of a repository do not leak to others
+        let fetched = default.fetch().unwrap();
+        assert!(fetched.find_reference("refs/remotes/mirror/dev").is_err());
+        assert_eq!(resolve_commit(&fetched, "dev").unwrap(), c1);
+        assert_eq!(resolve_commit(&fetched, "v1~1").unwrap(), c1);
+    }
+
+    /// `small` (1 line changed from `base`) merged into `large` (3 lines changed),
+    /// the merge keeps the content of `small` so that its diff with `small` is the smallest.
+    fn merge(name: &str) -> (Repository, [Oid; 4]) {
+        let (repository, _) = crate::tests::repository(name, &[]);
+        let commits = {
+            let sig = git2::Signature::now("t", "t@t").unwrap();
+            let commit = |text: &str, parents: &[Oid]| {
+                let blob = repository.blob(text.as_bytes()).unwrap();
+                let mut tree = repository.treebuilder(None).unwrap();
+                tree.insert("a.txt", blob, 0o100644).unwrap();
+                let tree = repository.find_tree(tree.write().unwrap()).unwrap();
+                let parents: Vec<_> = parents
+                    .iter()
+                    .map(|x| repository.find_commit(*x).unwrap())
+                    .collect();
+                let parents: Vec<_> = parents.iter().collect();
+                repository
+                    .commit(None, &sig, &sig, "c", &tree, &parents)
+                    .unwrap()
+            };
+            let base = commit("a\nb\nc\n", &[]);
+            let large = commit("x\ny\nz\n", &[base]);
+            let small = commit("a\nb\nz\n", &[base]);
+            let merge = commit("a\nb\nz\n", &[large, small]);
+            [base, large, small, merge]
+        };
+        (repository, commits)
+    }
+
+    #[test]
+    fn merge_strategies() {
+        let (repository, [base, large, small, merge]) = merge("merges");
+        let walk = |strategy| {
+            let (before, after) = (base.to_string(), merge.to_string());
+            commits_between_with(&repository, &before, &after, strategy)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        assert_eq!(walk(MergeStrategy::FirstParent), [merge, large, base]);
+        assert_eq!(walk(MergeStrategy::SmallestDiff), [merge, small, base]);
+        let mut all = walk(MergeStrategy::AllParents);
+        assert_eq!(all.first(), Some(&merge));
+        assert_eq!(all.last(), Some(&base));
+        all.sort();
+        let mut expected = [base, large, small, merge];
+        expected.sort();
+        assert_eq!(all, expected);
+
+        let merge = repository.find_commit(merge).unwrap();
+        let parents = |strategy| parents_with(&repository, &merge, strategy).unwrap();
+        assert_eq!(parents(MergeStrategy::FirstParent), [large]);
+        assert_eq!(parents(MergeStrategy::AllParents), [large, small]);
+        assert_eq!(parents(MergeStrategy::SmallestDiff), [small]);
+        assert_eq!("smallest_diff".parse(), Ok(MergeStrategy::SmallestDiff));
+        assert!("last_parent".parse::<MergeStrategy>().is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Gradle build scripts, ie. settings.gradle, build.gradle and their kotlin variants.
+//!
+//! There is no Groovy or Kotlin grammar in the HyperAST (yet),
+//! so scripts are not part of the tree, only the module structure they declare is extracted from their tokens.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{
+    maven::{
+        normalize_module_path, MavenCoordinates, MavenDependency, MavenModuleAcc, MavenModuleInfo,
+        SemFlags,
+    },
+    processing::{file_sys, InFiles, ObjectName},
+    ParseErr,
+};
+
+pub(crate) fn handle_gradle_file(name: &ObjectName, text: &[u8]) -> Result<GradleFile, ParseErr> {
+    let text = std::str::from_utf8(text)?;
+    let statements = statements(&tokenize(text));
+    if file_sys::GradleSettings::matches(name) {
+        Ok(GradleFile::from_settings(&statements))
+    } else {
+        Ok(GradleFile::from_build(&statements))
+    }
+}
+
+/// What a gradle script declares about the project in its directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GradleFile {
+    /// directories of the included projects, relative to the settings script
+    pub(crate) submodules: Vec<String>,
+    /// from `sourceSets`, relative to the project
+    pub(crate) source_dirs: Vec<String>,
+    pub(crate) test_source_dirs: Vec<String>,
+    pub(crate) group: Option<String>,
+    pub(crate) version: Option<String>,
+    /// the configuration (eg. implementation, testImplementation) is used as scope,
+    /// `project(':a:b')` dependencies only have the name of the project as artifactId
+    pub(crate) dependencies: Vec<MavenDependency>,
+}
+
+impl GradleFile {
+    fn from_settings(statements: &[Statement]) -> Self {
+        let mut included = vec![];
+        let mut project_dirs = BTreeMap::new();
+        for stmt in statements {
+            match stmt.tokens.as_slice() {
+                [Token::Ident("include"), rest @ ..] => {
+                    included.extend(rest.iter().filter_map(Token::as_str))
+                }
+                tokens => {
+                    if let Some((project, dir)) = project_dir(tokens) {
+                        project_dirs.insert(project, dir);
+                    }
+                }
+            }
+        }
+        let submodules = included
+            .into_iter()
+            .filter_map(|project| match project_dirs.get(project) {
+                Some(dir) => normalize_module_path(dir),
+                None => normalize_module_path(&project.trim_start_matches(':').replace(':', "/")),
+            })
+            .collect();
+        Self {
+            submodules,
+            ..Default::default()
+        }
+    }
+
+    fn from_build(statements: &[Statement]) -> Self {
+        let mut file = Self::default();
+        for stmt in statements {
+            match stmt.tokens.as_slice() {
+                [Token::Ident(prop @ ("group" | "version")), Token::Punct('='), Token::Str(value)]
+                    if stmt.scope.is_empty() =>
+                {
+                    if *prop == "group" {
+                        file.group = Some(value.to_string());
+                    } else {
+                        file.version = Some(value.to_string());
+                    }
+                    continue;
+                }
+                _ => (),
+            }
+            if let Some(i) = stmt
+                .tokens
+                .iter()
+                .position(|x| matches!(x, Token::Ident("srcDir" | "srcDirs" | "setSrcDirs")))
+            {
+                let context: Vec<_> = (stmt.scope.iter().flatten().copied())
+                    .chain(words(&stmt.tokens[..i]))
+                    .collect();
+                if !context.contains(&"java") {
+                    continue;
+                }
+                let (dirs, default) = if context.contains(&"test") {
+                    (
+                        &mut file.test_source_dirs,
+                        MavenModuleInfo::DEFAULT_TEST_SOURCE_DIRECTORY,
+                    )
+                } else if context.contains(&"main") {
+                    (
+                        &mut file.source_dirs,
+                        MavenModuleInfo::DEFAULT_SOURCE_DIRECTORY,
+                    )
+                } else {
+                    continue;
+                };
+                // srcDir and srcDirs(...) add to the default directory, srcDirs = and setSrcDirs replace it
+                let additive = matches!(stmt.tokens[i], Token::Ident("srcDir"))
+                    || matches!(stmt.tokens[i], Token::Ident("srcDirs"))
+                        && !matches!(stmt.tokens.get(i + 1), Some(Token::Punct('=' | '+')));
+                if additive && dirs.is_empty() {
+                    dirs.push(default.to_string());
+                }
+                for dir in stmt.tokens[i + 1..].iter().filter_map(Token::as_str) {
+                    if let Some(dir) = normalize_module_path(dir) {
+                        if !dirs.contains(&dir) {
+                            dirs.push(dir);
+                        }
+                    }
+                }
+                continue;
+            }
+            if stmt
+                .scope
+                .last()
+                .map_or(false, |x| x.contains(&"dependencies"))
+                && !stmt.scope.iter().any(|x| x.contains(&"buildscript"))
+            {
+                if let Some(dependency) = dependency(&stmt.tokens) {
+                    file.dependencies.push(dependency);
+                }
+            }
+        }
+        file
+    }
+}
+
+/// `project(':a').projectDir = file('path/to/a')`
+fn project_dir<'a>(tokens: &[Token<'a>]) -> Option<(&'a str, &'a str)> {
+    let project = match tokens.get(..4)? {
+        [Token::Ident("project"), Token::Punct('('), Token::Str(project), Token::Punct(')')] => {
+            *project
+        }
+        _ => return None,
+    };
+    if tokens.get(4..7)?
+        != [
+            Token::Punct('.'),
+            Token::Ident("projectDir"),
+            Token::Punct('='),
+        ]
+    {
+        return None;
+    }
+    let dir = tokens[7..].iter().rev().find_map(Token::as_str)?;
+    Some((project, dir))
+}
+
+fn dependency(tokens: &[Token]) -> Option<MavenDependency> {
+    let Some(Token::Ident(configuration)) = tokens.first() else {
+        return None;
+    };
+    if *configuration == "classpath" {
+        return None;
+    }
+    let scope = Some(configuration.to_string());
+    let rest = &tokens[1..];
+    // implementation project(':a:b')
+    if let Some(i) = rest.iter().position(|x| x == &Token::Ident("project")) {
+        let project = rest[i..].iter().find_map(Token::as_str)?;
+        let name = project.rsplit(':').next()?;
+        return Some(MavenDependency {
+            coordinates: MavenCoordinates {
+                group_id: None,
+                artifact_id: Some(name.to_string()),
+                version: None,
+            },
+            scope,
+        });
+    }
+    // implementation group: 'g', name: 'a', version: 'v'
+    let named = |key: &str| {
+        rest.windows(3).find_map(|w| match w {
+            [Token::Ident(k), Token::Punct(':' | '='), Token::Str(v)] if *k == key => {
+                Some(v.to_string())
+            }
+            _ => None,
+        })
+    };
+    if let Some(artifact_id) = named("name") {
+        return Some(MavenDependency {
+            coordinates: MavenCoordinates {
+                group_id: named("group"),
+                artifact_id: Some(artifact_id),
+                version: named("version"),
+            },
+            scope,
+        });
+    }
+    // implementation 'g:a:v'
+    let notation = rest.iter().find_map(Token::as_str)?;
+    let mut parts = notation.split(':');
+    let group_id = parts.next()?;
+    let artifact_id = parts.next()?;
+    Some(MavenDependency {
+        coordinates: MavenCoordinates {
+            group_id: Some(group_id.to_string()),
+            artifact_id: Some(artifact_id.to_string()),
+            version: parts.next().map(|x| x.to_string()),
+        },
+        scope,
+    })
+}
+
+impl MavenModuleAcc {
+    /// Gradle projects are handled as maven modules, so that analyses on maven modules also work on them.
+    ///
+    /// Also called for projects included in a settings script that do not have a build script.
+    pub(crate) fn push_gradle_file(&mut self, file: GradleFile) {
+        self.status |= SemFlags::IsMavenModule;
+        if !file.submodules.is_empty() {
+            self.sub_modules
+                .get_or_insert_with(Default::default)
+                .extend(file.submodules.iter().map(PathBuf::from));
+        }
+        if !file.source_dirs.is_empty() {
+            self.main_dirs = Some(outermost(&file.source_dirs));
+        } else if self.main_dirs.is_none() {
+            self.main_dirs = Some(vec![MavenModuleInfo::DEFAULT_SOURCE_DIRECTORY.into()]);
+        }
+        if !file.test_source_dirs.is_empty() {
+            self.test_dirs = Some(outermost(&file.test_source_dirs));
+        } else if self.test_dirs.is_none() {
+            self.test_dirs = Some(vec![MavenModuleInfo::DEFAULT_TEST_SOURCE_DIRECTORY.into()]);
+        }
+        let name = self.name.clone();
+        let info = self.info.get_or_insert_with(|| MavenModuleInfo {
+            coordinates: MavenCoordinates {
+                artifact_id: Some(name),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        info.modules.extend(file.submodules);
+        if let Some(dir) = file.source_dirs.into_iter().next() {
+            info.source_directory = dir;
+        }
+        if let Some(dir) = file.test_source_dirs.into_iter().next() {
+            info.test_source_directory = dir;
+        }
+        if file.group.is_some() {
+            info.coordinates.group_id = file.group;
+        }
+        if file.version.is_some() {
+            info.coordinates.version = file.version;
+        }
+        info.dependencies.extend(file.dependencies);
+    }
+}
+
+/// A directory is handled as a whole, so directories inside other ones are dropped.
+fn outermost(dirs: &[String]) -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = dirs.iter().map(PathBuf::from).collect();
+    dirs.iter()
+        .filter(|x| !dirs.iter().any(|y| y != *x && x.starts_with(y)))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    /// content of a string literal, without the quotes
+    Str(&'a str),
+    Punct(char),
+    NewLine,
+}
+
+impl<'a> Token<'a> {
+    fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Token::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Good enough for both Groovy and Kotlin scripts, comments are skipped.
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\n' {
+            tokens.push(Token::NewLine);
+            i += 1;
+        } else if c.is_ascii_whitespace() {
+            i += 1;
+        } else if text[i..].starts_with("//") {
+            i = text[i..].find('\n').map_or(bytes.len(), |x| i + x);
+        } else if text[i..].starts_with("/*") {
+            i = text[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |x| i + 2 + x + 2);
+        } else if c == b'"' || c == b'\'' {
+            let quote = if text[i..].starts_with("\"\"\"") || text[i..].starts_with("'''") {
+                &text[i..i + 3]
+            } else {
+                &text[i..i + 1]
+            };
+            let start = i + quote.len();
+            let mut j = start;
+            while j < bytes.len() && !bytes[j..].starts_with(quote.as_bytes()) {
+                if bytes[j] == b'\\' && j + 1 < bytes.len() {
+                    j += 1;
+                }
+                j += text[j..].chars().next().map_or(1, char::len_utf8);
+            }
+            let end = j.min(bytes.len());
+            tokens.push(Token::Str(&text[start..end]));
+            i = end + quote.len();
+        } else if c.is_ascii_alphanumeric() || c == b'_' || c == b'$' {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(&text[start..i]));
+        } else {
+            let c = text[i..].chars().next().unwrap();
+            tokens.push(Token::Punct(c));
+            i += c.len_utf8();
+        }
+    }
+    tokens
+}
+
+#[derive(Debug)]
+struct Statement<'a> {
+    /// identifiers and strings of the statements opening the enclosing blocks,
+    /// eg. `sourceSets { main {` gives `[["sourceSets"], ["main"]]`
+    scope: Vec<Vec<&'a str>>,
+    tokens: Vec<Token<'a>>,
+}
+
+fn words<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens
+        .iter()
+        .filter_map(|x| match x {
+            Token::Ident(s) | Token::Str(s) => Some(*s),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Statements end at line breaks outside of parenthesis and brackets, at `;`, `{` and `}`.
+fn statements<'a>(tokens: &[Token<'a>]) -> Vec<Statement<'a>> {
+    let mut statements = vec![];
+    let mut scope = vec![];
+    let mut current = vec![];
+    let mut depth = 0usize;
+    for t in tokens {
+        // a statement continues on the next line inside brackets or after a `,` or `=`
+        let continued = depth > 0 || matches!(current.last(), Some(Token::Punct(',' | '=')));
+        match t {
+            Token::Punct('(' | '[') => {
+                depth += 1;
+                current.push(*t);
+            }
+            Token::Punct(')' | ']') => {
+                depth = depth.saturating_sub(1);
+                current.push(*t);
+            }
+            Token::NewLine if continued => (),
+            Token::NewLine | Token::Punct(';') => {
+                if !current.is_empty() {
+                    statements.push(Statement {
+                        scope: scope.clone(),
+                        tokens: std::mem::take(&mut current),
+                    });
+                }
+            }
+            Token::Punct('{') => {
+                depth = 0;
+                scope.push(words(&std::mem::take(&mut current)));
+            }
+            Token::Punct('}') => {
+                depth = 0;
+                if !current.is_empty() {
+                    statements.push(Statement {
+                        scope: scope.clone(),
+                        tokens: std::mem::take(&mut current),
+                    });
+                }
+                scope.pop();
+            }
+            t => current.push(*t),
+        }
+    }
+    if !current.is_empty() {
+        statements.push(Statement {
+            scope,
+            tokens: current,
+        });
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings() {
+        let text = br#"rootProject.name = 'demo'
+include 'core', ':app'
+include(":libs:util",
+    ":libs:io")
+// include 'ignored'
+project(':app').projectDir = file('applications/app')
+"#;
+        let file = handle_gradle_file(&ObjectName::from(b"settings.gradle"), text).unwrap();
+        assert_eq!(
+            file.submodules,
+            ["core", "applications/app", "libs/util", "libs/io"]
+        );
+    }
+
+    #[test]
+    fn build() {
+        let text = br#"plugins { id 'java' }
+group = 'org.example'
+version = '1.0'
+sourceSets {
+    main {
+        java {
+            srcDirs = ['src/java']
+        }
+    }
+    test.java.srcDir 'src/it/java'
+}
+dependencies {
+    implementation project(':libs:util')
+    testImplementation("junit:junit:4.13")
+    compileOnly group: 'org.projectlombok', name: 'lombok', version: '1.18.24'
+}
+buildscript {
+    dependencies {
+        classpath 'com.example:plugin:1.0'
+    }
+}
+"#;
+        let file = handle_gradle_file(&ObjectName::from(b"build.gradle"), text).unwrap();
+        assert_eq!(file.source_dirs, ["src/java"]);
+        assert_eq!(file.test_source_dirs, ["src/test/java", "src/it/java"]);
+        assert_eq!(file.group.as_deref(), Some("org.example"));
+        let deps: Vec<_> = file
+            .dependencies
+            .iter()
+            .map(|x| {
+                (
+                    x.scope.as_deref().unwrap(),
+                    x.coordinates.artifact_id.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            deps,
+            [
+                ("implementation", "util"),
+                ("testImplementation", "junit"),
+                ("compileOnly", "lombok")
+            ]
+        );
+    }
+}
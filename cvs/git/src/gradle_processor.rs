@@ -0,0 +1,521 @@
+use std::{
+    iter::Peekable,
+    marker::PhantomData,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::store::defaults::NodeIdentifier;
+
+use crate::{
+    git::BasicGitObject,
+    gradle::GradleFile,
+    maven::{MavenModuleAcc, MD},
+    maven_processor::ModuleWalk,
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    Processor,
+};
+
+/// Builds the same hierarchy of modules as the [`crate::maven_processor::MavenProcessor`],
+/// where gradle projects are maven modules.
+///
+/// Scripts are not part of the tree, what they declare is summarized in the [`crate::maven::MavenModuleInfo`] of the module,
+/// which is part of the identity of the module node (see [`crate::maven_processor::make`]).
+///
+/// RMS: Resursive Module Search, ie. also look for build scripts in directories not included by a settings script
+pub struct GradleProcessor<'a, 'b, 'c, const RMS: bool> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>,
+}
+
+impl<'a, 'b, 'c, const RMS: bool> GradleProcessor<'a, 'b, 'c, RMS> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        mut dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, &mut dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, MavenModuleAcc::new(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> Processor<MavenModuleAcc> for GradleProcessor<'a, 'b, 'c, RMS> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                if crate::processing::file_sys::GradleFile::matches(&name) {
+                    self.prepro
+                        .handle_gradle_file(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            name,
+                            &self.repository,
+                            self.handle.into(),
+                        )
+                        .unwrap()
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: MavenModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        Self::WALK.post(self.prepro, &mut self.stack, oid, acc)
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> GradleProcessor<'a, 'b, 'c, RMS> {
+    const WALK: ModuleWalk = ModuleWalk {
+        prepare: prepare_dir_exploration,
+        object_map: |prepro| {
+            &mut prepro
+                .processing_systems
+                .mut_or_default::<GradleProcessorHolder>()
+                .get_caches_mut()
+                .object_map
+        },
+        // included by a settings script, a build script is not mandatory
+        init_module: |acc| acc.push_gradle_file(GradleFile::default()),
+        rms: RMS,
+        ffwd: false,
+    };
+
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        Self::WALK.handle_tree(
+            self.prepro,
+            self.repository,
+            self.dir_path,
+            &mut self.stack,
+            name,
+            oid,
+        )
+    }
+}
+
+impl RepositoryProcessor {
+    fn handle_gradle_file(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut MavenModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleFileProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::GradleFile>()
+            .handle(oid, repository, &name, parameters, |_, n, t| {
+                crate::gradle::handle_gradle_file(n, t)
+            })?;
+        parent_acc.push_gradle_file(x);
+        Ok(())
+    }
+}
+
+/// Gradle scripts are handled first,
+/// as they tell how to handle the other directories.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        children_objects.reverse(); // we use it like a stack
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) => crate::processing::file_sys::GradleFile::matches(n),
+            _ => false,
+        });
+    }
+    children_objects
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+
+// # Gradle scripts
+
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleFileProc>
+{
+    fn from(
+        value: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>,
+    ) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+
+struct GradleFileProcessorHolder(Option<GradleFileProc>);
+impl Default for GradleFileProcessorHolder {
+    fn default() -> Self {
+        Self(Some(GradleFileProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+struct GradleFileProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::GradleFile,
+}
+impl crate::processing::erased::Parametrized for GradleFileProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(GradleFileProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+impl crate::processing::erased::CommitProc for GradleFileProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for GradleFileProc {
+    type Holder = GradleFileProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for GradleFileProcessorHolder {
+    type Proc = GradleFileProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::GradleFile> for GradleFileProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::GradleFile {
+        &mut self.cache
+    }
+
+    fn get_caches(&self) -> &crate::processing::caches::GradleFile {
+        &self.cache
+    }
+}
+
+// # Gradle
+
+#[derive(Default)]
+pub struct GradleProcessorHolder(Option<GradleProc>);
+pub struct GradleProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Gradle,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for GradleProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(GradleProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedGradleCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedGradleCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedGradleCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = GradleProcessor::<true>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for GradleProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("cannot access retrieve RepositoryProcessor as a CommitProc is likely part of it, double mutable borrow RIP")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        oids: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedGradleCommitProc {
+            repository,
+            commit_builder: oids,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for GradleProc {
+    type Holder = GradleProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for GradleProcessorHolder {
+    type Proc = GradleProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Gradle> for GradleProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Gradle {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Gradle {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Gradle> for GradleProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Gradle {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Gradle {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maven::{MavenModuleInfo, SemFlags};
+    use crate::tests::repository;
+    use enumset::EnumSet;
+
+    fn process(
+        prepro: &mut RepositoryProcessor,
+        name: &str,
+        files: &[(&str, &str)],
+    ) -> NodeIdentifier {
+        let (repository, tree) = repository(name, files);
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        GradleProcessor::<false>::new(&repository, prepro, &mut dir_path, b"", tree)
+            .process()
+            .0
+    }
+
+    const CLASS: &str = "package org.example;\nclass A {}\n";
+
+    #[test]
+    fn gradle_projects_as_modules() {
+        let mut prepro = RepositoryProcessor::default();
+        let root = process(
+            &mut prepro,
+            "gradle-projects",
+            &[
+                ("settings.gradle", "include 'core', 'app'\n"),
+                ("core/build.gradle", "group = 'org.example'\n"),
+                ("core/src/main/java/org/example/A.java", CLASS),
+                ("app/src/main/java/org/example/A.java", CLASS),
+                ("docs/A.java", CLASS),
+            ],
+        );
+        let info = |id| {
+            let stores = prepro.main_stores();
+            let b = stores.node_store.resolve(id);
+            let status = b.get_component::<EnumSet<SemFlags>>().ok().copied();
+            (status, b.get_component::<MavenModuleInfo>().ok().cloned())
+        };
+        let (status, _) = info(root);
+        assert!(status.unwrap().contains(SemFlags::HoldMavenSubModule));
+        // scripts are summarized in the module, not kept as children
+        assert!(prepro.child_by_name(root, "settings.gradle").is_none());
+        // not included by the settings script
+        assert!(prepro.child_by_name(root, "docs").is_none());
+
+        let core = prepro.child_by_name(root, "core").unwrap();
+        let (status, core_info) = info(core);
+        assert!(status.unwrap().contains(SemFlags::IsMavenModule));
+        let core_info = core_info.unwrap();
+        assert_eq!(
+            core_info.coordinates.group_id.as_deref(),
+            Some("org.example")
+        );
+        assert!(prepro.child_by_name(core, "src").is_some());
+
+        // included without a build script
+        let app = prepro.child_by_name(root, "app").unwrap();
+        let (status, app_info) = info(app);
+        assert!(status.unwrap().contains(SemFlags::IsMavenModule));
+        assert_eq!(
+            app_info.unwrap().coordinates.artifact_id.as_deref(),
+            Some("app")
+        );
+    }
+
+    #[test]
+    fn gradle_summary_in_identity() {
+        let mut prepro = RepositoryProcessor::default();
+        let mut core = |name, group| {
+            let root = process(
+                &mut prepro,
+                name,
+                &[
+                    ("settings.gradle", "include 'core'\n"),
+                    ("core/build.gradle", group),
+                    ("core/src/main/java/org/example/A.java", CLASS),
+                ],
+            );
+            prepro.child_by_name(root, "core").unwrap()
+        };
+        let a = core("gradle-identity-a", "group = 'org.a'\n");
+        let b = core("gradle-identity-b", "group = 'org.b'\n");
+        let c = core("gradle-identity-c", "group = 'org.a'\n\n");
+        // same children, but declaring different things
+        assert_ne!(a, b);
+        // different scripts, declaring the same things
+        assert_eq!(a, c);
+    }
+
+    /// The tree of `app` is unchanged, but it is not included by the settings script anymore.
+    #[test]
+    fn settings_in_cache_key() {
+        let mut prepro = RepositoryProcessor::default();
+        let mut commit = |name, settings| {
+            let files = [
+                ("settings.gradle", settings),
+                ("core/src/main/java/org/example/A.java", CLASS),
+                ("app/src/main/java/org/example/A.java", CLASS),
+            ];
+            let root = process(&mut prepro, name, &files);
+            (root, prepro.child_by_name(root, "app"))
+        };
+        let (_, app) = commit("gradle-settings-a", "include 'core', 'app'\n");
+        assert!(app.is_some());
+        let (root, app) = commit("gradle-settings-b", "include 'core'\n");
+        assert!(app.is_none());
+        let stores = prepro.main_stores();
+        let status = stores
+            .node_store
+            .resolve(root)
+            .get_component::<EnumSet<SemFlags>>()
+            .ok()
+            .copied();
+        assert!(status.unwrap().contains(SemFlags::HoldMavenSubModule));
+    }
+}
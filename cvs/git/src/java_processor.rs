@@ -466,6 +466,12 @@ impl RepositoryProcessor {
         }
     }
 
+    /// The metadata of the java subtrees processed so far, eg. their partial analysis.
+    pub fn java_md_cache(&self) -> Option<&java_tree_gen::MDCache> {
+        let holder = self.processing_systems.get::<JavaProcessorHolder>()?;
+        Some(&holder.0.as_ref()?.cache.md_cache)
+    }
+
     pub(crate) fn help_handle_java_folder<'a, 'b, 'c, 'd: 'c>(
         &'a mut self,
         repository: &'b Repository,
@@ -478,7 +484,7 @@ impl RepositoryProcessor {
         (name, full_node)
     }
 
-    fn handle_java_blob(
+    pub(crate) fn handle_java_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
@@ -3,20 +3,52 @@
 #![feature(trait_upcasting)]
 #![feature(os_str_bytes)]
 pub mod allrefs;
+pub mod call_graph;
+#[cfg(feature = "cargo")]
+pub mod cargo;
 pub mod cpp;
 pub mod git;
+pub mod gradle;
 pub mod java;
 pub mod make;
 pub mod maven;
-
+#[cfg(feature = "npm")]
+pub mod npm;
+#[cfg(feature = "pip")]
+pub mod pip;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "rust")]
+pub mod rust;
+#[cfg(feature = "ts")]
+pub mod ts;
+
+#[cfg(feature = "any")]
+pub mod any_processor;
+#[cfg(feature = "cargo")]
+pub mod cargo_processor;
 #[cfg(feature = "cpp")]
 pub mod cpp_processor;
+#[cfg(feature = "gradle")]
+pub mod gradle_processor;
 #[cfg(feature = "java")]
 pub mod java_processor;
 #[cfg(feature = "make")]
 pub mod make_processor;
 #[cfg(feature = "maven")]
 pub mod maven_processor;
+#[cfg(feature = "npm")]
+pub mod npm_processor;
+#[cfg(any(feature = "npm", feature = "pip", feature = "cargo"))]
+pub mod package_processor;
+#[cfg(feature = "pip")]
+pub mod pip_processor;
+#[cfg(feature = "python")]
+pub mod python_processor;
+#[cfg(feature = "rust")]
+pub mod rust_processor;
+#[cfg(feature = "ts")]
+pub mod ts_processor;
 pub mod multi_preprocessed;
 pub mod no_space;
 /// for now only tested on maven repositories with a pom in root.
@@ -114,6 +146,14 @@ mod type_store {
     use hyper_ast_gen_ts_cpp::types::CppEnabledTypeStore;
     #[cfg(feature = "java")]
     use hyper_ast_gen_ts_java::types::JavaEnabledTypeStore;
+    #[cfg(feature = "make")]
+    use hyper_ast_gen_ts_make::types::MakeEnabledTypeStore;
+    #[cfg(feature = "python")]
+    use hyper_ast_gen_ts_python::types::PythonEnabledTypeStore;
+    #[cfg(feature = "rust")]
+    use hyper_ast_gen_ts_rust::types::RustEnabledTypeStore;
+    #[cfg(feature = "ts")]
+    use hyper_ast_gen_ts_ts::types::TsEnabledTypeStore;
     #[cfg(feature = "maven")]
     use hyper_ast_gen_ts_xml::types::XmlEnabledTypeStore;
 
@@ -124,6 +164,10 @@ mod type_store {
         Maven = 0,
         Java = 1,
         Cpp = 2,
+        Make = 3,
+        Ts = 4,
+        Python = 5,
+        Rust = 6,
     }
 
     impl Default for TStore {
@@ -156,7 +200,11 @@ mod type_store {
             on_multi!(n, [
                     hyper_ast_gen_ts_java,
                     hyper_ast_gen_ts_cpp,
-                    hyper_ast_gen_ts_xml
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_make,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_python,
+                    hyper_ast_gen_ts_rust
                 ], 
                 (t, u) => u::types::as_any(t),
                 {
@@ -173,7 +221,11 @@ mod type_store {
             on_multi!(n, [
                     hyper_ast_gen_ts_java,
                     hyper_ast_gen_ts_cpp,
-                    hyper_ast_gen_ts_xml
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_make,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_python,
+                    hyper_ast_gen_ts_rust
                 ], 
                 (_t, u) => From::<&'static (dyn LangRef<AnyType>)>::from(&u::types::Lang),
                 {
@@ -189,7 +241,11 @@ mod type_store {
             on_multi!(n, [
                     hyper_ast_gen_ts_java,
                     hyper_ast_gen_ts_cpp,
-                    hyper_ast_gen_ts_xml
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_make,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_python,
+                    hyper_ast_gen_ts_rust
                 ], 
                 (t, u) => {
                     let ty = <u::types::Lang as hyper_ast::types::Lang<_>>::to_u16(*t);
@@ -487,11 +543,208 @@ mod type_store {
         }
     }
 
+    impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_make::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        type Ty = hyper_ast_gen_ts_make::types::Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_make::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Ty {
+            *n.get_component::<hyper_ast_gen_ts_make::types::Type>()
+                .unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_make::types::TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Self::Ty>)>::from(&hyper_ast_gen_ts_make::types::Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_make::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Self::Ty>::name(&hyper_ast_gen_ts_make::types::Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a>
+        MakeEnabledTypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_make::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        const LANG: u16 = 0;
+
+        fn _intern(l: u16, t: u16) -> Self::Ty {
+            hyper_ast_gen_ts_make::types::Type::resolve(t)
+        }
+
+        fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_make::types::Type {
+            t
+        }
+    }
+
+    impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        type Ty = hyper_ast_gen_ts_ts::types::Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Ty {
+            *n.get_component::<hyper_ast_gen_ts_ts::types::Type>()
+                .unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Self::Ty>)>::from(&hyper_ast_gen_ts_ts::types::Ts)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Self::Ty>::name(&hyper_ast_gen_ts_ts::types::Ts),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a>
+        TsEnabledTypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        const LANG: u16 = 0;
+
+        fn _intern(l: u16, t: u16) -> Self::Ty {
+            hyper_ast_gen_ts_ts::types::Type::resolve(t)
+        }
+
+        fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_ts::types::Type {
+            t
+        }
+    }
+
+    impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_python::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        type Ty = hyper_ast_gen_ts_python::types::Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_python::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Ty {
+            *n.get_component::<hyper_ast_gen_ts_python::types::Type>()
+                .unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_python::types::TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Self::Ty>)>::from(&hyper_ast_gen_ts_python::types::Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_python::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Self::Ty>::name(&hyper_ast_gen_ts_python::types::Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a>
+        PythonEnabledTypeStore<
+            HashedNodeRef<'a, hyper_ast_gen_ts_python::types::TIdN<NodeIdentifier>>,
+        > for TStore
+    {
+        const LANG: u16 = 0;
+
+        fn _intern(l: u16, t: u16) -> Self::Ty {
+            hyper_ast_gen_ts_python::types::Type::resolve(t)
+        }
+
+        fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_python::types::Type {
+            t
+        }
+    }
+
+    impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_rust::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        type Ty = hyper_ast_gen_ts_rust::types::Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_rust::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Ty {
+            *n.get_component::<hyper_ast_gen_ts_rust::types::Type>()
+                .unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_rust::types::TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Self::Ty>)>::from(&hyper_ast_gen_ts_rust::types::Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(
+            &self,
+            n: &HashedNodeRef<'a, hyper_ast_gen_ts_rust::types::TIdN<NodeIdentifier>>,
+        ) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Self::Ty>::name(&hyper_ast_gen_ts_rust::types::Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a>
+        RustEnabledTypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_rust::types::TIdN<NodeIdentifier>>>
+        for TStore
+    {
+        const LANG: u16 = 0;
+
+        fn _intern(l: u16, t: u16) -> Self::Ty {
+            hyper_ast_gen_ts_rust::types::Type::resolve(t)
+        }
+
+        fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_rust::types::Type {
+            t
+        }
+    }
+
     #[derive(Clone, Copy, Debug)]
     pub enum MultiType {
         Java(hyper_ast_gen_ts_java::types::Type),
         Cpp(hyper_ast_gen_ts_cpp::types::Type),
         Xml(hyper_ast_gen_ts_xml::types::Type),
+        Make(hyper_ast_gen_ts_make::types::Type),
+        Ts(hyper_ast_gen_ts_ts::types::Type),
+        Python(hyper_ast_gen_ts_python::types::Type),
+        Rust(hyper_ast_gen_ts_rust::types::Type),
     }
 
     macro_rules! on_multi {
@@ -500,6 +753,10 @@ mod type_store {
                 MultiType::Java($with) => $body,
                 MultiType::Cpp($with) => $body,
                 MultiType::Xml($with) => $body,
+                MultiType::Make($with) => $body,
+                MultiType::Ts($with) => $body,
+                MultiType::Python($with) => $body,
+                MultiType::Rust($with) => $body,
             }
         };
         ($on1:ident, $on2:ident, ($with1:ident,$with2:ident) => $body:expr, _ => $default:expr) => {
@@ -507,6 +764,10 @@ mod type_store {
                 (MultiType::Java($with1), MultiType::Java($with2)) => $body,
                 (MultiType::Cpp($with1), MultiType::Cpp($with2)) => $body,
                 (MultiType::Xml($with1), MultiType::Xml($with2)) => $body,
+                (MultiType::Make($with1), MultiType::Make($with2)) => $body,
+                (MultiType::Ts($with1), MultiType::Ts($with2)) => $body,
+                (MultiType::Python($with1), MultiType::Python($with2)) => $body,
+                (MultiType::Rust($with1), MultiType::Rust($with2)) => $body,
                 _ => $default,
             }
         };
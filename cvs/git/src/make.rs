@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug},
     ops::AddAssign,
     path::PathBuf,
@@ -11,19 +12,19 @@ use hyper_ast::{
     tree_gen::SubTreeMetrics,
 };
 use hyper_ast_gen_ts_cpp::legion as cpp_tree_gen;
-use hyper_ast_gen_ts_xml::legion::XmlTreeGen;
+use hyper_ast_gen_ts_make::legion::MakeTreeGen;
 
 use crate::{
-    processing::ObjectName, Accumulator, DefaultMetrics, SimpleStores, TStore,
-    PROPAGATE_ERROR_ON_BAD_CST_NODE,
+    processing::{file_sys, InFiles, ObjectName},
+    Accumulator, DefaultMetrics, SimpleStores, TStore, PROPAGATE_ERROR_ON_BAD_CST_NODE,
 };
 
 pub(crate) fn handle_makefile_file<'a>(
-    tree_gen: &mut XmlTreeGen<'a, TStore>,
+    tree_gen: &mut MakeTreeGen<'a, TStore>,
     name: &ObjectName,
     text: &'a [u8],
 ) -> Result<MakeFile, ()> {
-    let tree = match XmlTreeGen::<TStore>::tree_sitter_parse(b"<proj></proj>") {
+    let tree = match MakeTreeGen::<TStore>::tree_sitter_parse(text) {
         Ok(tree) => tree,
         Err(tree) => {
             log::warn!("bad CST");
@@ -36,24 +37,335 @@ pub(crate) fn handle_makefile_file<'a>(
             }
         }
     };
+    let content = MakefileContent::extract(text, tree.root_node());
     let x = tree_gen
-        .generate_file(name.as_bytes(), b"<proj></proj>", tree.walk())
+        .generate_file(name.as_bytes(), text, tree.walk())
         .local;
-    // TODO extract submodules, dependencies and directories. maybe even more ie. artefact id, ...
     let x = MakeFile {
         compressed_node: x.compressed_node,
         metrics: x.metrics,
-        submodules: vec![],
-        source_dirs: vec![".".to_owned()],
-        test_source_dirs: vec!["../tests".to_owned()],
+        targets: content.targets,
+        submodules: content.submodules,
+        source_dirs: content.source_dirs,
+        test_source_dirs: content.test_source_dirs,
     };
     Ok(x)
 }
 
+/// What is extracted from a Makefile to structure its module.
+///
+/// Variables are expanded naively, assignments in every branch of conditionals are considered,
+/// and most functions are ignored, except the ones commonly used to list sources.
+#[derive(Debug, Default)]
+struct MakefileContent {
+    variables: BTreeMap<String, String>,
+    /// explicit targets, special (eg. `.PHONY`) and pattern targets are ignored
+    targets: Vec<String>,
+    /// from `SUBDIRS`-like variables and recursive invocations, eg. `$(MAKE) -C lib`
+    submodules: Vec<String>,
+    /// directories of the files listed in `SRCS`-like variables
+    source_dirs: Vec<String>,
+    /// same as [`MakefileContent::source_dirs`] but for variables mentioning tests
+    test_source_dirs: Vec<String>,
+}
+
+impl MakefileContent {
+    fn extract(text: &[u8], root: tree_sitter::Node) -> Self {
+        let mut content = Self::default();
+        let mut recipes = vec![];
+        let mut cursor = root.walk();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let node_text = || node.utf8_text(text).unwrap_or_default();
+            match node.kind() {
+                "variable_assignment" => content.assign(node_text()),
+                "rule" => {
+                    let Some((targets, _)) = split_rule(node_text()) else {
+                        continue;
+                    };
+                    let targets: Vec<_> = content
+                        .expand(targets, 0)
+                        .split_whitespace()
+                        .map(|x| x.to_string())
+                        .collect();
+                    let mut lines = vec![];
+                    let mut stack = vec![node];
+                    while let Some(n) = stack.pop() {
+                        if n.kind() == "recipe_line" {
+                            lines.push(n.utf8_text(text).unwrap_or_default().to_string());
+                        } else {
+                            stack.extend(n.children(&mut cursor));
+                        }
+                    }
+                    content.targets.extend(
+                        targets
+                            .iter()
+                            .filter(|x| !x.starts_with('.') && !x.contains(['%', '$']))
+                            .cloned(),
+                    );
+                    recipes.push((targets, lines));
+                }
+                // the body of a define is only text, and there is nothing to find in comments
+                "define_directive" | "comment" => (),
+                _ => {
+                    let children: Vec<_> = node.children(&mut cursor).collect();
+                    stack.extend(children.into_iter().rev());
+                }
+            }
+        }
+
+        let mut submodules = vec![];
+        let mut source_dirs = vec![];
+        let mut test_source_dirs = vec![];
+        for (name, value) in &content.variables {
+            let name = name.to_uppercase();
+            let value = content.expand(value, 0);
+            if name == "SUBDIRS" || name == "SUBDIR" || name == "DIRS" || name == "MODULES" {
+                submodules.extend(value.split_whitespace().map(|x| x.to_string()));
+            } else if name.contains("SRC") || name.contains("SOURCE") {
+                let dirs = value
+                    .split_whitespace()
+                    .filter(|x| file_sys::Cpp::matches(&ObjectName::from(x.as_bytes())))
+                    .filter_map(|x| x.rsplit_once('/').map(|(dir, _)| dir.to_string()));
+                if name.contains("TEST") {
+                    test_source_dirs.extend(dirs);
+                } else {
+                    source_dirs.extend(dirs);
+                }
+            }
+        }
+        for (targets, lines) in recipes {
+            for line in lines {
+                for dir in recursive_make_dirs(&line) {
+                    if dir == "$@" {
+                        submodules.extend(targets.iter().cloned());
+                    } else {
+                        let dir = content.expand(&dir, 0);
+                        submodules.extend(dir.split_whitespace().map(|x| x.to_string()));
+                    }
+                }
+            }
+        }
+        content.submodules = normalize_dirs(submodules);
+        content.source_dirs = normalize_dirs(source_dirs);
+        content.test_source_dirs = normalize_dirs(test_source_dirs);
+        content
+    }
+
+    fn assign(&mut self, assignment: &str) {
+        let assignment = assignment.replace("\\\n", " ");
+        let Some(i) = assignment.find('=') else {
+            return;
+        };
+        let (head, value) = (&assignment[..i], assignment[i + 1..].trim());
+        let (name, op) = if let Some(name) = head.strip_suffix("::") {
+            (name, "::=")
+        } else if let Some(name) = head.strip_suffix([':', '?', '+', '!']) {
+            (name, &head[name.len()..])
+        } else {
+            (head, "=")
+        };
+        let name = name.trim();
+        if name.is_empty() || name.contains([':', ' ', '$']) {
+            // target-specific or computed variable names are not handled
+            return;
+        }
+        match op {
+            "+" => {
+                let v = self.variables.entry(name.to_string()).or_default();
+                if !v.is_empty() {
+                    v.push(' ');
+                }
+                v.push_str(value);
+            }
+            "?" => {
+                self.variables
+                    .entry(name.to_string())
+                    .or_insert_with(|| value.to_string());
+            }
+            // the result of a shell command is unknown
+            "!" => (),
+            // simply expanded variables
+            ":" | "::=" => {
+                let value = self.expand(value, 0);
+                self.variables.insert(name.to_string(), value);
+            }
+            _ => {
+                self.variables.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Expands references to known variables,
+    /// automatic variables such as `$@` are left as is.
+    fn expand(&self, s: &str, depth: usize) -> String {
+        if depth > 8 {
+            return String::new();
+        }
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            let Some(c) = rest.chars().next() else {
+                out.push('$');
+                break;
+            };
+            let (inner, after) = match c {
+                '(' | '{' => {
+                    let close = if c == '(' { ')' } else { '}' };
+                    let mut level = 0;
+                    let Some(end) = rest.find(|x| {
+                        if x == c {
+                            level += 1;
+                        } else if x == close {
+                            level -= 1;
+                        }
+                        level == 0
+                    }) else {
+                        break;
+                    };
+                    (&rest[1..end], &rest[end + 1..])
+                }
+                '$' => {
+                    out.push('$');
+                    rest = &rest[1..];
+                    continue;
+                }
+                _ if "@<^*?%+|".contains(c) => {
+                    out.push('$');
+                    out.push(c);
+                    rest = &rest[c.len_utf8()..];
+                    continue;
+                }
+                _ => rest.split_at(c.len_utf8()),
+            };
+            rest = after;
+            out.push_str(&self.expand_reference(inner, depth));
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn expand_reference(&self, inner: &str, depth: usize) -> String {
+        if let Some((function, args)) = inner.split_once([' ', '\t']) {
+            let args = self.expand(args, depth + 1);
+            return match function {
+                "wildcard" | "sort" | "strip" => args,
+                "addprefix" => match args.split_once(',') {
+                    Some((prefix, names)) => names
+                        .split_whitespace()
+                        .map(|x| format!("{}{}", prefix.trim(), x))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    None => String::new(),
+                },
+                _ => String::new(),
+            };
+        }
+        // substitution reference, eg. `$(SRCS:.c=.o)`
+        let (name, substitution) = match inner.split_once(':') {
+            Some((name, subst)) => (name, subst.split_once('=')),
+            None => (inner, None),
+        };
+        let Some(value) = self.variables.get(name) else {
+            return String::new();
+        };
+        let value = self.expand(value, depth + 1);
+        match substitution {
+            Some((from, to)) => value
+                .split_whitespace()
+                .map(|x| match x.strip_suffix(from) {
+                    Some(x) => format!("{}{}", x, to),
+                    None => x.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => value,
+        }
+    }
+}
+
+/// Splits the head of a rule on the first colon outside of variable references.
+fn split_rule(rule: &str) -> Option<(&str, &str)> {
+    let mut level = 0;
+    let i = rule.find(|c| {
+        match c {
+            '(' | '{' => level += 1,
+            ')' | '}' => level -= 1,
+            _ => (),
+        }
+        level == 0 && c == ':'
+    })?;
+    Some((&rule[..i], &rule[i + 1..]))
+}
+
+/// Finds the directories of recursive invocations of make in a recipe line,
+/// ie. `$(MAKE) -C dir`, `make -Cdir` or `cd dir && $(MAKE)`.
+fn recursive_make_dirs(line: &str) -> Vec<String> {
+    let mut dirs = vec![];
+    for command in line.split(['&', ';', '|']) {
+        let mut words: Vec<_> = command.split_whitespace().collect();
+        if let Some(first) = words.first_mut() {
+            // prefixes of recipe lines
+            *first = first.trim_start_matches(['@', '-', '+']);
+        }
+        let Some(make) = words
+            .iter()
+            .position(|x| *x == "make" || *x == "$(MAKE)" || *x == "${MAKE}")
+        else {
+            if let ["cd", dir, ..] = words.as_slice() {
+                if line.contains("$(MAKE)") || line.contains("${MAKE}") || line.contains("make ") {
+                    dirs.push(dir.to_string());
+                }
+            }
+            continue;
+        };
+        let mut args = words[make + 1..].iter();
+        while let Some(arg) = args.next() {
+            if *arg == "-C" || *arg == "--directory" {
+                dirs.extend(args.next().map(|x| x.to_string()));
+            } else if let Some(dir) = arg
+                .strip_prefix("--directory=")
+                .or_else(|| arg.strip_prefix("-C"))
+            {
+                dirs.push(dir.to_string());
+            }
+        }
+    }
+    dirs
+}
+
+/// Keeps relative paths to sub-directories, without duplicates.
+fn normalize_dirs(dirs: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for dir in dirs {
+        let dir = dir.trim_start_matches("./").trim_end_matches('/');
+        // only keep the literal prefix of globs
+        let dir = match dir.find(['*', '?', '[']) {
+            Some(i) => dir[..i].rsplit_once('/').map_or("", |(dir, _)| dir),
+            None => dir,
+        };
+        if dir.is_empty()
+            || dir == "."
+            || dir.starts_with('/')
+            || dir.contains(['$', '(', ')'])
+            || dir.split('/').any(|x| x == "..")
+        {
+            continue;
+        }
+        if !result.iter().any(|x| x == dir) {
+            result.push(dir.to_string());
+        }
+    }
+    result
+}
+
 #[derive(Debug, Clone)]
 pub struct MakeFile {
     pub compressed_node: NodeIdentifier,
     pub metrics: DefaultMetrics,
+    targets: Vec<String>,
     submodules: Vec<String>,
     source_dirs: Vec<String>,
     test_source_dirs: Vec<String>,
@@ -143,15 +455,14 @@ impl MakeModuleAcc {
     pub(crate) fn push_makefile(&mut self, name: LabelIdentifier, full_node: MakeFile) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
-        self.main_dirs = Some(full_node.source_dirs.iter().map(|x| x.into()).collect());
-        self.test_dirs = Some(
-            full_node
-                .test_source_dirs
-                .iter()
-                .map(|x| x.into())
-                .collect(),
-        );
-        self.sub_modules = Some(full_node.submodules.iter().map(|x| x.into()).collect());
+        // without any information the directories are explored as before
+        let dirs = |dirs: &[String]| -> Option<Vec<PathBuf>> {
+            (!dirs.is_empty()).then(|| dirs.iter().map(|x| x.into()).collect())
+        };
+        self.main_dirs = dirs(&full_node.source_dirs);
+        self.test_dirs = dirs(&full_node.test_source_dirs);
+        self.sub_modules = dirs(&full_node.submodules);
+        self.ana.targets.extend(full_node.targets);
         self.metrics.acc(full_node.metrics);
         // TODO
         // full_node.2.acc(&Type::Directory, &mut self.ana);
@@ -214,6 +525,7 @@ impl MakeModuleAcc {
 
 #[derive(Debug, Clone)]
 pub(crate) struct MakePartialAnalysis {
+    targets: Vec<String>,
     submodules: Vec<()>,
     main_dirs: Vec<()>,
     test_dirs: Vec<()>,
@@ -223,6 +535,7 @@ impl MakePartialAnalysis {
     pub(crate) fn new() -> Self {
         // TODO
         Self {
+            targets: vec![],
             submodules: vec![],
             main_dirs: vec![],
             test_dirs: vec![],
@@ -230,6 +543,7 @@ impl MakePartialAnalysis {
     }
     pub(crate) fn resolve(&self) -> Self {
         Self {
+            targets: self.targets.clone(),
             submodules: self.submodules.clone(),
             main_dirs: self.main_dirs.clone(),
             test_dirs: self.test_dirs.clone(),
@@ -403,3 +717,36 @@ impl Accumulator for MakeModuleAcc {
     //     // full_node.2.acc(&Type::Directory, &mut self.ana);
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn makefile_content() {
+        let text = br#"CC = gcc
+SUBDIRS = lib tools
+SRCS = src/main.c src/util.c
+TEST_SRCS := $(wildcard tests/*.c)
+
+all: app $(SUBDIRS)
+
+app: $(SRCS:.c=.o)
+	$(CC) -o $@ $^
+
+$(SUBDIRS):
+	$(MAKE) -C $@
+
+docs:
+	cd doc && $(MAKE) html
+
+.PHONY: all docs $(SUBDIRS)
+"#;
+        let tree = MakeTreeGen::<TStore>::tree_sitter_parse(text).unwrap();
+        let content = MakefileContent::extract(text, tree.root_node());
+        assert_eq!(content.targets, ["all", "app", "lib", "tools", "docs"]);
+        assert_eq!(content.submodules, ["lib", "tools", "doc"]);
+        assert_eq!(content.source_dirs, ["src"]);
+        assert_eq!(content.test_source_dirs, ["tests"]);
+    }
+}
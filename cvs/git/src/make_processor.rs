@@ -106,13 +106,15 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<MakeModuleAcc>
                     w.push_submodule(name, full_node);
                     return;
                 }
-                // TODO use Make pom.xml to find source_dir  and tests_dir ie. ignore resources, maybe also tests
                 // TODO maybe at some point try to handle Make modules and source dirs that reference parent directory in their path
                 log::debug!("mm tree {:?}", name.try_str());
 
                 let parent_acc = &mut self.stack.last_mut().unwrap().2;
-                if true {
-                    // TODO also try to handle nested Makefiles
+                if parent_acc.sub_modules.is_none()
+                    && parent_acc.main_dirs.is_none()
+                    && parent_acc.test_dirs.is_none()
+                {
+                    // nothing was found in a Makefile, handle everything as C/C++ sources
                     let (name, (full_node, _)) = self.prepro.help_handle_cpp_folder(
                         &self.repository,
                         &mut self.dir_path,
@@ -312,7 +314,7 @@ pub(crate) fn make(mut acc: MakeModuleAcc, stores: &mut SimpleStores) -> (NodeId
     full_node
 }
 
-use hyper_ast_gen_ts_xml::legion::XmlTreeGen;
+use hyper_ast_gen_ts_make::legion::MakeTreeGen;
 impl RepositoryProcessor {
     fn help_handle_makefile(
         &mut self,
@@ -327,7 +329,7 @@ impl RepositoryProcessor {
             .caching_blob_handler::<crate::processing::file_sys::MakeFile>()
             .handle(oid, repository, &name, parameters, |c, n, t| {
                 crate::make::handle_makefile_file(
-                    &mut XmlTreeGen {
+                    &mut MakeTreeGen {
                         line_break: "\n".as_bytes().to_vec(),
                         stores: &mut self.main_stores,
                     },
@@ -41,13 +41,11 @@ pub(crate) fn handle_pom_file<'a>(
     let x = tree_gen
         .generate_file(name.as_bytes(), text, tree.walk())
         .local;
-    // TODO extract submodules, dependencies and directories. maybe even more ie. artefact id, ...
+    let info = MavenModuleInfo::read(&*tree_gen.stores, x.compressed_node);
     let x = POM {
         compressed_node: x.compressed_node,
         metrics: x.metrics,
-        submodules: vec![],
-        source_dirs: vec!["src/main/java".to_owned()],
-        test_source_dirs: vec!["src/test/java".to_owned()],
+        info,
     };
     Ok(x)
 }
@@ -56,9 +54,363 @@ pub(crate) fn handle_pom_file<'a>(
 pub struct POM {
     pub compressed_node: NodeIdentifier,
     pub metrics: DefaultMetrics,
-    submodules: Vec<String>,
-    source_dirs: Vec<String>,
-    test_source_dirs: Vec<String>,
+    /// as declared, POMs are cached by blob so inheritance happens in [`MavenModuleAcc::push_pom`]
+    info: MavenModuleInfo,
+}
+
+/// Identifies a maven artifact, missing parts are inherited from the parent POM.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MavenCoordinates {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MavenDependency {
+    pub coordinates: MavenCoordinates,
+    /// `compile` when not specified
+    pub scope: Option<String>,
+}
+
+/// What is read from the pom.xml of a maven module.
+///
+/// It is first read as declared, then completed by its parent with [`MavenModuleInfo::inherit`]
+/// and finally [`MavenModuleInfo::interpolated`], which is what is stored as a component of the maven module node.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MavenModuleInfo {
+    pub coordinates: MavenCoordinates,
+    pub parent: Option<MavenCoordinates>,
+    /// paths of the declared submodules, relative to the module
+    pub modules: Vec<String>,
+    pub dependencies: Vec<MavenDependency>,
+    /// from `dependencyManagement`, completes the versions and scopes of `dependencies`
+    pub managed_dependencies: Vec<MavenDependency>,
+    /// relative to the module, `src/main/java` by default
+    pub source_directory: String,
+    /// relative to the module, `src/test/java` by default
+    pub test_source_directory: String,
+    pub properties: Vec<(String, String)>,
+}
+
+impl Default for MavenModuleInfo {
+    fn default() -> Self {
+        Self {
+            coordinates: Default::default(),
+            parent: None,
+            modules: vec![],
+            dependencies: vec![],
+            managed_dependencies: vec![],
+            source_directory: Self::DEFAULT_SOURCE_DIRECTORY.to_string(),
+            test_source_directory: Self::DEFAULT_TEST_SOURCE_DIRECTORY.to_string(),
+            properties: vec![],
+        }
+    }
+}
+
+impl MavenModuleInfo {
+    pub const DEFAULT_SOURCE_DIRECTORY: &'static str = "src/main/java";
+    pub const DEFAULT_TEST_SOURCE_DIRECTORY: &'static str = "src/test/java";
+
+    /// Reads the `project` element of the pom.xml at `pom`, without inheriting nor interpolating.
+    pub(crate) fn read(stores: &SimpleStores, pom: NodeIdentifier) -> Self {
+        let reader = PomReader { stores };
+        let project = reader
+            .elements(pom)
+            .find(|(name, _)| name == "project")
+            .map(|(_, id)| id);
+        let mut info = Self::default();
+        let Some(project) = project else {
+            return info;
+        };
+        info.coordinates = reader.coordinates(project);
+        info.parent = reader
+            .child(project, "parent")
+            .map(|x| reader.coordinates(x));
+        if let Some(properties) = reader.child(project, "properties") {
+            info.properties = reader
+                .elements(properties)
+                .map(|(name, id)| (name, reader.text(id)))
+                .collect();
+        }
+        if let Some(modules) = reader.child(project, "modules") {
+            info.modules = reader
+                .elements(modules)
+                .filter(|(name, _)| name == "module")
+                .map(|(_, id)| reader.text(id))
+                .collect();
+        }
+        let dependencies = |parent| {
+            reader
+                .child(parent, "dependencies")
+                .into_iter()
+                .flat_map(|x| reader.elements(x).collect::<Vec<_>>())
+                .filter(|(name, _)| name == "dependency")
+                .map(|(_, id)| MavenDependency {
+                    coordinates: reader.coordinates(id),
+                    scope: reader.child(id, "scope").map(|x| reader.text(x)),
+                })
+                .collect()
+        };
+        info.dependencies = dependencies(project);
+        if let Some(management) = reader.child(project, "dependencyManagement") {
+            info.managed_dependencies = dependencies(management);
+        }
+        if let Some(build) = reader.child(project, "build") {
+            let dir = |name| reader.child(build, name).map(|x| reader.text(x));
+            if let Some(dir) = dir("sourceDirectory") {
+                info.source_directory = dir;
+            }
+            if let Some(dir) = dir("testSourceDirectory") {
+                info.test_source_directory = dir;
+            }
+        }
+        info
+    }
+
+    /// Completes what is declared in this module with what is declared in its `parent` element
+    /// and in the enclosing `parent` module if any,
+    /// ie. missing coordinates, properties, dependencies and managed dependencies.
+    ///
+    /// Values are not interpolated yet, as placeholders inherited from the parent
+    /// refer to the properties and coordinates of this module.
+    pub(crate) fn inherit(&mut self, parent: Option<&MavenModuleInfo>) {
+        let Some(declared) = &self.parent else {
+            return;
+        };
+        // the groupId and version of a module are inherited from its parent
+        if self.coordinates.group_id.is_none() {
+            self.coordinates.group_id = declared
+                .group_id
+                .clone()
+                .or_else(|| parent?.coordinates.group_id.clone());
+        }
+        if self.coordinates.version.is_none() {
+            self.coordinates.version = declared
+                .version
+                .clone()
+                .or_else(|| parent?.coordinates.version.clone());
+        }
+        let Some(parent) = parent else {
+            return;
+        };
+        for (k, v) in &parent.properties {
+            if !self.properties.iter().any(|(x, _)| x == k) {
+                self.properties.push((k.clone(), v.clone()));
+            }
+        }
+        let merge = |deps: &mut Vec<MavenDependency>, inherited: &[MavenDependency]| {
+            for d in inherited {
+                if !deps.iter().any(|x| {
+                    x.coordinates.group_id == d.coordinates.group_id
+                        && x.coordinates.artifact_id == d.coordinates.artifact_id
+                }) {
+                    deps.push(d.clone());
+                }
+            }
+        };
+        merge(&mut self.dependencies, &parent.dependencies);
+        merge(&mut self.managed_dependencies, &parent.managed_dependencies);
+    }
+
+    /// Replaces the placeholders of everything but the properties,
+    /// normalizes the paths and completes dependencies with the managed ones.
+    pub(crate) fn interpolated(&self) -> Self {
+        let dir = |dir: &str, default: &str| {
+            normalize_module_path(&self.interpolate(dir)).unwrap_or_else(|| default.to_string())
+        };
+        let dependency = |x: &MavenDependency| MavenDependency {
+            coordinates: self.interpolate_coordinates(&x.coordinates),
+            scope: x.scope.as_ref().map(|x| self.interpolate(x)),
+        };
+        let managed_dependencies: Vec<_> =
+            self.managed_dependencies.iter().map(dependency).collect();
+        let dependencies = self
+            .dependencies
+            .iter()
+            .map(dependency)
+            .map(|mut x| {
+                let managed = managed_dependencies.iter().find(|m| {
+                    m.coordinates.group_id == x.coordinates.group_id
+                        && m.coordinates.artifact_id == x.coordinates.artifact_id
+                });
+                if let Some(managed) = managed {
+                    if x.coordinates.version.is_none() {
+                        x.coordinates.version = managed.coordinates.version.clone();
+                    }
+                    if x.scope.is_none() {
+                        x.scope = managed.scope.clone();
+                    }
+                }
+                x
+            })
+            .collect();
+        Self {
+            coordinates: self.interpolate_coordinates(&self.coordinates),
+            parent: self
+                .parent
+                .as_ref()
+                .map(|x| self.interpolate_coordinates(x)),
+            modules: self
+                .modules
+                .iter()
+                .filter_map(|x| normalize_module_path(&self.interpolate(x)))
+                .collect(),
+            dependencies,
+            managed_dependencies,
+            source_directory: dir(&self.source_directory, Self::DEFAULT_SOURCE_DIRECTORY),
+            test_source_directory: dir(
+                &self.test_source_directory,
+                Self::DEFAULT_TEST_SOURCE_DIRECTORY,
+            ),
+            properties: self.properties.clone(),
+        }
+    }
+
+    /// Replaces the `${...}` placeholders of `s` using the properties and coordinates of the module,
+    /// unknown placeholders are left as is.
+    pub fn interpolate(&self, s: &str) -> String {
+        // properties can refer to other properties, the depth bounds cycles
+        self.interpolate_bounded(s, 8)
+    }
+
+    fn interpolate_bounded(&self, s: &str, depth: usize) -> String {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find("${") {
+            out.push_str(&rest[..i]);
+            let Some(end) = rest[i..].find('}') else {
+                rest = &rest[i..];
+                break;
+            };
+            let key = &rest[i + 2..i + end];
+            let value = match key {
+                "project.groupId" | "pom.groupId" => self.coordinates.group_id.clone(),
+                "project.artifactId" | "pom.artifactId" => self.coordinates.artifact_id.clone(),
+                "project.version" | "pom.version" => self.coordinates.version.clone(),
+                "project.parent.version" => self.parent.as_ref().and_then(|x| x.version.clone()),
+                "project.basedir" | "basedir" => Some(".".to_string()),
+                key => self
+                    .properties
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| {
+                        if depth == 0 {
+                            v.clone()
+                        } else {
+                            self.interpolate_bounded(v, depth - 1)
+                        }
+                    }),
+            };
+            match value {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&rest[i..i + end + 1]),
+            }
+            rest = &rest[i + end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn interpolate_coordinates(&self, coordinates: &MavenCoordinates) -> MavenCoordinates {
+        let f = |x: &Option<String>| x.as_ref().map(|x| self.interpolate(x));
+        MavenCoordinates {
+            group_id: f(&coordinates.group_id),
+            artifact_id: f(&coordinates.artifact_id),
+            version: f(&coordinates.version),
+        }
+    }
+}
+
+/// Only keeps paths inside the module, see the `todo!()` in [`crate::maven_processor::make`].
+pub(crate) fn normalize_module_path(path: &str) -> Option<String> {
+    let path = path.trim().trim_start_matches("./").trim_end_matches('/');
+    if path.is_empty() || path.starts_with('/') || path.contains("${") {
+        return None;
+    }
+    if path.split('/').any(|x| x == "..") {
+        log::warn!("paths outside of a maven module are not handled: {}", path);
+        return None;
+    }
+    Some(path.to_string())
+}
+
+/// Navigates the elements of an XML HyperAST.
+struct PomReader<'a> {
+    stores: &'a SimpleStores,
+}
+
+impl<'a> PomReader<'a> {
+    fn resolve(&self, id: NodeIdentifier) -> Option<XmlNode<'a>> {
+        self.stores
+            .node_store
+            .try_resolve_typed::<XmlIdN>(&id)
+            .map(|x| x.0)
+    }
+
+    fn children(&self, id: NodeIdentifier) -> Vec<NodeIdentifier> {
+        self.resolve(id)
+            .and_then(|b| b.children().map(|cs| cs.iter_children().cloned().collect()))
+            .unwrap_or_default()
+    }
+
+    fn label(&self, id: NodeIdentifier) -> Option<&'a str> {
+        let b = self.resolve(id)?;
+        b.has_label()
+            .then(|| self.stores.label_store.resolve(b.get_label_unchecked()))
+    }
+
+    /// The name of an element is the Name of its start tag.
+    fn element_name(&self, element: NodeIdentifier) -> Option<String> {
+        let tag = self.children(element).into_iter().find(|x| {
+            self.resolve(*x).map_or(false, |b| {
+                b.get_type() == Type::STag || b.get_type() == Type::EmptyElemTag
+            })
+        })?;
+        let name = self.children(tag).into_iter().find(|x| {
+            self.resolve(*x)
+                .map_or(false, |b| b.get_type() == Type::Name)
+        })?;
+        self.label(name).map(|x| x.to_string())
+    }
+
+    /// Child elements of `id` with their names.
+    fn elements(&self, id: NodeIdentifier) -> impl Iterator<Item = (String, NodeIdentifier)> + '_ {
+        self.children(id).into_iter().filter_map(|x| {
+            let b = self.resolve(x)?;
+            if b.get_type() != Type::Element {
+                return None;
+            }
+            Some((self.element_name(x)?, x))
+        })
+    }
+
+    fn child(&self, id: NodeIdentifier, name: &str) -> Option<NodeIdentifier> {
+        self.elements(id).find(|(n, _)| n == name).map(|(_, x)| x)
+    }
+
+    /// Concatenation of the character data directly in `element`.
+    fn text(&self, element: NodeIdentifier) -> String {
+        let mut text = String::new();
+        for x in self.children(element) {
+            if self
+                .resolve(x)
+                .map_or(false, |b| b.get_type() == Type::CharData)
+            {
+                text.push_str(self.label(x).unwrap_or_default());
+            }
+        }
+        text.trim().to_string()
+    }
+
+    fn coordinates(&self, element: NodeIdentifier) -> MavenCoordinates {
+        let text = |name| self.child(element, name).map(|x| self.text(x));
+        MavenCoordinates {
+            group_id: text("groupId"),
+            artifact_id: text("artifactId"),
+            version: text("version"),
+        }
+    }
 }
 
 pub struct IterMavenModules2<'a> {
@@ -201,6 +553,19 @@ pub struct MD {
     pub(crate) status: EnumSet<SemFlags>,
 }
 
+/// What a directory inherits from its enclosing modules.
+/// The same tree is built differently depending on it, so it is part of the cache key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ModuleCtx {
+    /// designated as a module by its parent, eg. by the modules of a pom.xml or a settings script
+    pub(crate) is_module: bool,
+    /// the submodules and source directories declared by enclosing modules, relative to the directory
+    pub(crate) sub_modules: Vec<PathBuf>,
+    pub(crate) main_dirs: Vec<PathBuf>,
+    pub(crate) test_dirs: Vec<PathBuf>,
+    pub(crate) inherited: Option<MavenModuleInfo>,
+}
+
 pub struct MavenModuleAcc {
     pub(crate) name: String,
     pub(crate) children_names: Vec<LabelIdentifier>,
@@ -211,6 +576,12 @@ pub struct MavenModuleAcc {
     pub(crate) main_dirs: Option<Vec<PathBuf>>,
     pub(crate) test_dirs: Option<Vec<PathBuf>>,
     pub(crate) status: EnumSet<SemFlags>,
+    /// what was read from the pom.xml of this module, completed by its parent but not interpolated
+    pub(crate) info: Option<MavenModuleInfo>,
+    /// what was read from the pom.xml of the closest enclosing module, used for inheritance
+    pub(crate) inherited: Option<MavenModuleInfo>,
+    /// what the directory inherited when it was created, see [`ModuleCtx`]
+    pub(crate) ctx: ModuleCtx,
 }
 
 impl From<String> for MavenModuleAcc {
@@ -226,6 +597,9 @@ impl From<String> for MavenModuleAcc {
             main_dirs: None,
             test_dirs: None,
             status: Default::default(),
+            info: None,
+            inherited: None,
+            ctx: Default::default(),
         }
     }
 }
@@ -243,6 +617,9 @@ impl MavenModuleAcc {
             main_dirs: None,
             test_dirs: None,
             status: Default::default(),
+            info: None,
+            inherited: None,
+            ctx: Default::default(),
         }
     }
     pub(crate) fn with_content(
@@ -274,6 +651,9 @@ impl MavenModuleAcc {
                 Some(test_dirs)
             },
             status: Default::default(),
+            info: None,
+            inherited: None,
+            ctx: Default::default(),
         }
     }
 }
@@ -292,15 +672,14 @@ impl MavenModuleAcc {
         assert!(!self.children_names.contains(&name));
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
-        self.main_dirs = Some(full_node.source_dirs.iter().map(|x| x.into()).collect());
-        self.test_dirs = Some(
-            full_node
-                .test_source_dirs
-                .iter()
-                .map(|x| x.into())
-                .collect(),
-        );
-        self.sub_modules = Some(full_node.submodules.iter().map(|x| x.into()).collect());
+        let mut info = full_node.info;
+        info.inherit(self.inherited.as_ref());
+        let resolved = info.interpolated();
+        self.main_dirs = Some(vec![resolved.source_directory.into()]);
+        self.test_dirs = Some(vec![resolved.test_source_directory.into()]);
+        self.sub_modules = Some(resolved.modules.iter().map(|x| x.into()).collect());
+        // kept as declared to be inherited by submodules
+        self.info = Some(info);
         self.metrics.acc(full_node.metrics);
         // TODO
         // full_node.2.acc(&Type::Directory, &mut self.ana);
@@ -497,9 +876,17 @@ impl<'a, T: TreePath<NodeIdentifier>> IterMavenModules<'a, T> {
             false
         };
 
-        is_src || t != Type::MavenDirectory
+        // directories known to not contain any maven module
+        let no_module = b.get_component::<EnumSet<SemFlags>>().map_or(false, |s| {
+            !s.contains(SemFlags::IsMavenModule) && !s.contains(SemFlags::HoldMavenSubModule)
+        });
+
+        is_src || no_module || t != Type::MavenDirectory
     }
     fn is_matching(&self, b: &XmlNode<'a>) -> bool {
+        if b.get_component::<MavenModuleInfo>().is_ok() {
+            return true;
+        }
         let contains_pom = b
             .children()
             .unwrap()
@@ -565,3 +952,119 @@ impl Accumulator for MavenModuleAcc {
     //     // full_node.2.acc(&Type::Directory, &mut self.ana);
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pom_info() {
+        let parent = br#"<?xml version="1.0" encoding="UTF-8"?>
+<project>
+  <groupId>org.example</groupId>
+  <artifactId>parent</artifactId>
+  <version>1.0</version>
+  <properties>
+    <junit.version>4.13</junit.version>
+  </properties>
+  <modules>
+    <module>core</module>
+    <module>./app/</module>
+    <module>../outside</module>
+  </modules>
+  <dependencies>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>${junit.version}</version>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.slf4j</groupId>
+        <artifactId>slf4j-api</artifactId>
+        <version>2.0.7</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+</project>
+"#;
+        let child = br#"<?xml version="1.0" encoding="UTF-8"?>
+<project>
+  <parent>
+    <groupId>org.example</groupId>
+    <artifactId>parent</artifactId>
+    <version>1.0</version>
+  </parent>
+  <artifactId>core</artifactId>
+  <properties>
+    <junit.version>4.12</junit.version>
+  </properties>
+  <dependencies>
+    <dependency>
+      <groupId>${project.groupId}</groupId>
+      <artifactId>util</artifactId>
+      <version>${project.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+    </dependency>
+  </dependencies>
+  <build>
+    <sourceDirectory>${project.basedir}/src/java</sourceDirectory>
+  </build>
+</project>
+"#;
+        let mut stores = SimpleStores::default();
+        let mut tree_gen = XmlTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+        };
+        let name = ObjectName::from(&b"pom.xml"[..]);
+        let mut parent = handle_pom_file(&mut tree_gen, &name, parent).unwrap().info;
+        parent.inherit(None);
+        let resolved = parent.interpolated();
+        assert_eq!(resolved.coordinates.artifact_id.as_deref(), Some("parent"));
+        assert_eq!(resolved.modules, ["core", "app"]);
+        assert_eq!(
+            resolved.dependencies[0].coordinates.version.as_deref(),
+            Some("4.13")
+        );
+        assert_eq!(resolved.dependencies[0].scope.as_deref(), Some("test"));
+        assert_eq!(
+            resolved.source_directory,
+            MavenModuleInfo::DEFAULT_SOURCE_DIRECTORY
+        );
+
+        let mut child = handle_pom_file(&mut tree_gen, &name, child).unwrap().info;
+        // placeholders are only replaced once inherited
+        assert_eq!(child.source_directory, "${project.basedir}/src/java");
+        assert_eq!(child.coordinates.group_id, None);
+        child.inherit(Some(&parent));
+        let child = child.interpolated();
+        assert_eq!(child.coordinates.group_id.as_deref(), Some("org.example"));
+        assert_eq!(child.source_directory, "src/java");
+        assert_eq!(
+            child.dependencies[0].coordinates,
+            MavenCoordinates {
+                group_id: Some("org.example".into()),
+                artifact_id: Some("util".into()),
+                version: Some("1.0".into()),
+            }
+        );
+        // managed by the parent
+        assert_eq!(
+            child.dependencies[1].coordinates.version.as_deref(),
+            Some("2.0.7")
+        );
+        // inherited from the parent, with the property of the child
+        assert_eq!(child.dependencies.len(), 3);
+        assert_eq!(
+            child.dependencies[2].coordinates.version.as_deref(),
+            Some("4.12")
+        );
+    }
+}
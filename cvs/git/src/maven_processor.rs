@@ -5,14 +5,20 @@ use std::{
 };
 
 use git2::{Oid, Repository};
-use hyper_ast::{store::defaults::NodeIdentifier, types::LabelStore, tree_gen::Accumulator};
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::Accumulator,
+    types::LabelStore,
+};
 use hyper_ast_gen_ts_xml::types::Type;
 
 use crate::{
     git::{BasicGitObject, NamedObject, ObjectType, TypedObject},
-    maven::{MavenModuleAcc, MD},
+    maven::{MavenModuleAcc, MavenModuleInfo, ModuleCtx, SemFlags, MD},
     preprocessed::RepositoryProcessor,
-    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    processing::{
+        caches::DirMap, erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName,
+    },
     Processor, SimpleStores,
 };
 
@@ -87,35 +93,7 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<MavenModuleAcc>
         }
     }
     fn post(&mut self, oid: Oid, acc: MavenModuleAcc) -> Option<(NodeIdentifier, MD)> {
-        let name = acc.name.clone();
-        let full_node = Self::make(acc, self.prepro.main_stores_mut());
-        self.prepro
-            .processing_systems
-            .mut_or_default::<MavenProcessorHolder>()
-            .get_caches_mut()
-            .object_map
-            .insert(oid, full_node.clone());
-        if name == "pac4j-kerberos" {
-            dbg!(full_node.1.status);
-        }
-        let name = self.prepro.intern_label(&name);
-        if self.stack.is_empty() {
-            Some(full_node)
-        } else {
-            let w = &mut self.stack.last_mut().unwrap().2;
-            assert!(
-                !w.children_names.contains(&name),
-                "{:?} {:?}",
-                w.children_names,
-                name
-            );
-            if full_node.1.status.contains(crate::maven::SemFlags::IsMavenModule) {
-                w.push_submodule(name, full_node);
-            } else {
-                w.push((name, full_node));
-            }
-            None
-        }
+        Self::WALK.post(self.prepro, &mut self.stack, oid, acc)
     }
 
     fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)> {
@@ -126,70 +104,89 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<MavenModuleAcc>
 impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
     MavenProcessor<'a, 'b, 'c, RMS, FFWD, MavenModuleAcc>
 {
-    fn make(acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
-        make(acc, stores)
-    }
+    const WALK: ModuleWalk = ModuleWalk {
+        prepare: prepare_dir_exploration,
+        object_map: |prepro| {
+            &mut prepro
+                .processing_systems
+                .mut_or_default::<MavenProcessorHolder>()
+                .get_caches_mut()
+                .object_map
+        },
+        init_module: |_| (),
+        rms: RMS,
+        ffwd: FFWD,
+    };
 
     fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
-        if let Some(s) = self.dir_path.peek() {
+        Self::WALK.handle_tree(
+            self.prepro,
+            self.repository,
+            self.dir_path,
+            &mut self.stack,
+            name,
+            oid,
+        )
+    }
+}
+
+/// The directory walking shared by the processors building maven modules,
+/// ie. the [`MavenProcessor`] and the [`crate::gradle_processor::GradleProcessor`].
+///
+/// They only differ by their build files and by where they cache the directories they built.
+pub(crate) struct ModuleWalk {
+    /// lists the children of a directory, build files first
+    pub(crate) prepare: fn(git2::Tree, &mut Peekable<Components>) -> Vec<BasicGitObject>,
+    /// the directories already built, by tree oid and inherited context
+    pub(crate) object_map:
+        fn(&mut RepositoryProcessor) -> &mut DirMap<ModuleCtx, (NodeIdentifier, MD)>,
+    /// completes the accumulator of a directory designated as a module by its parent
+    pub(crate) init_module: fn(&mut MavenModuleAcc),
+    /// Resursive Module Search
+    pub(crate) rms: bool,
+    /// Fast ForWarD to java directories without looking at maven stuff
+    pub(crate) ffwd: bool,
+}
+
+impl ModuleWalk {
+    pub(crate) fn handle_tree(
+        &self,
+        prepro: &mut RepositoryProcessor,
+        repository: &Repository,
+        dir_path: &mut Peekable<Components>,
+        stack: &mut Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)>,
+        name: ObjectName,
+        oid: Oid,
+    ) {
+        if let Some(s) = dir_path.peek() {
             if name
                 .as_bytes()
                 .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
             {
-                self.dir_path.next();
-                self.stack.last_mut().expect("never empty").1.clear();
-                let tree = self.repository.find_tree(oid).unwrap();
-                let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
-                self.stack
-                    .push((oid, prepared, MavenModuleAcc::new(name.try_into().unwrap())));
-                return;
-            } else {
-                return;
-            }
-        }
-        if let Some(already) = self
-            .prepro
-            .processing_systems
-            .mut_or_default::<MavenProcessorHolder>()
-            .get_caches_mut()
-            .object_map
-            .get(&oid)
-        {
-            // reinit already computed node for post order
-            let full_node = already.clone();
-            if name.try_str().unwrap() == "pac4j-kerberos" {
-                dbg!(full_node.1.status);
-            }
-            let w = &mut self.stack.last_mut().unwrap().2;
-            let name = self.prepro.intern_object_name(&name);
-            assert!(!w.children_names.contains(&name));
-            if full_node.1.status.contains(crate::maven::SemFlags::IsMavenModule) {
-                w.push_submodule(name, full_node);
-            } else {
-                w.push((name, full_node));
+                dir_path.next();
+                stack.last_mut().expect("never empty").1.clear();
+                let tree = repository.find_tree(oid).unwrap();
+                let prepared = (self.prepare)(tree, dir_path);
+                stack.push((oid, prepared, MavenModuleAcc::new(name.try_into().unwrap())));
             }
             return;
         }
         log::debug!("mm tree {:?}", name.try_str());
-        let parent_acc = &mut self.stack.last_mut().unwrap().2;
-        if FFWD {
-            let (name, (full_node, _)) = self.prepro.help_handle_java_folder(
-                &self.repository,
-                &mut self.dir_path,
-                oid,
-                &name,
-            );
+        if self.ffwd {
+            let (name, (full_node, _)) =
+                prepro.help_handle_java_folder(repository, dir_path, oid, &name);
+            let parent_acc = &mut stack.last_mut().unwrap().2;
             assert!(!parent_acc.children_names.contains(&name));
             parent_acc.push_source_directory(name, full_node);
             return;
         }
+        let parent_acc = &mut stack.last_mut().unwrap().2;
         let helper = MavenModuleHelper::from((parent_acc, &name));
         if helper.source_directories.0 || helper.test_source_directories.0 {
             // handle as source dir
             let (name, (full_node, _)) =
-                self.prepro
-                    .help_handle_java_folder(&self.repository, self.dir_path, oid, &name);
-            let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                prepro.help_handle_java_folder(repository, dir_path, oid, &name);
+            let parent_acc = &mut stack.last_mut().unwrap().2;
             assert!(!parent_acc.children_names.contains(&name));
             if helper.source_directories.0 {
                 parent_acc.push_source_directory(name, full_node);
@@ -197,40 +194,80 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
                 // test_source_folders.0
                 parent_acc.push_test_source_directory(name, full_node);
             }
+            return;
+        }
+        let key = (oid, helper.ctx());
+        if let Some(already) = (self.object_map)(prepro).get(&key) {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let name = prepro.intern_object_name(&name);
+            push_built(&mut stack.last_mut().unwrap().2, name, full_node);
+            return;
         }
         // check if module or src/main/java or src/test/java
-        // TODO use maven pom.xml to find source_dir  and tests_dir ie. ignore resources, maybe also tests
         // TODO maybe at some point try to handle maven modules and source dirs that reference parent directory in their path
 
         // TODO check it we can use more info from context and prepare analysis more specifically
-        if helper.submodules.0
+        let is_module = helper.submodules.0;
+        if is_module
             || !helper.submodules.1.is_empty()
             || !helper.source_directories.1.is_empty()
             || !helper.test_source_directories.1.is_empty()
+            || self.rms
         {
-            let tree = self.repository.find_tree(oid).unwrap();
-            let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
-            if helper.submodules.0 {
-                // handle as maven module
-                self.stack.push((oid, prepared, helper.into()));
-            } else {
-                // search further inside
-                self.stack.push((oid, prepared, helper.into()));
-            };
-        } else if RMS && !(helper.source_directories.0 || helper.test_source_directories.0) {
-            let tree = self.repository.find_tree(oid).unwrap();
-            // anyway try to find maven modules, but maybe can do better
-            let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
-            self.stack.push((oid, prepared, helper.into()));
+            // if not a module, search further inside,
+            // with RMS anyway try to find modules, but maybe can do better
+            let tree = repository.find_tree(oid).unwrap();
+            let prepared = (self.prepare)(tree, dir_path);
+            let mut acc: MavenModuleAcc = helper.into();
+            if is_module {
+                (self.init_module)(&mut acc);
+            }
+            stack.push((oid, prepared, acc));
+        }
+    }
+
+    pub(crate) fn post(
+        &self,
+        prepro: &mut RepositoryProcessor,
+        stack: &mut Vec<(Oid, Vec<BasicGitObject>, MavenModuleAcc)>,
+        oid: Oid,
+        acc: MavenModuleAcc,
+    ) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.name.clone();
+        let key = (oid, acc.ctx.clone());
+        let full_node = make(acc, prepro.main_stores_mut());
+        (self.object_map)(prepro).insert(key, full_node.clone());
+        let name = prepro.intern_label(&name);
+        if let Some((_, _, w)) = stack.last_mut() {
+            push_built(w, name, full_node);
+            None
+        } else {
+            Some(full_node)
         }
     }
 }
 
+/// Adds a built directory to its parent, as a submodule if it is a module.
+fn push_built(w: &mut MavenModuleAcc, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+    assert!(
+        !w.children_names.contains(&name),
+        "{:?} {:?}",
+        w.children_names,
+        name
+    );
+    if full_node.1.status.contains(SemFlags::IsMavenModule) {
+        w.push_submodule(name, full_node);
+    } else {
+        w.push((name, full_node));
+    }
+}
+
 pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
     use hyper_ast::{
         filter::BloomSize,
         hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
-        store::nodes::legion::{compo, compo::CS, NodeStore},
+        store::nodes::legion::{compo, compo::CS, EntryRef, NodeStore},
         tree_gen::SubTreeMetrics,
     };
     use hyper_ast_gen_ts_java::legion_with_refs::{eq_node, hash32};
@@ -240,10 +277,17 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
     let height = acc.metrics.height + 1;
     let size_no_spaces = acc.metrics.size_no_spaces + 1;
     let hbuilder = hashed::Builder::new(hashs, &dir_hash, &acc.name, size_no_spaces);
-    let hashable = hbuilder.most_discriminating();
     let label = stores.label_store.get_or_insert(acc.name.clone());
 
-    let eq = eq_node(&Type::MavenDirectory, Some(&label), &acc.children);
+    // what is inherited from enclosing modules is not in the children,
+    // so the resolved info is part of the identity of the module
+    let info = acc.info.as_ref().map(MavenModuleInfo::interpolated);
+    let hashable = (hbuilder.most_discriminating(), &info);
+    let eq = {
+        let eq = eq_node(&Type::MavenDirectory, Some(&label), &acc.children);
+        let info = info.as_ref();
+        move |x: EntryRef| eq(x) && x.get_component::<MavenModuleInfo>().ok() == info
+    };
     let ana = {
         let new_sub_modules = drain_filter_strip(&mut acc.sub_modules, b"..");
         let new_main_dirs = drain_filter_strip(&mut acc.main_dirs, b"..");
@@ -307,6 +351,9 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
             if !acc.status.is_empty() {
                 dyn_builder.add(acc.status);
             }
+            if let Some(info) = info.clone() {
+                dyn_builder.add(info);
+            }
             NodeStore::insert_built_after_prepare(vacant, dyn_builder.build())
         }
     };
@@ -393,11 +440,12 @@ impl RepositoryProcessor {
     }
 }
 
-struct MavenModuleHelper {
+pub(crate) struct MavenModuleHelper {
     name: String,
-    submodules: (bool, Vec<PathBuf>),
-    source_directories: (bool, Vec<PathBuf>),
-    test_source_directories: (bool, Vec<PathBuf>),
+    pub(crate) submodules: (bool, Vec<PathBuf>),
+    pub(crate) source_directories: (bool, Vec<PathBuf>),
+    pub(crate) test_source_directories: (bool, Vec<PathBuf>),
+    inherited: Option<MavenModuleInfo>,
 }
 
 impl From<(&mut MavenModuleAcc, &ObjectName)> for MavenModuleHelper {
@@ -412,18 +460,37 @@ impl From<(&mut MavenModuleAcc, &ObjectName)> for MavenModuleHelper {
             submodules: process(&mut parent_acc.sub_modules),
             source_directories: process(&mut parent_acc.main_dirs),
             test_source_directories: process(&mut parent_acc.test_dirs),
+            inherited: parent_acc
+                .info
+                .clone()
+                .or_else(|| parent_acc.inherited.clone()),
         }
     }
 }
 
 impl From<MavenModuleHelper> for MavenModuleAcc {
     fn from(helper: MavenModuleHelper) -> Self {
-        MavenModuleAcc::with_content(
+        let mut acc = MavenModuleAcc::with_content(
             helper.name,
             helper.submodules.1,
             helper.source_directories.1,
             helper.test_source_directories.1,
-        )
+        );
+        acc.ctx = helper.ctx();
+        acc.inherited = helper.inherited;
+        acc
+    }
+}
+
+impl MavenModuleHelper {
+    fn ctx(&self) -> ModuleCtx {
+        ModuleCtx {
+            is_module: self.submodules.0,
+            sub_modules: self.submodules.1.clone(),
+            main_dirs: self.source_directories.1.clone(),
+            test_dirs: self.test_source_directories.1.clone(),
+            inherited: self.inherited.clone(),
+        }
     }
 }
 
@@ -746,3 +813,70 @@ impl CacheHolding<crate::processing::caches::Maven> for MavenProcessorHolder {
         &self.0.as_ref().unwrap().cache
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::repository;
+
+    fn process(
+        prepro: &mut RepositoryProcessor,
+        name: &str,
+        files: &[(&str, &str)],
+    ) -> NodeIdentifier {
+        let (repository, tree) = repository(name, files);
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        MavenProcessor::<false, false, MavenModuleAcc>::new(
+            &repository,
+            prepro,
+            &mut dir_path,
+            b"",
+            tree,
+        )
+        .process()
+        .0
+    }
+
+    const CORE: &str = "<project>
+  <parent><groupId>org.example</groupId><artifactId>parent</artifactId></parent>
+  <artifactId>core</artifactId>
+</project>";
+
+    fn parent(version: &str) -> String {
+        format!(
+            "<project>
+  <groupId>org.example</groupId><artifactId>parent</artifactId><version>{}</version>
+  <modules><module>core</module></modules>
+</project>",
+            version
+        )
+    }
+
+    /// The tree of `core` is the same in both commits, but not what it inherits from its parent.
+    #[test]
+    fn inherited_info_in_cache_key() {
+        let mut prepro = RepositoryProcessor::default();
+        let mut core = |name, version| {
+            let files = [
+                ("pom.xml", parent(version)),
+                ("core/pom.xml", CORE.to_string()),
+                ("core/src/main/java/A.java", "class A {}".to_string()),
+            ];
+            let files: Vec<_> = files.iter().map(|(p, t)| (*p, t.as_str())).collect();
+            let root = process(&mut prepro, name, &files);
+            prepro.child_by_name(root, "core").unwrap()
+        };
+        let a = core("maven-inherited-a", "1.0");
+        let b = core("maven-inherited-b", "2.0");
+        assert_ne!(a, b);
+        let stores = prepro.main_stores();
+        let version = |id| {
+            let n = stores.node_store.resolve(id);
+            let info = n.get_component::<MavenModuleInfo>().unwrap();
+            info.coordinates.version.clone()
+        };
+        assert_eq!(version(a).as_deref(), Some("1.0"));
+        assert_eq!(version(b).as_deref(), Some("2.0"));
+    }
+}
@@ -32,8 +32,11 @@ pub struct PreProcessedRepositories {
 #[derive(Default)]
 pub struct CommitsPerSys {
     pub maven: HashMap<git2::Oid, Commit>,
+    pub gradle: HashMap<git2::Oid, Commit>,
     pub make: HashMap<git2::Oid, Commit>,
     pub npm: HashMap<git2::Oid, Commit>,
+    pub pip: HashMap<git2::Oid, Commit>,
+    pub cargo: HashMap<git2::Oid, Commit>,
     pub any: HashMap<git2::Oid, Commit>,
 }
 
@@ -49,8 +52,11 @@ impl CommitsPerSys {
     pub fn accessCommits<'a>(&'a self, sys: &RepoConfig) -> &'a HashMap<git2::Oid, Commit> {
         match sys {
             RepoConfig::JavaMaven => &self.maven,
+            RepoConfig::JavaGradle => &self.gradle,
             RepoConfig::CppMake => &self.make,
             RepoConfig::TsNpm => &self.npm,
+            RepoConfig::PythonPip => &self.pip,
+            RepoConfig::RustCargo => &self.cargo,
             RepoConfig::Any => &self.any,
         }
     }
@@ -149,6 +155,16 @@ impl PreProcessedRepositories {
                     config: h.register_param(crate::maven_processor::Parameter),
                 }
             }
+            RepoConfig::JavaGradle => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::gradle_processor::GradleProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::gradle_processor::Parameter),
+                }
+            }
             RepoConfig::CppMake => {
                 let h = self
                     .processor
@@ -159,13 +175,58 @@ impl PreProcessedRepositories {
                     config: h.register_param(crate::make_processor::Parameter),
                 }
             }
-            _ => todo!(),
+            RepoConfig::TsNpm => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::npm_processor::NpmProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::npm_processor::Parameter),
+                }
+            }
+            RepoConfig::PythonPip => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::pip_processor::PipProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::pip_processor::Parameter),
+                }
+            }
+            RepoConfig::RustCargo => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::cargo_processor::CargoProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::cargo_processor::Parameter),
+                }
+            }
+            RepoConfig::Any => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::any_processor::AnyProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::any_processor::Parameter),
+                }
+            }
         };
 
+        // replace the key too, it might hold a new location
+        self.configs.remove(&r.spec);
         self.configs.insert(r.spec.clone(), r.config);
         r
     }
 
+    pub fn remove_config(&mut self, repo: &Repo) -> bool {
+        self.configs.remove(repo).is_some()
+    }
+
     pub fn get_config(&mut self, repo: Repo) -> Option<ConfiguredRepoHandle2> {
         // let proc = self
         //     .processor.processing_systems
@@ -174,9 +235,13 @@ impl PreProcessedRepositories {
         //     .get(repository.config.1);
         // proc.get_commit(*commit_oid)
         // self
+        // the registered spec also holds the location of local and remote repositories
         self.configs
-            .get(&repo)
-            .map(|&config| ConfiguredRepoHandle2 { config, spec: repo })
+            .get_key_value(&repo)
+            .map(|(spec, &config)| ConfiguredRepoHandle2 {
+                config,
+                spec: spec.clone(),
+            })
     }
 
     pub fn pre_process_with_limit(
@@ -191,6 +256,18 @@ impl PreProcessedRepositories {
             .pre_process_with_limit(repository, before, after, limit)
     }
 
+    pub fn pre_process_with_strategy(
+        &mut self,
+        repository: &mut ConfiguredRepo2,
+        before: &str,
+        after: &str,
+        limit: usize,
+        strategy: crate::git::MergeStrategy,
+    ) -> Result<Vec<git2::Oid>, git2::Error> {
+        self.processor
+            .pre_process_with_strategy(repository, before, after, limit, strategy)
+    }
+
     pub fn pre_process_with_config2(
         &mut self,
         repository: &mut ConfiguredRepo2,
@@ -224,6 +224,34 @@ impl<'a> types::Typed for NoSpaceWrapper<'a, MIdN<NodeIdentifier>> {
             let t = *t as u16;
             let t = <hyper_ast_gen_ts_xml::types::Xml as hyper_ast::types::Lang<_>>::make(t);
             MultiType::Xml(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_make::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_make::types::Make as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Make(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_ts::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_ts::types::Ts as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Ts(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_python::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_python::types::Python as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Python(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_rust::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_rust::types::Rust as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Rust(*t)
         } else {
             panic!()
         }
@@ -255,6 +283,34 @@ impl<'a> types::Typed for NoSpaceWrapper<'a, NodeIdentifier> {
             let t = *t as u16;
             let t = <hyper_ast_gen_ts_xml::types::Xml as hyper_ast::types::Lang<_>>::make(t);
             MultiType::Xml(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_make::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_make::types::Make as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Make(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_ts::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_ts::types::Ts as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Ts(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_python::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_python::types::Python as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Python(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_rust::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_rust::types::Rust as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Rust(*t)
         } else {
             panic!()
         }
@@ -0,0 +1,235 @@
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::SubTreeMetrics,
+};
+use hyper_ast_gen_ts_ts::legion as ts_tree_gen;
+
+use crate::{package_processor::ConfigFile, Accumulator, DefaultMetrics, ParseErr};
+
+#[cfg(test)]
+use crate::utils::step_workspaces;
+
+/// What is extracted from a package.json to structure its package.
+///
+/// The package.json is kept in the tree as a text leaf,
+/// this component summarizes it on the package directory,
+/// which is part of the identity of the directory (see [`crate::npm_processor::make`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NpmPackage {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// patterns designating the packages of the workspace, eg. `packages/*`
+    pub workspaces: Vec<String>,
+    pub dependencies: Vec<NpmDependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NpmDependency {
+    pub name: String,
+    /// eg. `^1.2.0`, `workspace:*` or `file:../a`
+    pub version: String,
+    /// from `devDependencies`
+    pub dev: bool,
+}
+
+impl NpmDependency {
+    /// Tells if the dependency must be found in the same repository.
+    pub fn is_local(&self) -> bool {
+        self.version.starts_with("workspace:")
+            || self.version.starts_with("file:")
+            || self.version.starts_with("link:")
+    }
+}
+
+pub(crate) fn handle_package_json(text: &[u8]) -> Result<NpmPackage, ParseErr> {
+    let value: serde_json::Value = serde_json::from_slice(text).map_err(|e| {
+        log::warn!("bad package.json: {}", e);
+        ParseErr::IllFormed
+    })?;
+    let text_field = |k: &str| value.get(k).and_then(|x| x.as_str()).map(str::to_string);
+    let name = text_field("name");
+    let version = text_field("version");
+    // npm and yarn accept a list of patterns,
+    // yarn also accepts an object with a `packages` field
+    let workspaces = match value.get("workspaces") {
+        Some(serde_json::Value::Array(x)) => x.as_slice(),
+        Some(serde_json::Value::Object(x)) => match x.get("packages") {
+            Some(serde_json::Value::Array(x)) => x.as_slice(),
+            _ => &[],
+        },
+        _ => &[],
+    };
+    let workspaces = workspaces
+        .iter()
+        .filter_map(|x| x.as_str())
+        .filter_map(normalize_workspace)
+        .collect();
+    let mut dependencies = vec![];
+    for (k, dev) in [("dependencies", false), ("devDependencies", true)] {
+        let Some(deps) = value.get(k).and_then(|x| x.as_object()) else {
+            continue;
+        };
+        dependencies.extend(deps.iter().map(|(name, version)| NpmDependency {
+            name: name.to_string(),
+            version: version.as_str().unwrap_or_default().to_string(),
+            dev,
+        }));
+    }
+    Ok(NpmPackage {
+        name,
+        version,
+        workspaces,
+        dependencies,
+    })
+}
+
+/// Negated patterns and patterns going out of the package are ignored.
+fn normalize_workspace(pattern: &str) -> Option<String> {
+    if pattern.starts_with('!') {
+        log::debug!("ignored workspace pattern {}", pattern);
+        return None;
+    }
+    let pattern = pattern.trim_start_matches("./").trim_matches('/');
+    if pattern.is_empty() || pattern.split('/').any(|x| x == "..") {
+        return None;
+    }
+    Some(pattern.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+}
+
+pub struct NpmModuleAcc {
+    pub(crate) name: String,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) metrics: DefaultMetrics,
+    /// set once the package.json of the directory is handled
+    pub(crate) info: Option<NpmPackage>,
+    /// the directory is designated as a package by a parent workspace (or is the root)
+    pub(crate) is_workspace: bool,
+    /// workspace patterns of the parent packages, relative to the directory,
+    /// the ones of its own package.json are in [`NpmModuleAcc::info`]
+    pub(crate) workspaces: Vec<String>,
+}
+
+impl From<String> for NpmModuleAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl NpmModuleAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+            info: None,
+            is_workspace: false,
+            workspaces: vec![],
+        }
+    }
+    pub(crate) fn with_workspaces(
+        name: String,
+        is_workspace: bool,
+        workspaces: Vec<String>,
+    ) -> Self {
+        Self {
+            is_workspace,
+            workspaces,
+            ..Self::new(name)
+        }
+    }
+}
+
+impl NpmModuleAcc {
+    pub(crate) fn push_config_file(
+        &mut self,
+        name: LabelIdentifier,
+        file: ConfigFile<NpmPackage>,
+        summarized: bool,
+    ) {
+        if summarized {
+            self.info = Some(file.info);
+        }
+        self.children.push(file.text.0);
+        self.children_names.push(name);
+        self.metrics.acc(file.text.1);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: ts_tree_gen::Local,
+    ) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(SubTreeMetrics {
+            hashs: full_node.metrics.hashs,
+            size: full_node.metrics.size,
+            height: full_node.metrics.height,
+            size_no_spaces: full_node.metrics.size_no_spaces,
+        });
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for NpmModuleAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+}
+
+impl Accumulator for NpmModuleAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
+
+#[test]
+fn package_json() {
+    let text = br#"{
+        "name": "@scope/root",
+        "version": "1.0.0",
+        "private": true,
+        "workspaces": ["packages/*", "./apps/web/", "!packages/ignored", "tools/**/plugin-*"],
+        "dependencies": { "lodash": "^4.17.21", "@scope/a": "workspace:*" },
+        "devDependencies": { "typescript": "~5.0.0" }
+    }"#;
+    let info = handle_package_json(text).unwrap();
+    assert_eq!(info.name.as_deref(), Some("@scope/root"));
+    assert_eq!(info.version.as_deref(), Some("1.0.0"));
+    assert_eq!(
+        info.workspaces,
+        vec!["packages/*", "apps/web", "tools/**/plugin-*"]
+    );
+    assert_eq!(info.dependencies.len(), 3);
+    assert!(info
+        .dependencies
+        .iter()
+        .any(|x| x.name == "@scope/a" && x.is_local()));
+    assert!(info
+        .dependencies
+        .iter()
+        .any(|x| x.name == "typescript" && x.dev));
+
+    let yarn = br#"{ "workspaces": { "packages": ["libs/*"] } }"#;
+    assert_eq!(
+        handle_package_json(yarn).unwrap().workspaces,
+        vec!["libs/*"]
+    );
+    assert!(handle_package_json(b"{ \"name\": ").is_err());
+
+    let (is_package, rest) = step_workspaces(&info.workspaces, "packages");
+    assert!(!is_package);
+    assert_eq!(rest, vec!["*"]);
+    assert!(step_workspaces(&rest, "core").0);
+    let (is_package, rest) = step_workspaces(&info.workspaces, "tools");
+    assert!(!is_package);
+    let (_, rest) = step_workspaces(&rest, "build");
+    assert!(step_workspaces(&rest, "plugin-lint").0);
+    assert!(!step_workspaces(&rest, "lint").0);
+}
@@ -0,0 +1,564 @@
+use std::{
+    iter::Peekable,
+    marker::PhantomData,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::{Accumulator, SubTreeMetrics},
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_ts::types::Type;
+
+use crate::{
+    git::BasicGitObject,
+    npm::{NpmModuleAcc, NpmPackage, MD},
+    package_processor::{ConfigFile, PackageAcc, PackageWalk},
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    utils::{make_text, step_workspaces},
+    Processor, SimpleStores,
+};
+
+/// Builds a hierarchy of npm packages,
+/// where the .ts and .js files are parsed wherever they are.
+/// The package.json files are kept in the tree as text,
+/// the ones designated by a workspace are also summarized as a component of their package.
+///
+/// RMS: Resursive Module Search, ie. also consider package.json files that are not designated by a workspace,
+/// otherwise they are only considered at the root and in the workspaces of a parent package.
+pub struct NpmProcessor<'a, 'b, 'c, const RMS: bool> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, NpmModuleAcc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+}
+
+impl<'a, 'b, 'c, const RMS: bool> NpmProcessor<'a, 'b, 'c, RMS> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        mut dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, &mut dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, NpmModuleAcc::root(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> Processor<NpmModuleAcc> for NpmProcessor<'a, 'b, 'c, RMS> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                if crate::processing::file_sys::PackageJson::matches(&name) {
+                    let summarized = RMS || parent_acc.is_workspace;
+                    if !summarized {
+                        log::debug!("package.json not in a workspace {:?}", parent_acc.name);
+                    }
+                    if let Err(err) = self.prepro.help_handle_package_json(
+                        oid,
+                        parent_acc,
+                        name,
+                        &self.repository,
+                        self.handle.into(),
+                        summarized,
+                    ) {
+                        log::warn!("skipped package.json in {:?}: {:?}", parent_acc.name, err);
+                    }
+                } else if crate::processing::file_sys::Ts::matches(&name) {
+                    if let Err(err) = self.prepro.help_handle_ts_file(
+                        oid,
+                        parent_acc,
+                        &name,
+                        self.repository,
+                        self.handle.into(),
+                    ) {
+                        log::warn!("skipped {:?}: {:?}", name.try_str(), err);
+                    }
+                } else {
+                    log::debug!("not ts source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: NpmModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        Self::WALK.post(self.prepro, &mut self.stack, oid, acc)
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, NpmModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool> NpmProcessor<'a, 'b, 'c, RMS> {
+    const WALK: PackageWalk<NpmModuleAcc> = PackageWalk {
+        prepare: prepare_dir_exploration,
+        object_map: |prepro| {
+            &mut prepro
+                .processing_systems
+                .mut_or_default::<NpmProcessorHolder>()
+                .get_caches_mut()
+                .object_map
+        },
+        make,
+        // installed dependencies, sometimes commited by mistake
+        skipped: |name| name.as_bytes() == b"node_modules",
+    };
+
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        Self::WALK.handle_tree(
+            self.prepro,
+            self.repository,
+            self.dir_path,
+            &mut self.stack,
+            name,
+            oid,
+        )
+    }
+}
+
+impl PackageAcc for NpmModuleAcc {
+    type MD = MD;
+    type Ctx = (bool, Vec<String>);
+    fn ctx(&self) -> Self::Ctx {
+        (self.is_workspace, self.workspaces.clone())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn children_names(&self) -> &[LabelIdentifier] {
+        &self.children_names
+    }
+    fn root(name: String) -> Self {
+        NpmModuleAcc::with_workspaces(name, true, vec![])
+    }
+    fn child(&self, name: String) -> Self {
+        let own = self.info.iter().flat_map(|x| &x.workspaces);
+        let patterns: Vec<_> = self.workspaces.iter().chain(own).cloned().collect();
+        let (is_workspace, workspaces) = step_workspaces(&patterns, &name);
+        NpmModuleAcc::with_workspaces(name, is_workspace, workspaces)
+    }
+    fn push_dir(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        self.push((name, full_node))
+    }
+}
+
+pub(crate) fn make(mut acc: NpmModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::legion::{compo, compo::CS, EntryRef, NodeStore},
+    };
+    use hyper_ast_gen_ts_ts::legion::eq_node;
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    // the same package.json is only summarized in the packages of a workspace,
+    // so what is extracted from it is part of the identity of the directory
+    let info = acc.info.take();
+    let hashable = (hbuilder.most_discriminating(), &info);
+    let eq = {
+        let eq = eq_node(&Type::Directory, Some(&label), &acc.children);
+        let info = info.as_ref();
+        move |x: EntryRef| eq(x) && x.get_component::<NpmPackage>().ok() == info
+    };
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make npm {} {}", &acc.name, acc.children.len());
+        let vacant = insertion.vacant();
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+        dyn_builder.add(Type::Directory);
+        dyn_builder.add(hashs.clone());
+        dyn_builder.add(label);
+        dyn_builder.add(BloomSize::Much);
+        dyn_builder.add(compo::Size(size));
+        dyn_builder.add(compo::SizeNoSpaces(size_no_spaces));
+        dyn_builder.add(compo::Height(height));
+        dyn_builder.add(CS(acc.children_names.into_boxed_slice()));
+        dyn_builder.add(CS(acc.children.into_boxed_slice()));
+        if let Some(info) = info.clone() {
+            dyn_builder.add(info);
+        }
+        NodeStore::insert_built_after_prepare(vacant, dyn_builder.build())
+    };
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (node_id, MD { metrics })
+}
+
+impl RepositoryProcessor {
+    fn help_handle_package_json(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut NpmModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<PackageJsonProc>,
+        summarized: bool,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::PackageJson>()
+            .handle(oid, repository, &name, parameters, |_, _, t| {
+                // still kept in the tree
+                let info = crate::npm::handle_package_json(t).unwrap_or_else(|err| {
+                    log::warn!("bad package.json in {:?}: {:?}", parent_acc.name, err);
+                    Default::default()
+                });
+                // the file is kept as text
+                let text = make_text(std::str::from_utf8(t)?, &mut self.main_stores);
+                Ok::<_, crate::ParseErr>(ConfigFile { text, info })
+            })?;
+        let name = self.intern_object_name(name);
+        assert!(!parent_acc.children_names.contains(&name));
+        parent_acc.push_config_file(name, x, summarized);
+        Ok(())
+    }
+}
+
+/// The package.json is handled first,
+/// as it tells which directories are packages.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        children_objects.reverse(); // we use it like a stack
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) => crate::processing::file_sys::PackageJson::matches(n),
+            _ => false,
+        });
+    }
+    children_objects
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<PackageJsonProc>
+{
+    fn from(value: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<crate::ts_processor::TsProc>
+{
+    fn from(value: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+
+// # package.json
+
+struct PackageJsonProcessorHolder(Option<PackageJsonProc>);
+impl Default for PackageJsonProcessorHolder {
+    fn default() -> Self {
+        Self(Some(PackageJsonProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+struct PackageJsonProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::PackageJson,
+}
+impl crate::processing::erased::Parametrized for PackageJsonProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(PackageJsonProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+// TODO should not have to impl this trait
+impl crate::processing::erased::CommitProc for PackageJsonProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for PackageJsonProc {
+    type Holder = PackageJsonProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for PackageJsonProcessorHolder {
+    type Proc = PackageJsonProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::PackageJson> for PackageJsonProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::PackageJson {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::PackageJson {
+        &self.cache
+    }
+}
+
+// # Npm
+
+#[derive(Default)]
+pub struct NpmProcessorHolder(Option<NpmProc>);
+pub struct NpmProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Npm,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for NpmProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(NpmProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedNpmCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedNpmCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedNpmCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = NpmProcessor::<false>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for NpmProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedNpmCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for NpmProc {
+    type Holder = NpmProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for NpmProcessorHolder {
+    type Proc = NpmProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Npm> for NpmProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Npm {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Npm {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Npm> for NpmProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Npm {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Npm {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::repository;
+
+    #[test]
+    fn package_json_kept_in_tree() {
+        let mut prepro = RepositoryProcessor::default();
+        let (repository, tree) = repository(
+            "npm-package-json",
+            &[
+                ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+                ("packages/a/package.json", r#"{ "name": "a" }"#),
+                ("packages/a/src/index.ts", "export const a = 1;\n"),
+                // the same package, outside of the workspaces
+                ("vendor/a/package.json", r#"{ "name": "a" }"#),
+                ("vendor/a/src/index.ts", "export const a = 1;\n"),
+            ],
+        );
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let root = NpmProcessor::<false>::new(&repository, &mut prepro, &mut dir_path, b"", tree)
+            .process()
+            .0;
+        let info = |id| {
+            let stores = prepro.main_stores();
+            let b = stores.node_store.resolve(id);
+            b.get_component::<NpmPackage>().ok().cloned()
+        };
+        assert_eq!(info(root).unwrap().workspaces, vec!["packages/*"]);
+        // summarized, but still in the tree
+        assert!(prepro.child_by_name(root, "package.json").is_some());
+
+        let packages = prepro.child_by_name(root, "packages").unwrap();
+        let a = prepro.child_by_name(packages, "a").unwrap();
+        assert_eq!(info(a).unwrap().name.as_deref(), Some("a"));
+        assert!(prepro.child_by_name(a, "package.json").is_some());
+
+        let vendor = prepro.child_by_name(root, "vendor").unwrap();
+        let vendored = prepro.child_by_name(vendor, "a").unwrap();
+        assert_ne!(a, vendored);
+        assert_eq!(info(vendored), None);
+        assert!(prepro.child_by_name(vendored, "package.json").is_some());
+    }
+}
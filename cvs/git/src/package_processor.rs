@@ -0,0 +1,126 @@
+//! The directory walking shared by the processors of package managers, ie. npm, pip and cargo.
+//!
+//! Contrary to maven modules, the source files of a package are parsed wherever they are,
+//! so these processors only differ by their configuration files, their accumulators
+//! and by where they cache the directories they built.
+
+use std::{iter::Peekable, path::Components};
+
+use git2::{Oid, Repository};
+use hyper_ast::store::defaults::{LabelIdentifier, NodeIdentifier};
+
+use crate::{
+    git::BasicGitObject,
+    preprocessed::RepositoryProcessor,
+    processing::{caches::DirMap, ObjectName},
+    DefaultMetrics, SimpleStores,
+};
+
+/// A configuration file kept in the tree as a text leaf, with what is extracted from it.
+#[derive(Debug, Clone)]
+pub struct ConfigFile<T> {
+    pub(crate) text: (NodeIdentifier, DefaultMetrics),
+    pub(crate) info: T,
+}
+
+/// The accumulator of a directory in a hierarchy of packages.
+pub(crate) trait PackageAcc: Sized {
+    type MD: Clone;
+    /// what a directory inherits from its parents, eg. the member patterns of a parent workspace.
+    /// The same tree is built differently depending on it, so it is part of the cache key.
+    type Ctx: Ord;
+    fn ctx(&self) -> Self::Ctx;
+    fn name(&self) -> &str;
+    fn children_names(&self) -> &[LabelIdentifier];
+    /// the accumulator of the root, or of a directory leading to the processed one
+    fn root(name: String) -> Self;
+    /// the accumulator of a subdirectory, eg. designated or not by a workspace of this directory
+    fn child(&self, name: String) -> Self;
+    /// adds a built subdirectory
+    fn push_dir(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, Self::MD));
+}
+
+pub(crate) struct PackageWalk<Acc: PackageAcc> {
+    /// lists the children of a directory, configuration files first
+    pub(crate) prepare: fn(git2::Tree, &mut Peekable<Components>) -> Vec<BasicGitObject>,
+    /// the directories already built, by tree oid and context
+    pub(crate) object_map:
+        fn(&mut RepositoryProcessor) -> &mut DirMap<Acc::Ctx, (NodeIdentifier, Acc::MD)>,
+    pub(crate) make: fn(Acc, &mut SimpleStores) -> (NodeIdentifier, Acc::MD),
+    /// directories that contain dependencies or build artifacts rather than sources
+    pub(crate) skipped: fn(&ObjectName) -> bool,
+}
+
+impl<Acc: PackageAcc> PackageWalk<Acc> {
+    pub(crate) fn handle_tree(
+        &self,
+        prepro: &mut RepositoryProcessor,
+        repository: &Repository,
+        dir_path: &mut Peekable<Components>,
+        stack: &mut Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+        name: ObjectName,
+        oid: Oid,
+    ) {
+        if let Some(s) = dir_path.peek() {
+            if name
+                .as_bytes()
+                .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+            {
+                dir_path.next();
+                stack.last_mut().expect("never empty").1.clear();
+                let tree = repository.find_tree(oid).unwrap();
+                let prepared = (self.prepare)(tree, dir_path);
+                stack.push((oid, prepared, Acc::root(name.try_into().unwrap())));
+            }
+            return;
+        }
+        if (self.skipped)(&name) {
+            log::debug!(
+                "skipped {:?} in {:?}",
+                name.try_str(),
+                stack.last().unwrap().2.name()
+            );
+            return;
+        }
+        let acc = stack.last().unwrap().2.child(name.try_into().unwrap());
+        if let Some(already) = (self.object_map)(prepro).get(&(oid, acc.ctx())) {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let name = prepro.intern_object_name(&name);
+            let w = &mut stack.last_mut().unwrap().2;
+            assert!(!w.children_names().contains(&name));
+            w.push_dir(name, full_node);
+            return;
+        }
+        log::debug!("package tree {:?}", name.try_str());
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = (self.prepare)(tree, dir_path);
+        stack.push((oid, prepared, acc));
+    }
+
+    pub(crate) fn post(
+        &self,
+        prepro: &mut RepositoryProcessor,
+        stack: &mut Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+        oid: Oid,
+        acc: Acc,
+    ) -> Option<(NodeIdentifier, Acc::MD)> {
+        let name = acc.name().to_string();
+        let key = (oid, acc.ctx());
+        let full_node = (self.make)(acc, prepro.main_stores_mut());
+        (self.object_map)(prepro).insert(key, full_node.clone());
+        let name = prepro.intern_label(&name);
+        if let Some((_, _, w)) = stack.last_mut() {
+            assert!(
+                !w.children_names().contains(&name),
+                "{:?} {:?}",
+                w.children_names(),
+                name
+            );
+            w.push_dir(name, full_node);
+            None
+        } else {
+            Some(full_node)
+        }
+    }
+}
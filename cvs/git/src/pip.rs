@@ -0,0 +1,254 @@
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::SubTreeMetrics,
+};
+use hyper_ast_gen_ts_python::legion as python_tree_gen;
+
+use crate::{package_processor::ConfigFile, Accumulator, DefaultMetrics, ParseErr};
+
+/// What is extracted from the configuration files of a python project,
+/// ie. a pyproject.toml, a setup.cfg or a setup.py.
+///
+/// The files are kept in the HyperAST, the pyproject.toml and the setup.cfg as text leaves
+/// and the setup.py as a python file, this component summarizes them on the project directory.
+/// The arguments of `setup()` in a setup.py are not evaluated, so it only designates a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PyProject {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// requirements as written in the configuration, eg. `requests>=2.0`
+    pub dependencies: Vec<String>,
+}
+
+impl PyProject {
+    /// Completes the missing information with the one of another configuration file of the same project.
+    pub(crate) fn merge(&mut self, other: PyProject) {
+        if self.name.is_none() {
+            self.name = other.name;
+        }
+        if self.version.is_none() {
+            self.version = other.version;
+        }
+        for d in other.dependencies {
+            if !self.dependencies.contains(&d) {
+                self.dependencies.push(d);
+            }
+        }
+    }
+}
+
+pub(crate) fn handle_pyproject(text: &[u8]) -> Result<PyProject, ParseErr> {
+    let text = std::str::from_utf8(text)?;
+    let value: toml::Value = toml::from_str(text).map_err(|e| {
+        log::warn!("bad pyproject.toml: {}", e);
+        ParseErr::IllFormed
+    })?;
+    let text_field = |t: Option<&toml::Value>, k: &str| {
+        t.and_then(|t| t.get(k))
+            .and_then(|x| x.as_str())
+            .map(str::to_string)
+    };
+    // PEP 621 metadata, then poetry's own table
+    let project = value.get("project");
+    let poetry = value.get("tool").and_then(|x| x.get("poetry"));
+    let name = text_field(project, "name").or_else(|| text_field(poetry, "name"));
+    let version = text_field(project, "version").or_else(|| text_field(poetry, "version"));
+    let mut dependencies: Vec<String> = project
+        .and_then(|x| x.get("dependencies"))
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.as_str())
+        .map(str::to_string)
+        .collect();
+    if let Some(deps) = poetry
+        .and_then(|x| x.get("dependencies"))
+        .and_then(|x| x.as_table())
+    {
+        dependencies.extend(
+            deps.keys()
+                .filter(|k| k.as_str() != "python")
+                .map(|k| k.to_string()),
+        );
+    }
+    Ok(PyProject {
+        name,
+        version,
+        dependencies,
+    })
+}
+
+/// Reads the `[metadata]` and `[options]` sections of a setup.cfg,
+/// multi-line values are made of the following indented lines.
+pub(crate) fn handle_setup_cfg(text: &[u8]) -> Result<PyProject, ParseErr> {
+    let text = std::str::from_utf8(text)?;
+    let mut result = PyProject::default();
+    let mut section = "";
+    let mut key = "";
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            // continuation of the previous value
+            if section == "options" && key == "install_requires" {
+                result.dependencies.push(trimmed.to_string());
+            }
+            continue;
+        }
+        if let Some(s) = trimmed.strip_prefix('[') {
+            section = s.trim_end_matches(']').trim();
+            key = "";
+            continue;
+        }
+        let Some((k, v)) = trimmed.split_once('=').or_else(|| trimmed.split_once(':')) else {
+            log::debug!("bad setup.cfg line: {}", line);
+            continue;
+        };
+        key = k.trim();
+        let v = v.trim();
+        match (section, key) {
+            ("metadata", "name") => result.name = Some(v.to_string()),
+            ("metadata", "version") => result.version = Some(v.to_string()),
+            ("options", "install_requires") if !v.is_empty() => {
+                result.dependencies.extend(
+                    v.split(';')
+                        .map(str::trim)
+                        .filter(|x| !x.is_empty())
+                        .map(str::to_string),
+                );
+            }
+            _ => (),
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+}
+
+pub struct PyModuleAcc {
+    pub(crate) name: String,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) metrics: DefaultMetrics,
+    /// set once a configuration file of the directory is handled
+    pub(crate) info: Option<PyProject>,
+}
+
+impl From<String> for PyModuleAcc {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl PyModuleAcc {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+            info: None,
+        }
+    }
+}
+
+impl PyModuleAcc {
+    pub(crate) fn push_pyproject(&mut self, info: PyProject) {
+        match &mut self.info {
+            Some(x) => x.merge(info),
+            None => self.info = Some(info),
+        }
+    }
+    pub(crate) fn push_config_file(&mut self, name: LabelIdentifier, file: ConfigFile<PyProject>) {
+        self.push_pyproject(file.info);
+        self.children.push(file.text.0);
+        self.children_names.push(name);
+        self.metrics.acc(file.text.1);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: python_tree_gen::Local,
+    ) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(SubTreeMetrics {
+            hashs: full_node.metrics.hashs,
+            size: full_node.metrics.size,
+            height: full_node.metrics.height,
+            size_no_spaces: full_node.metrics.size_no_spaces,
+        });
+    }
+}
+
+impl hyper_ast::tree_gen::Accumulator for PyModuleAcc {
+    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+}
+
+impl Accumulator for PyModuleAcc {
+    type Unlabeled = (NodeIdentifier, MD);
+}
+
+#[test]
+fn pyproject() {
+    let text = br#"
+[build-system]
+requires = ["setuptools>=61"]
+
+[project]
+name = "greeter"
+version = "0.3.1"
+dependencies = ["requests>=2.0", "click"]
+"#;
+    let info = handle_pyproject(text).unwrap();
+    assert_eq!(info.name.as_deref(), Some("greeter"));
+    assert_eq!(info.version.as_deref(), Some("0.3.1"));
+    assert_eq!(info.dependencies, vec!["requests>=2.0", "click"]);
+
+    let poetry = br#"
+[tool.poetry]
+name = "poetic"
+version = "1.0.0"
+
+[tool.poetry.dependencies]
+python = "^3.8"
+numpy = "^1.24"
+"#;
+    let info = handle_pyproject(poetry).unwrap();
+    assert_eq!(info.name.as_deref(), Some("poetic"));
+    assert_eq!(info.dependencies, vec!["numpy"]);
+    assert!(handle_pyproject(b"[project").is_err());
+
+    let cfg = br#"
+[metadata]
+name = legacy
+version = 2.1
+
+[options]
+packages = find:
+install_requires =
+    six
+    attrs>=19.0
+"#;
+    let mut info = handle_setup_cfg(cfg).unwrap();
+    assert_eq!(info.name.as_deref(), Some("legacy"));
+    assert_eq!(info.version.as_deref(), Some("2.1"));
+    assert_eq!(info.dependencies, vec!["six", "attrs>=19.0"]);
+    info.merge(PyProject {
+        name: Some("other".to_string()),
+        version: None,
+        dependencies: vec!["six".to_string(), "toml".to_string()],
+    });
+    assert_eq!(info.name.as_deref(), Some("legacy"));
+    assert_eq!(info.dependencies, vec!["six", "attrs>=19.0", "toml"]);
+}
@@ -0,0 +1,523 @@
+use std::{
+    iter::Peekable,
+    marker::PhantomData,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    store::defaults::{LabelIdentifier, NodeIdentifier},
+    tree_gen::{Accumulator, SubTreeMetrics},
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_python::types::Type;
+
+use crate::{
+    git::BasicGitObject,
+    package_processor::{ConfigFile, PackageAcc, PackageWalk},
+    pip::{PyModuleAcc, MD},
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    utils::make_text,
+    Processor, SimpleStores,
+};
+
+/// Directories that contain environments, caches or build artifacts rather than sources.
+const SKIPPED_DIRS: &[&[u8]] = &[
+    b"__pycache__",
+    b".venv",
+    b"venv",
+    b".tox",
+    b".eggs",
+    b"site-packages",
+    b"node_modules",
+];
+
+/// Builds a hierarchy of python projects,
+/// where the .py files are parsed wherever they are.
+///
+/// Each directory with a pyproject.toml, a setup.cfg or a setup.py is considered as a project,
+/// these files are kept in the tree.
+pub struct PipProcessor<'a, 'b, 'c> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, PyModuleAcc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<PipProc>,
+}
+
+impl<'a, 'b, 'c> PipProcessor<'a, 'b, 'c> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        mut dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<PipProcessorHolder>();
+        let handle =
+            <PipProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, &mut dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, PyModuleAcc::root(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c> Processor<PyModuleAcc> for PipProcessor<'a, 'b, 'c> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                if name.as_bytes() == b"setup.py" {
+                    // the arguments of setup() are not evaluated,
+                    // it only designates the directory as a project
+                    parent_acc.push_pyproject(Default::default());
+                } else if crate::processing::file_sys::PyProject::matches(&name) {
+                    if let Err(err) = self.prepro.help_handle_pyproject(
+                        oid,
+                        parent_acc,
+                        &name,
+                        &self.repository,
+                        self.handle.into(),
+                    ) {
+                        log::warn!("skipped {:?}: {:?}", name.try_str(), err);
+                    }
+                }
+                // setup.py is both a configuration file and a source file
+                if crate::processing::file_sys::Python::matches(&name) {
+                    if let Err(err) = self.prepro.help_handle_python_file(
+                        oid,
+                        parent_acc,
+                        &name,
+                        self.repository,
+                        self.handle.into(),
+                    ) {
+                        log::warn!("skipped {:?}: {:?}", name.try_str(), err);
+                    }
+                } else {
+                    log::debug!("not python source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: PyModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        Self::WALK.post(self.prepro, &mut self.stack, oid, acc)
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, PyModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c> PipProcessor<'a, 'b, 'c> {
+    const WALK: PackageWalk<PyModuleAcc> = PackageWalk {
+        prepare: prepare_dir_exploration,
+        object_map: |prepro| {
+            &mut prepro
+                .processing_systems
+                .mut_or_default::<PipProcessorHolder>()
+                .get_caches_mut()
+                .object_map
+        },
+        make,
+        skipped: |name| {
+            SKIPPED_DIRS.contains(&name.as_bytes()) || name.as_bytes().ends_with(b".egg-info")
+        },
+    };
+
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        Self::WALK.handle_tree(
+            self.prepro,
+            self.repository,
+            self.dir_path,
+            &mut self.stack,
+            name,
+            oid,
+        )
+    }
+}
+
+impl PackageAcc for PyModuleAcc {
+    type MD = MD;
+    type Ctx = ();
+    fn ctx(&self) -> Self::Ctx {}
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn children_names(&self) -> &[LabelIdentifier] {
+        &self.children_names
+    }
+    fn root(name: String) -> Self {
+        PyModuleAcc::new(name)
+    }
+    fn child(&self, name: String) -> Self {
+        PyModuleAcc::new(name)
+    }
+    fn push_dir(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+        self.push((name, full_node))
+    }
+}
+
+pub(crate) fn make(mut acc: PyModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::legion::{compo, compo::CS, NodeStore},
+    };
+    use hyper_ast_gen_ts_python::legion::eq_node;
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node(&Type::Directory, Some(&label), &acc.children);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make pip {} {}", &acc.name, acc.children.len());
+        let vacant = insertion.vacant();
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        let mut dyn_builder = hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+        dyn_builder.add(Type::Directory);
+        dyn_builder.add(hashs.clone());
+        dyn_builder.add(label);
+        dyn_builder.add(BloomSize::Much);
+        dyn_builder.add(compo::Size(size));
+        dyn_builder.add(compo::SizeNoSpaces(size_no_spaces));
+        dyn_builder.add(compo::Height(height));
+        dyn_builder.add(CS(acc.children_names.into_boxed_slice()));
+        dyn_builder.add(CS(acc.children.into_boxed_slice()));
+        if let Some(info) = acc.info.take() {
+            dyn_builder.add(info);
+        }
+        NodeStore::insert_built_after_prepare(vacant, dyn_builder.build())
+    };
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (node_id, MD { metrics })
+}
+
+impl RepositoryProcessor {
+    fn help_handle_pyproject(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut PyModuleAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<PyProjectProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::PyProject>()
+            .handle2(oid, repository, name, parameters, |_, n, t| {
+                let info = match n.as_bytes() {
+                    b"setup.cfg" => crate::pip::handle_setup_cfg(t),
+                    _ => crate::pip::handle_pyproject(t),
+                };
+                // still kept in the tree, and still designates a project
+                let info = info.unwrap_or_else(|err| {
+                    log::warn!("bad python project config {:?}: {:?}", n.try_str(), err);
+                    Default::default()
+                });
+                // there is no toml or ini grammar, the file is kept as text
+                let text = make_text(std::str::from_utf8(t)?, &mut self.main_stores);
+                Ok::<_, crate::ParseErr>(ConfigFile { text, info })
+            })?;
+        let name = self.intern_object_name(name);
+        assert!(!parent_acc.children_names.contains(&name));
+        parent_acc.push_config_file(name, x);
+        Ok(())
+    }
+}
+
+/// The configuration files are handled first,
+/// pyproject.toml before the legacy setup.cfg and setup.py.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        children_objects.reverse(); // we use it like a stack
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) if n.as_bytes() == b"pyproject.toml" => 2,
+            BasicGitObject::Blob(_, n) if crate::processing::file_sys::PyProject::matches(n) => 1,
+            _ => 0,
+        });
+    }
+    children_objects
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<PipProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<PyProjectProc>
+{
+    fn from(value: crate::processing::erased::ParametrizedCommitProcessor2Handle<PipProc>) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<PipProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<
+        crate::python_processor::PythonProc,
+    >
+{
+    fn from(value: crate::processing::erased::ParametrizedCommitProcessor2Handle<PipProc>) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+
+// # pyproject.toml, setup.cfg and setup.py
+
+struct PyProjectProcessorHolder(Option<PyProjectProc>);
+impl Default for PyProjectProcessorHolder {
+    fn default() -> Self {
+        Self(Some(PyProjectProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+struct PyProjectProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::PyProject,
+}
+impl crate::processing::erased::Parametrized for PyProjectProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(PyProjectProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+// TODO should not have to impl this trait
+impl crate::processing::erased::CommitProc for PyProjectProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for PyProjectProc {
+    type Holder = PyProjectProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for PyProjectProcessorHolder {
+    type Proc = PyProjectProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::PyProject> for PyProjectProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::PyProject {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::PyProject {
+        &self.cache
+    }
+}
+
+// # Pip
+
+#[derive(Default)]
+pub struct PipProcessorHolder(Option<PipProc>);
+pub struct PipProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Pip,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for PipProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(PipProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedPipCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedPipCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedPipCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = PipProcessor::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<PipProcessorHolder>();
+        let handle =
+            <PipProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for PipProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedPipCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for PipProc {
+    type Holder = PipProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for PipProcessorHolder {
+    type Proc = PipProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Pip> for PipProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Pip {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Pip {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Pip> for PipProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Pip {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Pip {
+        &self.0.as_ref().unwrap().cache
+    }
+}
@@ -16,7 +16,7 @@ use hyper_ast_gen_ts_java::impact::partial_analysis::PartialAnalysis;
 use log::info;
 
 use crate::{
-    git::{all_commits_between, retrieve_commit},
+    git::{all_commits_between, commits_between_with, retrieve_commit, MergeStrategy},
     make::MakeModuleAcc,
     make_processor::MakeProcessor,
     maven::MavenModuleAcc,
@@ -214,12 +214,24 @@ impl RepositoryProcessor {
         before: &str,
         after: &str,
         limit: usize,
+    ) -> Result<Vec<git2::Oid>, git2::Error> {
+        self.pre_process_with_strategy(repository, before, after, limit, Default::default())
+    }
+
+    /// Like [`Self::pre_process_with_limit`] but going through merge commits according to `strategy`.
+    pub fn pre_process_with_strategy(
+        &mut self,
+        repository: &mut ConfiguredRepo2,
+        before: &str,
+        after: &str,
+        limit: usize,
+        strategy: MergeStrategy,
     ) -> Result<Vec<git2::Oid>, git2::Error> {
         log::info!(
             "commits to process: {:?}",
             all_commits_between(&repository.repo, before, after).map(|x| x.count())
         );
-        let rw = all_commits_between(&repository.repo, before, after)?;
+        let rw = commits_between_with(&repository.repo, before, after, strategy)?;
         let r = rw
             .take(limit)
             .map(|oid| {
@@ -6,8 +6,11 @@ pub use erased::ParametrizedCommitProcessorHandle;
 
 pub enum BuildSystem {
     Maven,
+    Gradle,
     Make,
     Npm,
+    Pip,
+    Cargo,
     None,
 }
 
@@ -15,23 +18,31 @@ enum Language {
     Java,
     Cpp,
     Ts,
+    Python,
+    Rust,
     Xml,
 }
 
 pub enum ProcessingConfig<P> {
     JavaMaven { limit: usize, dir_path: P },
+    JavaGradle { limit: usize, dir_path: P },
     CppMake { limit: usize, dir_path: P },
     TsNpm { limit: usize, dir_path: P },
+    PythonPip { limit: usize, dir_path: P },
+    RustCargo { limit: usize, dir_path: P },
     Any { limit: usize, dir_path: P },
 }
 
 /// Contains repository configuration,
 /// where each config given the same commit should produce the same result in the hyperast
-#[derive(serde::Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum RepoConfig {
     CppMake,
     JavaMaven,
+    JavaGradle,
     TsNpm,
+    PythonPip,
+    RustCargo,
     Any,
 }
 
@@ -44,10 +55,18 @@ impl std::str::FromStr for RepoConfig {
             "cpp" => Self::CppMake,
             "Java" => Self::JavaMaven,
             "java" => Self::JavaMaven,
+            "Gradle" => Self::JavaGradle,
+            "gradle" => Self::JavaGradle,
             "typescript" => Self::TsNpm,
             "javascript" => Self::TsNpm,
             "Ts" => Self::TsNpm,
             "ts" => Self::TsNpm,
+            "Python" => Self::PythonPip,
+            "python" => Self::PythonPip,
+            "py" => Self::PythonPip,
+            "Rust" => Self::RustCargo,
+            "rust" => Self::RustCargo,
+            "rs" => Self::RustCargo,
             "any" => Self::Any,
             x => return Err(format!("'{}' is not anvailable config", x))
         })
@@ -65,8 +84,26 @@ impl From<&RepoConfig> for ProcessingConfig<&'static str> {
                 limit: 3,
                 dir_path: "",
             },
-            RepoConfig::TsNpm => todo!(),
-            RepoConfig::Any => todo!(),
+            RepoConfig::JavaGradle => Self::JavaGradle {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::TsNpm => Self::TsNpm {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::PythonPip => Self::PythonPip {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::RustCargo => Self::RustCargo {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::Any => Self::Any {
+                limit: 3,
+                dir_path: "",
+            },
         }
     }
 }
@@ -98,12 +135,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle {
 }
 
 impl ConfiguredRepoHandle {
-    pub fn fetch(self) -> ConfiguredRepo {
-        ConfiguredRepo {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo, git2::Error> {
+        Ok(ConfiguredRepo {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -125,12 +162,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle2 {
     }
 }
 impl ConfiguredRepoHandle2 {
-    pub fn fetch(self) -> ConfiguredRepo2 {
-        ConfiguredRepo2 {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo2, git2::Error> {
+        Ok(ConfiguredRepo2 {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -252,6 +289,9 @@ pub(crate) mod caches {
 
     pub(crate) type OidMap<T> = std::collections::BTreeMap<git2::Oid, T>;
     pub(crate) type NamedMap<T> = std::collections::BTreeMap<(git2::Oid, ObjectName), T>;
+    /// for directories built depending on what they inherit from their parents,
+    /// see [`crate::package_processor::PackageAcc::Ctx`] and [`crate::maven::ModuleCtx`]
+    pub(crate) type DirMap<C, T> = std::collections::BTreeMap<(git2::Oid, C), T>;
 
     #[derive(Default)]
     pub struct Java {
@@ -295,7 +335,7 @@ pub(crate) mod caches {
 
     #[derive(Default)]
     pub struct Maven {
-        pub object_map: OidMap<(NodeIdentifier, crate::maven::MD)>,
+        pub object_map: DirMap<crate::maven::ModuleCtx, (NodeIdentifier, crate::maven::MD)>,
     }
 
     #[derive(Default)]
@@ -317,6 +357,30 @@ pub(crate) mod caches {
         }
     }
 
+    #[derive(Default)]
+    pub struct Gradle {
+        pub object_map: DirMap<crate::maven::ModuleCtx, (NodeIdentifier, crate::maven::MD)>,
+    }
+
+    #[derive(Default)]
+    pub struct GradleFile {
+        pub object_map: OidMap<crate::gradle::GradleFile>,
+    }
+
+    impl super::ObjectMapper for GradleFile {
+        type K = git2::Oid;
+
+        type V = crate::gradle::GradleFile;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
     #[derive(Default)]
     pub struct Make {
         pub object_map: OidMap<(NodeIdentifier, crate::make::MD)>,
@@ -341,6 +405,199 @@ pub(crate) mod caches {
         }
     }
 
+    #[cfg(feature = "npm")]
+    #[derive(Default)]
+    pub struct Npm {
+        pub object_map: DirMap<(bool, Vec<String>), (NodeIdentifier, crate::npm::MD)>,
+    }
+
+    #[cfg(feature = "npm")]
+    #[derive(Default)]
+    pub struct PackageJson {
+        pub object_map: OidMap<crate::package_processor::ConfigFile<crate::npm::NpmPackage>>,
+    }
+
+    #[cfg(feature = "npm")]
+    impl super::ObjectMapper for PackageJson {
+        type K = git2::Oid;
+
+        type V = crate::package_processor::ConfigFile<crate::npm::NpmPackage>;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "ts")]
+    #[derive(Default)]
+    pub struct Ts {
+        pub(crate) md_cache: hyper_ast_gen_ts_ts::legion::MDCache,
+        pub object_map: NamedMap<(hyper_ast_gen_ts_ts::legion::Local, IsSkippedAna)>,
+    }
+
+    #[cfg(feature = "ts")]
+    impl super::ObjectMapper for Ts {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (hyper_ast_gen_ts_ts::legion::Local, IsSkippedAna);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "pip")]
+    #[derive(Default)]
+    pub struct Pip {
+        pub object_map: DirMap<(), (NodeIdentifier, crate::pip::MD)>,
+    }
+
+    #[cfg(feature = "pip")]
+    #[derive(Default)]
+    pub struct PyProject {
+        pub object_map: NamedMap<crate::package_processor::ConfigFile<crate::pip::PyProject>>,
+    }
+
+    #[cfg(feature = "pip")]
+    impl super::ObjectMapper for PyProject {
+        type K = (git2::Oid, ObjectName);
+
+        type V = crate::package_processor::ConfigFile<crate::pip::PyProject>;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "python")]
+    #[derive(Default)]
+    pub struct Python {
+        pub object_map: NamedMap<(hyper_ast_gen_ts_python::legion::Local, IsSkippedAna)>,
+    }
+
+    #[cfg(feature = "python")]
+    impl super::ObjectMapper for Python {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (hyper_ast_gen_ts_python::legion::Local, IsSkippedAna);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "cargo")]
+    #[derive(Default)]
+    pub struct Cargo {
+        pub object_map:
+            DirMap<(bool, Vec<String>, Vec<String>), (NodeIdentifier, crate::cargo::MD)>,
+    }
+
+    #[cfg(feature = "cargo")]
+    #[derive(Default)]
+    pub struct CargoToml {
+        pub object_map: OidMap<crate::package_processor::ConfigFile<crate::cargo::CargoManifest>>,
+    }
+
+    #[cfg(feature = "cargo")]
+    impl super::ObjectMapper for CargoToml {
+        type K = git2::Oid;
+
+        type V = crate::package_processor::ConfigFile<crate::cargo::CargoManifest>;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "rust")]
+    #[derive(Default)]
+    pub struct Rust {
+        pub object_map: NamedMap<(hyper_ast_gen_ts_rust::legion::Local, IsSkippedAna)>,
+    }
+
+    #[cfg(feature = "rust")]
+    impl super::ObjectMapper for Rust {
+        type K = (git2::Oid, ObjectName);
+
+        type V = (hyper_ast_gen_ts_rust::legion::Local, IsSkippedAna);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Any {
+        pub object_map: OidMap<(NodeIdentifier, crate::DefaultMetrics)>,
+    }
+
+    #[cfg(feature = "any")]
+    #[derive(Default)]
+    pub struct Xml {
+        pub object_map: NamedMap<hyper_ast_gen_ts_xml::legion::Local>,
+    }
+
+    #[cfg(feature = "any")]
+    impl super::ObjectMapper for Xml {
+        type K = (git2::Oid, ObjectName);
+
+        type V = hyper_ast_gen_ts_xml::legion::Local;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[cfg(feature = "any")]
+    #[derive(Default)]
+    pub struct Text {
+        pub object_map: OidMap<(NodeIdentifier, crate::DefaultMetrics)>,
+    }
+
+    #[cfg(feature = "any")]
+    impl super::ObjectMapper for Text {
+        type K = git2::Oid;
+
+        type V = (NodeIdentifier, crate::DefaultMetrics);
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
     // // any
     // pub object_map_any: OidMap<(NodeIdentifier, DefaultMetrics)>,
     // // maven
@@ -382,6 +639,35 @@ pub mod file_sys {
     /// The default file system, directories and files
     pub struct Any;
 
+    impl CachesHolding for Any {
+        type Caches = super::caches::Any;
+    }
+
+    /// Xml files that are not handled by a build system
+    #[cfg(feature = "any")]
+    pub struct Xml;
+
+    #[cfg(feature = "any")]
+    impl CachesHolding for Xml {
+        type Caches = super::caches::Xml;
+    }
+
+    #[cfg(feature = "any")]
+    impl super::InFiles for Xml {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".xml")
+        }
+    }
+
+    /// Any file decoded as utf8 text, kept as an opaque leaf
+    #[cfg(feature = "any")]
+    pub struct Text;
+
+    #[cfg(feature = "any")]
+    impl CachesHolding for Text {
+        type Caches = super::caches::Text;
+    }
+
     /// The maven scheme https://maven.apache.org/guides/introduction/introduction-to-the-standard-directory-layout.html ,
     /// made of nested maven modules.
     /// Each maven module has a config file (often a pom.xml),
@@ -392,6 +678,7 @@ pub mod file_sys {
     #[cfg(feature = "maven")]
     pub struct Maven;
 
+    #[cfg(feature = "maven")]
     impl CachesHolding for Maven {
         type Caches = super::caches::Maven;
     }
@@ -404,21 +691,73 @@ pub mod file_sys {
         type Caches = super::caches::Pom;
     }
 
+    #[cfg(feature = "maven")]
     impl super::InFiles for Pom {
         fn matches(name: &ObjectName) -> bool {
             name.0.eq(b"pom.xml")
         }
     }
 
+    /// The gradle scheme https://docs.gradle.org/current/userguide/multi_project_builds.html ,
+    /// made of projects included by a settings.gradle (or settings.gradle.kts) at the root.
+    /// Each project can have a build.gradle (or build.gradle.kts) that configures its sourceSets,
+    /// by default following the maven layout ie. src/main/java/ and src/test/java/.
+    #[cfg(feature = "gradle")]
+    pub struct Gradle;
+
+    #[cfg(feature = "gradle")]
+    impl CachesHolding for Gradle {
+        type Caches = super::caches::Gradle;
+    }
+
+    /// settings and build scripts
+    #[cfg(feature = "gradle")]
+    pub struct GradleFile;
+
+    #[cfg(feature = "gradle")]
+    impl CachesHolding for GradleFile {
+        type Caches = super::caches::GradleFile;
+    }
+
+    #[cfg(feature = "gradle")]
+    impl super::InFiles for GradleFile {
+        fn matches(name: &ObjectName) -> bool {
+            <GradleSettings as super::InFiles>::matches(name)
+                || <GradleBuild as super::InFiles>::matches(name)
+        }
+    }
+
+    #[cfg(feature = "gradle")]
+    pub struct GradleSettings;
+
+    #[cfg(feature = "gradle")]
+    impl super::InFiles for GradleSettings {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"settings.gradle") || name.0.eq(b"settings.gradle.kts")
+        }
+    }
+
+    #[cfg(feature = "gradle")]
+    pub struct GradleBuild;
+
+    #[cfg(feature = "gradle")]
+    impl super::InFiles for GradleBuild {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"build.gradle") || name.0.eq(b"build.gradle.kts")
+        }
+    }
+
     /// The java scheme,
     /// made of packages and modules https://docs.oracle.com/javase/specs/jls/se11/html/jls-7.html
     #[cfg(feature = "maven")]
     pub struct Java;
 
+    #[cfg(feature = "maven")]
     impl CachesHolding for Java {
         type Caches = super::caches::Java;
     }
 
+    #[cfg(feature = "maven")]
     impl super::InFiles for Java {
         fn matches(name: &ObjectName) -> bool {
             name.0.ends_with(b".java")
@@ -431,6 +770,7 @@ pub mod file_sys {
     #[cfg(feature = "make")]
     pub struct Make;
 
+    #[cfg(feature = "make")]
     impl CachesHolding for Make {
         type Caches = super::caches::Make;
     }
@@ -438,23 +778,27 @@ pub mod file_sys {
     #[cfg(feature = "make")]
     pub struct MakeFile;
 
+    #[cfg(feature = "make")]
     impl CachesHolding for MakeFile {
         type Caches = super::caches::Makefile;
     }
 
+    #[cfg(feature = "make")]
     impl super::InFiles for MakeFile {
         fn matches(name: &ObjectName) -> bool {
-            name.0.eq(b"Makefile")
+            name.0.eq(b"Makefile") || name.0.eq(b"makefile") || name.0.eq(b"GNUmakefile")
         }
     }
 
     #[cfg(feature = "cpp")]
     pub struct Cpp;
 
+    #[cfg(feature = "cpp")]
     impl CachesHolding for Cpp {
         type Caches = super::caches::Cpp;
     }
 
+    #[cfg(feature = "cpp")]
     impl super::InFiles for Cpp {
         fn matches(name: &ObjectName) -> bool {
             name.0.ends_with(b".cpp")
@@ -471,6 +815,130 @@ pub mod file_sys {
     /// or is a collection of packages that contains a packages/ directory where each package is located
     #[cfg(feature = "npm")]
     pub struct Npm;
+
+    #[cfg(feature = "npm")]
+    impl CachesHolding for Npm {
+        type Caches = super::caches::Npm;
+    }
+
+    #[cfg(feature = "npm")]
+    pub struct PackageJson;
+
+    #[cfg(feature = "npm")]
+    impl CachesHolding for PackageJson {
+        type Caches = super::caches::PackageJson;
+    }
+
+    #[cfg(feature = "npm")]
+    impl super::InFiles for PackageJson {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"package.json")
+        }
+    }
+
+    #[cfg(feature = "ts")]
+    pub struct Ts;
+
+    #[cfg(feature = "ts")]
+    impl CachesHolding for Ts {
+        type Caches = super::caches::Ts;
+    }
+
+    #[cfg(feature = "ts")]
+    impl super::InFiles for Ts {
+        fn matches(name: &ObjectName) -> bool {
+            (name.0.ends_with(b".ts")
+                || name.0.ends_with(b".tsx")
+                || name.0.ends_with(b".js")
+                || name.0.ends_with(b".mjs")
+                || name.0.ends_with(b".cjs"))
+                // type declarations and bundles are not sources
+                && !name.0.ends_with(b".d.ts")
+                && !name.0.ends_with(b".min.js")
+        }
+    }
+
+    /// The pip scheme, ie. setuptools and pyproject projects,
+    /// each project has a pyproject.toml, a setup.cfg or a setup.py,
+    /// its packages are directories of python files, sometimes put in a src/ directory
+    #[cfg(feature = "pip")]
+    pub struct Pip;
+
+    #[cfg(feature = "pip")]
+    impl CachesHolding for Pip {
+        type Caches = super::caches::Pip;
+    }
+
+    #[cfg(feature = "pip")]
+    pub struct PyProject;
+
+    #[cfg(feature = "pip")]
+    impl CachesHolding for PyProject {
+        type Caches = super::caches::PyProject;
+    }
+
+    #[cfg(feature = "pip")]
+    impl super::InFiles for PyProject {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"pyproject.toml") || name.0.eq(b"setup.cfg") || name.0.eq(b"setup.py")
+        }
+    }
+
+    #[cfg(feature = "python")]
+    pub struct Python;
+
+    #[cfg(feature = "python")]
+    impl CachesHolding for Python {
+        type Caches = super::caches::Python;
+    }
+
+    #[cfg(feature = "python")]
+    impl super::InFiles for Python {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".py") || name.0.ends_with(b".pyi")
+        }
+    }
+
+    /// The cargo scheme,
+    /// each crate has a Cargo.toml, its sources are usually in a src/ directory,
+    /// a workspace designates its crates with the members of its Cargo.toml
+    #[cfg(feature = "cargo")]
+    pub struct Cargo;
+
+    #[cfg(feature = "cargo")]
+    impl CachesHolding for Cargo {
+        type Caches = super::caches::Cargo;
+    }
+
+    #[cfg(feature = "cargo")]
+    pub struct CargoToml;
+
+    #[cfg(feature = "cargo")]
+    impl CachesHolding for CargoToml {
+        type Caches = super::caches::CargoToml;
+    }
+
+    #[cfg(feature = "cargo")]
+    impl super::InFiles for CargoToml {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"Cargo.toml")
+        }
+    }
+
+    #[cfg(feature = "rust")]
+    pub struct Rust;
+
+    #[cfg(feature = "rust")]
+    impl CachesHolding for Rust {
+        type Caches = super::caches::Rust;
+    }
+
+    #[cfg(feature = "rust")]
+    impl super::InFiles for Rust {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.ends_with(b".rs")
+        }
+    }
 }
 
 impl crate::preprocessed::RepositoryProcessor {
@@ -0,0 +1,24 @@
+use crate::{processing::ObjectName, TStore, PROPAGATE_ERROR_ON_BAD_CST_NODE};
+
+use hyper_ast_gen_ts_rust::legion as rust_tree_gen;
+
+pub(crate) fn handle_rust_file<'stores, 'b: 'stores>(
+    tree_gen: &mut rust_tree_gen::RustTreeGen<'stores, TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<rust_tree_gen::FNode, ()> {
+    let tree = match rust_tree_gen::RustTreeGen::<TStore>::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST");
+            log::debug!("{:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
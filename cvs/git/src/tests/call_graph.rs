@@ -0,0 +1,239 @@
+use hyper_ast::{
+    position::TreePath,
+    store::{labels::LabelStore, nodes::DefaultNodeStore as NodeStore, SimpleStores},
+};
+use hyper_ast_gen_ts_java::legion_with_refs as java_tree_gen;
+
+use crate::{
+    call_graph::{CallGraph, CallKind},
+    java::handle_java_file,
+    TStore,
+};
+
+static CASE_1: &'static str = r#"package p;
+class A {
+    B b;
+    A() { this(1); }
+    A(int i) {}
+    void m() { b.n(); n2(); new B().n(); java.util.Objects.hash(b); }
+    void n2() {}
+}
+class B { void n() {} }
+class C extends B { void n() { super.n(); } }
+"#;
+
+/// overloads with the same number of parameters, and a local variable shadowing a field in a block
+static CASE_OVERLOADS: &'static str = r#"package p;
+class A {
+    B b;
+    void f(int i) {}
+    void f(String s) {}
+    void f(B b) {}
+    void g(B b, long l) { f(1); f("s"); f(b); f(l); }
+    void h() { { String b = ""; f(b); } f(b); }
+    void f(long l) {}
+}
+class B {}
+"#;
+
+static CASE_INHERITANCE: &'static str = r#"package p;
+interface I { void run(); }
+class A implements I {
+    public void run() { helper(); }
+    void helper() {}
+}
+class B extends A {
+    void helper() {}
+    void go() { run(); }
+}
+class C {
+    void use(I i, A a) {
+        i.run();
+        a.helper();
+        Runnable r = new Runnable() {
+            public void run() { new B().go(); }
+        };
+        r.run();
+    }
+}
+"#;
+
+/// the bodies of the methods `m` are the same subtree
+static CASE_DUPLICATES: &'static str = r#"package p;
+class A { void m() { n(); } void n() {} }
+class B { void m() { n(); } void n() {} void o() {} }
+"#;
+
+fn make_call_graph(text: &str) -> CallGraph {
+    make_stores_and_call_graph(text).1
+}
+
+fn make_stores_and_call_graph(text: &str) -> (SimpleStores<TStore>, CallGraph) {
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: NodeStore::new(),
+    };
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = java_tree_gen::JavaTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+        md_cache: &mut md_cache,
+    };
+    let a = handle_java_file(&mut java_tree_gen, &b"A.java".into(), text.as_bytes()).unwrap();
+    let graph = CallGraph::new(&stores, &md_cache, a.local.compressed_node);
+    (stores, graph)
+}
+
+fn find(graph: &CallGraph, name: &str, arity: usize) -> usize {
+    graph
+        .find(name)
+        .find(|x| graph.methods[*x].arity == arity)
+        .unwrap()
+}
+
+fn callees(graph: &CallGraph, m: usize) -> Vec<(String, CallKind)> {
+    let mut r: Vec<_> = graph
+        .callees(m)
+        .map(|c| (graph.qualified_name(c.callee), c.kind))
+        .collect();
+    r.sort_by(|a, b| a.0.cmp(&b.0));
+    r.dedup();
+    r
+}
+
+#[test]
+fn call_graph_simple() {
+    let graph = make_call_graph(CASE_1);
+    assert_eq!(graph.types.len(), 3);
+    assert_eq!(graph.methods.len(), 6);
+    assert_eq!(
+        callees(&graph, find(&graph, "A.A", 0)),
+        vec![("p.A.A".to_string(), CallKind::Constructor)]
+    );
+    let a = find(&graph, "A.A", 0);
+    let this = graph.callees(a).next().unwrap().callee;
+    assert_eq!(graph.methods[this].arity, 1);
+    assert_eq!(
+        callees(&graph, find(&graph, "A.m", 0)),
+        vec![
+            ("p.A.n2".to_string(), CallKind::Direct),
+            ("p.B.n".to_string(), CallKind::Direct),
+            ("p.C.n".to_string(), CallKind::Override),
+        ]
+    );
+    assert_eq!(
+        callees(&graph, find(&graph, "C.n", 0)),
+        vec![("p.B.n".to_string(), CallKind::Direct)]
+    );
+    assert_eq!(graph.unresolved.len(), 1);
+    assert_eq!(graph.unresolved[0].name, "hash");
+
+    let around = graph.around([find(&graph, "C.n", 0)], 1);
+    assert_eq!(around.methods.len(), 3);
+    let mut dot = vec![];
+    around.write_dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.contains("[style=dashed]"));
+}
+
+#[test]
+fn call_graph_overloads() {
+    let graph = make_call_graph(CASE_OVERLOADS);
+    let parameters = |m| {
+        let mut r: Vec<_> = graph
+            .callees(m)
+            .map(|c| graph.methods[c.callee].params.clone())
+            .collect();
+        r.sort();
+        r
+    };
+    let ty = |x: &str| vec![Some(x.to_string())];
+    // one callee per invocation
+    assert_eq!(
+        parameters(find(&graph, "A.g", 2)),
+        vec![ty("B"), ty("String"), ty("int"), ty("long")]
+    );
+    // the local variable is only visible in its block
+    assert_eq!(
+        parameters(find(&graph, "A.h", 0)),
+        vec![ty("B"), ty("String")]
+    );
+    assert!(graph.unresolved.is_empty());
+}
+
+#[test]
+fn call_graph_inheritance() {
+    let graph = make_call_graph(CASE_INHERITANCE);
+    assert_eq!(
+        callees(&graph, find(&graph, "C.use", 2)),
+        vec![
+            ("p.A.helper".to_string(), CallKind::Direct),
+            ("p.A.run".to_string(), CallKind::Override),
+            ("p.B.helper".to_string(), CallKind::Override),
+            ("p.I.run".to_string(), CallKind::Direct),
+        ]
+    );
+    // the body of the anonymous class is not part of the enclosing method
+    assert_eq!(
+        callees(&graph, find(&graph, "C$1.run", 0)),
+        vec![("p.B.go".to_string(), CallKind::Direct)]
+    );
+    assert_eq!(
+        callees(&graph, find(&graph, "B.go", 0)),
+        vec![("p.A.run".to_string(), CallKind::Direct)]
+    );
+    let mut unresolved: Vec<_> = graph.unresolved.iter().map(|x| x.name.as_str()).collect();
+    unresolved.sort();
+    assert_eq!(unresolved, vec!["new Runnable", "run"]);
+}
+
+#[test]
+fn call_graph_duplicate_bodies() {
+    let graph = make_call_graph(CASE_DUPLICATES);
+    let a_m = find(&graph, "A.m", 0);
+    let b_m = find(&graph, "B.m", 0);
+    // the same subtree at distinct positions
+    assert_eq!(
+        graph.methods[a_m].position.node(),
+        graph.methods[b_m].position.node()
+    );
+    assert_ne!(a_m, b_m);
+    assert_eq!(
+        callees(&graph, a_m),
+        vec![("p.A.n".to_string(), CallKind::Direct)]
+    );
+    assert_eq!(
+        callees(&graph, b_m),
+        vec![("p.B.n".to_string(), CallKind::Direct)]
+    );
+    assert_eq!(graph.callers(find(&graph, "A.n", 0)).count(), 1);
+}
+
+#[test]
+fn call_graph_json_with_node_ids() {
+    let (stores, graph) = make_stores_and_call_graph(CASE_DUPLICATES);
+    let mut json = vec![];
+    graph.write_json(&stores, &mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let methods = json["methods"].as_array().unwrap();
+    let id = |m: usize| methods[m]["id"].as_u64().unwrap();
+    let a_m = find(&graph, "A.m", 0);
+    let b_m = find(&graph, "B.m", 0);
+    // distinct methods, but the same node
+    assert_eq!(id(a_m), id(b_m));
+    assert_ne!(methods[a_m]["position"], methods[b_m]["position"]);
+    assert_ne!(id(find(&graph, "B.n", 0)), id(find(&graph, "B.o", 0)));
+
+    let calls = json["calls"].as_array().unwrap();
+    assert_eq!(calls.len(), 2);
+    for c in calls {
+        let caller = c["caller"].as_u64().unwrap() as usize;
+        let callee = c["callee"].as_u64().unwrap() as usize;
+        assert_eq!(c["caller_id"].as_u64(), Some(id(caller)));
+        assert_eq!(c["callee_id"].as_u64(), Some(id(callee)));
+    }
+    // the invocations `n()` are identical subtrees
+    assert_eq!(calls[0]["site_id"], calls[1]["site_id"]);
+    assert_ne!(calls[0]["site"], calls[1]["site"]);
+}
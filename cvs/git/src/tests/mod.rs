@@ -1,3 +1,4 @@
+pub mod call_graph;
 pub mod direct_type_ref;
 #[cfg(test)]
 pub mod extends_package_local;
@@ -13,6 +14,24 @@ use hyper_ast_gen_ts_java::impact::{
     partial_analysis::PartialAnalysis,
 };
 
+/// Commits nothing, only writes the tree of `files` in a fresh repository.
+pub(crate) fn repository(name: &str, files: &[(&str, &str)]) -> (git2::Repository, git2::Oid) {
+    let dir = std::env::temp_dir().join(format!("hyperast-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let repository = git2::Repository::init(&dir).unwrap();
+    for (path, text) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+    let mut index = repository.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = index.write_tree().unwrap();
+    (repository, tree)
+}
+
 #[test]
 fn example_main() {
     let args: Vec<String> = env::args().collect();
@@ -0,0 +1,31 @@
+use crate::{processing::ObjectName, TStore, PROPAGATE_ERROR_ON_BAD_CST_NODE};
+
+use hyper_ast_gen_ts_ts::legion as ts_tree_gen;
+
+/// NOTE .js files are also parsed with the typescript grammar,
+/// .tsx files are parsed with the tsx grammar for their jsx elements.
+pub(crate) fn handle_ts_file<'stores, 'cache, 'b: 'stores>(
+    tree_gen: &mut ts_tree_gen::TsTreeGen<'stores, 'cache, TStore>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<ts_tree_gen::FNode, ()> {
+    let parsed = if name.as_bytes().ends_with(b".tsx") {
+        ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse_tsx(text)
+    } else {
+        ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse(text)
+    };
+    let tree = match parsed {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST");
+            log::debug!("{:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
@@ -0,0 +1,159 @@
+use git2::{Oid, Repository};
+use hyper_ast_gen_ts_ts::legion as ts_gen;
+
+use crate::{
+    npm::NpmModuleAcc,
+    preprocessed::{IsSkippedAna, RepositoryProcessor},
+    processing::{CacheHolding, ObjectName},
+};
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+pub(crate) struct TsProcessorHolder(Option<TsProc>);
+impl Default for TsProcessorHolder {
+    fn default() -> Self {
+        Self(Some(TsProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+pub(crate) struct TsProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Ts,
+}
+impl crate::processing::erased::Parametrized for TsProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(TsProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+// TODO should not have to impl this trait
+impl crate::processing::erased::CommitProc for TsProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for TsProc {
+    type Holder = TsProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for TsProcessorHolder {
+    type Proc = TsProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+}
+impl CacheHolding<crate::processing::caches::Ts> for TsProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Ts {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Ts {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Ts> for TsProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Ts {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Ts {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(feature = "ts")]
+impl RepositoryProcessor {
+    pub(crate) fn handle_ts_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Result<(ts_gen::Local, IsSkippedAna), crate::ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::Ts>()
+            .handle2(oid, repository, &name, parameters, |c, n, t| {
+                let line_break = if t.contains(&b'\r') {
+                    "\r\n".as_bytes().to_vec()
+                } else {
+                    "\n".as_bytes().to_vec()
+                };
+                crate::ts::handle_ts_file(
+                    &mut ts_gen::TsTreeGen {
+                        line_break,
+                        stores: &mut self.main_stores,
+                        md_cache: &mut c
+                            .mut_or_default::<TsProcessorHolder>()
+                            .get_caches_mut()
+                            .md_cache,
+                    },
+                    n,
+                    t,
+                )
+                .map_err(|_| crate::ParseErr::IllFormed)
+                .map(|x| (x.local.clone(), true))
+            })
+    }
+
+    pub(crate) fn help_handle_ts_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut NpmModuleAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let (full_node, _skiped_ana) = self.handle_ts_blob(oid, name, repository, parameters)?;
+        let name = self.intern_object_name(name);
+        assert!(!parent.children_names.contains(&name));
+        parent.push_source_file(name, full_node);
+        Ok(())
+    }
+}
@@ -129,3 +129,93 @@ impl TypeMap3<Box<dyn GG>> {
             .or_insert_with(|| Box::new(T::default()).to_gg()).as_mut()
     }
 }
+
+/// Matches a path segment against a segment of a workspace pattern, where `*` matches any sequence of characters.
+fn matches_segment(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// Advances the workspace patterns of a directory into its child directory `name`,
+/// eg. the `workspaces` of a package.json or the `members` of a Cargo.toml.
+///
+/// Returns if the child is designated as a package and the patterns that remain to match inside it.
+pub(crate) fn step_workspaces(patterns: &[String], name: &str) -> (bool, Vec<String>) {
+    let mut is_package = false;
+    let mut remaining = vec![];
+    let mut push = |pattern: &str| {
+        if pattern.is_empty() {
+            is_package = true;
+        } else if !remaining.iter().any(|x| x == pattern) {
+            remaining.push(pattern.to_string());
+        }
+    };
+    for pattern in patterns {
+        let (head, tail) = pattern.split_once('/').unwrap_or((pattern, ""));
+        if head == "**" {
+            // matches any number of directories
+            push(pattern);
+            if tail.is_empty() {
+                push(tail);
+            } else if matches_segment(tail.split('/').next().unwrap_or_default(), name) {
+                push(tail.split_once('/').map_or("", |x| x.1));
+            }
+        } else if matches_segment(head, name) {
+            push(tail);
+        }
+    }
+    (is_package, remaining)
+}
+
+/// A leaf holding the whole text of a file, for files without a grammar.
+#[cfg(any(feature = "any", feature = "npm", feature = "pip", feature = "cargo"))]
+pub(crate) fn make_text(
+    text: &str,
+    stores: &mut crate::SimpleStores,
+) -> (
+    hyper_ast::store::defaults::NodeIdentifier,
+    crate::DefaultMetrics,
+) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::legion::NodeStore,
+        tree_gen::SubTreeMetrics,
+        types::LabelStore,
+    };
+    use hyper_ast_gen_ts_xml::{legion::eq_node, types::Type};
+    let hbuilder: hashed::Builder<hyper_ast::hashed::SyntaxNodeHashs<u32>> =
+        hashed::Builder::new(Default::default(), &Type::Text, text, 1);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(text);
+    let eq = eq_node(&Type::Text, Some(&label), &[]);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        let vacant = insertion.vacant();
+        NodeStore::insert_after_prepare(vacant, (Type::Text, label, hashs, BloomSize::None))
+    };
+    let metrics = SubTreeMetrics {
+        size: 1,
+        height: 1,
+        hashs,
+        size_no_spaces: 1,
+    };
+    (node_id, metrics)
+}
@@ -425,6 +425,22 @@ impl PartialAnalysis {
         self.solver.decls_count()
     }
 
+    /// The reference designated by the analyzed expression, in [`Self::solver`],
+    /// eg. the invocation of a method invocation or of an instance creation.
+    pub fn current_ref(&self) -> Option<RefPtr> {
+        match &self.current_node {
+            State::ScopedIdentifier(i)
+            | State::ScopedTypeIdentifier(i)
+            | State::FieldIdentifier(i)
+            | State::LiteralType(i)
+            | State::Invocation(i)
+            | State::ConstructorInvocation(i)
+            | State::This(i)
+            | State::Super(i) => Some(*i),
+            _ => None,
+        }
+    }
+
     pub fn init<F: FnMut(&str) -> RawLabelPtr>(
         kind: &Type,
         label: Option<&str>,
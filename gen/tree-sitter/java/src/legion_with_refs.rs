@@ -91,6 +91,13 @@ pub struct MD {
     mcc: Mcc,
 }
 
+impl MD {
+    /// The partial analysis of the subtree, resolved at declarations.
+    pub fn ana(&self) -> Option<&PartialAnalysis> {
+        self.ana.as_ref()
+    }
+}
+
 impl From<Local> for MD {
     fn from(x: Local) -> Self {
         MD {
@@ -0,0 +1,82 @@
+use hyper_ast::store::{labels::LabelStore, SimpleStores};
+
+use crate::{
+    legion::{tree_sitter_parse_make, MakeTreeGen},
+    types::TStore,
+};
+
+static CASE_1: &str = r#"CC = gcc
+CFLAGS := -Wall -O2
+SUBDIRS = lib tools
+SRCS = src/main.c src/util.c
+
+# build everything
+all: app $(SUBDIRS)
+
+app: $(SRCS:.c=.o)
+	$(CC) $(CFLAGS) -o $@ $^
+
+$(SUBDIRS):
+	$(MAKE) -C $@
+
+.PHONY: all $(SUBDIRS)
+"#;
+
+#[test]
+fn make_tree_sitter_simple() {
+    let tree = match tree_sitter_parse_make(CASE_1.as_bytes()) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    assert!(
+        !tree.root_node().has_error(),
+        "{}",
+        tree.root_node().to_sexp()
+    );
+}
+
+#[test]
+fn make_hyperast_serialize() {
+    let text = CASE_1.as_bytes();
+    let tree = match tree_sitter_parse_make(text) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: hyper_ast::store::nodes::legion::NodeStore::new(),
+    };
+    let mut tree_gen = MakeTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+    };
+    let x = tree_gen.generate_file(b"Makefile", text, tree.walk()).local;
+    let serialized = hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node).to_string();
+    assert_eq!(CASE_1, serialized);
+}
+
+#[test]
+fn type_test_generic_eq() {
+    use crate::types::Type;
+    use hyper_ast::types::HyperType;
+
+    let k = Type::Rule;
+    let k0 = Type::Rule;
+    let k1 = Type::VariableAssignment;
+    assert!(k.eq(&k0));
+    assert!(k.ne(&k1));
+    assert!(k.generic_eq(&k0));
+    assert!(!k.generic_eq(&k1));
+
+    // the name of a function is also the name of a named node
+    assert_eq!(Some(Type::Word), Type::from_str("word"));
+    assert_eq!(Some(Type::FnWord), Type::from_token("word"));
+    assert_eq!(Type::Rule, Type::resolve(Type::Rule as u16));
+
+    use hyper_ast::types::{AnyType, LangRef};
+    let any: &AnyType = LangRef::<AnyType>::make(&crate::types::Lang, Type::Rule as u16);
+    assert_eq!(any.to_string(), Type::Rule.to_string());
+    let t = LangRef::<AnyType>::to_u16(&crate::types::Lang, *any);
+    assert_eq!(Type::Rule as u16, t);
+}
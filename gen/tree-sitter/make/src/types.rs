@@ -0,0 +1,922 @@
+use std::fmt::Display;
+
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{AnyType, HyperType, LangRef, NodeId, TypeStore, TypeTrait, TypedNodeId},
+};
+
+#[cfg(feature = "legion")]
+mod legion_impls {
+    use super::*;
+
+    use crate::TNode;
+
+    impl<'a> TNode<'a> {
+        /// Kinds are resolved by name,
+        /// so that the node types do not depend on the ids of a particular revision of the grammar.
+        pub fn obtain_type<T>(&self, _: &mut impl MakeEnabledTypeStore<T>) -> Type {
+            let kind = self.0.kind();
+            let t = if self.0.is_named() {
+                Type::from_str(kind)
+            } else {
+                Type::from_token(kind)
+            };
+            t.unwrap_or(Type::ERROR)
+        }
+    }
+
+    use hyper_ast::{store::nodes::legion::HashedNodeRef, types::TypeIndex};
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            n.get_component::<Type>().unwrap().clone()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a> MakeEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        const LANG: TypeInternalSize = Self::Make as u16;
+
+        fn _intern(_l: u16, t: u16) -> Self::Ty {
+            Type::resolve(t)
+        }
+        fn intern(&self, t: Type) -> Self::Ty {
+            t
+        }
+
+        fn resolve(&self, t: Self::Ty) -> Type {
+            t
+        }
+    }
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            let t = n.get_component::<Type>().unwrap();
+            as_any(t)
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<Type>().unwrap() as u16,
+            }
+        }
+    }
+}
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Make as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <Make as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
+pub trait MakeEnabledTypeStore<T>: TypeStore<T> {
+    const LANG: u16;
+    fn intern(&self, t: Type) -> Self::Ty {
+        let t = t as u16;
+        Self::_intern(Self::LANG, t)
+    }
+    fn _intern(l: u16, t: u16) -> Self::Ty;
+    fn resolve(&self, t: Self::Ty) -> Type;
+}
+
+#[repr(u8)]
+pub enum TStore {
+    Make = 0,
+}
+
+impl Default for TStore {
+    fn default() -> Self {
+        Self::Make
+    }
+}
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TIdN<IdN>(IdN);
+
+impl<IdN: Clone + Eq + NodeId> NodeId for TIdN<IdN> {
+    type IdN = IdN;
+
+    fn as_id(&self) -> &Self::IdN {
+        &self.0
+    }
+
+    unsafe fn from_id(id: Self::IdN) -> Self {
+        Self(id)
+    }
+
+    unsafe fn from_ref_id(id: &Self::IdN) -> &Self {
+        std::mem::transmute(id)
+    }
+}
+
+impl<IdN: Clone + Eq + NodeId> TypedNodeId for TIdN<IdN> {
+    type Ty = Type;
+}
+
+type TypeInternalSize = u16;
+
+pub struct Lang;
+pub type Make = Lang;
+
+impl hyper_ast::types::Lang<Type> for Make {
+    fn make(t: u16) -> &'static Type {
+        Lang.make(t)
+    }
+    fn to_u16(t: Type) -> u16 {
+        Lang.to_u16(t)
+    }
+}
+
+impl LangRef<Type> for Make {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Make>()
+    }
+
+    fn make(&self, t: u16) -> &'static Type {
+        &S_T_L[t as usize]
+    }
+
+    fn to_u16(&self, t: Type) -> u16 {
+        t as u16
+    }
+}
+
+impl LangRef<AnyType> for Make {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Make>()
+    }
+
+    fn make(&self, t: u16) -> &'static AnyType {
+        // AnyType cannot be built in a const context, so the table is built on first use
+        static ANY_T_L: std::sync::OnceLock<Vec<AnyType>> = std::sync::OnceLock::new();
+        let any_t_l = ANY_T_L.get_or_init(|| S_T_L.iter().map(as_any).collect());
+        &any_t_l[t as usize]
+    }
+
+    fn to_u16(&self, t: AnyType) -> u16 {
+        let t = t.as_any().downcast_ref::<Type>().unwrap();
+        *t as u16
+    }
+}
+impl HyperType for Type {
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + PartialEq + Sized,
+    {
+        // Do a type-safe casting. If the types are different,
+        // return false, otherwise test the values for equality.
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn as_shared(&self) -> hyper_ast::types::Shared {
+        use hyper_ast::types::Shared;
+        match self {
+            Type::Comment => Shared::Comment,
+            _ => Shared::Other,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        let t = <Make as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <Make as hyper_ast::types::Lang<Type>>::make(t);
+        t
+    }
+
+    fn is_file(&self) -> bool {
+        self == &Type::Makefile
+    }
+
+    fn is_directory(&self) -> bool {
+        self == &Type::Directory || self == &Type::MakeDirectory
+    }
+
+    fn is_spaces(&self) -> bool {
+        self == &Type::Spaces
+    }
+
+    fn is_syntax(&self) -> bool {
+        self == &Type::Colon // ":"
+        || self == &Type::AmpColon // "&:"
+        || self == &Type::ColonColon // "::"
+        || self == &Type::Pipe // "|"
+        || self == &Type::SemiColon // ";"
+        || self == &Type::Eq // "="
+        || self == &Type::ColonEq // ":="
+        || self == &Type::ColonColonEq // "::="
+        || self == &Type::QMarkEq // "?="
+        || self == &Type::PlusEq // "+="
+        || self == &Type::BangEq // "!="
+        || self == &Type::At // "@"
+        || self == &Type::Dash // "-"
+        || self == &Type::Plus // "+"
+        || self == &Type::Dollar // "$"
+        || self == &Type::DollarDollar // "$$"
+        || self == &Type::LParen // "("
+        || self == &Type::RParen // ")"
+        || self == &Type::LBrace // "{"
+        || self == &Type::RBrace // "}"
+        || self == &Type::Comma // ","
+        || self == &Type::Percent // "%"
+        || self == &Type::LT // "<"
+        || self == &Type::QMark // "?"
+        || self == &Type::Caret // "^"
+        || self == &Type::Star // "*"
+        || self == &Type::Slash // "/"
+        || self == &Type::TSD // "D"
+        || self == &Type::TSF // "F"
+        || self == &Type::DQuote // "\""
+        || self == &Type::SQuote // "'"
+        || self == &Type::BSlash // "\\"
+        || self == &Type::Define // "define"
+        || self == &Type::Endef // "endef"
+        || self == &Type::Include // "include"
+        || self == &Type::DashInclude // "-include"
+        || self == &Type::Sinclude // "sinclude"
+        || self == &Type::Vpath // "vpath"
+        || self == &Type::Export // "export"
+        || self == &Type::Unexport // "unexport"
+        || self == &Type::Override // "override"
+        || self == &Type::Undefine // "undefine"
+        || self == &Type::Private // "private"
+        || self == &Type::Ifeq // "ifeq"
+        || self == &Type::Ifneq // "ifneq"
+        || self == &Type::Ifdef // "ifdef"
+        || self == &Type::Ifndef // "ifndef"
+        || self == &Type::Else // "else"
+        || self == &Type::Endif // "endif"
+    }
+
+    fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+    }
+}
+
+impl TypeTrait for Type {
+    type Lang = Make;
+
+    fn is_fork(&self) -> bool {
+        false
+    }
+
+    fn is_literal(&self) -> bool {
+        false
+    }
+
+    fn is_primitive(&self) -> bool {
+        false
+    }
+
+    fn is_type_declaration(&self) -> bool {
+        false
+    }
+
+    fn is_identifier(&self) -> bool {
+        self == &Type::Word
+    }
+
+    fn is_instance_ref(&self) -> bool {
+        false
+    }
+
+    fn is_type_body(&self) -> bool {
+        false
+    }
+
+    fn is_value_member(&self) -> bool {
+        false
+    }
+
+    fn is_executable_member(&self) -> bool {
+        false
+    }
+
+    fn is_statement(&self) -> bool {
+        false
+    }
+
+    fn is_declarative_statement(&self) -> bool {
+        false
+    }
+
+    fn is_structural_statement(&self) -> bool {
+        false
+    }
+
+    fn is_block_related(&self) -> bool {
+        false
+    }
+
+    fn is_simple_statement(&self) -> bool {
+        false
+    }
+
+    fn is_local_declare(&self) -> bool {
+        false
+    }
+
+    fn is_parameter(&self) -> bool {
+        false
+    }
+
+    fn is_parameter_list(&self) -> bool {
+        false
+    }
+
+    fn is_argument_list(&self) -> bool {
+        false
+    }
+
+    fn is_expression(&self) -> bool {
+        false
+    }
+
+    fn is_comment(&self) -> bool {
+        self == &Type::Comment
+    }
+}
+
+impl Type {
+    pub fn resolve(t: u16) -> Self {
+        assert!(t < COUNT);
+        unsafe { std::mem::transmute(t) }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Type::to_str(*self))
+    }
+}
+
+#[repr(u16)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Type {
+    Makefile,
+    Comment,
+    Rule,
+    Targets,
+    Prerequisites,
+    Recipe,
+    RecipeLine,
+    ShellText,
+    VariableAssignment,
+    ShellAssignment,
+    DefineDirective,
+    IncludeDirective,
+    VpathDirective,
+    ExportDirective,
+    UnexportDirective,
+    OverrideDirective,
+    UndefineDirective,
+    PrivateDirective,
+    Conditional,
+    IfeqDirective,
+    IfneqDirective,
+    IfdefDirective,
+    IfndefDirective,
+    ElseDirective,
+    VariableReference,
+    SubstitutionReference,
+    AutomaticVariable,
+    FunctionCall,
+    ShellFunction,
+    ShellCommand,
+    Arguments,
+    List,
+    Paths,
+    Concatenation,
+    Word,
+    Archive,
+    String,
+    Text,
+    RawText,
+    Colon,
+    AmpColon,
+    ColonColon,
+    Pipe,
+    SemiColon,
+    Eq,
+    ColonEq,
+    ColonColonEq,
+    QMarkEq,
+    PlusEq,
+    BangEq,
+    At,
+    Dash,
+    Plus,
+    Dollar,
+    DollarDollar,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+    Percent,
+    LT,
+    QMark,
+    Caret,
+    Star,
+    Slash,
+    TSD,
+    TSF,
+    DQuote,
+    SQuote,
+    BSlash,
+    Define,
+    Endef,
+    Include,
+    DashInclude,
+    Sinclude,
+    Vpath,
+    Export,
+    Unexport,
+    Override,
+    Undefine,
+    Private,
+    Ifeq,
+    Ifneq,
+    Ifdef,
+    Ifndef,
+    Else,
+    Endif,
+    FnSubst,
+    FnPatsubst,
+    FnStrip,
+    FnFindstring,
+    FnFilter,
+    FnFilterOut,
+    FnSort,
+    FnWord,
+    FnWords,
+    FnWordlist,
+    FnFirstword,
+    FnLastword,
+    FnDir,
+    FnNotdir,
+    FnSuffix,
+    FnBasename,
+    FnAddsuffix,
+    FnAddprefix,
+    FnJoin,
+    FnWildcard,
+    FnRealpath,
+    FnAbspath,
+    FnError,
+    FnWarning,
+    FnInfo,
+    FnOrigin,
+    FnFlavor,
+    FnForeach,
+    FnIf,
+    FnOr,
+    FnAnd,
+    FnCall,
+    FnEval,
+    FnFile,
+    FnValue,
+    FnShell,
+    Spaces,
+    MakeDirectory,
+    Directory,
+    ERROR,
+}
+impl Type {
+    /// resolves a named node kind of the grammar
+    pub fn from_str(t: &str) -> Option<Type> {
+        Some(match t {
+            "makefile" => Type::Makefile,
+            "comment" => Type::Comment,
+            "rule" => Type::Rule,
+            "targets" => Type::Targets,
+            "prerequisites" => Type::Prerequisites,
+            "recipe" => Type::Recipe,
+            "recipe_line" => Type::RecipeLine,
+            "shell_text" => Type::ShellText,
+            "variable_assignment" => Type::VariableAssignment,
+            "shell_assignment" => Type::ShellAssignment,
+            "define_directive" => Type::DefineDirective,
+            "include_directive" => Type::IncludeDirective,
+            "vpath_directive" => Type::VpathDirective,
+            "export_directive" => Type::ExportDirective,
+            "unexport_directive" => Type::UnexportDirective,
+            "override_directive" => Type::OverrideDirective,
+            "undefine_directive" => Type::UndefineDirective,
+            "private_directive" => Type::PrivateDirective,
+            "conditional" => Type::Conditional,
+            "ifeq_directive" => Type::IfeqDirective,
+            "ifneq_directive" => Type::IfneqDirective,
+            "ifdef_directive" => Type::IfdefDirective,
+            "ifndef_directive" => Type::IfndefDirective,
+            "else_directive" => Type::ElseDirective,
+            "variable_reference" => Type::VariableReference,
+            "substitution_reference" => Type::SubstitutionReference,
+            "automatic_variable" => Type::AutomaticVariable,
+            "function_call" => Type::FunctionCall,
+            "shell_function" => Type::ShellFunction,
+            "shell_command" => Type::ShellCommand,
+            "arguments" => Type::Arguments,
+            "list" => Type::List,
+            "paths" => Type::Paths,
+            "concatenation" => Type::Concatenation,
+            "word" => Type::Word,
+            "archive" => Type::Archive,
+            "string" => Type::String,
+            "text" => Type::Text,
+            "raw_text" => Type::RawText,
+            "ERROR" => Type::ERROR,
+            _ => return None,
+        })
+    }
+    /// resolves an anonymous node kind of the grammar, ie. keywords and punctuation
+    pub fn from_token(t: &str) -> Option<Type> {
+        Some(match t {
+            ":" => Type::Colon,
+            "&:" => Type::AmpColon,
+            "::" => Type::ColonColon,
+            "|" => Type::Pipe,
+            ";" => Type::SemiColon,
+            "=" => Type::Eq,
+            ":=" => Type::ColonEq,
+            "::=" => Type::ColonColonEq,
+            "?=" => Type::QMarkEq,
+            "+=" => Type::PlusEq,
+            "!=" => Type::BangEq,
+            "@" => Type::At,
+            "-" => Type::Dash,
+            "+" => Type::Plus,
+            "$" => Type::Dollar,
+            "$$" => Type::DollarDollar,
+            "(" => Type::LParen,
+            ")" => Type::RParen,
+            "{" => Type::LBrace,
+            "}" => Type::RBrace,
+            "," => Type::Comma,
+            "%" => Type::Percent,
+            "<" => Type::LT,
+            "?" => Type::QMark,
+            "^" => Type::Caret,
+            "*" => Type::Star,
+            "/" => Type::Slash,
+            "D" => Type::TSD,
+            "F" => Type::TSF,
+            "\"" => Type::DQuote,
+            "'" => Type::SQuote,
+            "\\" => Type::BSlash,
+            "define" => Type::Define,
+            "endef" => Type::Endef,
+            "include" => Type::Include,
+            "-include" => Type::DashInclude,
+            "sinclude" => Type::Sinclude,
+            "vpath" => Type::Vpath,
+            "export" => Type::Export,
+            "unexport" => Type::Unexport,
+            "override" => Type::Override,
+            "undefine" => Type::Undefine,
+            "private" => Type::Private,
+            "ifeq" => Type::Ifeq,
+            "ifneq" => Type::Ifneq,
+            "ifdef" => Type::Ifdef,
+            "ifndef" => Type::Ifndef,
+            "else" => Type::Else,
+            "endif" => Type::Endif,
+            "subst" => Type::FnSubst,
+            "patsubst" => Type::FnPatsubst,
+            "strip" => Type::FnStrip,
+            "findstring" => Type::FnFindstring,
+            "filter" => Type::FnFilter,
+            "filter-out" => Type::FnFilterOut,
+            "sort" => Type::FnSort,
+            "word" => Type::FnWord,
+            "words" => Type::FnWords,
+            "wordlist" => Type::FnWordlist,
+            "firstword" => Type::FnFirstword,
+            "lastword" => Type::FnLastword,
+            "dir" => Type::FnDir,
+            "notdir" => Type::FnNotdir,
+            "suffix" => Type::FnSuffix,
+            "basename" => Type::FnBasename,
+            "addsuffix" => Type::FnAddsuffix,
+            "addprefix" => Type::FnAddprefix,
+            "join" => Type::FnJoin,
+            "wildcard" => Type::FnWildcard,
+            "realpath" => Type::FnRealpath,
+            "abspath" => Type::FnAbspath,
+            "error" => Type::FnError,
+            "warning" => Type::FnWarning,
+            "info" => Type::FnInfo,
+            "origin" => Type::FnOrigin,
+            "flavor" => Type::FnFlavor,
+            "foreach" => Type::FnForeach,
+            "if" => Type::FnIf,
+            "or" => Type::FnOr,
+            "and" => Type::FnAnd,
+            "call" => Type::FnCall,
+            "eval" => Type::FnEval,
+            "file" => Type::FnFile,
+            "value" => Type::FnValue,
+            "shell" => Type::FnShell,
+            _ => return None,
+        })
+    }
+    pub fn to_str(t: Type) -> &'static str {
+        match t {
+            Type::Makefile => "makefile",
+            Type::Comment => "comment",
+            Type::Rule => "rule",
+            Type::Targets => "targets",
+            Type::Prerequisites => "prerequisites",
+            Type::Recipe => "recipe",
+            Type::RecipeLine => "recipe_line",
+            Type::ShellText => "shell_text",
+            Type::VariableAssignment => "variable_assignment",
+            Type::ShellAssignment => "shell_assignment",
+            Type::DefineDirective => "define_directive",
+            Type::IncludeDirective => "include_directive",
+            Type::VpathDirective => "vpath_directive",
+            Type::ExportDirective => "export_directive",
+            Type::UnexportDirective => "unexport_directive",
+            Type::OverrideDirective => "override_directive",
+            Type::UndefineDirective => "undefine_directive",
+            Type::PrivateDirective => "private_directive",
+            Type::Conditional => "conditional",
+            Type::IfeqDirective => "ifeq_directive",
+            Type::IfneqDirective => "ifneq_directive",
+            Type::IfdefDirective => "ifdef_directive",
+            Type::IfndefDirective => "ifndef_directive",
+            Type::ElseDirective => "else_directive",
+            Type::VariableReference => "variable_reference",
+            Type::SubstitutionReference => "substitution_reference",
+            Type::AutomaticVariable => "automatic_variable",
+            Type::FunctionCall => "function_call",
+            Type::ShellFunction => "shell_function",
+            Type::ShellCommand => "shell_command",
+            Type::Arguments => "arguments",
+            Type::List => "list",
+            Type::Paths => "paths",
+            Type::Concatenation => "concatenation",
+            Type::Word => "word",
+            Type::Archive => "archive",
+            Type::String => "string",
+            Type::Text => "text",
+            Type::RawText => "raw_text",
+            Type::Colon => ":",
+            Type::AmpColon => "&:",
+            Type::ColonColon => "::",
+            Type::Pipe => "|",
+            Type::SemiColon => ";",
+            Type::Eq => "=",
+            Type::ColonEq => ":=",
+            Type::ColonColonEq => "::=",
+            Type::QMarkEq => "?=",
+            Type::PlusEq => "+=",
+            Type::BangEq => "!=",
+            Type::At => "@",
+            Type::Dash => "-",
+            Type::Plus => "+",
+            Type::Dollar => "$",
+            Type::DollarDollar => "$$",
+            Type::LParen => "(",
+            Type::RParen => ")",
+            Type::LBrace => "{",
+            Type::RBrace => "}",
+            Type::Comma => ",",
+            Type::Percent => "%",
+            Type::LT => "<",
+            Type::QMark => "?",
+            Type::Caret => "^",
+            Type::Star => "*",
+            Type::Slash => "/",
+            Type::TSD => "D",
+            Type::TSF => "F",
+            Type::DQuote => "\"",
+            Type::SQuote => "'",
+            Type::BSlash => "\\",
+            Type::Define => "define",
+            Type::Endef => "endef",
+            Type::Include => "include",
+            Type::DashInclude => "-include",
+            Type::Sinclude => "sinclude",
+            Type::Vpath => "vpath",
+            Type::Export => "export",
+            Type::Unexport => "unexport",
+            Type::Override => "override",
+            Type::Undefine => "undefine",
+            Type::Private => "private",
+            Type::Ifeq => "ifeq",
+            Type::Ifneq => "ifneq",
+            Type::Ifdef => "ifdef",
+            Type::Ifndef => "ifndef",
+            Type::Else => "else",
+            Type::Endif => "endif",
+            Type::FnSubst => "subst",
+            Type::FnPatsubst => "patsubst",
+            Type::FnStrip => "strip",
+            Type::FnFindstring => "findstring",
+            Type::FnFilter => "filter",
+            Type::FnFilterOut => "filter-out",
+            Type::FnSort => "sort",
+            Type::FnWord => "word",
+            Type::FnWords => "words",
+            Type::FnWordlist => "wordlist",
+            Type::FnFirstword => "firstword",
+            Type::FnLastword => "lastword",
+            Type::FnDir => "dir",
+            Type::FnNotdir => "notdir",
+            Type::FnSuffix => "suffix",
+            Type::FnBasename => "basename",
+            Type::FnAddsuffix => "addsuffix",
+            Type::FnAddprefix => "addprefix",
+            Type::FnJoin => "join",
+            Type::FnWildcard => "wildcard",
+            Type::FnRealpath => "realpath",
+            Type::FnAbspath => "abspath",
+            Type::FnError => "error",
+            Type::FnWarning => "warning",
+            Type::FnInfo => "info",
+            Type::FnOrigin => "origin",
+            Type::FnFlavor => "flavor",
+            Type::FnForeach => "foreach",
+            Type::FnIf => "if",
+            Type::FnOr => "or",
+            Type::FnAnd => "and",
+            Type::FnCall => "call",
+            Type::FnEval => "eval",
+            Type::FnFile => "file",
+            Type::FnValue => "value",
+            Type::FnShell => "shell",
+            Type::Spaces => "Spaces",
+            Type::MakeDirectory => "MakeDirectory",
+            Type::Directory => "Directory",
+            Type::ERROR => "ERROR",
+        }
+    }
+}
+
+const COUNT: u16 = 128;
+
+const S_T_L: &'static [Type] = &[
+    Type::Makefile,
+    Type::Comment,
+    Type::Rule,
+    Type::Targets,
+    Type::Prerequisites,
+    Type::Recipe,
+    Type::RecipeLine,
+    Type::ShellText,
+    Type::VariableAssignment,
+    Type::ShellAssignment,
+    Type::DefineDirective,
+    Type::IncludeDirective,
+    Type::VpathDirective,
+    Type::ExportDirective,
+    Type::UnexportDirective,
+    Type::OverrideDirective,
+    Type::UndefineDirective,
+    Type::PrivateDirective,
+    Type::Conditional,
+    Type::IfeqDirective,
+    Type::IfneqDirective,
+    Type::IfdefDirective,
+    Type::IfndefDirective,
+    Type::ElseDirective,
+    Type::VariableReference,
+    Type::SubstitutionReference,
+    Type::AutomaticVariable,
+    Type::FunctionCall,
+    Type::ShellFunction,
+    Type::ShellCommand,
+    Type::Arguments,
+    Type::List,
+    Type::Paths,
+    Type::Concatenation,
+    Type::Word,
+    Type::Archive,
+    Type::String,
+    Type::Text,
+    Type::RawText,
+    Type::Colon,
+    Type::AmpColon,
+    Type::ColonColon,
+    Type::Pipe,
+    Type::SemiColon,
+    Type::Eq,
+    Type::ColonEq,
+    Type::ColonColonEq,
+    Type::QMarkEq,
+    Type::PlusEq,
+    Type::BangEq,
+    Type::At,
+    Type::Dash,
+    Type::Plus,
+    Type::Dollar,
+    Type::DollarDollar,
+    Type::LParen,
+    Type::RParen,
+    Type::LBrace,
+    Type::RBrace,
+    Type::Comma,
+    Type::Percent,
+    Type::LT,
+    Type::QMark,
+    Type::Caret,
+    Type::Star,
+    Type::Slash,
+    Type::TSD,
+    Type::TSF,
+    Type::DQuote,
+    Type::SQuote,
+    Type::BSlash,
+    Type::Define,
+    Type::Endef,
+    Type::Include,
+    Type::DashInclude,
+    Type::Sinclude,
+    Type::Vpath,
+    Type::Export,
+    Type::Unexport,
+    Type::Override,
+    Type::Undefine,
+    Type::Private,
+    Type::Ifeq,
+    Type::Ifneq,
+    Type::Ifdef,
+    Type::Ifndef,
+    Type::Else,
+    Type::Endif,
+    Type::FnSubst,
+    Type::FnPatsubst,
+    Type::FnStrip,
+    Type::FnFindstring,
+    Type::FnFilter,
+    Type::FnFilterOut,
+    Type::FnSort,
+    Type::FnWord,
+    Type::FnWords,
+    Type::FnWordlist,
+    Type::FnFirstword,
+    Type::FnLastword,
+    Type::FnDir,
+    Type::FnNotdir,
+    Type::FnSuffix,
+    Type::FnBasename,
+    Type::FnAddsuffix,
+    Type::FnAddprefix,
+    Type::FnJoin,
+    Type::FnWildcard,
+    Type::FnRealpath,
+    Type::FnAbspath,
+    Type::FnError,
+    Type::FnWarning,
+    Type::FnInfo,
+    Type::FnOrigin,
+    Type::FnFlavor,
+    Type::FnForeach,
+    Type::FnIf,
+    Type::FnOr,
+    Type::FnAnd,
+    Type::FnCall,
+    Type::FnEval,
+    Type::FnFile,
+    Type::FnValue,
+    Type::FnShell,
+    Type::Spaces,
+    Type::MakeDirectory,
+    Type::Directory,
+    Type::ERROR,
+];
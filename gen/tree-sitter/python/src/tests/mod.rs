@@ -0,0 +1,128 @@
+use hyper_ast::store::{labels::LabelStore, SimpleStores};
+
+use crate::{
+    legion::{tree_sitter_parse_python, PythonTreeGen},
+    types::TStore,
+};
+
+static CASE_1: &str = r#"import os
+from typing import Optional
+
+# a comment
+class Greeter(object):
+    """Greets people."""
+
+    def __init__(self, name: str, excited=False):
+        self.name = name
+        self.excited = excited
+
+    @property
+    def greeting(self) -> Optional[str]:
+        if not self.name:
+            return None
+        suffix = "!" if self.excited else "."
+        return f"Hello {self.name}{suffix}\n"
+
+
+def main(*args, **kwargs):
+    for x in [y * 2 for y in range(3)]:
+        print(x, os.sep)
+    with open("a.txt") as f:
+        return lambda: f.read()
+"#;
+
+#[test]
+fn python_tree_sitter_simple() {
+    let tree = match tree_sitter_parse_python(CASE_1.as_bytes()) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    assert!(
+        !tree.root_node().has_error(),
+        "{}",
+        tree.root_node().to_sexp()
+    );
+}
+
+#[test]
+fn python_hyperast_serialize() {
+    let text = CASE_1.as_bytes();
+    let tree = match tree_sitter_parse_python(text) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: hyper_ast::store::nodes::legion::NodeStore::new(),
+    };
+    let mut tree_gen = PythonTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+    };
+    let x = tree_gen
+        .generate_file(b"greeter.py", text, tree.walk())
+        .local;
+    let serialized = hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node).to_string();
+    assert_eq!(CASE_1, serialized);
+}
+
+#[test]
+fn type_test_generic_eq() {
+    use crate::types::Type;
+    use hyper_ast::types::HyperType;
+
+    let k = Type::FunctionDefinition;
+    let k0 = Type::FunctionDefinition;
+    let k1 = Type::ClassDefinition;
+    assert!(k.eq(&k0));
+    assert!(k.ne(&k1));
+    assert!(k.generic_eq(&k0));
+    assert!(!k.generic_eq(&k1));
+
+    // some keywords are also the names of named nodes
+    assert_eq!(Some(Type::Await), Type::from_str("await"));
+    assert_eq!(Some(Type::TSAwait), Type::from_token("await"));
+    assert_eq!(Type::Module, Type::resolve(Type::Module as u16));
+
+    use hyper_ast::types::{AnyType, LangRef};
+    let any: &AnyType = LangRef::<AnyType>::make(&crate::types::Lang, Type::Module as u16);
+    assert_eq!(any.to_string(), Type::Module.to_string());
+    let t = LangRef::<AnyType>::to_u16(&crate::types::Lang, *any);
+    assert_eq!(Type::Module as u16, t);
+}
+
+/// Checks that every node of the tree has a type, recursively.
+fn assert_typed(node: tree_sitter::Node) -> usize {
+    use crate::types::Type;
+    let t = Type::from_u16(node.kind_id());
+    assert_ne!(t, Type::ERROR, "{}", node.to_sexp());
+    assert_eq!(t.to_string(), node.kind());
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor);
+    1 + children.map(assert_typed).sum::<usize>()
+}
+
+#[test]
+fn python_types_without_error() {
+    let tree = match tree_sitter_parse_python(CASE_1.as_bytes()) {
+        Ok(t) => t,
+        Err(t) => panic!("{}", t.root_node().to_sexp()),
+    };
+    assert!(assert_typed(tree.root_node()) > 100);
+}
+
+#[test]
+fn python_types_of_grammar() {
+    use crate::types::Type;
+    let language = tree_sitter_python::language();
+    for id in 0..language.node_kind_count() as u16 {
+        let kind = language.node_kind_for_id(id).unwrap();
+        let named = language.node_kind_is_named(id);
+        // the ids of hidden symbols and of duplicated names are never the kinds of nodes
+        if !language.node_kind_is_visible(id) || language.id_for_node_kind(kind, named) != id {
+            continue;
+        }
+        assert_eq!(Type::from_u16(id).to_string(), kind, "{}", id);
+    }
+}
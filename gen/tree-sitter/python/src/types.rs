@@ -0,0 +1,1603 @@
+use std::fmt::Display;
+
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{AnyType, HyperType, LangRef, NodeId, TypeStore, TypeTrait, TypedNodeId},
+};
+
+#[cfg(feature = "legion")]
+mod legion_impls {
+    use super::*;
+
+    use crate::TNode;
+
+    impl<'a> TNode<'a> {
+        pub fn obtain_type<T>(&self, _: &mut impl PythonEnabledTypeStore<T>) -> Type {
+            let t = self.0.kind_id();
+            Type::from_u16(t)
+        }
+    }
+
+    use hyper_ast::{store::nodes::legion::HashedNodeRef, types::TypeIndex};
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            n.get_component::<Type>().unwrap().clone()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a> PythonEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        const LANG: TypeInternalSize = Self::Python as u16;
+
+        fn _intern(_l: u16, t: u16) -> Self::Ty {
+            Type::resolve(t)
+        }
+        fn intern(&self, t: Type) -> Self::Ty {
+            t
+        }
+
+        fn resolve(&self, t: Self::Ty) -> Type {
+            t
+        }
+    }
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            let t = n.get_component::<Type>().unwrap();
+            as_any(t)
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<Type>().unwrap() as u16,
+            }
+        }
+    }
+}
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Python as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <Python as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
+pub trait PythonEnabledTypeStore<T>: TypeStore<T> {
+    const LANG: u16;
+    fn intern(&self, t: Type) -> Self::Ty {
+        let t = t as u16;
+        Self::_intern(Self::LANG, t)
+    }
+    fn _intern(l: u16, t: u16) -> Self::Ty;
+    fn resolve(&self, t: Self::Ty) -> Type;
+}
+
+#[repr(u8)]
+pub enum TStore {
+    Python = 0,
+}
+
+impl Default for TStore {
+    fn default() -> Self {
+        Self::Python
+    }
+}
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TIdN<IdN>(IdN);
+
+impl<IdN: Clone + Eq + NodeId> NodeId for TIdN<IdN> {
+    type IdN = IdN;
+
+    fn as_id(&self) -> &Self::IdN {
+        &self.0
+    }
+
+    unsafe fn from_id(id: Self::IdN) -> Self {
+        Self(id)
+    }
+
+    unsafe fn from_ref_id(id: &Self::IdN) -> &Self {
+        std::mem::transmute(id)
+    }
+}
+
+impl<IdN: Clone + Eq + NodeId> TypedNodeId for TIdN<IdN> {
+    type Ty = Type;
+}
+
+type TypeInternalSize = u16;
+
+pub struct Lang;
+pub type Python = Lang;
+
+impl hyper_ast::types::Lang<Type> for Python {
+    fn make(t: u16) -> &'static Type {
+        Lang.make(t)
+    }
+    fn to_u16(t: Type) -> u16 {
+        Lang.to_u16(t)
+    }
+}
+
+impl LangRef<Type> for Python {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Python>()
+    }
+
+    fn make(&self, t: u16) -> &'static Type {
+        &S_T_L[t as usize]
+    }
+
+    fn to_u16(&self, t: Type) -> u16 {
+        t as u16
+    }
+}
+
+impl LangRef<AnyType> for Python {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Python>()
+    }
+
+    fn make(&self, t: u16) -> &'static AnyType {
+        // AnyType cannot be built in a const context, so the table is built on first use
+        static ANY_T_L: std::sync::OnceLock<Vec<AnyType>> = std::sync::OnceLock::new();
+        let any_t_l = ANY_T_L.get_or_init(|| S_T_L.iter().map(as_any).collect());
+        &any_t_l[t as usize]
+    }
+
+    fn to_u16(&self, t: AnyType) -> u16 {
+        let t = t.as_any().downcast_ref::<Type>().unwrap();
+        *t as u16
+    }
+}
+impl HyperType for Type {
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + PartialEq + Sized,
+    {
+        // Do a type-safe casting. If the types are different,
+        // return false, otherwise test the values for equality.
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn as_shared(&self) -> hyper_ast::types::Shared {
+        use hyper_ast::types::Shared;
+        match self {
+            Type::Comment => Shared::Comment,
+            Type::Identifier => Shared::Identifier,
+            Type::ClassDefinition => Shared::TypeDeclaration,
+            _ => Shared::Other,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        let t = <Python as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <Python as hyper_ast::types::Lang<Type>>::make(t);
+        t
+    }
+
+    fn is_file(&self) -> bool {
+        self == &Type::Module
+    }
+
+    fn is_directory(&self) -> bool {
+        self == &Type::Directory
+    }
+
+    fn is_spaces(&self) -> bool {
+        self == &Type::Spaces
+    }
+
+    fn is_syntax(&self) -> bool {
+        self == &Type::BangEq // "!="
+        || self == &Type::Percent // "%"
+        || self == &Type::PercentEq // "%="
+        || self == &Type::Amp // "&"
+        || self == &Type::AmpEq // "&="
+        || self == &Type::LParen // "("
+        || self == &Type::RParen // ")"
+        || self == &Type::Star // "*"
+        || self == &Type::StarStar // "**"
+        || self == &Type::StarStarEq // "**="
+        || self == &Type::StarEq // "*="
+        || self == &Type::Plus // "+"
+        || self == &Type::PlusEq // "+="
+        || self == &Type::Comma // ","
+        || self == &Type::Dash // "-"
+        || self == &Type::DashEq // "-="
+        || self == &Type::DashGt // "->"
+        || self == &Type::Dot // "."
+        || self == &Type::Slash // "/"
+        || self == &Type::SlashSlash // "//"
+        || self == &Type::SlashSlashEq // "//="
+        || self == &Type::SlashEq // "/="
+        || self == &Type::Colon // ":"
+        || self == &Type::ColonEq // ":="
+        || self == &Type::SemiColon // ";"
+        || self == &Type::LT // "<"
+        || self == &Type::LtLt // "<<"
+        || self == &Type::LtLtEq // "<<="
+        || self == &Type::LTEq // "<="
+        || self == &Type::LtGt // "<>"
+        || self == &Type::Eq // "="
+        || self == &Type::EqEq // "=="
+        || self == &Type::GT // ">"
+        || self == &Type::GTEq // ">="
+        || self == &Type::GtGt // ">>"
+        || self == &Type::GtGtEq // ">>="
+        || self == &Type::At // "@"
+        || self == &Type::AtEq // "@="
+        || self == &Type::LBracket // "["
+        || self == &Type::RBracket // "]"
+        || self == &Type::Caret // "^"
+        || self == &Type::CaretEq // "^="
+        || self == &Type::Underscore // "_"
+        || self == &Type::LBrace // "{"
+        || self == &Type::Pipe // "|"
+        || self == &Type::PipeEq // "|="
+        || self == &Type::RBrace // "}"
+        || self == &Type::Tilde // "~"
+        || self == &Type::Future // "__future__"
+        || self == &Type::And // "and"
+        || self == &Type::As // "as"
+        || self == &Type::Assert // "assert"
+        || self == &Type::Async // "async"
+        || self == &Type::TSAwait // "await"
+        || self == &Type::Break // "break"
+        || self == &Type::Case // "case"
+        || self == &Type::Class // "class"
+        || self == &Type::Continue // "continue"
+        || self == &Type::Def // "def"
+        || self == &Type::Del // "del"
+        || self == &Type::Elif // "elif"
+        || self == &Type::Else // "else"
+        || self == &Type::Except // "except"
+        || self == &Type::ExceptStar // "except*"
+        || self == &Type::Exec // "exec"
+        || self == &Type::Finally // "finally"
+        || self == &Type::For // "for"
+        || self == &Type::From // "from"
+        || self == &Type::Global // "global"
+        || self == &Type::If // "if"
+        || self == &Type::Import // "import"
+        || self == &Type::In // "in"
+        || self == &Type::Is // "is"
+        || self == &Type::IsNot // "is not"
+        || self == &Type::TSLambda // "lambda"
+        || self == &Type::Match // "match"
+        || self == &Type::Nonlocal // "nonlocal"
+        || self == &Type::Not // "not"
+        || self == &Type::NotIn // "not in"
+        || self == &Type::Or // "or"
+        || self == &Type::Pass // "pass"
+        || self == &Type::Print // "print"
+        || self == &Type::Raise // "raise"
+        || self == &Type::Return // "return"
+        || self == &Type::Try // "try"
+        || self == &Type::TSType // "type"
+        || self == &Type::While // "while"
+        || self == &Type::With // "with"
+        || self == &Type::TSYield // "yield"
+    }
+
+    fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+    }
+}
+
+impl TypeTrait for Type {
+    type Lang = Python;
+
+    fn is_fork(&self) -> bool {
+        false
+    }
+
+    fn is_literal(&self) -> bool {
+        match self {
+            Type::Integer
+            | Type::Float
+            | Type::String
+            | Type::ConcatenatedString
+            | Type::True
+            | Type::False
+            | Type::None
+            | Type::Ellipsis => true,
+            _ => false,
+        }
+    }
+
+    fn is_primitive(&self) -> bool {
+        false
+    }
+
+    fn is_type_declaration(&self) -> bool {
+        self == &Type::ClassDefinition
+    }
+
+    fn is_identifier(&self) -> bool {
+        self == &Type::Identifier
+    }
+
+    fn is_instance_ref(&self) -> bool {
+        false
+    }
+
+    fn is_type_body(&self) -> bool {
+        false
+    }
+
+    fn is_value_member(&self) -> bool {
+        false
+    }
+
+    fn is_executable_member(&self) -> bool {
+        self == &Type::FunctionDefinition
+    }
+
+    fn is_statement(&self) -> bool {
+        self.is_declarative_statement()
+            || self.is_structural_statement()
+            || self.is_simple_statement()
+    }
+
+    fn is_declarative_statement(&self) -> bool {
+        match self {
+            Type::FunctionDefinition
+            | Type::ClassDefinition
+            | Type::DecoratedDefinition
+            | Type::TypeAliasStatement => true,
+            _ => false,
+        }
+    }
+
+    fn is_structural_statement(&self) -> bool {
+        match self {
+            Type::IfStatement
+            | Type::ForStatement
+            | Type::WhileStatement
+            | Type::TryStatement
+            | Type::WithStatement
+            | Type::MatchStatement => true,
+            _ => false,
+        }
+    }
+
+    fn is_block_related(&self) -> bool {
+        self == &Type::Block
+    }
+
+    fn is_simple_statement(&self) -> bool {
+        match self {
+            Type::ExpressionStatement
+            | Type::ReturnStatement
+            | Type::DeleteStatement
+            | Type::RaiseStatement
+            | Type::PassStatement
+            | Type::BreakStatement
+            | Type::ContinueStatement
+            | Type::AssertStatement
+            | Type::GlobalStatement
+            | Type::NonlocalStatement
+            | Type::PrintStatement
+            | Type::ExecStatement
+            | Type::ImportStatement
+            | Type::ImportFromStatement
+            | Type::FutureImportStatement => true,
+            _ => false,
+        }
+    }
+
+    fn is_local_declare(&self) -> bool {
+        false
+    }
+
+    fn is_parameter(&self) -> bool {
+        match self {
+            Type::DefaultParameter
+            | Type::TypedParameter
+            | Type::TypedDefaultParameter
+            | Type::ListSplatPattern
+            | Type::DictionarySplatPattern => true,
+            _ => false,
+        }
+    }
+
+    fn is_parameter_list(&self) -> bool {
+        self == &Type::Parameters || self == &Type::LambdaParameters
+    }
+
+    fn is_argument_list(&self) -> bool {
+        self == &Type::ArgumentList
+    }
+
+    fn is_expression(&self) -> bool {
+        match self {
+            Type::Call
+            | Type::Attribute
+            | Type::Subscript
+            | Type::BinaryOperator
+            | Type::UnaryOperator
+            | Type::BooleanOperator
+            | Type::NotOperator
+            | Type::ComparisonOperator
+            | Type::ConditionalExpression
+            | Type::NamedExpression
+            | Type::Lambda
+            | Type::Await
+            | Type::ParenthesizedExpression
+            | Type::GeneratorExpression
+            | Type::ListComprehension
+            | Type::SetComprehension
+            | Type::DictionaryComprehension
+            | Type::List
+            | Type::Set
+            | Type::Tuple
+            | Type::Dictionary => true,
+            _ => self.is_literal(),
+        }
+    }
+
+    fn is_comment(&self) -> bool {
+        self == &Type::Comment
+    }
+}
+
+impl Type {
+    pub fn resolve(t: u16) -> Self {
+        assert!(t < COUNT);
+        unsafe { std::mem::transmute(t) }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Type::to_str(*self))
+    }
+}
+
+#[repr(u16)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Type {
+    Module,
+    Comment,
+    Identifier,
+    Integer,
+    Float,
+    True,
+    False,
+    None,
+    Ellipsis,
+    String,
+    StringStart,
+    StringContent,
+    StringEnd,
+    EscapeSequence,
+    EscapeInterpolation,
+    Interpolation,
+    FormatSpecifier,
+    FormatExpression,
+    TypeConversion,
+    ConcatenatedString,
+    LineContinuation,
+    ImportStatement,
+    ImportPrefix,
+    RelativeImport,
+    FutureImportStatement,
+    ImportFromStatement,
+    AliasedImport,
+    WildcardImport,
+    DottedName,
+    PrintStatement,
+    Chevron,
+    AssertStatement,
+    ExpressionStatement,
+    NamedExpression,
+    ReturnStatement,
+    DeleteStatement,
+    RaiseStatement,
+    PassStatement,
+    BreakStatement,
+    ContinueStatement,
+    IfStatement,
+    ElifClause,
+    ElseClause,
+    MatchStatement,
+    CaseClause,
+    CasePattern,
+    Block,
+    ForStatement,
+    WhileStatement,
+    TryStatement,
+    ExceptClause,
+    ExceptGroupClause,
+    FinallyClause,
+    WithStatement,
+    WithClause,
+    WithItem,
+    FunctionDefinition,
+    Parameters,
+    LambdaParameters,
+    ListSplat,
+    DictionarySplat,
+    GlobalStatement,
+    NonlocalStatement,
+    ExecStatement,
+    TypeAliasStatement,
+    ClassDefinition,
+    TypeParameter,
+    ParenthesizedListSplat,
+    ArgumentList,
+    DecoratedDefinition,
+    Decorator,
+    ListSplatPattern,
+    DictionarySplatPattern,
+    AsPattern,
+    AsPatternTarget,
+    UnionPattern,
+    DictPattern,
+    KeywordPattern,
+    SplatPattern,
+    ClassPattern,
+    ComplexPattern,
+    ListPattern,
+    TuplePattern,
+    PatternList,
+    DefaultParameter,
+    TypedDefaultParameter,
+    TypedParameter,
+    KeywordSeparator,
+    PositionalSeparator,
+    ExpressionList,
+    NotOperator,
+    BooleanOperator,
+    BinaryOperator,
+    UnaryOperator,
+    ComparisonOperator,
+    Lambda,
+    Assignment,
+    AugmentedAssignment,
+    Yield,
+    Attribute,
+    Subscript,
+    Slice,
+    Call,
+    Type,
+    SplatType,
+    GenericType,
+    UnionType,
+    ConstrainedType,
+    MemberType,
+    KeywordArgument,
+    List,
+    Set,
+    Tuple,
+    Dictionary,
+    Pair,
+    ListComprehension,
+    DictionaryComprehension,
+    SetComprehension,
+    GeneratorExpression,
+    ForInClause,
+    IfClause,
+    ConditionalExpression,
+    ParenthesizedExpression,
+    Await,
+    BangEq,
+    Percent,
+    PercentEq,
+    Amp,
+    AmpEq,
+    LParen,
+    RParen,
+    Star,
+    StarStar,
+    StarStarEq,
+    StarEq,
+    Plus,
+    PlusEq,
+    Comma,
+    Dash,
+    DashEq,
+    DashGt,
+    Dot,
+    Slash,
+    SlashSlash,
+    SlashSlashEq,
+    SlashEq,
+    Colon,
+    ColonEq,
+    SemiColon,
+    LT,
+    LtLt,
+    LtLtEq,
+    LTEq,
+    LtGt,
+    Eq,
+    EqEq,
+    GT,
+    GTEq,
+    GtGt,
+    GtGtEq,
+    At,
+    AtEq,
+    LBracket,
+    RBracket,
+    Caret,
+    CaretEq,
+    Underscore,
+    LBrace,
+    Pipe,
+    PipeEq,
+    RBrace,
+    Tilde,
+    Future,
+    And,
+    As,
+    Assert,
+    Async,
+    TSAwait,
+    Break,
+    Case,
+    Class,
+    Continue,
+    Def,
+    Del,
+    Elif,
+    Else,
+    Except,
+    ExceptStar,
+    Exec,
+    Finally,
+    For,
+    From,
+    Global,
+    If,
+    Import,
+    In,
+    Is,
+    IsNot,
+    TSLambda,
+    Match,
+    Nonlocal,
+    Not,
+    NotIn,
+    Or,
+    Pass,
+    Print,
+    Raise,
+    Return,
+    Try,
+    TSType,
+    While,
+    With,
+    TSYield,
+    Spaces,
+    Directory,
+    ERROR,
+}
+impl Type {
+    /// resolves the id of a node kind, the table is generated from the parser of tree-sitter-python 0.20.4
+    pub fn from_u16(t: u16) -> Type {
+        match t {
+            1u16 => Type::Identifier,
+            2u16 => Type::SemiColon,
+            3u16 => Type::Import,
+            4u16 => Type::Dot,
+            5u16 => Type::From,
+            6u16 => Type::Future,
+            7u16 => Type::LParen,
+            8u16 => Type::RParen,
+            9u16 => Type::Comma,
+            10u16 => Type::As,
+            11u16 => Type::Star,
+            12u16 => Type::Print,
+            13u16 => Type::GtGt,
+            14u16 => Type::Assert,
+            15u16 => Type::ColonEq,
+            16u16 => Type::Return,
+            17u16 => Type::Del,
+            18u16 => Type::Raise,
+            19u16 => Type::Pass,
+            20u16 => Type::Break,
+            21u16 => Type::Continue,
+            22u16 => Type::If,
+            23u16 => Type::Colon,
+            24u16 => Type::Elif,
+            25u16 => Type::Else,
+            26u16 => Type::Match,
+            27u16 => Type::Case,
+            28u16 => Type::Async,
+            29u16 => Type::For,
+            30u16 => Type::In,
+            31u16 => Type::While,
+            32u16 => Type::Try,
+            33u16 => Type::Except,
+            34u16 => Type::ExceptStar,
+            35u16 => Type::Finally,
+            36u16 => Type::With,
+            37u16 => Type::Def,
+            38u16 => Type::DashGt,
+            39u16 => Type::StarStar,
+            40u16 => Type::Global,
+            41u16 => Type::Nonlocal,
+            42u16 => Type::Exec,
+            43u16 => Type::TSType,
+            44u16 => Type::Eq,
+            45u16 => Type::Class,
+            46u16 => Type::LBracket,
+            47u16 => Type::RBracket,
+            48u16 => Type::At,
+            49u16 => Type::Dash,
+            50u16 => Type::Underscore,
+            51u16 => Type::Pipe,
+            52u16 => Type::LBrace,
+            53u16 => Type::RBrace,
+            54u16 => Type::Plus,
+            55u16 => Type::Not,
+            56u16 => Type::And,
+            57u16 => Type::Or,
+            58u16 => Type::Slash,
+            59u16 => Type::Percent,
+            60u16 => Type::SlashSlash,
+            61u16 => Type::Amp,
+            62u16 => Type::Caret,
+            63u16 => Type::LtLt,
+            64u16 => Type::Tilde,
+            65u16 => Type::LT,
+            66u16 => Type::LTEq,
+            67u16 => Type::EqEq,
+            68u16 => Type::BangEq,
+            69u16 => Type::GTEq,
+            70u16 => Type::GT,
+            71u16 => Type::LtGt,
+            72u16 => Type::Is,
+            73u16 => Type::TSLambda,
+            74u16 => Type::PlusEq,
+            75u16 => Type::DashEq,
+            76u16 => Type::StarEq,
+            77u16 => Type::SlashEq,
+            78u16 => Type::AtEq,
+            79u16 => Type::SlashSlashEq,
+            80u16 => Type::PercentEq,
+            81u16 => Type::StarStarEq,
+            82u16 => Type::GtGtEq,
+            83u16 => Type::LtLtEq,
+            84u16 => Type::AmpEq,
+            85u16 => Type::CaretEq,
+            86u16 => Type::PipeEq,
+            87u16 => Type::TSYield,
+            88u16 => Type::Ellipsis,
+            89u16 => Type::EscapeSequence,
+            92u16 => Type::TypeConversion,
+            93u16 => Type::Integer,
+            94u16 => Type::Float,
+            95u16 => Type::TSAwait,
+            96u16 => Type::True,
+            97u16 => Type::False,
+            98u16 => Type::None,
+            99u16 => Type::Comment,
+            100u16 => Type::LineContinuation,
+            104u16 => Type::StringStart,
+            106u16 => Type::EscapeInterpolation,
+            107u16 => Type::StringEnd,
+            108u16 => Type::Module,
+            111u16 => Type::ImportStatement,
+            112u16 => Type::ImportPrefix,
+            113u16 => Type::RelativeImport,
+            114u16 => Type::FutureImportStatement,
+            115u16 => Type::ImportFromStatement,
+            117u16 => Type::AliasedImport,
+            118u16 => Type::WildcardImport,
+            119u16 => Type::PrintStatement,
+            120u16 => Type::Chevron,
+            121u16 => Type::AssertStatement,
+            122u16 => Type::ExpressionStatement,
+            123u16 => Type::NamedExpression,
+            125u16 => Type::ReturnStatement,
+            126u16 => Type::DeleteStatement,
+            127u16 => Type::RaiseStatement,
+            128u16 => Type::PassStatement,
+            129u16 => Type::BreakStatement,
+            130u16 => Type::ContinueStatement,
+            131u16 => Type::IfStatement,
+            132u16 => Type::ElifClause,
+            133u16 => Type::ElseClause,
+            134u16 => Type::MatchStatement,
+            136u16 => Type::CaseClause,
+            137u16 => Type::ForStatement,
+            138u16 => Type::WhileStatement,
+            139u16 => Type::TryStatement,
+            140u16 => Type::ExceptClause,
+            141u16 => Type::ExceptGroupClause,
+            142u16 => Type::FinallyClause,
+            143u16 => Type::WithStatement,
+            144u16 => Type::WithClause,
+            145u16 => Type::WithItem,
+            146u16 => Type::FunctionDefinition,
+            147u16 => Type::Parameters,
+            148u16 => Type::LambdaParameters,
+            149u16 => Type::ListSplat,
+            150u16 => Type::DictionarySplat,
+            151u16 => Type::GlobalStatement,
+            152u16 => Type::NonlocalStatement,
+            153u16 => Type::ExecStatement,
+            154u16 => Type::TypeAliasStatement,
+            155u16 => Type::ClassDefinition,
+            156u16 => Type::TypeParameter,
+            157u16 => Type::ParenthesizedListSplat,
+            158u16 => Type::ArgumentList,
+            159u16 => Type::DecoratedDefinition,
+            160u16 => Type::Decorator,
+            161u16 => Type::Block,
+            162u16 => Type::ExpressionList,
+            163u16 => Type::DottedName,
+            164u16 => Type::CasePattern,
+            167u16 => Type::UnionPattern,
+            170u16 => Type::DictPattern,
+            172u16 => Type::KeywordPattern,
+            173u16 => Type::SplatPattern,
+            174u16 => Type::ClassPattern,
+            175u16 => Type::ComplexPattern,
+            180u16 => Type::TuplePattern,
+            181u16 => Type::ListPattern,
+            182u16 => Type::DefaultParameter,
+            183u16 => Type::TypedDefaultParameter,
+            184u16 => Type::ListSplatPattern,
+            185u16 => Type::DictionarySplatPattern,
+            186u16 => Type::AsPattern,
+            190u16 => Type::NotOperator,
+            191u16 => Type::BooleanOperator,
+            192u16 => Type::BinaryOperator,
+            193u16 => Type::UnaryOperator,
+            194u16 => Type::ComparisonOperator,
+            195u16 => Type::Lambda,
+            197u16 => Type::Assignment,
+            198u16 => Type::AugmentedAssignment,
+            199u16 => Type::PatternList,
+            201u16 => Type::Yield,
+            202u16 => Type::Attribute,
+            203u16 => Type::Subscript,
+            204u16 => Type::Slice,
+            205u16 => Type::Call,
+            206u16 => Type::TypedParameter,
+            207u16 => Type::Type,
+            208u16 => Type::SplatType,
+            209u16 => Type::GenericType,
+            210u16 => Type::UnionType,
+            211u16 => Type::ConstrainedType,
+            212u16 => Type::MemberType,
+            213u16 => Type::KeywordArgument,
+            214u16 => Type::List,
+            215u16 => Type::Set,
+            216u16 => Type::Tuple,
+            217u16 => Type::Dictionary,
+            218u16 => Type::Pair,
+            219u16 => Type::ListComprehension,
+            220u16 => Type::DictionaryComprehension,
+            221u16 => Type::SetComprehension,
+            222u16 => Type::GeneratorExpression,
+            224u16 => Type::ParenthesizedExpression,
+            226u16 => Type::ForInClause,
+            227u16 => Type::IfClause,
+            228u16 => Type::ConditionalExpression,
+            229u16 => Type::ConcatenatedString,
+            230u16 => Type::String,
+            231u16 => Type::StringContent,
+            232u16 => Type::Interpolation,
+            234u16 => Type::FormatSpecifier,
+            235u16 => Type::Await,
+            236u16 => Type::PositionalSeparator,
+            237u16 => Type::KeywordSeparator,
+            270u16 => Type::AsPatternTarget,
+            271u16 => Type::FormatExpression,
+            272u16 => Type::IsNot,
+            273u16 => Type::NotIn,
+            u16::MAX => Type::ERROR,
+            x => panic!("{}", x),
+        }
+    }
+    /// resolves a named node kind of the grammar
+    pub fn from_str(t: &str) -> Option<Type> {
+        Some(match t {
+            "module" => Type::Module,
+            "comment" => Type::Comment,
+            "identifier" => Type::Identifier,
+            "integer" => Type::Integer,
+            "float" => Type::Float,
+            "true" => Type::True,
+            "false" => Type::False,
+            "none" => Type::None,
+            "ellipsis" => Type::Ellipsis,
+            "string" => Type::String,
+            "string_start" => Type::StringStart,
+            "string_content" => Type::StringContent,
+            "string_end" => Type::StringEnd,
+            "escape_sequence" => Type::EscapeSequence,
+            "escape_interpolation" => Type::EscapeInterpolation,
+            "interpolation" => Type::Interpolation,
+            "format_specifier" => Type::FormatSpecifier,
+            "format_expression" => Type::FormatExpression,
+            "type_conversion" => Type::TypeConversion,
+            "concatenated_string" => Type::ConcatenatedString,
+            "line_continuation" => Type::LineContinuation,
+            "import_statement" => Type::ImportStatement,
+            "import_prefix" => Type::ImportPrefix,
+            "relative_import" => Type::RelativeImport,
+            "future_import_statement" => Type::FutureImportStatement,
+            "import_from_statement" => Type::ImportFromStatement,
+            "aliased_import" => Type::AliasedImport,
+            "wildcard_import" => Type::WildcardImport,
+            "dotted_name" => Type::DottedName,
+            "print_statement" => Type::PrintStatement,
+            "chevron" => Type::Chevron,
+            "assert_statement" => Type::AssertStatement,
+            "expression_statement" => Type::ExpressionStatement,
+            "named_expression" => Type::NamedExpression,
+            "return_statement" => Type::ReturnStatement,
+            "delete_statement" => Type::DeleteStatement,
+            "raise_statement" => Type::RaiseStatement,
+            "pass_statement" => Type::PassStatement,
+            "break_statement" => Type::BreakStatement,
+            "continue_statement" => Type::ContinueStatement,
+            "if_statement" => Type::IfStatement,
+            "elif_clause" => Type::ElifClause,
+            "else_clause" => Type::ElseClause,
+            "match_statement" => Type::MatchStatement,
+            "case_clause" => Type::CaseClause,
+            "case_pattern" => Type::CasePattern,
+            "block" => Type::Block,
+            "for_statement" => Type::ForStatement,
+            "while_statement" => Type::WhileStatement,
+            "try_statement" => Type::TryStatement,
+            "except_clause" => Type::ExceptClause,
+            "except_group_clause" => Type::ExceptGroupClause,
+            "finally_clause" => Type::FinallyClause,
+            "with_statement" => Type::WithStatement,
+            "with_clause" => Type::WithClause,
+            "with_item" => Type::WithItem,
+            "function_definition" => Type::FunctionDefinition,
+            "parameters" => Type::Parameters,
+            "lambda_parameters" => Type::LambdaParameters,
+            "list_splat" => Type::ListSplat,
+            "dictionary_splat" => Type::DictionarySplat,
+            "global_statement" => Type::GlobalStatement,
+            "nonlocal_statement" => Type::NonlocalStatement,
+            "exec_statement" => Type::ExecStatement,
+            "type_alias_statement" => Type::TypeAliasStatement,
+            "class_definition" => Type::ClassDefinition,
+            "type_parameter" => Type::TypeParameter,
+            "parenthesized_list_splat" => Type::ParenthesizedListSplat,
+            "argument_list" => Type::ArgumentList,
+            "decorated_definition" => Type::DecoratedDefinition,
+            "decorator" => Type::Decorator,
+            "list_splat_pattern" => Type::ListSplatPattern,
+            "dictionary_splat_pattern" => Type::DictionarySplatPattern,
+            "as_pattern" => Type::AsPattern,
+            "as_pattern_target" => Type::AsPatternTarget,
+            "union_pattern" => Type::UnionPattern,
+            "dict_pattern" => Type::DictPattern,
+            "keyword_pattern" => Type::KeywordPattern,
+            "splat_pattern" => Type::SplatPattern,
+            "class_pattern" => Type::ClassPattern,
+            "complex_pattern" => Type::ComplexPattern,
+            "list_pattern" => Type::ListPattern,
+            "tuple_pattern" => Type::TuplePattern,
+            "pattern_list" => Type::PatternList,
+            "default_parameter" => Type::DefaultParameter,
+            "typed_default_parameter" => Type::TypedDefaultParameter,
+            "typed_parameter" => Type::TypedParameter,
+            "keyword_separator" => Type::KeywordSeparator,
+            "positional_separator" => Type::PositionalSeparator,
+            "expression_list" => Type::ExpressionList,
+            "not_operator" => Type::NotOperator,
+            "boolean_operator" => Type::BooleanOperator,
+            "binary_operator" => Type::BinaryOperator,
+            "unary_operator" => Type::UnaryOperator,
+            "comparison_operator" => Type::ComparisonOperator,
+            "lambda" => Type::Lambda,
+            "assignment" => Type::Assignment,
+            "augmented_assignment" => Type::AugmentedAssignment,
+            "yield" => Type::Yield,
+            "attribute" => Type::Attribute,
+            "subscript" => Type::Subscript,
+            "slice" => Type::Slice,
+            "call" => Type::Call,
+            "type" => Type::Type,
+            "splat_type" => Type::SplatType,
+            "generic_type" => Type::GenericType,
+            "union_type" => Type::UnionType,
+            "constrained_type" => Type::ConstrainedType,
+            "member_type" => Type::MemberType,
+            "keyword_argument" => Type::KeywordArgument,
+            "list" => Type::List,
+            "set" => Type::Set,
+            "tuple" => Type::Tuple,
+            "dictionary" => Type::Dictionary,
+            "pair" => Type::Pair,
+            "list_comprehension" => Type::ListComprehension,
+            "dictionary_comprehension" => Type::DictionaryComprehension,
+            "set_comprehension" => Type::SetComprehension,
+            "generator_expression" => Type::GeneratorExpression,
+            "for_in_clause" => Type::ForInClause,
+            "if_clause" => Type::IfClause,
+            "conditional_expression" => Type::ConditionalExpression,
+            "parenthesized_expression" => Type::ParenthesizedExpression,
+            "await" => Type::Await,
+            "ERROR" => Type::ERROR,
+            _ => return None,
+        })
+    }
+    /// resolves an anonymous node kind of the grammar, ie. keywords and punctuation
+    pub fn from_token(t: &str) -> Option<Type> {
+        Some(match t {
+            "!=" => Type::BangEq,
+            "%" => Type::Percent,
+            "%=" => Type::PercentEq,
+            "&" => Type::Amp,
+            "&=" => Type::AmpEq,
+            "(" => Type::LParen,
+            ")" => Type::RParen,
+            "*" => Type::Star,
+            "**" => Type::StarStar,
+            "**=" => Type::StarStarEq,
+            "*=" => Type::StarEq,
+            "+" => Type::Plus,
+            "+=" => Type::PlusEq,
+            "," => Type::Comma,
+            "-" => Type::Dash,
+            "-=" => Type::DashEq,
+            "->" => Type::DashGt,
+            "." => Type::Dot,
+            "/" => Type::Slash,
+            "//" => Type::SlashSlash,
+            "//=" => Type::SlashSlashEq,
+            "/=" => Type::SlashEq,
+            ":" => Type::Colon,
+            ":=" => Type::ColonEq,
+            ";" => Type::SemiColon,
+            "<" => Type::LT,
+            "<<" => Type::LtLt,
+            "<<=" => Type::LtLtEq,
+            "<=" => Type::LTEq,
+            "<>" => Type::LtGt,
+            "=" => Type::Eq,
+            "==" => Type::EqEq,
+            ">" => Type::GT,
+            ">=" => Type::GTEq,
+            ">>" => Type::GtGt,
+            ">>=" => Type::GtGtEq,
+            "@" => Type::At,
+            "@=" => Type::AtEq,
+            "[" => Type::LBracket,
+            "]" => Type::RBracket,
+            "^" => Type::Caret,
+            "^=" => Type::CaretEq,
+            "_" => Type::Underscore,
+            "{" => Type::LBrace,
+            "|" => Type::Pipe,
+            "|=" => Type::PipeEq,
+            "}" => Type::RBrace,
+            "~" => Type::Tilde,
+            "__future__" => Type::Future,
+            "and" => Type::And,
+            "as" => Type::As,
+            "assert" => Type::Assert,
+            "async" => Type::Async,
+            "await" => Type::TSAwait,
+            "break" => Type::Break,
+            "case" => Type::Case,
+            "class" => Type::Class,
+            "continue" => Type::Continue,
+            "def" => Type::Def,
+            "del" => Type::Del,
+            "elif" => Type::Elif,
+            "else" => Type::Else,
+            "except" => Type::Except,
+            "except*" => Type::ExceptStar,
+            "exec" => Type::Exec,
+            "finally" => Type::Finally,
+            "for" => Type::For,
+            "from" => Type::From,
+            "global" => Type::Global,
+            "if" => Type::If,
+            "import" => Type::Import,
+            "in" => Type::In,
+            "is" => Type::Is,
+            "is not" => Type::IsNot,
+            "lambda" => Type::TSLambda,
+            "match" => Type::Match,
+            "nonlocal" => Type::Nonlocal,
+            "not" => Type::Not,
+            "not in" => Type::NotIn,
+            "or" => Type::Or,
+            "pass" => Type::Pass,
+            "print" => Type::Print,
+            "raise" => Type::Raise,
+            "return" => Type::Return,
+            "try" => Type::Try,
+            "type" => Type::TSType,
+            "while" => Type::While,
+            "with" => Type::With,
+            "yield" => Type::TSYield,
+            _ => return None,
+        })
+    }
+    pub fn to_str(t: Type) -> &'static str {
+        match t {
+            Type::Module => "module",
+            Type::Comment => "comment",
+            Type::Identifier => "identifier",
+            Type::Integer => "integer",
+            Type::Float => "float",
+            Type::True => "true",
+            Type::False => "false",
+            Type::None => "none",
+            Type::Ellipsis => "ellipsis",
+            Type::String => "string",
+            Type::StringStart => "string_start",
+            Type::StringContent => "string_content",
+            Type::StringEnd => "string_end",
+            Type::EscapeSequence => "escape_sequence",
+            Type::EscapeInterpolation => "escape_interpolation",
+            Type::Interpolation => "interpolation",
+            Type::FormatSpecifier => "format_specifier",
+            Type::FormatExpression => "format_expression",
+            Type::TypeConversion => "type_conversion",
+            Type::ConcatenatedString => "concatenated_string",
+            Type::LineContinuation => "line_continuation",
+            Type::ImportStatement => "import_statement",
+            Type::ImportPrefix => "import_prefix",
+            Type::RelativeImport => "relative_import",
+            Type::FutureImportStatement => "future_import_statement",
+            Type::ImportFromStatement => "import_from_statement",
+            Type::AliasedImport => "aliased_import",
+            Type::WildcardImport => "wildcard_import",
+            Type::DottedName => "dotted_name",
+            Type::PrintStatement => "print_statement",
+            Type::Chevron => "chevron",
+            Type::AssertStatement => "assert_statement",
+            Type::ExpressionStatement => "expression_statement",
+            Type::NamedExpression => "named_expression",
+            Type::ReturnStatement => "return_statement",
+            Type::DeleteStatement => "delete_statement",
+            Type::RaiseStatement => "raise_statement",
+            Type::PassStatement => "pass_statement",
+            Type::BreakStatement => "break_statement",
+            Type::ContinueStatement => "continue_statement",
+            Type::IfStatement => "if_statement",
+            Type::ElifClause => "elif_clause",
+            Type::ElseClause => "else_clause",
+            Type::MatchStatement => "match_statement",
+            Type::CaseClause => "case_clause",
+            Type::CasePattern => "case_pattern",
+            Type::Block => "block",
+            Type::ForStatement => "for_statement",
+            Type::WhileStatement => "while_statement",
+            Type::TryStatement => "try_statement",
+            Type::ExceptClause => "except_clause",
+            Type::ExceptGroupClause => "except_group_clause",
+            Type::FinallyClause => "finally_clause",
+            Type::WithStatement => "with_statement",
+            Type::WithClause => "with_clause",
+            Type::WithItem => "with_item",
+            Type::FunctionDefinition => "function_definition",
+            Type::Parameters => "parameters",
+            Type::LambdaParameters => "lambda_parameters",
+            Type::ListSplat => "list_splat",
+            Type::DictionarySplat => "dictionary_splat",
+            Type::GlobalStatement => "global_statement",
+            Type::NonlocalStatement => "nonlocal_statement",
+            Type::ExecStatement => "exec_statement",
+            Type::TypeAliasStatement => "type_alias_statement",
+            Type::ClassDefinition => "class_definition",
+            Type::TypeParameter => "type_parameter",
+            Type::ParenthesizedListSplat => "parenthesized_list_splat",
+            Type::ArgumentList => "argument_list",
+            Type::DecoratedDefinition => "decorated_definition",
+            Type::Decorator => "decorator",
+            Type::ListSplatPattern => "list_splat_pattern",
+            Type::DictionarySplatPattern => "dictionary_splat_pattern",
+            Type::AsPattern => "as_pattern",
+            Type::AsPatternTarget => "as_pattern_target",
+            Type::UnionPattern => "union_pattern",
+            Type::DictPattern => "dict_pattern",
+            Type::KeywordPattern => "keyword_pattern",
+            Type::SplatPattern => "splat_pattern",
+            Type::ClassPattern => "class_pattern",
+            Type::ComplexPattern => "complex_pattern",
+            Type::ListPattern => "list_pattern",
+            Type::TuplePattern => "tuple_pattern",
+            Type::PatternList => "pattern_list",
+            Type::DefaultParameter => "default_parameter",
+            Type::TypedDefaultParameter => "typed_default_parameter",
+            Type::TypedParameter => "typed_parameter",
+            Type::KeywordSeparator => "keyword_separator",
+            Type::PositionalSeparator => "positional_separator",
+            Type::ExpressionList => "expression_list",
+            Type::NotOperator => "not_operator",
+            Type::BooleanOperator => "boolean_operator",
+            Type::BinaryOperator => "binary_operator",
+            Type::UnaryOperator => "unary_operator",
+            Type::ComparisonOperator => "comparison_operator",
+            Type::Lambda => "lambda",
+            Type::Assignment => "assignment",
+            Type::AugmentedAssignment => "augmented_assignment",
+            Type::Yield => "yield",
+            Type::Attribute => "attribute",
+            Type::Subscript => "subscript",
+            Type::Slice => "slice",
+            Type::Call => "call",
+            Type::Type => "type",
+            Type::SplatType => "splat_type",
+            Type::GenericType => "generic_type",
+            Type::UnionType => "union_type",
+            Type::ConstrainedType => "constrained_type",
+            Type::MemberType => "member_type",
+            Type::KeywordArgument => "keyword_argument",
+            Type::List => "list",
+            Type::Set => "set",
+            Type::Tuple => "tuple",
+            Type::Dictionary => "dictionary",
+            Type::Pair => "pair",
+            Type::ListComprehension => "list_comprehension",
+            Type::DictionaryComprehension => "dictionary_comprehension",
+            Type::SetComprehension => "set_comprehension",
+            Type::GeneratorExpression => "generator_expression",
+            Type::ForInClause => "for_in_clause",
+            Type::IfClause => "if_clause",
+            Type::ConditionalExpression => "conditional_expression",
+            Type::ParenthesizedExpression => "parenthesized_expression",
+            Type::Await => "await",
+            Type::BangEq => "!=",
+            Type::Percent => "%",
+            Type::PercentEq => "%=",
+            Type::Amp => "&",
+            Type::AmpEq => "&=",
+            Type::LParen => "(",
+            Type::RParen => ")",
+            Type::Star => "*",
+            Type::StarStar => "**",
+            Type::StarStarEq => "**=",
+            Type::StarEq => "*=",
+            Type::Plus => "+",
+            Type::PlusEq => "+=",
+            Type::Comma => ",",
+            Type::Dash => "-",
+            Type::DashEq => "-=",
+            Type::DashGt => "->",
+            Type::Dot => ".",
+            Type::Slash => "/",
+            Type::SlashSlash => "//",
+            Type::SlashSlashEq => "//=",
+            Type::SlashEq => "/=",
+            Type::Colon => ":",
+            Type::ColonEq => ":=",
+            Type::SemiColon => ";",
+            Type::LT => "<",
+            Type::LtLt => "<<",
+            Type::LtLtEq => "<<=",
+            Type::LTEq => "<=",
+            Type::LtGt => "<>",
+            Type::Eq => "=",
+            Type::EqEq => "==",
+            Type::GT => ">",
+            Type::GTEq => ">=",
+            Type::GtGt => ">>",
+            Type::GtGtEq => ">>=",
+            Type::At => "@",
+            Type::AtEq => "@=",
+            Type::LBracket => "[",
+            Type::RBracket => "]",
+            Type::Caret => "^",
+            Type::CaretEq => "^=",
+            Type::Underscore => "_",
+            Type::LBrace => "{",
+            Type::Pipe => "|",
+            Type::PipeEq => "|=",
+            Type::RBrace => "}",
+            Type::Tilde => "~",
+            Type::Future => "__future__",
+            Type::And => "and",
+            Type::As => "as",
+            Type::Assert => "assert",
+            Type::Async => "async",
+            Type::TSAwait => "await",
+            Type::Break => "break",
+            Type::Case => "case",
+            Type::Class => "class",
+            Type::Continue => "continue",
+            Type::Def => "def",
+            Type::Del => "del",
+            Type::Elif => "elif",
+            Type::Else => "else",
+            Type::Except => "except",
+            Type::ExceptStar => "except*",
+            Type::Exec => "exec",
+            Type::Finally => "finally",
+            Type::For => "for",
+            Type::From => "from",
+            Type::Global => "global",
+            Type::If => "if",
+            Type::Import => "import",
+            Type::In => "in",
+            Type::Is => "is",
+            Type::IsNot => "is not",
+            Type::TSLambda => "lambda",
+            Type::Match => "match",
+            Type::Nonlocal => "nonlocal",
+            Type::Not => "not",
+            Type::NotIn => "not in",
+            Type::Or => "or",
+            Type::Pass => "pass",
+            Type::Print => "print",
+            Type::Raise => "raise",
+            Type::Return => "return",
+            Type::Try => "try",
+            Type::TSType => "type",
+            Type::While => "while",
+            Type::With => "with",
+            Type::TSYield => "yield",
+            Type::Spaces => "Spaces",
+            Type::Directory => "Directory",
+            Type::ERROR => "ERROR",
+        }
+    }
+}
+
+const COUNT: u16 = 216;
+
+const S_T_L: &'static [Type] = &[
+    Type::Module,
+    Type::Comment,
+    Type::Identifier,
+    Type::Integer,
+    Type::Float,
+    Type::True,
+    Type::False,
+    Type::None,
+    Type::Ellipsis,
+    Type::String,
+    Type::StringStart,
+    Type::StringContent,
+    Type::StringEnd,
+    Type::EscapeSequence,
+    Type::EscapeInterpolation,
+    Type::Interpolation,
+    Type::FormatSpecifier,
+    Type::FormatExpression,
+    Type::TypeConversion,
+    Type::ConcatenatedString,
+    Type::LineContinuation,
+    Type::ImportStatement,
+    Type::ImportPrefix,
+    Type::RelativeImport,
+    Type::FutureImportStatement,
+    Type::ImportFromStatement,
+    Type::AliasedImport,
+    Type::WildcardImport,
+    Type::DottedName,
+    Type::PrintStatement,
+    Type::Chevron,
+    Type::AssertStatement,
+    Type::ExpressionStatement,
+    Type::NamedExpression,
+    Type::ReturnStatement,
+    Type::DeleteStatement,
+    Type::RaiseStatement,
+    Type::PassStatement,
+    Type::BreakStatement,
+    Type::ContinueStatement,
+    Type::IfStatement,
+    Type::ElifClause,
+    Type::ElseClause,
+    Type::MatchStatement,
+    Type::CaseClause,
+    Type::CasePattern,
+    Type::Block,
+    Type::ForStatement,
+    Type::WhileStatement,
+    Type::TryStatement,
+    Type::ExceptClause,
+    Type::ExceptGroupClause,
+    Type::FinallyClause,
+    Type::WithStatement,
+    Type::WithClause,
+    Type::WithItem,
+    Type::FunctionDefinition,
+    Type::Parameters,
+    Type::LambdaParameters,
+    Type::ListSplat,
+    Type::DictionarySplat,
+    Type::GlobalStatement,
+    Type::NonlocalStatement,
+    Type::ExecStatement,
+    Type::TypeAliasStatement,
+    Type::ClassDefinition,
+    Type::TypeParameter,
+    Type::ParenthesizedListSplat,
+    Type::ArgumentList,
+    Type::DecoratedDefinition,
+    Type::Decorator,
+    Type::ListSplatPattern,
+    Type::DictionarySplatPattern,
+    Type::AsPattern,
+    Type::AsPatternTarget,
+    Type::UnionPattern,
+    Type::DictPattern,
+    Type::KeywordPattern,
+    Type::SplatPattern,
+    Type::ClassPattern,
+    Type::ComplexPattern,
+    Type::ListPattern,
+    Type::TuplePattern,
+    Type::PatternList,
+    Type::DefaultParameter,
+    Type::TypedDefaultParameter,
+    Type::TypedParameter,
+    Type::KeywordSeparator,
+    Type::PositionalSeparator,
+    Type::ExpressionList,
+    Type::NotOperator,
+    Type::BooleanOperator,
+    Type::BinaryOperator,
+    Type::UnaryOperator,
+    Type::ComparisonOperator,
+    Type::Lambda,
+    Type::Assignment,
+    Type::AugmentedAssignment,
+    Type::Yield,
+    Type::Attribute,
+    Type::Subscript,
+    Type::Slice,
+    Type::Call,
+    Type::Type,
+    Type::SplatType,
+    Type::GenericType,
+    Type::UnionType,
+    Type::ConstrainedType,
+    Type::MemberType,
+    Type::KeywordArgument,
+    Type::List,
+    Type::Set,
+    Type::Tuple,
+    Type::Dictionary,
+    Type::Pair,
+    Type::ListComprehension,
+    Type::DictionaryComprehension,
+    Type::SetComprehension,
+    Type::GeneratorExpression,
+    Type::ForInClause,
+    Type::IfClause,
+    Type::ConditionalExpression,
+    Type::ParenthesizedExpression,
+    Type::Await,
+    Type::BangEq,
+    Type::Percent,
+    Type::PercentEq,
+    Type::Amp,
+    Type::AmpEq,
+    Type::LParen,
+    Type::RParen,
+    Type::Star,
+    Type::StarStar,
+    Type::StarStarEq,
+    Type::StarEq,
+    Type::Plus,
+    Type::PlusEq,
+    Type::Comma,
+    Type::Dash,
+    Type::DashEq,
+    Type::DashGt,
+    Type::Dot,
+    Type::Slash,
+    Type::SlashSlash,
+    Type::SlashSlashEq,
+    Type::SlashEq,
+    Type::Colon,
+    Type::ColonEq,
+    Type::SemiColon,
+    Type::LT,
+    Type::LtLt,
+    Type::LtLtEq,
+    Type::LTEq,
+    Type::LtGt,
+    Type::Eq,
+    Type::EqEq,
+    Type::GT,
+    Type::GTEq,
+    Type::GtGt,
+    Type::GtGtEq,
+    Type::At,
+    Type::AtEq,
+    Type::LBracket,
+    Type::RBracket,
+    Type::Caret,
+    Type::CaretEq,
+    Type::Underscore,
+    Type::LBrace,
+    Type::Pipe,
+    Type::PipeEq,
+    Type::RBrace,
+    Type::Tilde,
+    Type::Future,
+    Type::And,
+    Type::As,
+    Type::Assert,
+    Type::Async,
+    Type::TSAwait,
+    Type::Break,
+    Type::Case,
+    Type::Class,
+    Type::Continue,
+    Type::Def,
+    Type::Del,
+    Type::Elif,
+    Type::Else,
+    Type::Except,
+    Type::ExceptStar,
+    Type::Exec,
+    Type::Finally,
+    Type::For,
+    Type::From,
+    Type::Global,
+    Type::If,
+    Type::Import,
+    Type::In,
+    Type::Is,
+    Type::IsNot,
+    Type::TSLambda,
+    Type::Match,
+    Type::Nonlocal,
+    Type::Not,
+    Type::NotIn,
+    Type::Or,
+    Type::Pass,
+    Type::Print,
+    Type::Raise,
+    Type::Return,
+    Type::Try,
+    Type::TSType,
+    Type::While,
+    Type::With,
+    Type::TSYield,
+    Type::Spaces,
+    Type::Directory,
+    Type::ERROR,
+];
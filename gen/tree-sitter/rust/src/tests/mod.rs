@@ -0,0 +1,150 @@
+use hyper_ast::store::{labels::LabelStore, SimpleStores};
+
+use crate::{
+    legion::{tree_sitter_parse_rust, RustTreeGen},
+    types::TStore,
+};
+
+static CASE_1: &str = r#"//! a module
+use std::{collections::HashMap, fmt::Display};
+
+/// a documented struct
+#[derive(Debug, Clone)]
+pub struct Greeter<'a, T: Display> {
+    name: &'a str,
+    extra: Option<T>,
+}
+
+impl<'a, T: Display> Greeter<'a, T> {
+    pub fn greet(&self) -> String {
+        let mut s = format!("Hello {}\n", self.name);
+        if let Some(x) = &self.extra {
+            s += &x.to_string();
+        }
+        s
+    }
+}
+
+fn main() {
+    let mut m: HashMap<u8, &str> = HashMap::new();
+    m.insert(b'a', r"raw\n");
+    for (k, v) in m.iter() {
+        match *k {
+            0..=9 => println!("{} {}", k, v),
+            _ => (),
+        }
+    }
+}
+"#;
+
+#[test]
+fn rust_tree_sitter_simple() {
+    let tree = match tree_sitter_parse_rust(CASE_1.as_bytes()) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    assert!(
+        !tree.root_node().has_error(),
+        "{}",
+        tree.root_node().to_sexp()
+    );
+}
+
+#[test]
+fn rust_hyperast_serialize() {
+    let text = CASE_1.as_bytes();
+    let tree = match tree_sitter_parse_rust(text) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: hyper_ast::store::nodes::legion::NodeStore::new(),
+    };
+    let mut tree_gen = RustTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+    };
+    let x = tree_gen.generate_file(b"main.rs", text, tree.walk()).local;
+    let serialized = hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node).to_string();
+    assert_eq!(CASE_1, serialized);
+}
+
+#[test]
+fn type_test_generic_eq() {
+    use crate::types::Type;
+    use hyper_ast::types::HyperType;
+
+    let k = Type::FunctionItem;
+    let k0 = Type::FunctionItem;
+    let k1 = Type::StructItem;
+    assert!(k.eq(&k0));
+    assert!(k.ne(&k1));
+    assert!(k.generic_eq(&k0));
+    assert!(!k.generic_eq(&k1));
+
+    // some fragment specifiers of macros are also the names of named nodes
+    assert_eq!(Some(Type::Block), Type::from_str("block"));
+    assert_eq!(Some(Type::TSBlock), Type::from_token("block"));
+    assert_eq!(Type::SourceFile, Type::resolve(Type::SourceFile as u16));
+
+    use hyper_ast::types::{AnyType, LangRef};
+    let any: &AnyType = LangRef::<AnyType>::make(&crate::types::Lang, Type::SourceFile as u16);
+    assert_eq!(any.to_string(), Type::SourceFile.to_string());
+    let t = LangRef::<AnyType>::to_u16(&crate::types::Lang, *any);
+    assert_eq!(Type::SourceFile as u16, t);
+}
+
+static CASE_2: &str = r#"macro_rules! twice {
+    ($e:expr) => {
+        $e + $e
+    };
+}
+
+fn fail() -> ! {
+    'outer: loop {
+        while twice!(1) > 0 {
+            break 'outer;
+        }
+    }
+    panic!()
+}
+"#;
+
+/// Checks that every node of the tree has a type, recursively.
+fn assert_typed(node: tree_sitter::Node) -> usize {
+    use crate::types::Type;
+    let t = Type::from_u16(node.kind_id());
+    assert_ne!(t, Type::ERROR, "{}", node.to_sexp());
+    assert_eq!(t.to_string(), node.kind());
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor);
+    1 + children.map(assert_typed).sum::<usize>()
+}
+
+#[test]
+fn rust_types_without_error() {
+    for case in [CASE_1, CASE_2] {
+        let tree = match tree_sitter_parse_rust(case.as_bytes()) {
+            Ok(t) => t,
+            Err(t) => panic!("{}", t.root_node().to_sexp()),
+        };
+        assert!(assert_typed(tree.root_node()) > 50);
+    }
+}
+
+#[test]
+fn rust_types_of_grammar() {
+    use crate::types::Type;
+    let language = tree_sitter_rust::language();
+    for id in 0..language.node_kind_count() as u16 {
+        let kind = language.node_kind_for_id(id).unwrap();
+        let named = language.node_kind_is_named(id);
+        // the ids of hidden symbols and of duplicated names are never the kinds of nodes
+        if !language.node_kind_is_visible(id) || language.id_for_node_kind(kind, named) != id {
+            continue;
+        }
+        assert_eq!(Type::from_u16(id).to_string(), kind, "{}", id);
+    }
+}
@@ -0,0 +1,1875 @@
+use std::fmt::Display;
+
+use hyper_ast::{
+    store::defaults::NodeIdentifier,
+    types::{AnyType, HyperType, LangRef, NodeId, TypeStore, TypeTrait, TypedNodeId},
+};
+
+#[cfg(feature = "legion")]
+mod legion_impls {
+    use super::*;
+
+    use crate::TNode;
+
+    impl<'a> TNode<'a> {
+        pub fn obtain_type<T>(&self, _: &mut impl RustEnabledTypeStore<T>) -> Type {
+            let t = self.0.kind_id();
+            Type::from_u16(t)
+        }
+    }
+
+    use hyper_ast::{store::nodes::legion::HashedNodeRef, types::TypeIndex};
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            n.get_component::<Type>().unwrap().clone()
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: self.resolve_type(n) as u16,
+            }
+        }
+    }
+    impl<'a> RustEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        const LANG: TypeInternalSize = Self::Rust as u16;
+
+        fn _intern(_l: u16, t: u16) -> Self::Ty {
+            Type::resolve(t)
+        }
+        fn intern(&self, t: Type) -> Self::Ty {
+            t
+        }
+
+        fn resolve(&self, t: Self::Ty) -> Type {
+            t
+        }
+    }
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            let t = n.get_component::<Type>().unwrap();
+            as_any(t)
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<Type>().unwrap() as u16,
+            }
+        }
+    }
+}
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Rust as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <Rust as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
+pub trait RustEnabledTypeStore<T>: TypeStore<T> {
+    const LANG: u16;
+    fn intern(&self, t: Type) -> Self::Ty {
+        let t = t as u16;
+        Self::_intern(Self::LANG, t)
+    }
+    fn _intern(l: u16, t: u16) -> Self::Ty;
+    fn resolve(&self, t: Self::Ty) -> Type;
+}
+
+#[repr(u8)]
+pub enum TStore {
+    Rust = 0,
+}
+
+impl Default for TStore {
+    fn default() -> Self {
+        Self::Rust
+    }
+}
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TIdN<IdN>(IdN);
+
+impl<IdN: Clone + Eq + NodeId> NodeId for TIdN<IdN> {
+    type IdN = IdN;
+
+    fn as_id(&self) -> &Self::IdN {
+        &self.0
+    }
+
+    unsafe fn from_id(id: Self::IdN) -> Self {
+        Self(id)
+    }
+
+    unsafe fn from_ref_id(id: &Self::IdN) -> &Self {
+        std::mem::transmute(id)
+    }
+}
+
+impl<IdN: Clone + Eq + NodeId> TypedNodeId for TIdN<IdN> {
+    type Ty = Type;
+}
+
+type TypeInternalSize = u16;
+
+pub struct Lang;
+pub type Rust = Lang;
+
+impl hyper_ast::types::Lang<Type> for Rust {
+    fn make(t: u16) -> &'static Type {
+        Lang.make(t)
+    }
+    fn to_u16(t: Type) -> u16 {
+        Lang.to_u16(t)
+    }
+}
+
+impl LangRef<Type> for Rust {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Rust>()
+    }
+
+    fn make(&self, t: u16) -> &'static Type {
+        &S_T_L[t as usize]
+    }
+
+    fn to_u16(&self, t: Type) -> u16 {
+        t as u16
+    }
+}
+
+impl LangRef<AnyType> for Rust {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Rust>()
+    }
+
+    fn make(&self, t: u16) -> &'static AnyType {
+        // AnyType cannot be built in a const context, so the table is built on first use
+        static ANY_T_L: std::sync::OnceLock<Vec<AnyType>> = std::sync::OnceLock::new();
+        let any_t_l = ANY_T_L.get_or_init(|| S_T_L.iter().map(as_any).collect());
+        &any_t_l[t as usize]
+    }
+
+    fn to_u16(&self, t: AnyType) -> u16 {
+        let t = t.as_any().downcast_ref::<Type>().unwrap();
+        *t as u16
+    }
+}
+impl HyperType for Type {
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + PartialEq + Sized,
+    {
+        // Do a type-safe casting. If the types are different,
+        // return false, otherwise test the values for equality.
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn as_shared(&self) -> hyper_ast::types::Shared {
+        use hyper_ast::types::Shared;
+        match self {
+            Type::LineComment | Type::BlockComment => Shared::Comment,
+            Type::Identifier => Shared::Identifier,
+            Type::StructItem | Type::EnumItem | Type::UnionItem | Type::TraitItem => {
+                Shared::TypeDeclaration
+            }
+            _ => Shared::Other,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        let t = <Rust as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <Rust as hyper_ast::types::Lang<Type>>::make(t);
+        t
+    }
+
+    fn is_file(&self) -> bool {
+        self == &Type::SourceFile
+    }
+
+    fn is_directory(&self) -> bool {
+        self == &Type::Directory
+    }
+
+    fn is_spaces(&self) -> bool {
+        self == &Type::Spaces
+    }
+
+    fn is_syntax(&self) -> bool {
+        self == &Type::Bang // "!"
+        || self == &Type::BangEq // "!="
+        || self == &Type::Hash // "#"
+        || self == &Type::Dollar // "$"
+        || self == &Type::Percent // "%"
+        || self == &Type::PercentEq // "%="
+        || self == &Type::Amp // "&"
+        || self == &Type::AmpAmp // "&&"
+        || self == &Type::AmpEq // "&="
+        || self == &Type::SQuote // "'"
+        || self == &Type::DQuote // "\""
+        || self == &Type::LParen // "("
+        || self == &Type::RParen // ")"
+        || self == &Type::Star // "*"
+        || self == &Type::StarEq // "*="
+        || self == &Type::Plus // "+"
+        || self == &Type::PlusEq // "+="
+        || self == &Type::Comma // ","
+        || self == &Type::Dash // "-"
+        || self == &Type::DashEq // "-="
+        || self == &Type::DashGt // "->"
+        || self == &Type::Dot // "."
+        || self == &Type::DotDot // ".."
+        || self == &Type::DotDotDot // "..."
+        || self == &Type::DotDotEq // "..="
+        || self == &Type::Slash // "/"
+        || self == &Type::SlashEq // "/="
+        || self == &Type::Colon // ":"
+        || self == &Type::ColonColon // "::"
+        || self == &Type::SemiColon // ";"
+        || self == &Type::LT // "<"
+        || self == &Type::LtLt // "<<"
+        || self == &Type::LtLtEq // "<<="
+        || self == &Type::LTEq // "<="
+        || self == &Type::Eq // "="
+        || self == &Type::EqEq // "=="
+        || self == &Type::EqGt // "=>"
+        || self == &Type::GT // ">"
+        || self == &Type::GTEq // ">="
+        || self == &Type::GtGt // ">>"
+        || self == &Type::GtGtEq // ">>="
+        || self == &Type::QMark // "?"
+        || self == &Type::At // "@"
+        || self == &Type::LBracket // "["
+        || self == &Type::RBracket // "]"
+        || self == &Type::Caret // "^"
+        || self == &Type::Tilde // "~"
+        || self == &Type::Backslash // "\\"
+        || self == &Type::CaretEq // "^="
+        || self == &Type::Underscore // "_"
+        || self == &Type::LBrace // "{"
+        || self == &Type::Pipe // "|"
+        || self == &Type::PipeEq // "|="
+        || self == &Type::PipePipe // "||"
+        || self == &Type::RBrace // "}"
+        || self == &Type::As // "as"
+        || self == &Type::Async // "async"
+        || self == &Type::Await // "await"
+        || self == &Type::Break // "break"
+        || self == &Type::Const // "const"
+        || self == &Type::Continue // "continue"
+        || self == &Type::Default // "default"
+        || self == &Type::Dyn // "dyn"
+        || self == &Type::Else // "else"
+        || self == &Type::Enum // "enum"
+        || self == &Type::Extern // "extern"
+        || self == &Type::False // "false"
+        || self == &Type::Fn // "fn"
+        || self == &Type::For // "for"
+        || self == &Type::If // "if"
+        || self == &Type::Impl // "impl"
+        || self == &Type::In // "in"
+        || self == &Type::Let // "let"
+        || self == &Type::Loop // "loop"
+        || self == &Type::MacroRules // "macro_rules!"
+        || self == &Type::Match // "match"
+        || self == &Type::Mod // "mod"
+        || self == &Type::Move // "move"
+        || self == &Type::Pub // "pub"
+        || self == &Type::Ref // "ref"
+        || self == &Type::Return // "return"
+        || self == &Type::Static // "static"
+        || self == &Type::Struct // "struct"
+        || self == &Type::Trait // "trait"
+        || self == &Type::True // "true"
+        || self == &Type::Type // "type"
+        || self == &Type::Union // "union"
+        || self == &Type::Unsafe // "unsafe"
+        || self == &Type::Use // "use"
+        || self == &Type::Where // "where"
+        || self == &Type::While // "while"
+        || self == &Type::Yield // "yield"
+        || self == &Type::TSBlock // "block"
+        || self == &Type::Expr // "expr"
+        || self == &Type::Ident // "ident"
+        || self == &Type::Item // "item"
+        || self == &Type::TSLifetime // "lifetime"
+        || self == &Type::Literal // "literal"
+        || self == &Type::Meta // "meta"
+        || self == &Type::Pat // "pat"
+        || self == &Type::Path // "path"
+        || self == &Type::Stmt // "stmt"
+        || self == &Type::Tt // "tt"
+        || self == &Type::Ty // "ty"
+        || self == &Type::Vis // "vis"
+    }
+
+    fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+    }
+}
+
+impl TypeTrait for Type {
+    type Lang = Rust;
+
+    fn is_fork(&self) -> bool {
+        false
+    }
+
+    fn is_literal(&self) -> bool {
+        match self {
+            Type::IntegerLiteral
+            | Type::FloatLiteral
+            | Type::BooleanLiteral
+            | Type::CharLiteral
+            | Type::StringLiteral
+            | Type::RawStringLiteral
+            | Type::NegativeLiteral => true,
+            _ => false,
+        }
+    }
+
+    fn is_primitive(&self) -> bool {
+        self == &Type::PrimitiveType
+    }
+
+    fn is_type_declaration(&self) -> bool {
+        match self {
+            Type::StructItem
+            | Type::EnumItem
+            | Type::UnionItem
+            | Type::TraitItem
+            | Type::TypeItem => true,
+            _ => false,
+        }
+    }
+
+    fn is_identifier(&self) -> bool {
+        match self {
+            Type::Identifier
+            | Type::TypeIdentifier
+            | Type::FieldIdentifier
+            | Type::ShorthandFieldIdentifier => true,
+            _ => false,
+        }
+    }
+
+    fn is_instance_ref(&self) -> bool {
+        self == &Type::Self_
+    }
+
+    fn is_type_body(&self) -> bool {
+        match self {
+            Type::DeclarationList
+            | Type::FieldDeclarationList
+            | Type::OrderedFieldDeclarationList
+            | Type::EnumVariantList => true,
+            _ => false,
+        }
+    }
+
+    fn is_value_member(&self) -> bool {
+        match self {
+            Type::FieldDeclaration | Type::EnumVariant | Type::ConstItem | Type::StaticItem => true,
+            _ => false,
+        }
+    }
+
+    fn is_executable_member(&self) -> bool {
+        self == &Type::FunctionItem || self == &Type::FunctionSignatureItem
+    }
+
+    fn is_statement(&self) -> bool {
+        self.is_declarative_statement()
+            || self.is_structural_statement()
+            || self.is_simple_statement()
+    }
+
+    fn is_declarative_statement(&self) -> bool {
+        match self {
+            Type::FunctionItem
+            | Type::StructItem
+            | Type::EnumItem
+            | Type::UnionItem
+            | Type::TraitItem
+            | Type::ImplItem
+            | Type::ModItem
+            | Type::TypeItem
+            | Type::ConstItem
+            | Type::StaticItem
+            | Type::MacroDefinition
+            | Type::UseDeclaration
+            | Type::ExternCrateDeclaration
+            | Type::ForeignModItem => true,
+            _ => false,
+        }
+    }
+
+    fn is_structural_statement(&self) -> bool {
+        match self {
+            Type::IfExpression
+            | Type::MatchExpression
+            | Type::WhileExpression
+            | Type::LoopExpression
+            | Type::ForExpression => true,
+            _ => false,
+        }
+    }
+
+    fn is_block_related(&self) -> bool {
+        match self {
+            Type::Block | Type::UnsafeBlock | Type::AsyncBlock | Type::ConstBlock => true,
+            _ => false,
+        }
+    }
+
+    fn is_simple_statement(&self) -> bool {
+        match self {
+            Type::ExpressionStatement | Type::LetDeclaration | Type::EmptyStatement => true,
+            _ => false,
+        }
+    }
+
+    fn is_local_declare(&self) -> bool {
+        self == &Type::LetDeclaration
+    }
+
+    fn is_parameter(&self) -> bool {
+        match self {
+            Type::Parameter | Type::SelfParameter | Type::VariadicParameter => true,
+            _ => false,
+        }
+    }
+
+    fn is_parameter_list(&self) -> bool {
+        self == &Type::Parameters || self == &Type::ClosureParameters
+    }
+
+    fn is_argument_list(&self) -> bool {
+        self == &Type::Arguments
+    }
+
+    fn is_expression(&self) -> bool {
+        match self {
+            Type::CallExpression
+            | Type::MacroInvocation
+            | Type::FieldExpression
+            | Type::IndexExpression
+            | Type::TryExpression
+            | Type::AwaitExpression
+            | Type::TypeCastExpression
+            | Type::ReferenceExpression
+            | Type::UnaryExpression
+            | Type::BinaryExpression
+            | Type::AssignmentExpression
+            | Type::CompoundAssignmentExpr
+            | Type::RangeExpression
+            | Type::ArrayExpression
+            | Type::TupleExpression
+            | Type::UnitExpression
+            | Type::ParenthesizedExpression
+            | Type::StructExpression
+            | Type::ClosureExpression
+            | Type::IfExpression
+            | Type::MatchExpression
+            | Type::BreakExpression
+            | Type::ContinueExpression
+            | Type::ReturnExpression
+            | Type::YieldExpression => true,
+            _ => self.is_literal(),
+        }
+    }
+
+    fn is_comment(&self) -> bool {
+        self == &Type::LineComment || self == &Type::BlockComment
+    }
+}
+
+impl Type {
+    pub fn resolve(t: u16) -> Self {
+        assert!(t < COUNT);
+        unsafe { std::mem::transmute(t) }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Type::to_str(*self))
+    }
+}
+
+#[repr(u16)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Type {
+    SourceFile,
+    LineComment,
+    BlockComment,
+    Shebang,
+    Identifier,
+    TypeIdentifier,
+    FieldIdentifier,
+    ShorthandFieldIdentifier,
+    PrimitiveType,
+    Self_,
+    Super,
+    Crate,
+    Metavariable,
+    MutableSpecifier,
+    FragmentSpecifier,
+    IntegerLiteral,
+    FloatLiteral,
+    BooleanLiteral,
+    CharLiteral,
+    StringLiteral,
+    RawStringLiteral,
+    EscapeSequence,
+    NegativeLiteral,
+    AttributeItem,
+    InnerAttributeItem,
+    Attribute,
+    ModItem,
+    DeclarationList,
+    ForeignModItem,
+    ExternCrateDeclaration,
+    ExternModifier,
+    UseDeclaration,
+    UseAsClause,
+    UseList,
+    ScopedUseList,
+    UseWildcard,
+    VisibilityModifier,
+    FunctionItem,
+    FunctionSignatureItem,
+    FunctionModifiers,
+    Parameters,
+    Parameter,
+    SelfParameter,
+    VariadicParameter,
+    ClosureParameters,
+    StructItem,
+    UnionItem,
+    EnumItem,
+    EnumVariant,
+    EnumVariantList,
+    FieldDeclaration,
+    FieldDeclarationList,
+    OrderedFieldDeclarationList,
+    TraitItem,
+    ImplItem,
+    AssociatedType,
+    TypeItem,
+    ConstItem,
+    StaticItem,
+    TypeParameters,
+    TypeArguments,
+    TypeBinding,
+    ConstrainedTypeParameter,
+    OptionalTypeParameter,
+    ConstParameter,
+    Lifetime,
+    TraitBounds,
+    HigherRankedTraitBound,
+    RemovedTraitBound,
+    ForLifetimes,
+    WhereClause,
+    WherePredicate,
+    GenericType,
+    GenericTypeWithTurbofish,
+    ScopedTypeIdentifier,
+    ScopedIdentifier,
+    QualifiedType,
+    BracketedType,
+    AbstractType,
+    BoundedType,
+    DynamicType,
+    FunctionType,
+    ArrayType,
+    PointerType,
+    ReferenceType,
+    TupleType,
+    UnitType,
+    EmptyType,
+    MacroDefinition,
+    MacroRule,
+    MacroInvocation,
+    TokenTree,
+    TokenTreePattern,
+    TokenBindingPattern,
+    TokenRepetition,
+    TokenRepetitionPattern,
+    Block,
+    ExpressionStatement,
+    LetDeclaration,
+    EmptyStatement,
+    LoopLabel,
+    ElseClause,
+    LetCondition,
+    LetChain,
+    IfExpression,
+    MatchExpression,
+    MatchBlock,
+    MatchArm,
+    MatchPattern,
+    WhileExpression,
+    LoopExpression,
+    ForExpression,
+    BreakExpression,
+    ContinueExpression,
+    ReturnExpression,
+    YieldExpression,
+    CallExpression,
+    GenericFunction,
+    Arguments,
+    FieldExpression,
+    IndexExpression,
+    TryExpression,
+    AwaitExpression,
+    TypeCastExpression,
+    ReferenceExpression,
+    UnaryExpression,
+    BinaryExpression,
+    AssignmentExpression,
+    CompoundAssignmentExpr,
+    RangeExpression,
+    ArrayExpression,
+    TupleExpression,
+    UnitExpression,
+    ParenthesizedExpression,
+    StructExpression,
+    FieldInitializerList,
+    FieldInitializer,
+    ShorthandFieldInitializer,
+    BaseFieldInitializer,
+    ClosureExpression,
+    AsyncBlock,
+    UnsafeBlock,
+    ConstBlock,
+    TuplePattern,
+    TupleStructPattern,
+    StructPattern,
+    FieldPattern,
+    RemainingFieldPattern,
+    SlicePattern,
+    RefPattern,
+    ReferencePattern,
+    MutPattern,
+    CapturedPattern,
+    RangePattern,
+    OrPattern,
+    Bang,
+    BangEq,
+    Hash,
+    Dollar,
+    Percent,
+    PercentEq,
+    Amp,
+    AmpAmp,
+    AmpEq,
+    SQuote,
+    DQuote,
+    LParen,
+    RParen,
+    Star,
+    StarEq,
+    Plus,
+    PlusEq,
+    Comma,
+    Dash,
+    DashEq,
+    DashGt,
+    Dot,
+    DotDot,
+    DotDotDot,
+    DotDotEq,
+    Slash,
+    SlashEq,
+    Colon,
+    ColonColon,
+    SemiColon,
+    LT,
+    LtLt,
+    LtLtEq,
+    LTEq,
+    Eq,
+    EqEq,
+    EqGt,
+    GT,
+    GTEq,
+    GtGt,
+    GtGtEq,
+    QMark,
+    At,
+    LBracket,
+    RBracket,
+    Caret,
+    Tilde,
+    Backslash,
+    CaretEq,
+    Underscore,
+    LBrace,
+    Pipe,
+    PipeEq,
+    PipePipe,
+    RBrace,
+    As,
+    Async,
+    Await,
+    Break,
+    Const,
+    Continue,
+    Default,
+    Dyn,
+    Else,
+    Enum,
+    Extern,
+    False,
+    Fn,
+    For,
+    If,
+    Impl,
+    In,
+    Let,
+    Loop,
+    MacroRules,
+    Match,
+    Mod,
+    Move,
+    Pub,
+    Ref,
+    Return,
+    Static,
+    Struct,
+    Trait,
+    True,
+    Type,
+    Union,
+    Unsafe,
+    Use,
+    Where,
+    While,
+    Yield,
+    TSBlock,
+    Expr,
+    Ident,
+    Item,
+    TSLifetime,
+    Literal,
+    Meta,
+    Pat,
+    Path,
+    Stmt,
+    Tt,
+    Ty,
+    Vis,
+    Spaces,
+    Directory,
+    ERROR,
+}
+impl Type {
+    /// resolves the id of a node kind, the table is generated from the parser of tree-sitter-rust 0.20.4
+    pub fn from_u16(t: u16) -> Type {
+        match t {
+            1u16 => Type::Identifier,
+            2u16 => Type::SemiColon,
+            3u16 => Type::MacroRules,
+            4u16 => Type::LParen,
+            5u16 => Type::RParen,
+            6u16 => Type::LBrace,
+            7u16 => Type::RBrace,
+            8u16 => Type::EqGt,
+            9u16 => Type::LBracket,
+            10u16 => Type::RBracket,
+            11u16 => Type::Colon,
+            12u16 => Type::Dollar,
+            14u16 => Type::Plus,
+            15u16 => Type::Star,
+            16u16 => Type::QMark,
+            17u16 => Type::TSBlock,
+            18u16 => Type::Expr,
+            19u16 => Type::Ident,
+            20u16 => Type::Item,
+            21u16 => Type::TSLifetime,
+            22u16 => Type::Literal,
+            23u16 => Type::Meta,
+            24u16 => Type::Pat,
+            25u16 => Type::Path,
+            26u16 => Type::Stmt,
+            27u16 => Type::Tt,
+            28u16 => Type::Ty,
+            29u16 => Type::Vis,
+            30u16 => Type::PrimitiveType,
+            47u16 => Type::Slash,
+            48u16 => Type::Underscore,
+            49u16 => Type::Backslash,
+            50u16 => Type::Dash,
+            51u16 => Type::Eq,
+            52u16 => Type::DashGt,
+            53u16 => Type::Comma,
+            54u16 => Type::ColonColon,
+            55u16 => Type::Bang,
+            56u16 => Type::Dot,
+            57u16 => Type::At,
+            58u16 => Type::Amp,
+            59u16 => Type::Hash,
+            60u16 => Type::Percent,
+            61u16 => Type::Caret,
+            62u16 => Type::LT,
+            63u16 => Type::GT,
+            64u16 => Type::Pipe,
+            65u16 => Type::Tilde,
+            66u16 => Type::SQuote,
+            67u16 => Type::As,
+            68u16 => Type::Async,
+            69u16 => Type::Await,
+            70u16 => Type::Break,
+            71u16 => Type::Const,
+            72u16 => Type::Continue,
+            73u16 => Type::Default,
+            74u16 => Type::Enum,
+            75u16 => Type::Fn,
+            76u16 => Type::For,
+            77u16 => Type::If,
+            78u16 => Type::Impl,
+            79u16 => Type::Let,
+            80u16 => Type::Loop,
+            81u16 => Type::Match,
+            82u16 => Type::Mod,
+            83u16 => Type::Pub,
+            84u16 => Type::Return,
+            85u16 => Type::Static,
+            86u16 => Type::Struct,
+            87u16 => Type::Trait,
+            88u16 => Type::Type,
+            89u16 => Type::Union,
+            90u16 => Type::Unsafe,
+            91u16 => Type::Use,
+            92u16 => Type::Where,
+            93u16 => Type::While,
+            94u16 => Type::Extern,
+            95u16 => Type::Ref,
+            96u16 => Type::Else,
+            97u16 => Type::DotDotDot,
+            98u16 => Type::In,
+            100u16 => Type::Dyn,
+            101u16 => Type::MutableSpecifier,
+            102u16 => Type::DotDot,
+            103u16 => Type::DotDotEq,
+            104u16 => Type::AmpAmp,
+            105u16 => Type::PipePipe,
+            106u16 => Type::EqEq,
+            107u16 => Type::BangEq,
+            108u16 => Type::LTEq,
+            109u16 => Type::GTEq,
+            110u16 => Type::LtLt,
+            111u16 => Type::GtGt,
+            112u16 => Type::PlusEq,
+            113u16 => Type::DashEq,
+            114u16 => Type::StarEq,
+            115u16 => Type::SlashEq,
+            116u16 => Type::PercentEq,
+            117u16 => Type::AmpEq,
+            118u16 => Type::PipeEq,
+            119u16 => Type::CaretEq,
+            120u16 => Type::LtLtEq,
+            121u16 => Type::GtGtEq,
+            122u16 => Type::Yield,
+            123u16 => Type::Move,
+            124u16 => Type::IntegerLiteral,
+            126u16 => Type::DQuote,
+            127u16 => Type::CharLiteral,
+            128u16 => Type::EscapeSequence,
+            129u16 => Type::True,
+            130u16 => Type::False,
+            131u16 => Type::LineComment,
+            132u16 => Type::Shebang,
+            133u16 => Type::Self_,
+            134u16 => Type::Super,
+            135u16 => Type::Crate,
+            136u16 => Type::Metavariable,
+            138u16 => Type::RawStringLiteral,
+            139u16 => Type::FloatLiteral,
+            140u16 => Type::BlockComment,
+            141u16 => Type::SourceFile,
+            143u16 => Type::EmptyStatement,
+            144u16 => Type::ExpressionStatement,
+            145u16 => Type::MacroDefinition,
+            146u16 => Type::MacroRule,
+            148u16 => Type::TokenTreePattern,
+            149u16 => Type::TokenBindingPattern,
+            150u16 => Type::TokenRepetitionPattern,
+            151u16 => Type::FragmentSpecifier,
+            152u16 => Type::TokenTree,
+            153u16 => Type::TokenRepetition,
+            154u16 => Type::AttributeItem,
+            155u16 => Type::InnerAttributeItem,
+            156u16 => Type::Attribute,
+            157u16 => Type::ModItem,
+            158u16 => Type::ForeignModItem,
+            159u16 => Type::DeclarationList,
+            160u16 => Type::StructItem,
+            161u16 => Type::UnionItem,
+            162u16 => Type::EnumItem,
+            163u16 => Type::EnumVariantList,
+            164u16 => Type::EnumVariant,
+            165u16 => Type::FieldDeclarationList,
+            166u16 => Type::FieldDeclaration,
+            167u16 => Type::OrderedFieldDeclarationList,
+            168u16 => Type::ExternCrateDeclaration,
+            169u16 => Type::ConstItem,
+            170u16 => Type::StaticItem,
+            171u16 => Type::TypeItem,
+            172u16 => Type::FunctionItem,
+            173u16 => Type::FunctionSignatureItem,
+            174u16 => Type::FunctionModifiers,
+            175u16 => Type::WhereClause,
+            176u16 => Type::WherePredicate,
+            177u16 => Type::ImplItem,
+            178u16 => Type::TraitItem,
+            179u16 => Type::AssociatedType,
+            180u16 => Type::TraitBounds,
+            181u16 => Type::HigherRankedTraitBound,
+            182u16 => Type::RemovedTraitBound,
+            183u16 => Type::TypeParameters,
+            184u16 => Type::ConstParameter,
+            185u16 => Type::ConstrainedTypeParameter,
+            186u16 => Type::OptionalTypeParameter,
+            187u16 => Type::LetDeclaration,
+            188u16 => Type::UseDeclaration,
+            190u16 => Type::ScopedUseList,
+            191u16 => Type::UseList,
+            192u16 => Type::UseAsClause,
+            193u16 => Type::UseWildcard,
+            194u16 => Type::Parameters,
+            195u16 => Type::SelfParameter,
+            196u16 => Type::VariadicParameter,
+            197u16 => Type::Parameter,
+            198u16 => Type::ExternModifier,
+            199u16 => Type::VisibilityModifier,
+            201u16 => Type::BracketedType,
+            202u16 => Type::QualifiedType,
+            203u16 => Type::Lifetime,
+            204u16 => Type::ArrayType,
+            205u16 => Type::ForLifetimes,
+            206u16 => Type::FunctionType,
+            207u16 => Type::TupleType,
+            208u16 => Type::UnitType,
+            209u16 => Type::GenericFunction,
+            210u16 => Type::GenericType,
+            211u16 => Type::GenericTypeWithTurbofish,
+            212u16 => Type::BoundedType,
+            213u16 => Type::TypeArguments,
+            214u16 => Type::TypeBinding,
+            215u16 => Type::ReferenceType,
+            216u16 => Type::PointerType,
+            217u16 => Type::EmptyType,
+            218u16 => Type::AbstractType,
+            219u16 => Type::DynamicType,
+            222u16 => Type::MacroInvocation,
+            226u16 => Type::ScopedIdentifier,
+            228u16 => Type::ScopedTypeIdentifier,
+            229u16 => Type::RangeExpression,
+            230u16 => Type::UnaryExpression,
+            231u16 => Type::TryExpression,
+            232u16 => Type::ReferenceExpression,
+            233u16 => Type::BinaryExpression,
+            234u16 => Type::AssignmentExpression,
+            235u16 => Type::CompoundAssignmentExpr,
+            236u16 => Type::TypeCastExpression,
+            237u16 => Type::ReturnExpression,
+            238u16 => Type::YieldExpression,
+            239u16 => Type::CallExpression,
+            240u16 => Type::Arguments,
+            241u16 => Type::ArrayExpression,
+            242u16 => Type::ParenthesizedExpression,
+            243u16 => Type::TupleExpression,
+            244u16 => Type::UnitExpression,
+            245u16 => Type::StructExpression,
+            246u16 => Type::FieldInitializerList,
+            247u16 => Type::ShorthandFieldInitializer,
+            248u16 => Type::FieldInitializer,
+            249u16 => Type::BaseFieldInitializer,
+            250u16 => Type::IfExpression,
+            251u16 => Type::LetCondition,
+            254u16 => Type::ElseClause,
+            255u16 => Type::MatchExpression,
+            256u16 => Type::MatchBlock,
+            257u16 => Type::MatchArm,
+            259u16 => Type::MatchPattern,
+            260u16 => Type::WhileExpression,
+            261u16 => Type::LoopExpression,
+            262u16 => Type::ForExpression,
+            263u16 => Type::ConstBlock,
+            264u16 => Type::ClosureExpression,
+            265u16 => Type::ClosureParameters,
+            266u16 => Type::LoopLabel,
+            267u16 => Type::BreakExpression,
+            268u16 => Type::ContinueExpression,
+            269u16 => Type::IndexExpression,
+            270u16 => Type::AwaitExpression,
+            271u16 => Type::FieldExpression,
+            272u16 => Type::UnsafeBlock,
+            273u16 => Type::AsyncBlock,
+            274u16 => Type::Block,
+            276u16 => Type::TuplePattern,
+            277u16 => Type::SlicePattern,
+            278u16 => Type::TupleStructPattern,
+            279u16 => Type::StructPattern,
+            280u16 => Type::FieldPattern,
+            281u16 => Type::RemainingFieldPattern,
+            282u16 => Type::MutPattern,
+            283u16 => Type::RangePattern,
+            284u16 => Type::RefPattern,
+            285u16 => Type::CapturedPattern,
+            286u16 => Type::ReferencePattern,
+            287u16 => Type::OrPattern,
+            290u16 => Type::NegativeLiteral,
+            291u16 => Type::StringLiteral,
+            292u16 => Type::BooleanLiteral,
+            322u16 => Type::FieldIdentifier,
+            323u16 => Type::LetChain,
+            324u16 => Type::ShorthandFieldIdentifier,
+            325u16 => Type::TypeIdentifier,
+            u16::MAX => Type::ERROR,
+            x => panic!("{}", x),
+        }
+    }
+    /// resolves a named node kind of the grammar
+    pub fn from_str(t: &str) -> Option<Type> {
+        Some(match t {
+            "source_file" => Type::SourceFile,
+            "line_comment" => Type::LineComment,
+            "block_comment" => Type::BlockComment,
+            "shebang" => Type::Shebang,
+            "identifier" => Type::Identifier,
+            "type_identifier" => Type::TypeIdentifier,
+            "field_identifier" => Type::FieldIdentifier,
+            "shorthand_field_identifier" => Type::ShorthandFieldIdentifier,
+            "primitive_type" => Type::PrimitiveType,
+            "self" => Type::Self_,
+            "super" => Type::Super,
+            "crate" => Type::Crate,
+            "metavariable" => Type::Metavariable,
+            "mutable_specifier" => Type::MutableSpecifier,
+            "fragment_specifier" => Type::FragmentSpecifier,
+            "integer_literal" => Type::IntegerLiteral,
+            "float_literal" => Type::FloatLiteral,
+            "boolean_literal" => Type::BooleanLiteral,
+            "char_literal" => Type::CharLiteral,
+            "string_literal" => Type::StringLiteral,
+            "raw_string_literal" => Type::RawStringLiteral,
+            "escape_sequence" => Type::EscapeSequence,
+            "negative_literal" => Type::NegativeLiteral,
+            "attribute_item" => Type::AttributeItem,
+            "inner_attribute_item" => Type::InnerAttributeItem,
+            "attribute" => Type::Attribute,
+            "mod_item" => Type::ModItem,
+            "declaration_list" => Type::DeclarationList,
+            "foreign_mod_item" => Type::ForeignModItem,
+            "extern_crate_declaration" => Type::ExternCrateDeclaration,
+            "extern_modifier" => Type::ExternModifier,
+            "use_declaration" => Type::UseDeclaration,
+            "use_as_clause" => Type::UseAsClause,
+            "use_list" => Type::UseList,
+            "scoped_use_list" => Type::ScopedUseList,
+            "use_wildcard" => Type::UseWildcard,
+            "visibility_modifier" => Type::VisibilityModifier,
+            "function_item" => Type::FunctionItem,
+            "function_signature_item" => Type::FunctionSignatureItem,
+            "function_modifiers" => Type::FunctionModifiers,
+            "parameters" => Type::Parameters,
+            "parameter" => Type::Parameter,
+            "self_parameter" => Type::SelfParameter,
+            "variadic_parameter" => Type::VariadicParameter,
+            "closure_parameters" => Type::ClosureParameters,
+            "struct_item" => Type::StructItem,
+            "union_item" => Type::UnionItem,
+            "enum_item" => Type::EnumItem,
+            "enum_variant" => Type::EnumVariant,
+            "enum_variant_list" => Type::EnumVariantList,
+            "field_declaration" => Type::FieldDeclaration,
+            "field_declaration_list" => Type::FieldDeclarationList,
+            "ordered_field_declaration_list" => Type::OrderedFieldDeclarationList,
+            "trait_item" => Type::TraitItem,
+            "impl_item" => Type::ImplItem,
+            "associated_type" => Type::AssociatedType,
+            "type_item" => Type::TypeItem,
+            "const_item" => Type::ConstItem,
+            "static_item" => Type::StaticItem,
+            "type_parameters" => Type::TypeParameters,
+            "type_arguments" => Type::TypeArguments,
+            "type_binding" => Type::TypeBinding,
+            "constrained_type_parameter" => Type::ConstrainedTypeParameter,
+            "optional_type_parameter" => Type::OptionalTypeParameter,
+            "const_parameter" => Type::ConstParameter,
+            "lifetime" => Type::Lifetime,
+            "trait_bounds" => Type::TraitBounds,
+            "higher_ranked_trait_bound" => Type::HigherRankedTraitBound,
+            "removed_trait_bound" => Type::RemovedTraitBound,
+            "for_lifetimes" => Type::ForLifetimes,
+            "where_clause" => Type::WhereClause,
+            "where_predicate" => Type::WherePredicate,
+            "generic_type" => Type::GenericType,
+            "generic_type_with_turbofish" => Type::GenericTypeWithTurbofish,
+            "scoped_type_identifier" => Type::ScopedTypeIdentifier,
+            "scoped_identifier" => Type::ScopedIdentifier,
+            "qualified_type" => Type::QualifiedType,
+            "bracketed_type" => Type::BracketedType,
+            "abstract_type" => Type::AbstractType,
+            "bounded_type" => Type::BoundedType,
+            "dynamic_type" => Type::DynamicType,
+            "function_type" => Type::FunctionType,
+            "array_type" => Type::ArrayType,
+            "pointer_type" => Type::PointerType,
+            "reference_type" => Type::ReferenceType,
+            "tuple_type" => Type::TupleType,
+            "unit_type" => Type::UnitType,
+            "empty_type" => Type::EmptyType,
+            "macro_definition" => Type::MacroDefinition,
+            "macro_rule" => Type::MacroRule,
+            "macro_invocation" => Type::MacroInvocation,
+            "token_tree" => Type::TokenTree,
+            "token_tree_pattern" => Type::TokenTreePattern,
+            "token_binding_pattern" => Type::TokenBindingPattern,
+            "token_repetition" => Type::TokenRepetition,
+            "token_repetition_pattern" => Type::TokenRepetitionPattern,
+            "block" => Type::Block,
+            "expression_statement" => Type::ExpressionStatement,
+            "let_declaration" => Type::LetDeclaration,
+            "empty_statement" => Type::EmptyStatement,
+            "loop_label" => Type::LoopLabel,
+            "else_clause" => Type::ElseClause,
+            "let_condition" => Type::LetCondition,
+            "let_chain" => Type::LetChain,
+            "if_expression" => Type::IfExpression,
+            "match_expression" => Type::MatchExpression,
+            "match_block" => Type::MatchBlock,
+            "match_arm" => Type::MatchArm,
+            "match_pattern" => Type::MatchPattern,
+            "while_expression" => Type::WhileExpression,
+            "loop_expression" => Type::LoopExpression,
+            "for_expression" => Type::ForExpression,
+            "break_expression" => Type::BreakExpression,
+            "continue_expression" => Type::ContinueExpression,
+            "return_expression" => Type::ReturnExpression,
+            "yield_expression" => Type::YieldExpression,
+            "call_expression" => Type::CallExpression,
+            "generic_function" => Type::GenericFunction,
+            "arguments" => Type::Arguments,
+            "field_expression" => Type::FieldExpression,
+            "index_expression" => Type::IndexExpression,
+            "try_expression" => Type::TryExpression,
+            "await_expression" => Type::AwaitExpression,
+            "type_cast_expression" => Type::TypeCastExpression,
+            "reference_expression" => Type::ReferenceExpression,
+            "unary_expression" => Type::UnaryExpression,
+            "binary_expression" => Type::BinaryExpression,
+            "assignment_expression" => Type::AssignmentExpression,
+            "compound_assignment_expr" => Type::CompoundAssignmentExpr,
+            "range_expression" => Type::RangeExpression,
+            "array_expression" => Type::ArrayExpression,
+            "tuple_expression" => Type::TupleExpression,
+            "unit_expression" => Type::UnitExpression,
+            "parenthesized_expression" => Type::ParenthesizedExpression,
+            "struct_expression" => Type::StructExpression,
+            "field_initializer_list" => Type::FieldInitializerList,
+            "field_initializer" => Type::FieldInitializer,
+            "shorthand_field_initializer" => Type::ShorthandFieldInitializer,
+            "base_field_initializer" => Type::BaseFieldInitializer,
+            "closure_expression" => Type::ClosureExpression,
+            "async_block" => Type::AsyncBlock,
+            "unsafe_block" => Type::UnsafeBlock,
+            "const_block" => Type::ConstBlock,
+            "tuple_pattern" => Type::TuplePattern,
+            "tuple_struct_pattern" => Type::TupleStructPattern,
+            "struct_pattern" => Type::StructPattern,
+            "field_pattern" => Type::FieldPattern,
+            "remaining_field_pattern" => Type::RemainingFieldPattern,
+            "slice_pattern" => Type::SlicePattern,
+            "ref_pattern" => Type::RefPattern,
+            "reference_pattern" => Type::ReferencePattern,
+            "mut_pattern" => Type::MutPattern,
+            "captured_pattern" => Type::CapturedPattern,
+            "range_pattern" => Type::RangePattern,
+            "or_pattern" => Type::OrPattern,
+            "ERROR" => Type::ERROR,
+            _ => return None,
+        })
+    }
+    /// resolves an anonymous node kind of the grammar, ie. keywords and punctuation
+    pub fn from_token(t: &str) -> Option<Type> {
+        Some(match t {
+            "!" => Type::Bang,
+            "!=" => Type::BangEq,
+            "#" => Type::Hash,
+            "$" => Type::Dollar,
+            "%" => Type::Percent,
+            "%=" => Type::PercentEq,
+            "&" => Type::Amp,
+            "&&" => Type::AmpAmp,
+            "&=" => Type::AmpEq,
+            "'" => Type::SQuote,
+            "\"" => Type::DQuote,
+            "(" => Type::LParen,
+            ")" => Type::RParen,
+            "*" => Type::Star,
+            "*=" => Type::StarEq,
+            "+" => Type::Plus,
+            "+=" => Type::PlusEq,
+            "," => Type::Comma,
+            "-" => Type::Dash,
+            "-=" => Type::DashEq,
+            "->" => Type::DashGt,
+            "." => Type::Dot,
+            ".." => Type::DotDot,
+            "..." => Type::DotDotDot,
+            "..=" => Type::DotDotEq,
+            "/" => Type::Slash,
+            "/=" => Type::SlashEq,
+            ":" => Type::Colon,
+            "::" => Type::ColonColon,
+            ";" => Type::SemiColon,
+            "<" => Type::LT,
+            "<<" => Type::LtLt,
+            "<<=" => Type::LtLtEq,
+            "<=" => Type::LTEq,
+            "=" => Type::Eq,
+            "==" => Type::EqEq,
+            "=>" => Type::EqGt,
+            ">" => Type::GT,
+            ">=" => Type::GTEq,
+            ">>" => Type::GtGt,
+            ">>=" => Type::GtGtEq,
+            "?" => Type::QMark,
+            "@" => Type::At,
+            "[" => Type::LBracket,
+            "]" => Type::RBracket,
+            "^" => Type::Caret,
+            "~" => Type::Tilde,
+            "\\" => Type::Backslash,
+            "^=" => Type::CaretEq,
+            "_" => Type::Underscore,
+            "{" => Type::LBrace,
+            "|" => Type::Pipe,
+            "|=" => Type::PipeEq,
+            "||" => Type::PipePipe,
+            "}" => Type::RBrace,
+            "as" => Type::As,
+            "async" => Type::Async,
+            "await" => Type::Await,
+            "break" => Type::Break,
+            "const" => Type::Const,
+            "continue" => Type::Continue,
+            "default" => Type::Default,
+            "dyn" => Type::Dyn,
+            "else" => Type::Else,
+            "enum" => Type::Enum,
+            "extern" => Type::Extern,
+            "false" => Type::False,
+            "fn" => Type::Fn,
+            "for" => Type::For,
+            "if" => Type::If,
+            "impl" => Type::Impl,
+            "in" => Type::In,
+            "let" => Type::Let,
+            "loop" => Type::Loop,
+            "macro_rules!" => Type::MacroRules,
+            "match" => Type::Match,
+            "mod" => Type::Mod,
+            "move" => Type::Move,
+            "pub" => Type::Pub,
+            "ref" => Type::Ref,
+            "return" => Type::Return,
+            "static" => Type::Static,
+            "struct" => Type::Struct,
+            "trait" => Type::Trait,
+            "true" => Type::True,
+            "type" => Type::Type,
+            "union" => Type::Union,
+            "unsafe" => Type::Unsafe,
+            "use" => Type::Use,
+            "where" => Type::Where,
+            "while" => Type::While,
+            "yield" => Type::Yield,
+            "block" => Type::TSBlock,
+            "expr" => Type::Expr,
+            "ident" => Type::Ident,
+            "item" => Type::Item,
+            "lifetime" => Type::TSLifetime,
+            "literal" => Type::Literal,
+            "meta" => Type::Meta,
+            "pat" => Type::Pat,
+            "path" => Type::Path,
+            "stmt" => Type::Stmt,
+            "tt" => Type::Tt,
+            "ty" => Type::Ty,
+            "vis" => Type::Vis,
+            _ => return None,
+        })
+    }
+    pub fn to_str(t: Type) -> &'static str {
+        match t {
+            Type::SourceFile => "source_file",
+            Type::LineComment => "line_comment",
+            Type::BlockComment => "block_comment",
+            Type::Shebang => "shebang",
+            Type::Identifier => "identifier",
+            Type::TypeIdentifier => "type_identifier",
+            Type::FieldIdentifier => "field_identifier",
+            Type::ShorthandFieldIdentifier => "shorthand_field_identifier",
+            Type::PrimitiveType => "primitive_type",
+            Type::Self_ => "self",
+            Type::Super => "super",
+            Type::Crate => "crate",
+            Type::Metavariable => "metavariable",
+            Type::MutableSpecifier => "mutable_specifier",
+            Type::FragmentSpecifier => "fragment_specifier",
+            Type::IntegerLiteral => "integer_literal",
+            Type::FloatLiteral => "float_literal",
+            Type::BooleanLiteral => "boolean_literal",
+            Type::CharLiteral => "char_literal",
+            Type::StringLiteral => "string_literal",
+            Type::RawStringLiteral => "raw_string_literal",
+            Type::EscapeSequence => "escape_sequence",
+            Type::NegativeLiteral => "negative_literal",
+            Type::AttributeItem => "attribute_item",
+            Type::InnerAttributeItem => "inner_attribute_item",
+            Type::Attribute => "attribute",
+            Type::ModItem => "mod_item",
+            Type::DeclarationList => "declaration_list",
+            Type::ForeignModItem => "foreign_mod_item",
+            Type::ExternCrateDeclaration => "extern_crate_declaration",
+            Type::ExternModifier => "extern_modifier",
+            Type::UseDeclaration => "use_declaration",
+            Type::UseAsClause => "use_as_clause",
+            Type::UseList => "use_list",
+            Type::ScopedUseList => "scoped_use_list",
+            Type::UseWildcard => "use_wildcard",
+            Type::VisibilityModifier => "visibility_modifier",
+            Type::FunctionItem => "function_item",
+            Type::FunctionSignatureItem => "function_signature_item",
+            Type::FunctionModifiers => "function_modifiers",
+            Type::Parameters => "parameters",
+            Type::Parameter => "parameter",
+            Type::SelfParameter => "self_parameter",
+            Type::VariadicParameter => "variadic_parameter",
+            Type::ClosureParameters => "closure_parameters",
+            Type::StructItem => "struct_item",
+            Type::UnionItem => "union_item",
+            Type::EnumItem => "enum_item",
+            Type::EnumVariant => "enum_variant",
+            Type::EnumVariantList => "enum_variant_list",
+            Type::FieldDeclaration => "field_declaration",
+            Type::FieldDeclarationList => "field_declaration_list",
+            Type::OrderedFieldDeclarationList => "ordered_field_declaration_list",
+            Type::TraitItem => "trait_item",
+            Type::ImplItem => "impl_item",
+            Type::AssociatedType => "associated_type",
+            Type::TypeItem => "type_item",
+            Type::ConstItem => "const_item",
+            Type::StaticItem => "static_item",
+            Type::TypeParameters => "type_parameters",
+            Type::TypeArguments => "type_arguments",
+            Type::TypeBinding => "type_binding",
+            Type::ConstrainedTypeParameter => "constrained_type_parameter",
+            Type::OptionalTypeParameter => "optional_type_parameter",
+            Type::ConstParameter => "const_parameter",
+            Type::Lifetime => "lifetime",
+            Type::TraitBounds => "trait_bounds",
+            Type::HigherRankedTraitBound => "higher_ranked_trait_bound",
+            Type::RemovedTraitBound => "removed_trait_bound",
+            Type::ForLifetimes => "for_lifetimes",
+            Type::WhereClause => "where_clause",
+            Type::WherePredicate => "where_predicate",
+            Type::GenericType => "generic_type",
+            Type::GenericTypeWithTurbofish => "generic_type_with_turbofish",
+            Type::ScopedTypeIdentifier => "scoped_type_identifier",
+            Type::ScopedIdentifier => "scoped_identifier",
+            Type::QualifiedType => "qualified_type",
+            Type::BracketedType => "bracketed_type",
+            Type::AbstractType => "abstract_type",
+            Type::BoundedType => "bounded_type",
+            Type::DynamicType => "dynamic_type",
+            Type::FunctionType => "function_type",
+            Type::ArrayType => "array_type",
+            Type::PointerType => "pointer_type",
+            Type::ReferenceType => "reference_type",
+            Type::TupleType => "tuple_type",
+            Type::UnitType => "unit_type",
+            Type::EmptyType => "empty_type",
+            Type::MacroDefinition => "macro_definition",
+            Type::MacroRule => "macro_rule",
+            Type::MacroInvocation => "macro_invocation",
+            Type::TokenTree => "token_tree",
+            Type::TokenTreePattern => "token_tree_pattern",
+            Type::TokenBindingPattern => "token_binding_pattern",
+            Type::TokenRepetition => "token_repetition",
+            Type::TokenRepetitionPattern => "token_repetition_pattern",
+            Type::Block => "block",
+            Type::ExpressionStatement => "expression_statement",
+            Type::LetDeclaration => "let_declaration",
+            Type::EmptyStatement => "empty_statement",
+            Type::LoopLabel => "loop_label",
+            Type::ElseClause => "else_clause",
+            Type::LetCondition => "let_condition",
+            Type::LetChain => "let_chain",
+            Type::IfExpression => "if_expression",
+            Type::MatchExpression => "match_expression",
+            Type::MatchBlock => "match_block",
+            Type::MatchArm => "match_arm",
+            Type::MatchPattern => "match_pattern",
+            Type::WhileExpression => "while_expression",
+            Type::LoopExpression => "loop_expression",
+            Type::ForExpression => "for_expression",
+            Type::BreakExpression => "break_expression",
+            Type::ContinueExpression => "continue_expression",
+            Type::ReturnExpression => "return_expression",
+            Type::YieldExpression => "yield_expression",
+            Type::CallExpression => "call_expression",
+            Type::GenericFunction => "generic_function",
+            Type::Arguments => "arguments",
+            Type::FieldExpression => "field_expression",
+            Type::IndexExpression => "index_expression",
+            Type::TryExpression => "try_expression",
+            Type::AwaitExpression => "await_expression",
+            Type::TypeCastExpression => "type_cast_expression",
+            Type::ReferenceExpression => "reference_expression",
+            Type::UnaryExpression => "unary_expression",
+            Type::BinaryExpression => "binary_expression",
+            Type::AssignmentExpression => "assignment_expression",
+            Type::CompoundAssignmentExpr => "compound_assignment_expr",
+            Type::RangeExpression => "range_expression",
+            Type::ArrayExpression => "array_expression",
+            Type::TupleExpression => "tuple_expression",
+            Type::UnitExpression => "unit_expression",
+            Type::ParenthesizedExpression => "parenthesized_expression",
+            Type::StructExpression => "struct_expression",
+            Type::FieldInitializerList => "field_initializer_list",
+            Type::FieldInitializer => "field_initializer",
+            Type::ShorthandFieldInitializer => "shorthand_field_initializer",
+            Type::BaseFieldInitializer => "base_field_initializer",
+            Type::ClosureExpression => "closure_expression",
+            Type::AsyncBlock => "async_block",
+            Type::UnsafeBlock => "unsafe_block",
+            Type::ConstBlock => "const_block",
+            Type::TuplePattern => "tuple_pattern",
+            Type::TupleStructPattern => "tuple_struct_pattern",
+            Type::StructPattern => "struct_pattern",
+            Type::FieldPattern => "field_pattern",
+            Type::RemainingFieldPattern => "remaining_field_pattern",
+            Type::SlicePattern => "slice_pattern",
+            Type::RefPattern => "ref_pattern",
+            Type::ReferencePattern => "reference_pattern",
+            Type::MutPattern => "mut_pattern",
+            Type::CapturedPattern => "captured_pattern",
+            Type::RangePattern => "range_pattern",
+            Type::OrPattern => "or_pattern",
+            Type::Bang => "!",
+            Type::BangEq => "!=",
+            Type::Hash => "#",
+            Type::Dollar => "$",
+            Type::Percent => "%",
+            Type::PercentEq => "%=",
+            Type::Amp => "&",
+            Type::AmpAmp => "&&",
+            Type::AmpEq => "&=",
+            Type::SQuote => "'",
+            Type::DQuote => "\"",
+            Type::LParen => "(",
+            Type::RParen => ")",
+            Type::Star => "*",
+            Type::StarEq => "*=",
+            Type::Plus => "+",
+            Type::PlusEq => "+=",
+            Type::Comma => ",",
+            Type::Dash => "-",
+            Type::DashEq => "-=",
+            Type::DashGt => "->",
+            Type::Dot => ".",
+            Type::DotDot => "..",
+            Type::DotDotDot => "...",
+            Type::DotDotEq => "..=",
+            Type::Slash => "/",
+            Type::SlashEq => "/=",
+            Type::Colon => ":",
+            Type::ColonColon => "::",
+            Type::SemiColon => ";",
+            Type::LT => "<",
+            Type::LtLt => "<<",
+            Type::LtLtEq => "<<=",
+            Type::LTEq => "<=",
+            Type::Eq => "=",
+            Type::EqEq => "==",
+            Type::EqGt => "=>",
+            Type::GT => ">",
+            Type::GTEq => ">=",
+            Type::GtGt => ">>",
+            Type::GtGtEq => ">>=",
+            Type::QMark => "?",
+            Type::At => "@",
+            Type::LBracket => "[",
+            Type::RBracket => "]",
+            Type::Caret => "^",
+            Type::Tilde => "~",
+            Type::Backslash => "\\",
+            Type::CaretEq => "^=",
+            Type::Underscore => "_",
+            Type::LBrace => "{",
+            Type::Pipe => "|",
+            Type::PipeEq => "|=",
+            Type::PipePipe => "||",
+            Type::RBrace => "}",
+            Type::As => "as",
+            Type::Async => "async",
+            Type::Await => "await",
+            Type::Break => "break",
+            Type::Const => "const",
+            Type::Continue => "continue",
+            Type::Default => "default",
+            Type::Dyn => "dyn",
+            Type::Else => "else",
+            Type::Enum => "enum",
+            Type::Extern => "extern",
+            Type::False => "false",
+            Type::Fn => "fn",
+            Type::For => "for",
+            Type::If => "if",
+            Type::Impl => "impl",
+            Type::In => "in",
+            Type::Let => "let",
+            Type::Loop => "loop",
+            Type::MacroRules => "macro_rules!",
+            Type::Match => "match",
+            Type::Mod => "mod",
+            Type::Move => "move",
+            Type::Pub => "pub",
+            Type::Ref => "ref",
+            Type::Return => "return",
+            Type::Static => "static",
+            Type::Struct => "struct",
+            Type::Trait => "trait",
+            Type::True => "true",
+            Type::Type => "type",
+            Type::Union => "union",
+            Type::Unsafe => "unsafe",
+            Type::Use => "use",
+            Type::Where => "where",
+            Type::While => "while",
+            Type::Yield => "yield",
+            Type::TSBlock => "block",
+            Type::Expr => "expr",
+            Type::Ident => "ident",
+            Type::Item => "item",
+            Type::TSLifetime => "lifetime",
+            Type::Literal => "literal",
+            Type::Meta => "meta",
+            Type::Pat => "pat",
+            Type::Path => "path",
+            Type::Stmt => "stmt",
+            Type::Tt => "tt",
+            Type::Ty => "ty",
+            Type::Vis => "vis",
+            Type::Spaces => "Spaces",
+            Type::Directory => "Directory",
+            Type::ERROR => "ERROR",
+        }
+    }
+}
+
+const COUNT: u16 = 263;
+
+const S_T_L: &'static [Type] = &[
+    Type::SourceFile,
+    Type::LineComment,
+    Type::BlockComment,
+    Type::Shebang,
+    Type::Identifier,
+    Type::TypeIdentifier,
+    Type::FieldIdentifier,
+    Type::ShorthandFieldIdentifier,
+    Type::PrimitiveType,
+    Type::Self_,
+    Type::Super,
+    Type::Crate,
+    Type::Metavariable,
+    Type::MutableSpecifier,
+    Type::FragmentSpecifier,
+    Type::IntegerLiteral,
+    Type::FloatLiteral,
+    Type::BooleanLiteral,
+    Type::CharLiteral,
+    Type::StringLiteral,
+    Type::RawStringLiteral,
+    Type::EscapeSequence,
+    Type::NegativeLiteral,
+    Type::AttributeItem,
+    Type::InnerAttributeItem,
+    Type::Attribute,
+    Type::ModItem,
+    Type::DeclarationList,
+    Type::ForeignModItem,
+    Type::ExternCrateDeclaration,
+    Type::ExternModifier,
+    Type::UseDeclaration,
+    Type::UseAsClause,
+    Type::UseList,
+    Type::ScopedUseList,
+    Type::UseWildcard,
+    Type::VisibilityModifier,
+    Type::FunctionItem,
+    Type::FunctionSignatureItem,
+    Type::FunctionModifiers,
+    Type::Parameters,
+    Type::Parameter,
+    Type::SelfParameter,
+    Type::VariadicParameter,
+    Type::ClosureParameters,
+    Type::StructItem,
+    Type::UnionItem,
+    Type::EnumItem,
+    Type::EnumVariant,
+    Type::EnumVariantList,
+    Type::FieldDeclaration,
+    Type::FieldDeclarationList,
+    Type::OrderedFieldDeclarationList,
+    Type::TraitItem,
+    Type::ImplItem,
+    Type::AssociatedType,
+    Type::TypeItem,
+    Type::ConstItem,
+    Type::StaticItem,
+    Type::TypeParameters,
+    Type::TypeArguments,
+    Type::TypeBinding,
+    Type::ConstrainedTypeParameter,
+    Type::OptionalTypeParameter,
+    Type::ConstParameter,
+    Type::Lifetime,
+    Type::TraitBounds,
+    Type::HigherRankedTraitBound,
+    Type::RemovedTraitBound,
+    Type::ForLifetimes,
+    Type::WhereClause,
+    Type::WherePredicate,
+    Type::GenericType,
+    Type::GenericTypeWithTurbofish,
+    Type::ScopedTypeIdentifier,
+    Type::ScopedIdentifier,
+    Type::QualifiedType,
+    Type::BracketedType,
+    Type::AbstractType,
+    Type::BoundedType,
+    Type::DynamicType,
+    Type::FunctionType,
+    Type::ArrayType,
+    Type::PointerType,
+    Type::ReferenceType,
+    Type::TupleType,
+    Type::UnitType,
+    Type::EmptyType,
+    Type::MacroDefinition,
+    Type::MacroRule,
+    Type::MacroInvocation,
+    Type::TokenTree,
+    Type::TokenTreePattern,
+    Type::TokenBindingPattern,
+    Type::TokenRepetition,
+    Type::TokenRepetitionPattern,
+    Type::Block,
+    Type::ExpressionStatement,
+    Type::LetDeclaration,
+    Type::EmptyStatement,
+    Type::LoopLabel,
+    Type::ElseClause,
+    Type::LetCondition,
+    Type::LetChain,
+    Type::IfExpression,
+    Type::MatchExpression,
+    Type::MatchBlock,
+    Type::MatchArm,
+    Type::MatchPattern,
+    Type::WhileExpression,
+    Type::LoopExpression,
+    Type::ForExpression,
+    Type::BreakExpression,
+    Type::ContinueExpression,
+    Type::ReturnExpression,
+    Type::YieldExpression,
+    Type::CallExpression,
+    Type::GenericFunction,
+    Type::Arguments,
+    Type::FieldExpression,
+    Type::IndexExpression,
+    Type::TryExpression,
+    Type::AwaitExpression,
+    Type::TypeCastExpression,
+    Type::ReferenceExpression,
+    Type::UnaryExpression,
+    Type::BinaryExpression,
+    Type::AssignmentExpression,
+    Type::CompoundAssignmentExpr,
+    Type::RangeExpression,
+    Type::ArrayExpression,
+    Type::TupleExpression,
+    Type::UnitExpression,
+    Type::ParenthesizedExpression,
+    Type::StructExpression,
+    Type::FieldInitializerList,
+    Type::FieldInitializer,
+    Type::ShorthandFieldInitializer,
+    Type::BaseFieldInitializer,
+    Type::ClosureExpression,
+    Type::AsyncBlock,
+    Type::UnsafeBlock,
+    Type::ConstBlock,
+    Type::TuplePattern,
+    Type::TupleStructPattern,
+    Type::StructPattern,
+    Type::FieldPattern,
+    Type::RemainingFieldPattern,
+    Type::SlicePattern,
+    Type::RefPattern,
+    Type::ReferencePattern,
+    Type::MutPattern,
+    Type::CapturedPattern,
+    Type::RangePattern,
+    Type::OrPattern,
+    Type::Bang,
+    Type::BangEq,
+    Type::Hash,
+    Type::Dollar,
+    Type::Percent,
+    Type::PercentEq,
+    Type::Amp,
+    Type::AmpAmp,
+    Type::AmpEq,
+    Type::SQuote,
+    Type::DQuote,
+    Type::LParen,
+    Type::RParen,
+    Type::Star,
+    Type::StarEq,
+    Type::Plus,
+    Type::PlusEq,
+    Type::Comma,
+    Type::Dash,
+    Type::DashEq,
+    Type::DashGt,
+    Type::Dot,
+    Type::DotDot,
+    Type::DotDotDot,
+    Type::DotDotEq,
+    Type::Slash,
+    Type::SlashEq,
+    Type::Colon,
+    Type::ColonColon,
+    Type::SemiColon,
+    Type::LT,
+    Type::LtLt,
+    Type::LtLtEq,
+    Type::LTEq,
+    Type::Eq,
+    Type::EqEq,
+    Type::EqGt,
+    Type::GT,
+    Type::GTEq,
+    Type::GtGt,
+    Type::GtGtEq,
+    Type::QMark,
+    Type::At,
+    Type::LBracket,
+    Type::RBracket,
+    Type::Caret,
+    Type::Tilde,
+    Type::Backslash,
+    Type::CaretEq,
+    Type::Underscore,
+    Type::LBrace,
+    Type::Pipe,
+    Type::PipeEq,
+    Type::PipePipe,
+    Type::RBrace,
+    Type::As,
+    Type::Async,
+    Type::Await,
+    Type::Break,
+    Type::Const,
+    Type::Continue,
+    Type::Default,
+    Type::Dyn,
+    Type::Else,
+    Type::Enum,
+    Type::Extern,
+    Type::False,
+    Type::Fn,
+    Type::For,
+    Type::If,
+    Type::Impl,
+    Type::In,
+    Type::Let,
+    Type::Loop,
+    Type::MacroRules,
+    Type::Match,
+    Type::Mod,
+    Type::Move,
+    Type::Pub,
+    Type::Ref,
+    Type::Return,
+    Type::Static,
+    Type::Struct,
+    Type::Trait,
+    Type::True,
+    Type::Type,
+    Type::Union,
+    Type::Unsafe,
+    Type::Use,
+    Type::Where,
+    Type::While,
+    Type::Yield,
+    Type::TSBlock,
+    Type::Expr,
+    Type::Ident,
+    Type::Item,
+    Type::TSLifetime,
+    Type::Literal,
+    Type::Meta,
+    Type::Pat,
+    Type::Path,
+    Type::Stmt,
+    Type::Tt,
+    Type::Ty,
+    Type::Vis,
+    Type::Spaces,
+    Type::Directory,
+    Type::ERROR,
+];
@@ -297,8 +297,19 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
     }
 
     pub fn tree_sitter_parse(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
+        Self::parse_with(tree_sitter_typescript::language_typescript(), text)
+    }
+
+    /// Parses with the tsx grammar, typescript with jsx elements.
+    pub fn tree_sitter_parse_tsx(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
+        Self::parse_with(tree_sitter_typescript::language_tsx(), text)
+    }
+
+    fn parse_with(
+        language: tree_sitter::Language,
+        text: &[u8],
+    ) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
         let mut parser = tree_sitter::Parser::new();
-        let language = tree_sitter_typescript::language_typescript();
         parser.set_language(language).unwrap();
         let tree = parser.parse(text, None).unwrap();
         if tree.root_node().has_error() {
@@ -3,6 +3,10 @@ pub mod legion;
 
 pub mod types;
 
+#[cfg(feature = "impl")]
+#[cfg(test)]
+mod tests;
+
 #[cfg(feature = "legion")]
 mod tnode {
     use super::*;
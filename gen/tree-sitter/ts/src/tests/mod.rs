@@ -0,0 +1,46 @@
+use crate::{
+    legion::TsTreeGen,
+    types::{TStore, Type},
+};
+
+static CASE_TSX: &str = r#"import * as React from "react";
+
+interface Props {
+    name: string;
+}
+
+export function Greeter({ name }: Props) {
+    const [count, setCount] = React.useState<number>(0);
+    return (
+        <div className="greeter" onClick={() => setCount(count + 1)}>
+            <h1>Hello {name}!</h1>
+            <svg:rect width="1" />
+            {count > 0 && <span>{count}</span>}
+        </div>
+    );
+}
+"#;
+
+/// Checks that every node of the tsx grammar has a type, the jsx ones included.
+fn assert_typed(node: tree_sitter::Node) -> usize {
+    let t = Type::from_tsx(node.kind(), node.is_named());
+    assert_ne!(t, Type::ERROR, "{}", node.kind());
+    if node.kind().starts_with("jsx_") {
+        assert_eq!(t.to_str(), node.kind());
+    }
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor);
+    1 + children.map(assert_typed).sum::<usize>()
+}
+
+#[test]
+fn tsx_tree_sitter_types() {
+    let text = CASE_TSX.as_bytes();
+    // jsx elements are not typescript
+    assert!(TsTreeGen::<TStore>::tree_sitter_parse(text).is_err());
+    let tree = match TsTreeGen::<TStore>::tree_sitter_parse_tsx(text) {
+        Ok(t) => t,
+        Err(t) => panic!("{}", t.root_node().to_sexp()),
+    };
+    assert!(assert_typed(tree.root_node()) > 100);
+}
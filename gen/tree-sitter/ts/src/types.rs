@@ -16,8 +16,12 @@ mod legion_impls {
 
     impl<'a> TNode<'a> {
         pub fn obtain_type<T>(&self, _: &mut impl TsEnabledTypeStore<T>) -> Type {
-            let t = self.kind_id();
-            Type::from_u16(t)
+            if self.0.language() == tree_sitter_typescript::language_typescript() {
+                let t = self.kind_id();
+                Type::from_u16(t)
+            } else {
+                Type::from_tsx(self.0.kind(), self.0.is_named())
+            }
         }
     }
 
@@ -92,6 +96,13 @@ mod legion_impls {
     }
 }
 
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Ts as Lang<Type>>::to_u16(*t);
+    let t = <Ts as Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
+
 pub trait TsEnabledTypeStore<T>: TypeStore<T> {
     const LANG: u16;
     fn intern(&self, t: Type) -> Self::Ty {
@@ -206,7 +217,7 @@ impl HyperType for Type {
     }
 
     fn is_file(&self) -> bool {
-        todo!()
+        self == &Type::Program
     }
 
     fn is_spaces(&self) -> bool {
@@ -339,8 +350,8 @@ impl TypeTrait for Type {
     }
 }
 
-// 356 + directory  + spaces
-const COUNT: u16 = 358;
+// 356 + jsx + directory  + spaces
+const COUNT: u16 = 368;
 
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -688,6 +699,16 @@ pub enum Type {
     StatementIdentifier,
     ThisType,
     TypeIdentifier,
+    JsxElement,
+    JsxOpeningElement,
+    JsxClosingElement,
+    JsxSelfClosingElement,
+    JsxAttribute,
+    JsxExpression,
+    JsxNamespaceName,
+    JsxText,
+    LTSlash,
+    SlashGT,
     Spaces,
     Directory,
     ERROR,
@@ -1055,6 +1076,34 @@ impl Type {
             x => panic!("{}", x),
         }
     }
+    /// Types of the nodes parsed with the tsx grammar,
+    /// its symbols are the ones of the typescript grammar but with different ids,
+    /// plus the ones of jsx.
+    #[cfg(feature = "impl")]
+    pub fn from_tsx(kind: &str, named: bool) -> Type {
+        match kind {
+            "jsx_element" => Type::JsxElement,
+            "jsx_opening_element" => Type::JsxOpeningElement,
+            "jsx_closing_element" => Type::JsxClosingElement,
+            "jsx_self_closing_element" => Type::JsxSelfClosingElement,
+            "jsx_attribute" => Type::JsxAttribute,
+            "jsx_expression" => Type::JsxExpression,
+            "jsx_namespace_name" => Type::JsxNamespaceName,
+            "jsx_text" => Type::JsxText,
+            "</" => Type::LTSlash,
+            "/>" => Type::SlashGT,
+            "ERROR" => Type::ERROR,
+            kind => {
+                let t = tree_sitter_typescript::language_typescript().id_for_node_kind(kind, named);
+                if t == 0 && kind != "end" {
+                    log::warn!("unknown tsx node kind: {}", kind);
+                    Type::ERROR
+                } else {
+                    Type::from_u16(t)
+                }
+            }
+        }
+    }
     pub fn from_str(&self) -> Option<Type> {
         todo!()
     }
@@ -1399,6 +1448,16 @@ impl Type {
             Type::StatementIdentifier => "statement_identifier",
             Type::ThisType => "this_type",
             Type::TypeIdentifier => "type_identifier",
+            Type::JsxElement => "jsx_element",
+            Type::JsxOpeningElement => "jsx_opening_element",
+            Type::JsxClosingElement => "jsx_closing_element",
+            Type::JsxSelfClosingElement => "jsx_self_closing_element",
+            Type::JsxAttribute => "jsx_attribute",
+            Type::JsxExpression => "jsx_expression",
+            Type::JsxNamespaceName => "jsx_namespace_name",
+            Type::JsxText => "jsx_text",
+            Type::LTSlash => "</",
+            Type::SlashGT => "/>",
             Type::Spaces => "Spaces",
             Type::Directory => "Directory",
             Type::ERROR => "ERROR",
@@ -1744,6 +1803,16 @@ const S_T_L: &'static [Type] = &[
     Type::StatementIdentifier,
     Type::ThisType,
     Type::TypeIdentifier,
+    Type::JsxElement,
+    Type::JsxOpeningElement,
+    Type::JsxClosingElement,
+    Type::JsxSelfClosingElement,
+    Type::JsxAttribute,
+    Type::JsxExpression,
+    Type::JsxNamespaceName,
+    Type::JsxText,
+    Type::LTSlash,
+    Type::SlashGT,
     Type::Spaces,
     Type::Directory,
     Type::ERROR,
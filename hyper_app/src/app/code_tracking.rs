@@ -162,14 +162,35 @@ pub struct SrcChanges {
     #[serde(flatten)]
     pub(crate) commit: Commit,
     /// Global position of deleted elements
-    pub(crate) deletions: Vec<u32>, // TODO diff encode
+    #[serde(with = "compressed_topo_indexes")]
+    pub(crate) deletions: Vec<u32>,
 }
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct DstChanges {
     #[serde(flatten)]
     pub(crate) commit: Commit,
     /// Global position of added elements
-    pub(crate) additions: Vec<u32>, // TODO diff encode
+    #[serde(with = "compressed_topo_indexes")]
+    pub(crate) additions: Vec<u32>,
+}
+
+/// The server sends changes as [`CompressedTopoIndexes`],
+/// they are decoded once here as the tree views need random access.
+mod compressed_topo_indexes {
+    use hyper_ast::position::CompressedTopoIndexes;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(x: &[u32], serializer: S) -> Result<S::Ok, S::Error> {
+        CompressedTopoIndexes::from_iter(x.iter().copied()).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u32>, D::Error> {
+        Ok(CompressedTopoIndexes::deserialize(deserializer)?
+            .decode()
+            .collect())
+    }
 }
 
 pub(super) type ComputeResult = Resource<TrackingResult>;
@@ -249,7 +270,15 @@ impl Resource<TrackingResultWithChanges> {
         response: ehttp::Response,
     ) -> Result<Self, String> {
         // wasm_rs_dbg::dbg!(&response);
-        // let content_type = response.content_type().unwrap_or_default();
+        let content_type = response.content_type().unwrap_or_default();
+
+        if response.status == 200 && content_type.starts_with("application/msgpack") {
+            let content = rmp_serde::from_slice(&response.bytes).map_err(|x| x.to_string())?;
+            return Ok(Self {
+                response,
+                content: Some(content),
+            });
+        }
 
         let text = response.text();
         let text = text.ok_or("")?;
@@ -2481,9 +2481,11 @@ pub(super) fn track_at_path_with_changes(
 
     // wasm_rs_dbg::dbg!(&url);
     let mut request = ehttp::Request::get(&url);
-    // request
-    //     .headers
-    //     .insert("Content-Type".to_string(), "text".to_string());
+    // changes are large, prefer the binary encoding
+    request.headers.insert(
+        "Accept".to_string(),
+        "application/msgpack, application/json;q=0.9".to_string(),
+    );
 
     ehttp::fetch(request, move |response| {
         // wasm_rs_dbg::dbg!(&response);
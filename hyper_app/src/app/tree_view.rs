@@ -56,6 +56,24 @@ impl<'a> hyper_ast::types::TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TSto
                     <hyper_ast_gen_ts_xml::types::Xml as Lang<_>>::make(raw);
                 t
             }
+            "hyper_ast_gen_ts_make::types::Make" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_make::types::Make as Lang<_>>::make(raw);
+                t
+            }
+            "hyper_ast_gen_ts_python::types::Python" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_python::types::Python as Lang<_>>::make(raw);
+                t
+            }
+            "hyper_ast_gen_ts_rust::types::Rust" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_rust::types::Rust as Lang<_>>::make(raw);
+                t
+            }
             "" => {
                 let t: &'static (dyn HyperType + 'static) =
                     <hyper_ast_gen_ts_java::types::Java as Lang<_>>::make(
@@ -84,6 +102,17 @@ impl<'a> hyper_ast::types::TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TSto
             "hyper_ast_gen_ts_xml::types::Xml" => {
                 From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_xml::types::Xml)
             }
+            "hyper_ast_gen_ts_make::types::Make" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_make::types::Make)
+            }
+            "hyper_ast_gen_ts_python::types::Python" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(
+                    &hyper_ast_gen_ts_python::types::Python,
+                )
+            }
+            "hyper_ast_gen_ts_rust::types::Rust" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_rust::types::Rust)
+            }
             "" => {
                 From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_java::types::Java)
             }
@@ -234,4 +234,52 @@ impl<IdN: Copy, Idx: PrimInt> StructuralPositionStore<IdN, Idx> {
         assert_eq!(self.nodes.len(), self.parents.len());
         SpHandle(self.nodes.len() - 1)
     }
+
+    /// Returns the nodes and offsets going down from the closest [`ancestor`] of [`x`] to [`x`],
+    /// None if [`ancestor`] is not an ancestor of [`x`].
+    pub fn path_from(&self, ancestor: &IdN, x: SpHandle) -> Option<Vec<(IdN, Idx)>>
+    where
+        IdN: Eq,
+    {
+        let mut i = x.0;
+        let mut path = vec![];
+        while &self.nodes[i] != ancestor {
+            if i == 0 {
+                return None;
+            }
+            path.push((self.nodes[i], self.offsets[i] - one()));
+            i = self.parents[i];
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Pushes the position reached from [`x`] by going down the nodes and offsets of [`path`],
+    /// eg. a path given by [`StructuralPositionStore::path_from`].
+    pub fn push_below(
+        &mut self,
+        x: SpHandle,
+        path: impl IntoIterator<Item = (IdN, Idx)>,
+    ) -> SpHandle {
+        let mut parent = x.0;
+        for (node, offset) in path {
+            self.nodes.push(node);
+            self.parents.push(parent);
+            self.offsets.push(offset + one());
+            parent = self.nodes.len() - 1;
+        }
+        SpHandle(parent)
+    }
+}
+
+#[test]
+fn path_from_push_below_roundtrip() {
+    let mut sps = StructuralPositionStore::<u32, u16>::new(0);
+    let x = sps.push_below(SpHandle(0), [(1, 2), (3, 0)]);
+    assert_eq!(sps.path_from(&1, x), Some(vec![(3, 0)]));
+    let path = sps.path_from(&0, x).unwrap();
+    assert_eq!(path, vec![(1, 2), (3, 0)]);
+    let y = sps.push_below(SpHandle(0), path.clone());
+    assert_eq!(sps.path_from(&0, y), Some(path));
+    assert_eq!(sps.path_from(&4, y), None);
 }
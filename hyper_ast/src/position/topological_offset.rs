@@ -1,2 +1,89 @@
 use super::PrimInt;
 pub struct Position<T: PrimInt>(T);
+
+/// Compact encoding of a set of topological indexes, eg. the global positions of changed nodes.
+///
+/// Indexes are sorted and grouped in runs of contiguous indexes,
+/// each run is stored as the gap since the end of the previous run followed by its length.
+/// Changes are mostly contiguous subtrees, so it stays short and made of small numbers,
+/// that binary formats like CBOR or MessagePack further pack as varints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(transparent))]
+pub struct CompressedTopoIndexes(Vec<u32>);
+
+impl CompressedTopoIndexes {
+    /// Wraps already encoded runs, eg. received from a client without serde
+    pub fn from_runs(runs: Vec<u32>) -> Self {
+        Self(runs)
+    }
+
+    /// The encoded runs, alternating gaps and lengths
+    pub fn runs(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// Number of encoded indexes
+    pub fn len(&self) -> usize {
+        self.0
+            .chunks(2)
+            .map(|x| x.get(1).copied().unwrap_or(0) as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decodes the indexes in increasing order
+    pub fn decode(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut end = 0;
+        self.0.chunks(2).flat_map(move |x| {
+            let start = end + x[0];
+            end = start + x.get(1).copied().unwrap_or(0);
+            start..end
+        })
+    }
+}
+
+impl FromIterator<u32> for CompressedTopoIndexes {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut indexes: Vec<u32> = iter.into_iter().collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        let mut runs = vec![];
+        let mut end = 0;
+        let mut it = indexes.into_iter().peekable();
+        while let Some(start) = it.next() {
+            let mut len = 1;
+            while it.next_if_eq(&(start + len)).is_some() {
+                len += 1;
+            }
+            runs.push(start - end);
+            runs.push(len);
+            end = start + len;
+        }
+        Self(runs)
+    }
+}
+
+impl From<Vec<u32>> for CompressedTopoIndexes {
+    fn from(value: Vec<u32>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_topo_indexes_roundtrip() {
+        let indexes = vec![0, 1, 2, 5, 6, 42, 43, 44, 45, 1000];
+        let compressed = CompressedTopoIndexes::from(indexes.clone());
+        assert_eq!(compressed.0, vec![0, 3, 2, 2, 35, 4, 954, 1]);
+        assert_eq!(compressed.len(), indexes.len());
+        assert_eq!(compressed.decode().collect::<Vec<_>>(), indexes);
+        assert!(CompressedTopoIndexes::from(vec![]).is_empty());
+    }
+}
@@ -175,6 +175,14 @@ impl NodeStore {
             .ok()
     }
 
+    /// Converts an id received as a number, eg. from a client, if it is a node of the store.
+    pub fn id_from_u64(&self, id: u64) -> Option<NodeIdentifier> {
+        let id = std::num::NonZeroU64::new(id)?;
+        // legion entities are transparent wrappers of a NonZeroU64
+        let id: NodeIdentifier = unsafe { std::mem::transmute(id) };
+        self.internal.contains(id).then_some(id)
+    }
+
     pub fn resolve_typed<TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>>(
         &self,
         id: &TIdN,
@@ -26,6 +26,15 @@ fn run() {
     log(&format!("Hello {}!", "mister"));
 }
 
+/// Decodes the additions or deletions of tracking results with changes,
+/// sent as compact runs, into global positions in increasing order.
+#[wasm_bindgen]
+pub fn decode_changes(runs: Vec<u32>) -> Vec<u32> {
+    hyper_ast::position::CompressedTopoIndexes::from_runs(runs)
+        .decode()
+        .collect()
+}
+
 use crate::store::FetchedHyperAST;
 use std::sync::Arc;
 use std::collections::HashMap;
@@ -33,7 +33,7 @@ impl<'a> hyper_ast::types::TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TSto
     fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
         let lang = n.get_lang();
         let t: &'static (dyn HyperType + 'static) = match lang {
-            "hyper_ast_gen_ts_ts::types::ts" => {
+            "hyper_ast_gen_ts_ts::types::Ts" => {
                 let raw = n.get_raw_type();
                 let t: &'static (dyn HyperType + 'static) =
                     <hyper_ast_gen_ts_ts::types::Ts as Lang<_>>::make(raw);
@@ -57,6 +57,24 @@ impl<'a> hyper_ast::types::TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TSto
                     <hyper_ast_gen_ts_xml::types::Xml as Lang<_>>::make(raw);
                 t
             }
+            "hyper_ast_gen_ts_make::types::Make" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_make::types::Make as Lang<_>>::make(raw);
+                t
+            }
+            "hyper_ast_gen_ts_python::types::Python" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_python::types::Python as Lang<_>>::make(raw);
+                t
+            }
+            "hyper_ast_gen_ts_rust::types::Rust" => {
+                let raw = n.get_raw_type();
+                let t: &'static (dyn HyperType + 'static) =
+                    <hyper_ast_gen_ts_rust::types::Rust as Lang<_>>::make(raw);
+                t
+            }
             "" => {
                 let t: &'static (dyn HyperType + 'static) =
                     <hyper_ast_gen_ts_java::types::Java as Lang<_>>::make(
@@ -88,6 +106,15 @@ impl<'a> hyper_ast::types::TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TSto
             "hyper_ast_gen_ts_xml::types::Xml" => {
                 From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_xml::types::Lang)
             }
+            "hyper_ast_gen_ts_make::types::Make" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_make::types::Lang)
+            }
+            "hyper_ast_gen_ts_python::types::Python" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_python::types::Lang)
+            }
+            "hyper_ast_gen_ts_rust::types::Rust" => {
+                From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_rust::types::Lang)
+            }
             "" => {
                 From::<&'static (dyn LangRef<AnyType>)>::from(&hyper_ast_gen_ts_java::types::Lang)
             }
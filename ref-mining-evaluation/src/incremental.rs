@@ -0,0 +1,84 @@
+use std::time::Instant;
+
+use hyper_ast_cvs_git::{
+    allrefs::{
+        write_referencial_relations, write_referencial_relations_with_cache, ReferencesCache,
+    },
+    git::fetch_repository,
+    preprocessed::PreProcessedRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::relations::{PerModule, Relation};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncrementalPerfs {
+    commit: String,
+    /// time in nano seconds of the search from scratch
+    baseline_time: u128,
+    /// time in nano seconds of the search reusing the references found on previous commits
+    incremental_time: u128,
+    /// number of searched subtrees whose references were reused
+    hits: usize,
+    /// number of searched subtrees whose references were not in the cache
+    misses: usize,
+    /// both searches found the same relations
+    same_relations: bool,
+}
+
+/// Searches the references on the commits between [`before`] and [`after`],
+/// once from scratch and once reusing the references found in the subtrees of previously searched commits.
+pub fn bench(
+    repository: &str,
+    before: &str,
+    after: &str,
+    dir_path: &str,
+    limit: usize,
+) -> Vec<IncrementalPerfs> {
+    let mut repo = fetch_repository(repository.to_string(), "/tmp/hyperastgitresources/repo")
+        .expect("should be able to fetch the repository");
+    let mut preprocessed = PreProcessedRepository::new(repository);
+    let commits = preprocessed.pre_process_with_limit(&mut repo, before, after, dir_path, limit);
+    let stores = &preprocessed.processor.main_stores;
+    let mut cache = ReferencesCache::default();
+    let mut perfs = vec![];
+    // oldest first, like when following the history
+    for c in commits.iter().rev() {
+        let root = preprocessed.commits[c].ast_root;
+
+        let mut baseline = vec![];
+        let now = Instant::now();
+        write_referencial_relations(stores, root, &mut baseline);
+        let baseline_time = now.elapsed().as_nanos();
+
+        let (hits, misses) = (cache.hits(), cache.misses());
+        let mut incremental = vec![];
+        let now = Instant::now();
+        write_referencial_relations_with_cache(stores, root, &mut cache, &mut incremental);
+        let incremental_time = now.elapsed().as_nanos();
+
+        perfs.push(IncrementalPerfs {
+            commit: c.to_string(),
+            baseline_time,
+            incremental_time,
+            hits: cache.hits() - hits,
+            misses: cache.misses() - misses,
+            same_relations: parse_relations(&baseline) == parse_relations(&incremental),
+        });
+    }
+    perfs
+}
+
+/// Ratio between the total times of the searches from scratch and the incremental ones.
+pub fn speedup(perfs: &[IncrementalPerfs]) -> f64 {
+    let baseline: u128 = perfs.iter().map(|x| x.baseline_time).sum();
+    let incremental: u128 = perfs.iter().map(|x| x.incremental_time).sum();
+    baseline as f64 / incremental.max(1) as f64
+}
+
+fn parse_relations(out: &[u8]) -> Option<Vec<PerModule<Vec<Relation>>>> {
+    let out = [b"[".as_slice(), out, b"]"].concat();
+    serde_json::from_slice(&out)
+        .map_err(|e| eprintln!("can't read relations: {}", e))
+        .ok()
+}
@@ -3,6 +3,7 @@
 
 pub mod compare;
 pub mod comparisons;
+pub mod incremental;
 pub mod relations;
 pub mod stats;
 
@@ -306,6 +307,24 @@ fn main() {
                 });
             }
         }
+        Commands::Incremental {
+            repository,
+            before,
+            after,
+            dir_path,
+            limit,
+            json,
+        } => {
+            let perfs = incremental::bench(repository, before, after, dir_path, *limit);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&perfs).unwrap());
+            } else {
+                perfs.iter().for_each(|x| {
+                    println!("{:?}", x);
+                });
+            }
+            eprintln!("speedup: {:.2}", incremental::speedup(&perfs));
+        }
         Commands::InteractiveDeclarations {
             repository,
             commit,
@@ -313,7 +332,7 @@ fn main() {
             evaluated,
             ..
         } => {
-            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources/repo");
+            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources/repo").unwrap();
             let bl_rs = handle_file(File::open(baseline).expect("should be a file")).unwrap();
             let t_rs = handle_file(File::open(evaluated).expect("should be a file")).unwrap();
             let mut per_module: HashMap<String, (_, _)> = Default::default();
@@ -394,7 +413,7 @@ fn main() {
             only_misses,
             ..
         } => {
-            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources");
+            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources").unwrap();
             let bl_rs = handle_file(File::open(baseline).expect("should be a file")).unwrap();
             let t_rs = handle_file(File::open(test).expect("should be a file")).unwrap();
             let mut per_module: HashMap<String, (_, _)> = Default::default();
@@ -1007,6 +1026,35 @@ enum Commands {
         json: bool,
     },
 
+    /// Time the references search on successive commits,
+    /// from scratch and reusing the references found in unchanged subtrees
+    Incremental {
+        /// The git repository that we want to analyse
+        /// ie. <domain>/user/project
+        /// eg. github.com/INRIA/spoon
+        #[clap(short, long)]
+        repository: String,
+
+        /// Commit before the searched ones, searching from the first commit if empty
+        #[clap(long, default_value = "")]
+        before: String,
+
+        /// Last searched commit
+        #[clap(long)]
+        after: String,
+
+        /// Path of the maven project in the repository
+        #[clap(long, default_value = "")]
+        dir_path: String,
+
+        /// Maximum number of searched commits
+        #[clap(long, default_value_t = 50)]
+        limit: usize,
+
+        #[clap(long)]
+        json: bool,
+    },
+
     /// look interactively at missed references to exactly matched declarations
     Interactive {
         /// The git repository that we want to analyse